@@ -0,0 +1,30 @@
+// Audit hash chain
+//
+// Recomputes a wallet's `audit_head` from its event history. Each entry is the event as
+// emitted through `emit_cpi`: the event discriminator followed by its Borsh data, without the
+// CPI event instruction tag.
+
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::Event;
+
+// Head after folding `event_data` into `head`
+pub fn next_head(head: &[u8; 32], event_data: &[u8]) -> [u8; 32] {
+    hashv(&[head, event_data]).to_bytes()
+}
+
+// Head after folding a typed event into `head`
+pub fn next_head_for<E: Event>(head: &[u8; 32], event: &E) -> [u8; 32] {
+    next_head(head, &event.data())
+}
+
+// Head of a complete event history, starting from the zeroed head of a new wallet; equal to
+// the wallet's `audit_head` only if no event is missing, reordered or altered
+pub fn replay<I, T>(events: I) -> [u8; 32]
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    events
+        .into_iter()
+        .fold([0; 32], |head, event| next_head(&head, event.as_ref()))
+}
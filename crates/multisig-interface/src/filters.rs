@@ -0,0 +1,54 @@
+// getProgramAccounts filters for wallet accounts
+//
+// Wallet config accounts start with the account header, then a fixed header ahead of any
+// variable-length field:
+//
+//   discriminator (8 bytes) | account header (16) | owner (32) | guardian (32) | version (u8)
+//
+// The escape in progress is part of the wallet's hot state instead, so `escape_type` matches
+// `WalletState` accounts; combine it with `wallet_states()` and read the wallet from each
+// match's `argent_account`.
+//
+// Each helper returns an `(offset, bytes)` pair for a memcmp filter, e.g.
+// `Memcmp::new_base58_encoded(offset, &bytes)`. Combine one with `wallets()` to find every
+// wallet of a key in a single call. Wallets not yet migrated to the account header (version
+// below `ACCOUNT_HEADER_VERSION`) keep these fields `AccountHeader::LEN` bytes earlier, and
+// don't match until migrate_account moves them.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Discriminator;
+
+use crate::{EscapeType, WalletConfig, WalletState};
+
+pub use multisig::{
+    ACCOUNT_HEADER_VERSION, FIXED_HEADER_ACCOUNT_VERSION, WALLET_GUARDIAN_OFFSET,
+    WALLET_OWNER_OFFSET, WALLET_STATE_ESCAPE_TYPE_OFFSET, WALLET_VERSION_OFFSET,
+};
+
+// Any wallet config account, by its discriminator
+pub fn wallets() -> (usize, Vec<u8>) {
+    (0, WalletConfig::DISCRIMINATOR.to_vec())
+}
+
+// Any wallet state account, by its discriminator
+pub fn wallet_states() -> (usize, Vec<u8>) {
+    (0, WalletState::DISCRIMINATOR.to_vec())
+}
+
+pub fn owner(owner: &Pubkey) -> (usize, Vec<u8>) {
+    (WALLET_OWNER_OFFSET, owner.to_bytes().to_vec())
+}
+
+pub fn guardian(guardian: &Pubkey) -> (usize, Vec<u8>) {
+    (WALLET_GUARDIAN_OFFSET, guardian.to_bytes().to_vec())
+}
+
+// Wallets at layout `version`, e.g. to find those still to migrate
+pub fn version(version: u8) -> (usize, Vec<u8>) {
+    (WALLET_VERSION_OFFSET, vec![version])
+}
+
+// Wallet states with an escape of `escape_type` in progress, or none with `EscapeType::None`
+pub fn escape_type(escape_type: EscapeType) -> (usize, Vec<u8>) {
+    (WALLET_STATE_ESCAPE_TYPE_OFFSET, vec![escape_type as u8])
+}
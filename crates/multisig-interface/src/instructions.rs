@@ -0,0 +1,73 @@
+// Instruction builders
+//
+// One builder per program instruction, grouped like the program's modules. Each derives the
+// wallet's PDAs from its address and takes every other account as an argument: optional
+// accounts are `None` when left out, and instructions that read remaining accounts take them
+// last. Anything else can be built with `build` from the re-exported `accounts` and
+// `instruction` modules.
+
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::{InstructionData, ToAccountMetas};
+
+use crate::ID;
+
+mod deposits;
+mod escape;
+mod fees;
+mod inheritance;
+mod keys;
+mod migrate_from;
+mod nft;
+mod outside;
+mod payments;
+mod recovery;
+mod signatures;
+mod staking;
+mod streams;
+mod tokens;
+mod trading;
+mod transactions;
+mod upgrades;
+mod vault;
+mod wallet;
+
+pub use deposits::*;
+pub use escape::*;
+pub use fees::*;
+pub use inheritance::*;
+pub use keys::*;
+pub use migrate_from::*;
+pub use nft::*;
+pub use outside::*;
+pub use payments::*;
+pub use recovery::*;
+pub use signatures::*;
+pub use staking::*;
+pub use streams::*;
+pub use tokens::*;
+pub use trading::*;
+pub use transactions::*;
+pub use upgrades::*;
+pub use vault::*;
+pub use wallet::*;
+
+// Build an instruction from Anchor-generated accounts and arguments
+pub fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+// Build an instruction whose handler also reads `remaining_accounts`, passed after the
+// accounts of its context
+pub fn build_with_remaining(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut instruction = build(accounts, data);
+    instruction.accounts.extend(remaining_accounts);
+    instruction
+}
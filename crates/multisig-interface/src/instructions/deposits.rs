@@ -0,0 +1,197 @@
+// Deposits
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+
+use super::build;
+use crate::{accounts, instruction, pda, ID};
+
+// Deposit SOL from `sender` into the wallet vault
+pub fn deposit_sol(wallet: &Pubkey, sender: &Pubkey, amount: u64, memo: String) -> Instruction {
+    build(
+        accounts::DepositSol {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            vault: pda::vault(wallet).0,
+            sender: *sender,
+            funding_policy: pda::funding_policy(wallet).0,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::DepositSol { amount, memo },
+    )
+}
+
+// Deposit SPL tokens into the vault's associated token account (anyone can deposit)
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_spl(
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    sender_token_account: &Pubkey,
+    vault_token_account: &Pubkey,
+    sender: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+    memo: String,
+) -> Instruction {
+    build(
+        accounts::DepositSpl {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            vault: pda::vault(wallet).0,
+            mint: *mint,
+            sender_token_account: *sender_token_account,
+            vault_token_account: *vault_token_account,
+            sender: *sender,
+            funding_policy: pda::funding_policy(wallet).0,
+            token_program: *token_program,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::DepositSpl { amount, memo },
+    )
+}
+
+// Restrict which senders may fund the wallet (requires both owner and guardian). Deposits
+// from anyone else still land in the vault but are flagged, and can be sent back with
+// refund_deposit or refund_spl_deposit; flagged deposits from a sender allowed here stop
+// being refundable
+pub fn set_funding_policy(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    allowed_senders: Vec<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::SetFundingPolicy {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            funding_policy: pda::funding_policy(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetFundingPolicy { allowed_senders },
+    )
+}
+
+// Lift the funding restriction (requires both owner and guardian); flagged deposits not yet
+// refunded stay in the vault
+pub fn remove_funding_policy(wallet: &Pubkey, owner: &Pubkey, guardian: &Pubkey) -> Instruction {
+    build(
+        accounts::RemoveFundingPolicy {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            funding_policy: pda::funding_policy(wallet).0,
+            vault: pda::vault(wallet).0,
+        },
+        instruction::RemoveFundingPolicy {},
+    )
+}
+
+// Send a flagged SOL deposit back to its sender from the vault (anyone can crank this)
+pub fn refund_deposit(wallet: &Pubkey, sender: &Pubkey) -> Instruction {
+    build(
+        accounts::RefundDeposit {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            funding_policy: pda::funding_policy(wallet).0,
+            vault: pda::vault(wallet).0,
+            sender: *sender,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::RefundDeposit {},
+    )
+}
+
+// Send a flagged token deposit back to the sender's token account from the vault (anyone
+// can crank this)
+pub fn refund_spl_deposit(
+    wallet: &Pubkey,
+    sender: &Pubkey,
+    mint: &Pubkey,
+    vault_token_account: &Pubkey,
+    sender_token_account: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::RefundSplDeposit {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            funding_policy: pda::funding_policy(wallet).0,
+            vault: pda::vault(wallet).0,
+            sender: *sender,
+            mint: *mint,
+            vault_token_account: *vault_token_account,
+            sender_token_account: *sender_token_account,
+            token_program: *token_program,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::RefundSplDeposit {},
+    )
+}
+
+// Quarantine an unsolicited mint so the owner alone can burn balances up to dust_threshold
+// (requires both owner and guardian)
+pub fn quarantine_mint(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    mint: Pubkey,
+    dust_threshold: u64,
+) -> Instruction {
+    build(
+        accounts::QuarantineMint {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            quarantine: pda::quarantine(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::QuarantineMint {
+            mint,
+            dust_threshold,
+        },
+    )
+}
+
+// Remove a mint from quarantine (requires both owner and guardian)
+pub fn unquarantine_mint(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    mint: Pubkey,
+) -> Instruction {
+    build(
+        accounts::UnquarantineMint {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            quarantine: pda::quarantine(wallet).0,
+        },
+        instruction::UnquarantineMint { mint },
+    )
+}
@@ -0,0 +1,360 @@
+// Escapes
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{system_program, sysvar};
+
+use super::{build, build_with_remaining};
+use crate::{accounts, instruction, pda, EscapeType, ID};
+
+// Have every escape trigger, completion and cancellation notify `notification_program`
+// through a CPI carrying the event, for push notifications that don't rely on log scraping
+// (requires both owner and guardian). Escapes fail while the program does, so it should
+// be one both parties trust not to be upgraded into rejecting them
+pub fn set_notification_program(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    notification_program: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::SetNotificationProgram {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            notification_config: pda::notification_config(wallet).0,
+            notification_program: *notification_program,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetNotificationProgram {},
+    )
+}
+
+// Stop escape notifications (requires both owner and guardian); the rent goes back to the
+// vault
+pub fn remove_notification_program(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::RemoveNotificationProgram {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            notification_config: pda::notification_config(wallet).0,
+            vault: pda::vault(wallet).0,
+        },
+        instruction::RemoveNotificationProgram {},
+    )
+}
+
+pub fn trigger_escape_guardian(wallet: &Pubkey, owner: &Pubkey) -> Instruction {
+    build(
+        accounts::TriggerEscapeGuardian {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            notification_config: pda::notification_config(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::TriggerEscapeGuardian {},
+    )
+}
+
+// Opt in to promoting the guardian backup when the guardian has not signed anything for
+// `inactivity_period` seconds (requires both owner and guardian)
+pub fn set_backup_promotion(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    inactivity_period: i64,
+) -> Instruction {
+    build(
+        accounts::SetBackupPromotion {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            backup_promotion: pda::backup_promotion(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetBackupPromotion { inactivity_period },
+    )
+}
+
+// Opt out of guardian backup promotion (requires both owner and guardian); the rent goes
+// back to the vault
+pub fn remove_backup_promotion(wallet: &Pubkey, owner: &Pubkey, guardian: &Pubkey) -> Instruction {
+    build(
+        accounts::RemoveBackupPromotion {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            backup_promotion: pda::backup_promotion(wallet).0,
+            vault: pda::vault(wallet).0,
+        },
+        instruction::RemoveBackupPromotion {},
+    )
+}
+
+// Start promoting the guardian backup once the guardian has been inactive for the opted-in
+// period (owner alone); this is a guardian escape with the backup committed as the new
+// guardian, so anyone can complete it with complete_escape after the security period
+pub fn request_backup_promotion(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build_with_remaining(
+        accounts::RequestBackupPromotion {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            backup_promotion: pda::backup_promotion(wallet).0,
+            notification_config: pda::notification_config(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::RequestBackupPromotion {},
+        remaining_accounts,
+    )
+}
+
+// Pre-authorize the key an escape may install (requires both owner and guardian, while
+// both keys are healthy): an escape of `escape_type` to exactly `new_key` completes after
+pub fn preauthorize_escape(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    escape_type: EscapeType,
+    new_key: Pubkey,
+    delay: i64,
+) -> Instruction {
+    build(
+        accounts::PreauthorizeEscape {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            escape_preauthorization: pda::escape_preauthorization(wallet).0,
+            payer: *payer,
+            sysvar_instructions: sysvar::instructions::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::PreauthorizeEscape {
+            escape_type,
+            new_key,
+            delay,
+        },
+    )
+}
+
+// Withdraw every escape pre-authorization (owner or guardian alone, as this only brings
+// escapes back to the full security period); the rent goes back to the vault
+pub fn revoke_escape_preauthorization(wallet: &Pubkey, authority: &Pubkey) -> Instruction {
+    build(
+        accounts::RevokeEscapePreauthorization {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            authority: *authority,
+            escape_preauthorization: pda::escape_preauthorization(wallet).0,
+            vault: pda::vault(wallet).0,
+        },
+        instruction::RevokeEscapePreauthorization {},
+    )
+}
+
+pub fn trigger_escape_owner(wallet: &Pubkey, guardian: &Pubkey) -> Instruction {
+    build(
+        accounts::TriggerEscapeOwner {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            guardian: *guardian,
+            notification_config: pda::notification_config(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::TriggerEscapeOwner {},
+    )
+}
+
+pub fn escape_guardian(wallet: &Pubkey, owner: &Pubkey, new_guardian: Pubkey) -> Instruction {
+    build(
+        accounts::EscapeGuardian {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            escape_preauthorization: pda::escape_preauthorization(wallet).0,
+            sysvar_instructions: sysvar::instructions::ID,
+            notification_config: pda::notification_config(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::EscapeGuardian { new_guardian },
+    )
+}
+
+pub fn escape_owner(wallet: &Pubkey, guardian: &Pubkey, new_owner: Pubkey) -> Instruction {
+    build(
+        accounts::EscapeOwner {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            guardian: *guardian,
+            escape_preauthorization: pda::escape_preauthorization(wallet).0,
+            sysvar_instructions: sysvar::instructions::ID,
+            notification_config: pda::notification_config(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::EscapeOwner { new_owner },
+    )
+}
+
+pub fn cancel_escape(wallet: &Pubkey, owner: &Pubkey, guardian: &Pubkey) -> Instruction {
+    build(
+        accounts::CancelEscape {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            notification_config: pda::notification_config(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CancelEscape {},
+    )
+}
+
+// Commit the key the escape in progress installs; `authority` is the escaping party
+pub fn commit_escape_key(wallet: &Pubkey, authority: &Pubkey, new_key: Pubkey) -> Instruction {
+    build(
+        accounts::CommitEscapeKey {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            authority: *authority,
+            sysvar_instructions: sysvar::instructions::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CommitEscapeKey { new_key },
+    )
+}
+
+// Complete an escape with its committed key, sent by anyone
+pub fn complete_escape(wallet: &Pubkey, cranker: &Pubkey) -> Instruction {
+    build(
+        accounts::CompleteEscape {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            cranker: *cranker,
+            escape_preauthorization: pda::escape_preauthorization(wallet).0,
+            notification_config: pda::notification_config(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CompleteEscape {},
+    )
+}
+
+// Rehearse an escape against a shadow state, to test recovery procedures: the escaping
+// party (the owner for a guardian escape, the guardian for an owner escape) starts it with
+// the key it would escape to, which proves possession of itself as in commit_escape_key,
+// and complete_escape_rehearsal finishes it once the escape delay has passed. Rehearsal
+// events mirroring a real escape's are emitted and audited, but no key, escape state or
+// statistic of the wallet changes
+pub fn rehearse_escape(
+    wallet: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    escape_type: EscapeType,
+    new_key: Pubkey,
+) -> Instruction {
+    build(
+        accounts::RehearseEscape {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            authority: *authority,
+            escape_rehearsal: pda::escape_rehearsal(wallet).0,
+            payer: *payer,
+            sysvar_instructions: sysvar::instructions::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::RehearseEscape {
+            escape_type,
+            new_key,
+        },
+    )
+}
+
+// Complete an escape rehearsal once the escape delay has passed (anyone can send this);
+// the rehearsal is closed back to its payer
+pub fn complete_escape_rehearsal(wallet: &Pubkey, cranker: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        accounts::CompleteEscapeRehearsal {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            cranker: *cranker,
+            escape_rehearsal: pda::escape_rehearsal(wallet).0,
+            payer: *payer,
+            escape_preauthorization: pda::escape_preauthorization(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CompleteEscapeRehearsal {},
+    )
+}
+
+// Abandon an escape rehearsal (owner or guardian); the rehearsal is closed back to its
+// payer
+pub fn cancel_escape_rehearsal(wallet: &Pubkey, authority: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        accounts::CancelEscapeRehearsal {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            authority: *authority,
+            escape_rehearsal: pda::escape_rehearsal(wallet).0,
+            payer: *payer,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CancelEscapeRehearsal {},
+    )
+}
+
+// Report how often the wallet's owner and guardian have been escaped, and when, so risk
+// dashboards can flag wallets under repeated recovery pressure by simulating it
+pub fn get_escape_stats(wallet: &Pubkey) -> Instruction {
+    build(
+        accounts::GetEscapeStats {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+        },
+        instruction::GetEscapeStats {},
+    )
+}
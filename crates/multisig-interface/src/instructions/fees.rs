@@ -0,0 +1,187 @@
+// Fees
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{system_program, sysvar};
+
+use super::build;
+use crate::{accounts, instruction, pda, ID};
+
+// Create the wallet's fee ledger, totalling proposal rent and relayer reimbursements of
+// the instructions it is passed to
+pub fn create_fee_ledger(wallet: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        accounts::CreateFeeLedger {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            fee_ledger: pda::fee_ledger(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::CreateFeeLedger {},
+    )
+}
+
+// Report the fee ledger totals of the current period. Returned to the caller so it can
+// be read by simulation; once FEE_REPORT_PERIOD has passed the report is also emitted
+// and a new period starts (anyone can close a period)
+pub fn report_fees(wallet: &Pubkey) -> Instruction {
+    build(
+        accounts::ReportFees {
+            fee_ledger: pda::fee_ledger(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ReportFees {},
+    )
+}
+
+// Rebate network fees that the owner, guardian and registered relayers spend on wallet
+// operations from the vault, crediting at most `max_fee_per_operation` lamports per
+// recorded transaction and paying out up to `monthly_cap` lamports in total per
+pub fn set_fee_rebate_policy(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    max_fee_per_operation: u64,
+    monthly_cap: u64,
+) -> Instruction {
+    build(
+        accounts::SetFeeRebatePolicy {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            fee_rebate_policy: pda::fee_rebate_policy(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetFeeRebatePolicy {
+            max_fee_per_operation,
+            monthly_cap,
+        },
+    )
+}
+
+// Credit `signer`, the wallet's owner, guardian or a registered relayer, with the fee of the
+// transaction carrying a wallet operation it signs, towards its fee rebate
+pub fn record_operation_fee(wallet: &Pubkey, signer: &Pubkey) -> Instruction {
+    build(
+        accounts::RecordOperationFee {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            fee_rebate_policy: pda::fee_rebate_policy(wallet).0,
+            relayer_registry: pda::relayer_registry(wallet).0,
+            signer: *signer,
+            fee_rebate: pda::fee_rebate(wallet, signer).0,
+            sysvar_instructions: sysvar::instructions::ID,
+            system_program: system_program::ID,
+        },
+        instruction::RecordOperationFee {},
+    )
+}
+
+// Reimburse the signer from the vault for the fees recorded and not yet rebated, as far
+// as this period's cap allows; whatever is left can be claimed in a later period
+pub fn claim_fee_rebate(wallet: &Pubkey, signer: &Pubkey) -> Instruction {
+    build(
+        accounts::ClaimFeeRebate {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            fee_rebate_policy: pda::fee_rebate_policy(wallet).0,
+            relayer_registry: pda::relayer_registry(wallet).0,
+            signer: *signer,
+            fee_rebate: pda::fee_rebate(wallet, signer).0,
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ClaimFeeRebate {},
+    )
+}
+
+// Pay the current guardian `amount` of SOL or `mint` from the vault every `interval`
+// seconds, starting now (requires both owner and guardian); replacing the fee forfeits
+// unclaimed periods, and the fee lapses once the guardian is changed
+pub fn set_guardian_fee(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    amount: u64,
+    mint: Option<Pubkey>,
+    interval: i64,
+) -> Instruction {
+    build(
+        accounts::SetGuardianFee {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            guardian_fee: pda::guardian_fee(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetGuardianFee {
+            amount,
+            mint,
+            interval,
+        },
+    )
+}
+
+// Stop paying the guardian fee (requires both owner and guardian); the rent goes back to
+// the vault
+pub fn remove_guardian_fee(wallet: &Pubkey, owner: &Pubkey, guardian: &Pubkey) -> Instruction {
+    build(
+        accounts::RemoveGuardianFee {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            guardian_fee: pda::guardian_fee(wallet).0,
+            vault: pda::vault(wallet).0,
+        },
+        instruction::RemoveGuardianFee {},
+    )
+}
+
+// Claim the guardian fee for every full interval elapsed since the last claim (guardian
+// alone); it is paid from the vault like a Solana Pay payment with the fee account as
+// the reference
+pub fn claim_guardian_fee(
+    wallet: &Pubkey,
+    guardian: &Pubkey,
+    mint: Option<Pubkey>,
+    vault_token_account: Option<Pubkey>,
+    guardian_token_account: Option<Pubkey>,
+    token_program: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::ClaimGuardianFee {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            guardian: *guardian,
+            guardian_fee: pda::guardian_fee(wallet).0,
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            mint,
+            vault_token_account,
+            guardian_token_account,
+            token_program,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ClaimGuardianFee {},
+    )
+}
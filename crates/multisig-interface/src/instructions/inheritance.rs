@@ -0,0 +1,148 @@
+// Inheritance
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+
+use super::{build, build_with_remaining};
+use crate::{accounts, instruction, pda, BeneficiaryShare, ID};
+
+// Name the beneficiaries who inherit the wallet once neither owner nor guardian has acted
+// for `inactivity_period` seconds, after a further `challenge_period` in which the owner
+// can cancel the claim (requires both owner and guardian); shares are in basis points and
+// must add up to 10,000, and `mints` lists the tokens distributed besides SOL. Replacing
+// the plan drops any claim in progress
+#[allow(clippy::too_many_arguments)]
+pub fn set_inheritance_plan(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    beneficiaries: Vec<BeneficiaryShare>,
+    mints: Vec<Pubkey>,
+    inactivity_period: i64,
+    challenge_period: i64,
+) -> Instruction {
+    build(
+        accounts::SetInheritancePlan {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            inheritance_plan: pda::inheritance_plan(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::SetInheritancePlan {
+            beneficiaries,
+            mints,
+            inactivity_period,
+            challenge_period,
+        },
+    )
+}
+
+// Remove the inheritance plan (requires both owner and guardian); the rent goes back to
+// the vault
+pub fn remove_inheritance_plan(wallet: &Pubkey, owner: &Pubkey, guardian: &Pubkey) -> Instruction {
+    build(
+        accounts::RemoveInheritancePlan {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            inheritance_plan: pda::inheritance_plan(wallet).0,
+            vault: pda::vault(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::RemoveInheritancePlan {},
+    )
+}
+
+// Start an inheritance claim (any beneficiary alone) once neither owner nor guardian has
+// acted for the plan's inactivity period; this opens the challenge period
+pub fn initiate_inheritance_claim(wallet: &Pubkey, beneficiary: &Pubkey) -> Instruction {
+    build(
+        accounts::InitiateInheritanceClaim {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            inheritance_plan: pda::inheritance_plan(wallet).0,
+            beneficiary: *beneficiary,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::InitiateInheritanceClaim {},
+    )
+}
+
+// Cancel an inheritance claim in progress (owner can do this alone)
+pub fn cancel_inheritance_claim(wallet: &Pubkey, owner: &Pubkey) -> Instruction {
+    build(
+        accounts::CancelInheritanceClaim {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            inheritance_plan: pda::inheritance_plan(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CancelInheritanceClaim {},
+    )
+}
+
+// Complete an inheritance claim by making the sole beneficiary of the plan the owner
+// (beneficiary alone, after the challenge period); plans with several beneficiaries are
+// settled with distribute_inheritance instead
+pub fn claim_inheritance(wallet: &Pubkey, beneficiary: &Pubkey) -> Instruction {
+    build(
+        accounts::ClaimInheritance {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            inheritance_plan: pda::inheritance_plan(wallet).0,
+            beneficiary: *beneficiary,
+            vault: pda::vault(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ClaimInheritance {},
+    )
+}
+
+// Distribute the vault's SOL (`mint` of None) or its balance of a listed token among the
+// beneficiaries by their shares (any beneficiary, after the challenge period); called once
+// per asset. The receiving accounts are passed as remaining accounts in plan order: the
+// beneficiaries themselves for SOL, their token accounts for the mint otherwise
+pub fn distribute_inheritance(
+    wallet: &Pubkey,
+    beneficiary: &Pubkey,
+    vault_token_account: Option<Pubkey>,
+    token_program: Option<Pubkey>,
+    mint: Option<Pubkey>,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build_with_remaining(
+        accounts::DistributeInheritance {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            inheritance_plan: pda::inheritance_plan(wallet).0,
+            beneficiary: *beneficiary,
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            mint,
+            vault_token_account,
+            token_program,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::DistributeInheritance { mint },
+        remaining_accounts,
+    )
+}
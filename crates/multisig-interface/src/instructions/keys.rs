@@ -0,0 +1,342 @@
+// Key management
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{system_program, sysvar};
+
+use super::build;
+use crate::{accounts, instruction, pda, ID};
+
+// Change the owner with both owner and guardian signatures
+pub fn change_owner(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    new_owner: Pubkey,
+    new_owner_signature: [u8; 64],
+) -> Instruction {
+    build(
+        accounts::ChangeOwner {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            sysvar_instructions: sysvar::instructions::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ChangeOwner {
+            new_owner,
+            new_owner_signature,
+        },
+    )
+}
+
+// Change the guardian with both owner and guardian signatures, and the new guardian's proof
+// of possession; passing the new guardian's registry entry additionally requires it to be
+// a vetted guardian service
+pub fn change_guardian(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    guardian_service: Option<Pubkey>,
+    new_guardian: Pubkey,
+) -> Instruction {
+    build(
+        accounts::ChangeGuardian {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            guardian_service,
+            sysvar_instructions: sysvar::instructions::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ChangeGuardian { new_guardian },
+    )
+}
+
+// Let the guardian rotate its own key without the owner signing: `new_key` proves
+// possession of itself with `proof_sig` over a ChangeGuardian off-chain message, verified
+// by the ed25519 program instruction right before this one. The rotation only takes
+// effect with complete_guardian_rotation after the security period, during which the
+// owner is notified and can veto it
+pub fn guardian_self_rotate(
+    wallet: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    new_key: Pubkey,
+    proof_sig: [u8; 64],
+) -> Instruction {
+    build(
+        accounts::GuardianSelfRotate {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            guardian: *guardian,
+            guardian_rotation: pda::guardian_rotation(wallet).0,
+            payer: *payer,
+            sysvar_instructions: sysvar::instructions::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::GuardianSelfRotate { new_key, proof_sig },
+    )
+}
+
+// Install the guardian's self-rotated key once the security period has passed without a
+// veto (anyone can send this); the rotation lapses if the guardian was replaced meanwhile
+pub fn complete_guardian_rotation(
+    wallet: &Pubkey,
+    cranker: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::CompleteGuardianRotation {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            cranker: *cranker,
+            guardian_rotation: pda::guardian_rotation(wallet).0,
+            payer: *payer,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CompleteGuardianRotation {},
+    )
+}
+
+// Veto the guardian's pending self-rotation (owner alone); the request is closed back to
+// its payer
+pub fn veto_guardian_rotation(wallet: &Pubkey, owner: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        accounts::VetoGuardianRotation {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            guardian_rotation: pda::guardian_rotation(wallet).0,
+            payer: *payer,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::VetoGuardianRotation {},
+    )
+}
+
+// Request a guardian backup change, taking effect after the security period; a new backup must
+// be registered by an earlier instruction in the same transaction
+pub fn change_guardian_backup(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    new_guardian_backup: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::ChangeGuardianBackup {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            guardian_backup_change: pda::guardian_backup_change(wallet).0,
+            payer: *payer,
+            sysvar_instructions: sysvar::instructions::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ChangeGuardianBackup {
+            new_guardian_backup,
+        },
+    )
+}
+
+// Apply a pending guardian backup change, sent by anyone; `payer` gets the request rent back
+pub fn complete_guardian_backup_change(
+    wallet: &Pubkey,
+    cranker: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::CompleteGuardianBackupChange {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            cranker: *cranker,
+            guardian_backup_change: pda::guardian_backup_change(wallet).0,
+            payer: *payer,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CompleteGuardianBackupChange {},
+    )
+}
+
+// Cancel a pending guardian backup change (owner or guardian); the request is closed back
+// to its payer
+pub fn cancel_guardian_backup_change(
+    wallet: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::CancelGuardianBackupChange {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            authority: *authority,
+            guardian_backup_change: pda::guardian_backup_change(wallet).0,
+            payer: *payer,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CancelGuardianBackupChange {},
+    )
+}
+
+// Replace owner, guardian and guardian backup together after a suspected key exposure,
+// with both owner and guardian signatures. Every new key proves possession of itself with
+// a registration for its role; the swap takes effect with complete_key_rotation after a
+// single security period, until which either party can cancel it
+pub fn rotate_all(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    new_owner: Pubkey,
+    new_guardian: Pubkey,
+    new_backup: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::RotateAll {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            key_rotation: pda::key_rotation(wallet).0,
+            payer: *payer,
+            sysvar_instructions: sysvar::instructions::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::RotateAll {
+            new_owner,
+            new_guardian,
+            new_backup,
+        },
+    )
+}
+
+// Swap in the keys of a requested rotation once the security period has passed (anyone can
+// send this); the request lapses if the owner or guardian was replaced meanwhile
+pub fn complete_key_rotation(wallet: &Pubkey, cranker: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        accounts::CompleteKeyRotation {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            cranker: *cranker,
+            key_rotation: pda::key_rotation(wallet).0,
+            payer: *payer,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CompleteKeyRotation {},
+    )
+}
+
+// Cancel a pending rotation of all keys (owner or guardian); the request is closed back to
+// its payer
+pub fn cancel_key_rotation(wallet: &Pubkey, authority: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        accounts::CancelKeyRotation {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            authority: *authority,
+            key_rotation: pda::key_rotation(wallet).0,
+            payer: *payer,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CancelKeyRotation {},
+    )
+}
+
+// Record that the guardian's key shares were redistributed while its public key stayed
+// the same (an MPC resharing), from a `GuardianReshare` message the guardian signed
+// off-chain and the ed25519 program instruction right before this one verifies. Moving
+// the wallet to a new state voids every off-chain message signed before the resharing
+pub fn record_guardian_reshare(
+    wallet: &Pubkey,
+    guardian: &Pubkey,
+    guardian_signature: [u8; 64],
+) -> Instruction {
+    build(
+        accounts::RecordGuardianReshare {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            guardian: *guardian,
+            sysvar_instructions: sysvar::instructions::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::RecordGuardianReshare { guardian_signature },
+    )
+}
+
+// List a guardian service in the program-wide registry (signed by the guardian key); it
+// only counts as vetted once the program upgrade authority approves it
+pub fn register_guardian_service(
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    name: String,
+    metadata_uri: String,
+) -> Instruction {
+    build(
+        accounts::RegisterGuardianService {
+            guardian_service: pda::guardian_service(guardian).0,
+            guardian: *guardian,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::RegisterGuardianService { name, metadata_uri },
+    )
+}
+
+// Vet or revoke a registered guardian service (program upgrade authority only)
+pub fn approve_guardian_service(
+    guardian_service: &Pubkey,
+    authority: &Pubkey,
+    approved: bool,
+) -> Instruction {
+    build(
+        accounts::ApproveGuardianService {
+            guardian_service: *guardian_service,
+            authority: *authority,
+            program_data: pda::program_data(&ID).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ApproveGuardianService { approved },
+    )
+}
+
+// Remove a guardian service from the registry (signed by the guardian key); wallets
+// already guarded by it keep it as their guardian
+pub fn unregister_guardian_service(guardian: &Pubkey) -> Instruction {
+    build(
+        accounts::UnregisterGuardianService {
+            guardian_service: pda::guardian_service(guardian).0,
+            guardian: *guardian,
+        },
+        instruction::UnregisterGuardianService {},
+    )
+}
@@ -0,0 +1,78 @@
+// Multisig import
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+
+use super::build_with_remaining;
+use crate::{accounts, instruction, pda, ID};
+
+// Create a wallet from an existing SPL Token multisig the owner and guardian belong to
+#[allow(clippy::too_many_arguments)]
+pub fn migrate_from_spl_multisig(
+    payer: &Pubkey,
+    source_multisig: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    guardian_service: Option<Pubkey>,
+    index: u64,
+    security_period: Option<i64>,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let wallet = pda::wallet(payer, index).0;
+    build_with_remaining(
+        accounts::MigrateFromSplMultisig {
+            argent_account: wallet,
+            pending_transaction: pda::pending_transaction(&wallet).0,
+            wallet_state: pda::wallet_state(&wallet).0,
+            guardian_service,
+            source_multisig: *source_multisig,
+            owner: *owner,
+            guardian: *guardian,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::MigrateFromSplMultisig {
+            index,
+            security_period,
+        },
+        remaining_accounts,
+    )
+}
+
+// Create a wallet from an existing Squads v4 multisig the owner and guardian belong to
+#[allow(clippy::too_many_arguments)]
+pub fn migrate_from_squads(
+    payer: &Pubkey,
+    source_multisig: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    guardian_service: Option<Pubkey>,
+    index: u64,
+    security_period: Option<i64>,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let wallet = pda::wallet(payer, index).0;
+    build_with_remaining(
+        accounts::MigrateFromSquads {
+            argent_account: wallet,
+            pending_transaction: pda::pending_transaction(&wallet).0,
+            wallet_state: pda::wallet_state(&wallet).0,
+            guardian_service,
+            source_multisig: *source_multisig,
+            owner: *owner,
+            guardian: *guardian,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::MigrateFromSquads {
+            index,
+            security_period,
+        },
+        remaining_accounts,
+    )
+}
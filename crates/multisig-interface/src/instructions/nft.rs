@@ -0,0 +1,233 @@
+// NFTs
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{system_program, sysvar};
+
+use super::{build, build_with_remaining};
+use crate::{accounts, instruction, pda, ID};
+
+// Transfer an NFT out of the vault (requires both owner and guardian)
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_nft(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    mint: &Pubkey,
+    vault_token_account: &Pubkey,
+    destination_owner: &Pubkey,
+    destination_token: &Pubkey,
+    metadata: &Pubkey,
+    edition: &Pubkey,
+    payer: &Pubkey,
+    token_program: &Pubkey,
+    token_record: Option<Pubkey>,
+    destination_token_record: Option<Pubkey>,
+    authorization_rules_program: Option<Pubkey>,
+    authorization_rules: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::TransferNft {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            mint: *mint,
+            vault_token_account: *vault_token_account,
+            destination_owner: *destination_owner,
+            destination_token: *destination_token,
+            metadata: *metadata,
+            edition: *edition,
+            token_record,
+            destination_token_record,
+            authorization_rules_program,
+            authorization_rules,
+            payer: *payer,
+            system_program: system_program::ID,
+            sysvar_instructions: sysvar::instructions::ID,
+            token_program: *token_program,
+            associated_token_program: anchor_spl::associated_token::ID,
+            token_metadata_program: anchor_spl::metadata::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::TransferNft {},
+    )
+}
+
+// Transfer a compressed NFT owned by the vault (requires both owner and guardian)
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_compressed_nft(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    leaf_delegate: &Pubkey,
+    new_leaf_owner: &Pubkey,
+    tree_config: &Pubkey,
+    merkle_tree: &Pubkey,
+    log_wrapper: &Pubkey,
+    compression_program: &Pubkey,
+    root: [u8; 32],
+    data_hash: [u8; 32],
+    creator_hash: [u8; 32],
+    nonce: u64,
+    index: u32,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build_with_remaining(
+        accounts::TransferCompressedNft {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            leaf_delegate: *leaf_delegate,
+            new_leaf_owner: *new_leaf_owner,
+            tree_config: *tree_config,
+            merkle_tree: *merkle_tree,
+            log_wrapper: *log_wrapper,
+            compression_program: *compression_program,
+            bubblegum_program: multisig::BUBBLEGUM_PROGRAM_ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::TransferCompressedNft {
+            root,
+            data_hash,
+            creator_hash,
+            nonce,
+            index,
+        },
+        remaining_accounts,
+    )
+}
+
+// Transfer a Metaplex Core asset owned by the vault (requires both owner and guardian)
+pub fn transfer_core_asset(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    asset: &Pubkey,
+    new_owner: &Pubkey,
+    payer: &Pubkey,
+    collection: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::TransferCoreAsset {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            asset: *asset,
+            collection,
+            new_owner: *new_owner,
+            payer: *payer,
+            system_program: system_program::ID,
+            mpl_core_program: multisig::MPL_CORE_PROGRAM_ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::TransferCoreAsset {},
+    )
+}
+
+// Burn a Metaplex Core asset owned by the vault (requires both owner and guardian)
+pub fn burn_core_asset(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    asset: &Pubkey,
+    payer: &Pubkey,
+    collection: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::BurnCoreAsset {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            asset: *asset,
+            collection,
+            payer: *payer,
+            system_program: system_program::ID,
+            mpl_core_program: multisig::MPL_CORE_PROGRAM_ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::BurnCoreAsset {},
+    )
+}
+
+// Set the update delegate of a Metaplex Core asset whose update authority is the vault
+// (requires both owner and guardian)
+pub fn approve_core_update_delegate(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    asset: &Pubkey,
+    payer: &Pubkey,
+    collection: Option<Pubkey>,
+    delegate: Pubkey,
+) -> Instruction {
+    build(
+        accounts::ApproveCoreUpdateDelegate {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            asset: *asset,
+            collection,
+            payer: *payer,
+            system_program: system_program::ID,
+            mpl_core_program: multisig::MPL_CORE_PROGRAM_ID,
+        },
+        instruction::ApproveCoreUpdateDelegate { delegate },
+    )
+}
+
+// Revoke the update delegate of a Metaplex Core asset (requires both owner and guardian)
+pub fn revoke_core_update_delegate(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    asset: &Pubkey,
+    payer: &Pubkey,
+    collection: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::RevokeCoreUpdateDelegate {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            asset: *asset,
+            collection,
+            payer: *payer,
+            system_program: system_program::ID,
+            mpl_core_program: multisig::MPL_CORE_PROGRAM_ID,
+        },
+        instruction::RevokeCoreUpdateDelegate {},
+    )
+}
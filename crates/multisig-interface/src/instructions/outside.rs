@@ -0,0 +1,177 @@
+// Outside execution
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{system_program, sysvar};
+
+use super::build;
+use crate::{accounts, instruction, pda, RelayerConfig, ID};
+
+// Approve a transaction submitted by a relayer who pays the network fee
+pub fn execute_from_outside(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    relayer: &Pubkey,
+    fee_ledger: Option<Pubkey>,
+    data: Vec<u8>,
+    fee: u64,
+) -> Instruction {
+    build(
+        accounts::ExecuteFromOutside {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            pending_transaction: pda::pending_transaction(wallet).0,
+            paymaster_config: pda::paymaster_config(wallet).0,
+            relayer_registry: pda::relayer_registry(wallet).0,
+            vault: pda::vault(wallet).0,
+            relayer: *relayer,
+            system_program: system_program::ID,
+            fee_ledger,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ExecuteFromOutside { data, fee },
+    )
+}
+
+// Approve a relayed transaction with the guardian's approval payload, signed air-gapped
+// (e.g. from a QR code) and verified by an ed25519 program instruction right before this
+// one, instead of the guardian signing the relayed transaction; the payload also caps the
+// relayer's reimbursement
+#[allow(clippy::too_many_arguments)]
+pub fn execute_from_outside_with_guardian_payload(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    relayer: &Pubkey,
+    fee_ledger: Option<Pubkey>,
+    data: Vec<u8>,
+    fee: u64,
+    guardian_signature: [u8; 64],
+) -> Instruction {
+    build(
+        accounts::ExecuteFromOutsideWithGuardianPayload {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            guardian: *guardian,
+            pending_transaction: pda::pending_transaction(wallet).0,
+            paymaster_config: pda::paymaster_config(wallet).0,
+            relayer_registry: pda::relayer_registry(wallet).0,
+            vault: pda::vault(wallet).0,
+            relayer: *relayer,
+            sysvar_instructions: sysvar::instructions::ID,
+            system_program: system_program::ID,
+            fee_ledger,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ExecuteFromOutsideWithGuardianPayload {
+            data,
+            fee,
+            guardian_signature,
+        },
+    )
+}
+
+// Set the most a relayer can be reimbursed per relayed transaction, in lamports and
+// optionally in an accepted token (requires both owner and guardian)
+pub fn set_paymaster_config(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    max_fee_reimbursement: u64,
+    fee_mint: Option<Pubkey>,
+    max_token_fee: u64,
+) -> Instruction {
+    build(
+        accounts::SetPaymasterConfig {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            paymaster_config: pda::paymaster_config(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetPaymasterConfig {
+            max_fee_reimbursement,
+            fee_mint,
+            max_token_fee,
+        },
+    )
+}
+
+// Approve a relayed transaction and pay the relayer in the wallet's accepted fee token
+// from the vault's token account, for wallets that hold no SOL
+#[allow(clippy::too_many_arguments)]
+pub fn execute_from_outside_with_token_fee(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    fee_mint: &Pubkey,
+    vault_token_account: &Pubkey,
+    relayer_token_account: &Pubkey,
+    relayer: &Pubkey,
+    token_program: &Pubkey,
+    fee_ledger: Option<Pubkey>,
+    data: Vec<u8>,
+    fee: u64,
+) -> Instruction {
+    build(
+        accounts::ExecuteFromOutsideWithTokenFee {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            pending_transaction: pda::pending_transaction(wallet).0,
+            paymaster_config: pda::paymaster_config(wallet).0,
+            relayer_registry: pda::relayer_registry(wallet).0,
+            vault: pda::vault(wallet).0,
+            fee_mint: *fee_mint,
+            vault_token_account: *vault_token_account,
+            relayer_token_account: *relayer_token_account,
+            relayer: *relayer,
+            token_program: *token_program,
+            fee_ledger,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ExecuteFromOutsideWithTokenFee { data, fee },
+    )
+}
+
+// Replace the relayers allowed to be reimbursed for outside execution, with their daily
+// quotas (requires both owner and guardian); spending counters restart
+pub fn set_relayers(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    relayers: Vec<RelayerConfig>,
+) -> Instruction {
+    build(
+        accounts::SetRelayers {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            relayer_registry: pda::relayer_registry(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetRelayers { relayers },
+    )
+}
@@ -0,0 +1,372 @@
+// Payments
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{system_program, sysvar};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+
+use super::build;
+use crate::{accounts, instruction, pda, BudgetAsset, ID};
+
+// Cap what payments (execute_payment, approve_payment and execute_typed_intent) can take
+// out of the vault each day across assets (requires both owner and guardian): each payment
+// is valued in USD (6 decimals) at execution time and counted against one shared
+pub fn set_payment_budget(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    daily_limit_usd: u64,
+    assets: Vec<BudgetAsset>,
+) -> Instruction {
+    build(
+        accounts::SetPaymentBudget {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            payment_budget: pda::payment_budget(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetPaymentBudget {
+            daily_limit_usd,
+            assets,
+        },
+    )
+}
+
+// Drop the payment budget (requires both owner and guardian); the rent goes back to the
+// vault
+pub fn remove_payment_budget(wallet: &Pubkey, owner: &Pubkey, guardian: &Pubkey) -> Instruction {
+    build(
+        accounts::RemovePaymentBudget {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            payment_budget: pda::payment_budget(wallet).0,
+            vault: pda::vault(wallet).0,
+        },
+        instruction::RemovePaymentBudget {},
+    )
+}
+
+// Pay a Solana Pay transfer request from the vault; `token` is the mint and its token
+// program for token payments, `None` for SOL. No price feed is passed, so payments in assets
+// a payment budget prices through a feed need `build` instead
+pub fn execute_payment(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    recipient: &Pubkey,
+    reference: Pubkey,
+    amount: u64,
+    token: Option<(Pubkey, Pubkey)>,
+) -> Instruction {
+    let vault = pda::vault(wallet).0;
+    let token_accounts = token.map(|(mint, token_program)| {
+        (
+            mint,
+            get_associated_token_address_with_program_id(&vault, &mint, &token_program),
+            get_associated_token_address_with_program_id(recipient, &mint, &token_program),
+            token_program,
+        )
+    });
+    build(
+        accounts::ExecutePayment {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            payment_budget: pda::payment_budget(wallet).0,
+            price_feed: None,
+            recipient: *recipient,
+            reference,
+            mint: token_accounts.map(|accounts| accounts.0),
+            vault_token_account: token_accounts.map(|accounts| accounts.1),
+            recipient_token_account: token_accounts.map(|accounts| accounts.2),
+            token_program: token_accounts.map(|accounts| accounts.3),
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ExecutePayment {
+            reference,
+            amount,
+            mint: token.map(|(mint, _)| mint),
+        },
+    )
+}
+
+// Set the categories payment proposals are labelled with for treasury reporting, e.g.
+// "payroll", "vendor", "investment" (requires both owner and guardian); with `required`,
+// proposals without one of them are rejected
+pub fn set_label_policy(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    required: bool,
+    categories: Vec<String>,
+) -> Instruction {
+    build(
+        accounts::SetLabelPolicy {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            label_policy: pda::label_policy(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetLabelPolicy {
+            required,
+            categories,
+        },
+    )
+}
+
+// Propose a Solana Pay payment from the vault with the owner's signature alone; the
+// proposal records the balance changes it will make so the guardian's wallet can show
+// them from chain data before approving. `category` labels it for treasury reporting
+// and must satisfy the wallet's label policy, if any
+#[allow(clippy::too_many_arguments)]
+pub fn propose_payment(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    recipient: &Pubkey,
+    payer: &Pubkey,
+    reference: Pubkey,
+    amount: u64,
+    mint: Option<Pubkey>,
+    category: String,
+) -> Instruction {
+    build(
+        accounts::ProposePayment {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            vault: pda::vault(wallet).0,
+            recipient: *recipient,
+            mint,
+            label_policy: pda::label_policy(wallet).0,
+            payment_proposal: pda::payment_proposal(wallet, &reference).0,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ProposePayment {
+            reference,
+            amount,
+            mint,
+            category,
+        },
+    )
+}
+
+// Approve and send a proposed payment with the guardian's signature; the proposal is
+// closed back to its payer
+#[allow(clippy::too_many_arguments)]
+pub fn approve_payment(
+    wallet: &Pubkey,
+    guardian: &Pubkey,
+    reference: &Pubkey,
+    recipient: &Pubkey,
+    payer: &Pubkey,
+    price_feed: Option<Pubkey>,
+    mint: Option<Pubkey>,
+    vault_token_account: Option<Pubkey>,
+    recipient_token_account: Option<Pubkey>,
+    token_program: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::ApprovePayment {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            guardian: *guardian,
+            payment_proposal: pda::payment_proposal(wallet, reference).0,
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            payment_budget: pda::payment_budget(wallet).0,
+            price_feed,
+            recipient: *recipient,
+            reference: *reference,
+            mint,
+            vault_token_account,
+            recipient_token_account,
+            token_program,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ApprovePayment {},
+    )
+}
+
+// Propose a transfer from the vault as a typed intent (owner alone), for guardians on
+// hardware wallets that can't parse transactions to this program: the canonical summary
+// of the transfer is rendered and stored on-chain, and the guardian approves it by
+// signing the summary's SHA-256 once its device has shown it
+pub fn propose_typed_intent(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    recipient: &Pubkey,
+    payer: &Pubkey,
+    reference: Pubkey,
+    amount: u64,
+    mint: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::ProposeTypedIntent {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            recipient: *recipient,
+            mint,
+            typed_intent: pda::typed_intent(wallet, &reference).0,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ProposeTypedIntent {
+            reference,
+            amount,
+            mint,
+        },
+    )
+}
+
+// Execute a typed intent with the guardian's signature over its summary hash, verified by
+// the ed25519 program instruction right before this one; anyone can submit it, and the
+// intent is closed back to its payer
+#[allow(clippy::too_many_arguments)]
+pub fn execute_typed_intent(
+    wallet: &Pubkey,
+    guardian: &Pubkey,
+    reference: &Pubkey,
+    recipient: &Pubkey,
+    payer: &Pubkey,
+    price_feed: Option<Pubkey>,
+    mint: Option<Pubkey>,
+    vault_token_account: Option<Pubkey>,
+    recipient_token_account: Option<Pubkey>,
+    token_program: Option<Pubkey>,
+    guardian_signature: [u8; 64],
+) -> Instruction {
+    build(
+        accounts::ExecuteTypedIntent {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            guardian: *guardian,
+            typed_intent: pda::typed_intent(wallet, reference).0,
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            payment_budget: pda::payment_budget(wallet).0,
+            price_feed,
+            recipient: *recipient,
+            reference: *reference,
+            mint,
+            vault_token_account,
+            recipient_token_account,
+            token_program,
+            payer: *payer,
+            sysvar_instructions: sysvar::instructions::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ExecuteTypedIntent { guardian_signature },
+    )
+}
+
+// Withdraw a typed intent before the guardian's signature is used (owner alone)
+pub fn cancel_typed_intent(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    typed_intent: &Pubkey,
+    payer: &Pubkey,
+    _reference: Pubkey,
+) -> Instruction {
+    build(
+        accounts::CancelTypedIntent {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            typed_intent: *typed_intent,
+            payer: *payer,
+        },
+        instruction::CancelTypedIntent { _reference },
+    )
+}
+
+// Withdraw a proposed payment before the guardian approves it (owner alone)
+pub fn cancel_payment(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    payment_proposal: &Pubkey,
+    payer: &Pubkey,
+    _reference: Pubkey,
+) -> Instruction {
+    build(
+        accounts::CancelPayment {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            payment_proposal: *payment_proposal,
+            payer: *payer,
+        },
+        instruction::CancelPayment { _reference },
+    )
+}
+
+// Amend a proposed payment in one step (owner alone): the proposal under `old_reference`
+// is closed and a new one under `reference` takes its place with the same category and
+// rent payer, so the guardian can never approve both the original and the amendment
+#[allow(clippy::too_many_arguments)]
+pub fn replace_payment(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    recipient: &Pubkey,
+    payer: &Pubkey,
+    old_reference: Pubkey,
+    reference: Pubkey,
+    amount: u64,
+    mint: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::ReplacePayment {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            vault: pda::vault(wallet).0,
+            recipient: *recipient,
+            mint,
+            old_payment_proposal: pda::payment_proposal(wallet, &old_reference).0,
+            payment_proposal: pda::payment_proposal(wallet, &reference).0,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ReplacePayment {
+            old_reference,
+            reference,
+            amount,
+            mint,
+        },
+    )
+}
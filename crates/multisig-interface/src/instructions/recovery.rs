@@ -0,0 +1,119 @@
+// Quorum recovery
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{system_program, sysvar};
+
+use super::{build, build_with_remaining};
+use crate::{accounts, instruction, pda, ID};
+
+// Opt in to quorum recovery (requires both owner and guardian): the guardian together with
+// `threshold` of the recovery contacts can replace the owner after `delay` seconds, which
+// must be shorter than the security period of a guardian-alone owner escape. Every contact
+// proves possession of its key
+pub fn set_recovery_policy(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    contacts: Vec<Pubkey>,
+    threshold: u8,
+    delay: i64,
+) -> Instruction {
+    build(
+        accounts::SetRecoveryPolicy {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            recovery_policy: pda::recovery_policy(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+            sysvar_instructions: sysvar::instructions::ID,
+        },
+        instruction::SetRecoveryPolicy {
+            contacts,
+            threshold,
+            delay,
+        },
+    )
+}
+
+// Opt out of quorum recovery (requires both owner and guardian); the rent goes back to
+// the vault
+pub fn remove_recovery_policy(wallet: &Pubkey, owner: &Pubkey, guardian: &Pubkey) -> Instruction {
+    build(
+        accounts::RemoveRecoveryPolicy {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            recovery_policy: pda::recovery_policy(wallet).0,
+            vault: pda::vault(wallet).0,
+        },
+        instruction::RemoveRecoveryPolicy {},
+    )
+}
+
+// Start replacing the owner with the guardian's signature and those of at least
+pub fn initiate_quorum_recovery(
+    wallet: &Pubkey,
+    guardian: &Pubkey,
+    new_owner: Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build_with_remaining(
+        accounts::InitiateQuorumRecovery {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            guardian: *guardian,
+            recovery_policy: pda::recovery_policy(wallet).0,
+            sysvar_instructions: sysvar::instructions::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::InitiateQuorumRecovery { new_owner },
+        remaining_accounts,
+    )
+}
+
+// Cancel a quorum recovery in progress (owner alone: an owner still able to sign
+// doesn't need recovering)
+pub fn cancel_quorum_recovery(wallet: &Pubkey, owner: &Pubkey) -> Instruction {
+    build(
+        accounts::CancelQuorumRecovery {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            recovery_policy: pda::recovery_policy(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CancelQuorumRecovery {},
+    )
+}
+
+// Install the owner of a quorum recovery once its delay has elapsed; anyone can send this
+pub fn complete_quorum_recovery(
+    wallet: &Pubkey,
+    cranker: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build_with_remaining(
+        accounts::CompleteQuorumRecovery {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            recovery_policy: pda::recovery_policy(wallet).0,
+            cranker: *cranker,
+            notification_config: pda::notification_config(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CompleteQuorumRecovery {},
+        remaining_accounts,
+    )
+}
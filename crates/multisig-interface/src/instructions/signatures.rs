@@ -0,0 +1,87 @@
+// Message signatures
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+
+use super::build;
+use crate::{accounts, instruction, pda};
+
+// Choose whether approved messages also need the guardian (requires both owner and guardian)
+pub fn set_message_policy(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    requires_guardian: bool,
+) -> Instruction {
+    build(
+        accounts::SetMessagePolicy {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+        },
+        instruction::SetMessagePolicy { requires_guardian },
+    )
+}
+
+// Approve an off-chain message (e.g. a sign-in message) by its hash so dApps can check it
+// with is_valid_signature; the owner signs, and the guardian too when the policy requires it
+pub fn approve_message(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    payer: &Pubkey,
+    guardian: Option<Pubkey>,
+    message_hash: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::ApproveMessage {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            guardian,
+            message_approval: pda::message_approval(wallet, &message_hash).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::ApproveMessage { message_hash },
+    )
+}
+
+// Withdraw a message approval (requires the owner); the rent goes back to the vault
+pub fn revoke_message(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    message_approval: &Pubkey,
+    _message_hash: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::RevokeMessage {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            message_approval: *message_approval,
+            vault: pda::vault(wallet).0,
+        },
+        instruction::RevokeMessage { _message_hash },
+    )
+}
+
+// Whether the wallet currently vouches for a message: it must have been approved by the
+// current owner, and by the current guardian if the policy requires it, so rotating a key
+// invalidates earlier approvals
+pub fn is_valid_signature(
+    wallet: &Pubkey,
+    message_approval: Option<Pubkey>,
+    _message_hash: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::IsValidSignature {
+            argent_account: *wallet,
+            message_approval,
+        },
+        instruction::IsValidSignature { _message_hash },
+    )
+}
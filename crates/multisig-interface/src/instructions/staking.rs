@@ -0,0 +1,174 @@
+// Staking
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{stake, system_program, sysvar};
+
+use super::build;
+use crate::{accounts, instruction, pda, ID};
+
+// Deposit vault SOL into an SPL stake pool (requires both owner and guardian)
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_stake_pool_sol(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    stake_pool: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    reserve_stake: &Pubkey,
+    pool_mint: &Pubkey,
+    pool_token_account: &Pubkey,
+    manager_fee_account: &Pubkey,
+    token_program: &Pubkey,
+    lamports: u64,
+    minimum_pool_tokens_out: u64,
+) -> Instruction {
+    build(
+        accounts::DepositStakePoolSol {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            stake_pool: *stake_pool,
+            stake_pool_withdraw_authority: *stake_pool_withdraw_authority,
+            reserve_stake: *reserve_stake,
+            pool_mint: *pool_mint,
+            pool_token_account: *pool_token_account,
+            manager_fee_account: *manager_fee_account,
+            system_program: system_program::ID,
+            token_program: *token_program,
+            stake_pool_program: multisig::SPL_STAKE_POOL_PROGRAM_ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::DepositStakePoolSol {
+            lamports,
+            minimum_pool_tokens_out,
+        },
+    )
+}
+
+// Withdraw SOL from an SPL stake pool into the vault (requires both owner and guardian)
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_stake_pool_sol(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    stake_pool: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    reserve_stake: &Pubkey,
+    pool_mint: &Pubkey,
+    pool_token_account: &Pubkey,
+    manager_fee_account: &Pubkey,
+    token_program: &Pubkey,
+    pool_tokens: u64,
+    minimum_lamports_out: u64,
+) -> Instruction {
+    build(
+        accounts::WithdrawStakePoolSol {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            stake_pool: *stake_pool,
+            stake_pool_withdraw_authority: *stake_pool_withdraw_authority,
+            reserve_stake: *reserve_stake,
+            pool_mint: *pool_mint,
+            pool_token_account: *pool_token_account,
+            manager_fee_account: *manager_fee_account,
+            clock: sysvar::clock::ID,
+            stake_history: sysvar::stake_history::ID,
+            stake_program: stake::program::ID,
+            token_program: *token_program,
+            stake_pool_program: multisig::SPL_STAKE_POOL_PROGRAM_ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::WithdrawStakePoolSol {
+            pool_tokens,
+            minimum_lamports_out,
+        },
+    )
+}
+
+// Let anyone compound the wallet's native stake between `min_lamports` and `max_lamports`
+// at a time with compound_rewards (owner alone, as the lamports never leave accounts the
+// vault controls)
+pub fn set_stake_compounding(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    payer: &Pubkey,
+    min_lamports: u64,
+    max_lamports: u64,
+) -> Instruction {
+    build(
+        accounts::SetStakeCompounding {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            stake_compounding: pda::stake_compounding(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetStakeCompounding {
+            min_lamports,
+            max_lamports,
+        },
+    )
+}
+
+// Stop stake compounding (owner alone); the rent goes back to the vault. A staged
+// delegation stays delegated until the vault merges or withdraws it
+pub fn remove_stake_compounding(wallet: &Pubkey, owner: &Pubkey) -> Instruction {
+    build(
+        accounts::RemoveStakeCompounding {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            stake_compounding: pda::stake_compounding(wallet).0,
+            vault: pda::vault(wallet).0,
+        },
+        instruction::RemoveStakeCompounding {},
+    )
+}
+
+// Compound a vault stake account's undelegated lamports (e.g. MEV tips or top-ups) into
+// its delegation (anyone can crank this). The stake program only merges active stake into
+// active stake, so this takes two calls an epoch apart: the first moves the lamports, up
+// to the configured maximum, into a staging stake account derived from the vault and
+// delegates it to the same validator; once that delegation is active, the next call
+// merges it back into the stake account
+pub fn compound_rewards(
+    wallet: &Pubkey,
+    stake_account: &Pubkey,
+    staging_stake_account: &Pubkey,
+    vote_account: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::CompoundRewards {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            stake_compounding: pda::stake_compounding(wallet).0,
+            vault: pda::vault(wallet).0,
+            stake_account: *stake_account,
+            staging_stake_account: *staging_stake_account,
+            vote_account: *vote_account,
+            clock: sysvar::clock::ID,
+            rent: sysvar::rent::ID,
+            stake_history: sysvar::stake_history::ID,
+            stake_config: stake::config::ID,
+            stake_program: stake::program::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CompoundRewards {},
+    )
+}
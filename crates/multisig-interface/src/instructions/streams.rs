@@ -0,0 +1,198 @@
+// Streams and vesting
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+
+use super::build;
+use crate::{accounts, instruction, pda, ID};
+
+// Stream `rate_per_second` of SOL (`mint` of None) or of a token from the vault to
+// `recipient` until `end_time` (requires both owner and guardian); the whole amount is
+// escrowed in the stream up front and accrues to the recipient second by second
+#[allow(clippy::too_many_arguments)]
+pub fn create_stream(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    stream_mint: Option<Pubkey>,
+    vault_token_account: Option<Pubkey>,
+    escrow_token_account: Option<Pubkey>,
+    token_program: Option<Pubkey>,
+    associated_token_program: Option<Pubkey>,
+    recipient: Pubkey,
+    rate_per_second: u64,
+    mint: Option<Pubkey>,
+    end_time: i64,
+) -> Instruction {
+    build(
+        accounts::CreateStream {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            asset_freeze: pda::asset_freeze(wallet).0,
+            stream: pda::stream(wallet, &recipient, mint.as_ref()).0,
+            vault: pda::vault(wallet).0,
+            stream_mint,
+            vault_token_account,
+            escrow_token_account,
+            token_program,
+            associated_token_program,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CreateStream {
+            recipient,
+            rate_per_second,
+            mint,
+            end_time,
+        },
+    )
+}
+
+// Pay the recipient what the stream has accrued so far (anyone can crank this, the
+// funds only ever go to the recipient)
+pub fn withdraw_stream(
+    wallet: &Pubkey,
+    stream: &Pubkey,
+    recipient: &Pubkey,
+    stream_mint: Option<Pubkey>,
+    escrow_token_account: Option<Pubkey>,
+    recipient_token_account: Option<Pubkey>,
+    token_program: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::WithdrawStream {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            stream: *stream,
+            recipient: *recipient,
+            stream_mint,
+            escrow_token_account,
+            recipient_token_account,
+            token_program,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::WithdrawStream {},
+    )
+}
+
+// Stop a stream (requires both owner and guardian): the recipient is paid what has accrued
+// and the unstreamed remainder, with the stream's rent, goes back to the vault
+#[allow(clippy::too_many_arguments)]
+pub fn cancel_stream(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    stream: &Pubkey,
+    recipient: &Pubkey,
+    stream_mint: Option<Pubkey>,
+    escrow_token_account: Option<Pubkey>,
+    recipient_token_account: Option<Pubkey>,
+    vault_token_account: Option<Pubkey>,
+    token_program: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::CancelStream {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            stream: *stream,
+            recipient: *recipient,
+            vault: pda::vault(wallet).0,
+            stream_mint,
+            escrow_token_account,
+            recipient_token_account,
+            vault_token_account,
+            token_program,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CancelStream {},
+    )
+}
+
+// Vest `amount` of the mint from the vault to `beneficiary` (requires both owner and
+// guardian): nothing unlocks before `cliff_period` has passed, then the amount unlocks
+// linearly until `duration` after creation. The tokens are escrowed up front
+#[allow(clippy::too_many_arguments)]
+pub fn create_vesting(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    mint: &Pubkey,
+    vault_token_account: &Pubkey,
+    escrow_token_account: &Pubkey,
+    token_program: &Pubkey,
+    payer: &Pubkey,
+    beneficiary: Pubkey,
+    amount: u64,
+    cliff_period: i64,
+    duration: i64,
+) -> Instruction {
+    build(
+        accounts::CreateVesting {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vesting: pda::vesting(wallet, &beneficiary, mint).0,
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            mint: *mint,
+            vault_token_account: *vault_token_account,
+            escrow_token_account: *escrow_token_account,
+            token_program: *token_program,
+            associated_token_program: anchor_spl::associated_token::ID,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CreateVesting {
+            beneficiary,
+            amount,
+            cliff_period,
+            duration,
+        },
+    )
+}
+
+// Pay the beneficiary everything unlocked so far and not yet claimed
+pub fn claim_vested(
+    wallet: &Pubkey,
+    beneficiary: &Pubkey,
+    mint: &Pubkey,
+    escrow_token_account: &Pubkey,
+    beneficiary_token_account: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::ClaimVested {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            vesting: pda::vesting(wallet, beneficiary, mint).0,
+            beneficiary: *beneficiary,
+            mint: *mint,
+            escrow_token_account: *escrow_token_account,
+            beneficiary_token_account: *beneficiary_token_account,
+            vault: pda::vault(wallet).0,
+            token_program: *token_program,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ClaimVested {},
+    )
+}
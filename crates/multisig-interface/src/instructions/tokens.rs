@@ -0,0 +1,377 @@
+// Vault token accounts
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+
+use super::{build, build_with_remaining};
+use crate::{accounts, instruction, pda, ID};
+
+// Create the vault's associated token account for a mint (anyone can pay for it)
+pub fn create_associated_token_account(
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    vault_token_account: &Pubkey,
+    payer: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::CreateAssociatedTokenAccount {
+            argent_account: *wallet,
+            vault: pda::vault(wallet).0,
+            mint: *mint,
+            vault_token_account: *vault_token_account,
+            payer: *payer,
+            token_program: *token_program,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateAssociatedTokenAccount {},
+    )
+}
+
+// Same as create_associated_token_account but succeeds if the account already exists
+pub fn create_associated_token_account_idempotent(
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    vault_token_account: &Pubkey,
+    payer: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::CreateAssociatedTokenAccountIdempotent {
+            argent_account: *wallet,
+            vault: pda::vault(wallet).0,
+            mint: *mint,
+            vault_token_account: *vault_token_account,
+            payer: *payer,
+            token_program: *token_program,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateAssociatedTokenAccountIdempotent {},
+    )
+}
+
+// Approve a delegate on one of the vault's token accounts (requires both owner and guardian)
+#[allow(clippy::too_many_arguments)]
+pub fn approve_token_delegate(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    mint: &Pubkey,
+    vault_token_account: &Pubkey,
+    delegate: &Pubkey,
+    payer: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::ApproveTokenDelegate {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            mint: *mint,
+            vault_token_account: *vault_token_account,
+            delegate: *delegate,
+            delegation_registry: pda::delegation_registry(wallet).0,
+            payer: *payer,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ApproveTokenDelegate { amount },
+    )
+}
+
+// Revoke the delegate of one of the vault's token accounts
+pub fn revoke_token_delegate(
+    wallet: &Pubkey,
+    authority: &Pubkey,
+    vault_token_account: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::RevokeTokenDelegate {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            authority: *authority,
+            vault: pda::vault(wallet).0,
+            vault_token_account: *vault_token_account,
+            delegation_registry: pda::delegation_registry(wallet).0,
+            token_program: *token_program,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::RevokeTokenDelegate {},
+    )
+}
+
+// Revoke the delegates of all vault token accounts passed as remaining accounts
+pub fn revoke_all_token_delegates(
+    wallet: &Pubkey,
+    authority: &Pubkey,
+    token_program: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build_with_remaining(
+        accounts::RevokeAllTokenDelegates {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            authority: *authority,
+            vault: pda::vault(wallet).0,
+            delegation_registry: pda::delegation_registry(wallet).0,
+            token_program: *token_program,
+        },
+        instruction::RevokeAllTokenDelegates {},
+        remaining_accounts,
+    )
+}
+
+// Wrap SOL from the vault into the vault's wSOL token account (requires both owner and guardian)
+pub fn wrap_sol(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    wsol_account: &Pubkey,
+    payer: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::WrapSol {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            native_mint: anchor_spl::token::spl_token::native_mint::ID,
+            wsol_account: *wsol_account,
+            payer: *payer,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::WrapSol { amount },
+    )
+}
+
+// Unwrap all wSOL by closing the vault's wSOL token account back into the vault
+// (requires both owner and guardian)
+pub fn unwrap_sol(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    wsol_account: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::UnwrapSol {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            native_mint: anchor_spl::token::spl_token::native_mint::ID,
+            wsol_account: *wsol_account,
+            token_program: anchor_spl::token::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::UnwrapSol {},
+    )
+}
+
+// Mint tokens of a mint whose authority is the vault (requires both owner and guardian)
+pub fn mint_tokens(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::MintTokens {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            mint: *mint,
+            destination: *destination,
+            token_program: *token_program,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::MintTokens { amount },
+    )
+}
+
+// Burn tokens held by the vault (requires both owner and guardian)
+pub fn burn_tokens(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    mint: &Pubkey,
+    vault_token_account: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::BurnTokens {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            mint: *mint,
+            vault_token_account: *vault_token_account,
+            token_program: *token_program,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::BurnTokens { amount },
+    )
+}
+
+// Freeze a token account of a mint whose freeze authority is the vault
+// (requires both owner and guardian)
+pub fn freeze_token_account(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    mint: &Pubkey,
+    token_account: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::FreezeTokenAccount {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            mint: *mint,
+            token_account: *token_account,
+            token_program: *token_program,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::FreezeTokenAccount {},
+    )
+}
+
+// Thaw a token account frozen by the vault (requires both owner and guardian)
+pub fn thaw_token_account(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    mint: &Pubkey,
+    token_account: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::ThawTokenAccount {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            mint: *mint,
+            token_account: *token_account,
+            token_program: *token_program,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ThawTokenAccount {},
+    )
+}
+
+// Close empty vault token accounts passed as remaining accounts, returning their rent to the vault
+pub fn close_token_accounts(
+    wallet: &Pubkey,
+    authority: &Pubkey,
+    token_program: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build_with_remaining(
+        accounts::CloseTokenAccounts {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            authority: *authority,
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            token_program: *token_program,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CloseTokenAccounts {},
+        remaining_accounts,
+    )
+}
+
+// Burn the whole balance of a vault token account of a quarantined mint (owner can do this alone)
+pub fn burn_dust(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    vault_token_account: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::BurnDust {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            vault: pda::vault(wallet).0,
+            quarantine: pda::quarantine(wallet).0,
+            mint: *mint,
+            vault_token_account: *vault_token_account,
+            token_program: *token_program,
+        },
+        instruction::BurnDust {},
+    )
+}
+
+// Burn and close vault token accounts of quarantined mints (owner can do this alone)
+pub fn close_dust_accounts(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    token_program: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build_with_remaining(
+        accounts::CloseDustAccounts {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            vault: pda::vault(wallet).0,
+            quarantine: pda::quarantine(wallet).0,
+            token_program: *token_program,
+        },
+        instruction::CloseDustAccounts {},
+        remaining_accounts,
+    )
+}
@@ -0,0 +1,189 @@
+// Trading
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+
+use super::{build, build_with_remaining};
+use crate::{accounts, instruction, pda, ID};
+
+// Replace the list of swap router programs the wallet may swap through
+// (requires both owner and guardian)
+pub fn set_swap_routers(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    routers: Vec<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::SetSwapRouters {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            swap_router_allowlist: pda::swap_routers(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetSwapRouters { routers },
+    )
+}
+
+// Swap vault tokens through an allowlisted router (requires both owner and guardian)
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    router_program: &Pubkey,
+    destination_token_account: &Pubkey,
+    data: Vec<u8>,
+    minimum_amount_out: u64,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build_with_remaining(
+        accounts::Swap {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            swap_router_allowlist: pda::swap_routers(wallet).0,
+            router_program: *router_program,
+            destination_token_account: *destination_token_account,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::Swap {
+            data,
+            minimum_amount_out,
+        },
+        remaining_accounts,
+    )
+}
+
+// Place a standing order to sell `amount` of `sell_mint` for `buy_mint` at `min_price` or
+// better until `expiry` (requires both owner and guardian). The tokens are escrowed in the
+// order, which anyone can then fill through one of the wallet's swap routers once the
+// market reaches the price. `min_price` is in buy-token base units per sell-token base
+// unit, scaled by LIMIT_PRICE_SCALE
+#[allow(clippy::too_many_arguments)]
+pub fn create_limit_order(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    sell_token_mint: &Pubkey,
+    vault_token_account: &Pubkey,
+    escrow_token_account: &Pubkey,
+    token_program: &Pubkey,
+    payer: &Pubkey,
+    order_id: u64,
+    sell_mint: Pubkey,
+    buy_mint: Pubkey,
+    amount: u64,
+    min_price: u64,
+    expiry: i64,
+) -> Instruction {
+    build(
+        accounts::CreateLimitOrder {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            asset_freeze: pda::asset_freeze(wallet).0,
+            limit_order: pda::limit_order(wallet, order_id).0,
+            vault: pda::vault(wallet).0,
+            sell_token_mint: *sell_token_mint,
+            vault_token_account: *vault_token_account,
+            escrow_token_account: *escrow_token_account,
+            token_program: *token_program,
+            associated_token_program: anchor_spl::associated_token::ID,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CreateLimitOrder {
+            order_id,
+            sell_mint,
+            buy_mint,
+            amount,
+            min_price,
+            expiry,
+        },
+    )
+}
+
+// Fill a limit order, in full or in part, through an allowlisted swap router (anyone can
+// crank this). The router instruction accounts are passed as remaining accounts and the
+// order signs for its escrow; the fill stands only if what reached the vault for what
+// left the escrow meets the order's price. Once the escrow is empty the order is closed
+#[allow(clippy::too_many_arguments)]
+pub fn fill_limit_order(
+    wallet: &Pubkey,
+    limit_order: &Pubkey,
+    sell_token_mint: &Pubkey,
+    escrow_token_account: &Pubkey,
+    vault_buy_token_account: &Pubkey,
+    router_program: &Pubkey,
+    token_program: &Pubkey,
+    data: Vec<u8>,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build_with_remaining(
+        accounts::FillLimitOrder {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            limit_order: *limit_order,
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            sell_token_mint: *sell_token_mint,
+            escrow_token_account: *escrow_token_account,
+            vault_buy_token_account: *vault_buy_token_account,
+            swap_router_allowlist: pda::swap_routers(wallet).0,
+            router_program: *router_program,
+            token_program: *token_program,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::FillLimitOrder { data },
+        remaining_accounts,
+    )
+}
+
+// Withdraw a limit order (owner or guardian): whatever is left in its escrow goes back to
+// the vault, with the rent
+pub fn cancel_limit_order(
+    wallet: &Pubkey,
+    authority: &Pubkey,
+    limit_order: &Pubkey,
+    sell_token_mint: &Pubkey,
+    escrow_token_account: &Pubkey,
+    vault_token_account: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::CancelLimitOrder {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            authority: *authority,
+            limit_order: *limit_order,
+            vault: pda::vault(wallet).0,
+            sell_token_mint: *sell_token_mint,
+            escrow_token_account: *escrow_token_account,
+            vault_token_account: *vault_token_account,
+            token_program: *token_program,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CancelLimitOrder {},
+    )
+}
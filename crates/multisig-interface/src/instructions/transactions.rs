@@ -0,0 +1,413 @@
+// Transaction approval and execution
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{system_program, sysvar};
+
+use super::{build, build_with_remaining};
+use crate::{accounts, instruction, pda, ComputeBudgetRequest, VaultInstruction, ID};
+
+// Approve a transaction with both owner and guardian signatures
+pub fn execute(wallet: &Pubkey, owner: &Pubkey, guardian: &Pubkey, data: Vec<u8>) -> Instruction {
+    build(
+        accounts::Execute {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            guardian: *guardian,
+            pending_transaction: pda::pending_transaction(wallet).0,
+            guardian_approval: None,
+            history: None,
+            proposal_nonce: pda::proposal_nonce(wallet).0,
+            sysvar_instructions: sysvar::instructions::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::Execute { data },
+    )
+}
+
+// Propose a transaction from the guardian side, for the owner to approve
+pub fn propose_guardian_transaction(
+    wallet: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    data: Vec<u8>,
+    reason: String,
+) -> Instruction {
+    build(
+        accounts::ProposeGuardianTransaction {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            guardian: *guardian,
+            guardian_proposal: pda::guardian_proposal(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ProposeGuardianTransaction { data, reason },
+    )
+}
+
+// Approve the guardian's proposal of the transaction data hashing to `data_hash`; `payer`
+// is the one recorded in the proposal
+pub fn approve_guardian_proposal(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    payer: &Pubkey,
+    data_hash: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::ApproveGuardianProposal {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            pending_transaction: pda::pending_transaction(wallet).0,
+            guardian_proposal: pda::guardian_proposal(wallet).0,
+            payer: *payer,
+            history: None,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ApproveGuardianProposal { data_hash },
+    )
+}
+
+// Drop the guardian's proposal, by the owner rejecting it or the guardian withdrawing it;
+// the rent goes back to its payer
+pub fn reject_guardian_proposal(
+    wallet: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::RejectGuardianProposal {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            authority: *authority,
+            guardian_proposal: pda::guardian_proposal(wallet).0,
+            payer: *payer,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::RejectGuardianProposal {},
+    )
+}
+
+// Run `instructions` right away from the vault with both owner and guardian signatures;
+// nothing is stored, so the transaction either executes in full or not at all. Programs
+// and accounts are referenced by index into the remaining accounts, and the vault signs
+// wherever it appears. Under a writable allowlist, instructions may only write to the
+// vault, its token accounts and the allowlisted addresses
+pub fn execute_immediate(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    instructions: Vec<VaultInstruction>,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build_with_remaining(
+        accounts::ExecuteImmediate {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            writable_allowlist: pda::writable_allowlist(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ExecuteImmediate { instructions },
+        remaining_accounts,
+    )
+}
+
+// Restrict the accounts `execute_immediate` instructions may mark writable to the vault,
+// its token accounts and `addresses` (requires both owner and guardian), containing what
+// a complex approved interaction can touch
+pub fn set_writable_allowlist(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    addresses: Vec<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::SetWritableAllowlist {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            writable_allowlist: pda::writable_allowlist(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetWritableAllowlist { addresses },
+    )
+}
+
+// Lift the writable allowlist (requires both owner and guardian); the rent goes back to
+// the vault
+pub fn remove_writable_allowlist(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::RemoveWritableAllowlist {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            writable_allowlist: pda::writable_allowlist(wallet).0,
+            vault: pda::vault(wallet).0,
+        },
+        instruction::RemoveWritableAllowlist {},
+    )
+}
+
+// Sign an instruction of this program as the wallet account itself (requires both owner
+// and guardian), so the wallet can hold the guardian role of other wallets. Only the
+// instructions a guardian takes part in are signed, see `is_guardian_role_instruction`.
+pub fn act_as_guardian(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    data: Vec<u8>,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build_with_remaining(
+        accounts::ActAsGuardian {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ActAsGuardian { data },
+        remaining_accounts,
+    )
+}
+
+// Have every execute start by advancing `nonce_account`, a System nonce account whose
+// authority is the owner or guardian (requires both owner and guardian). Approvals can
+// then be signed as durable nonce transactions that outlive the blockhash while the other
+// signature is gathered, and alternatives pre-signed against the same nonce can't all land.
+pub fn set_proposal_nonce(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    nonce_account: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::SetProposalNonce {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            proposal_nonce: pda::proposal_nonce(wallet).0,
+            nonce_account: *nonce_account,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetProposalNonce {},
+    )
+}
+
+// Stop requiring a nonce advance on execute (requires both owner and guardian); the rent
+// goes back to the vault and the nonce account itself is left alone
+pub fn remove_proposal_nonce(wallet: &Pubkey, owner: &Pubkey, guardian: &Pubkey) -> Instruction {
+    build(
+        accounts::RemoveProposalNonce {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            proposal_nonce: pda::proposal_nonce(wallet).0,
+            vault: pda::vault(wallet).0,
+        },
+        instruction::RemoveProposalNonce {},
+    )
+}
+
+// Create a System program account funded by the vault and assigned to `program_owner`
+// (requires both owner and guardian), e.g. a stake or nonce account the wallet will manage.
+#[allow(clippy::too_many_arguments)]
+pub fn create_vault_account(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    new_account: &Pubkey,
+    lamports: u64,
+    space: u64,
+    program_owner: Pubkey,
+    seed: Option<String>,
+) -> Instruction {
+    build(
+        accounts::CreateVaultAccount {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            new_account: *new_account,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CreateVaultAccount {
+            lamports,
+            space,
+            program_owner,
+            seed,
+        },
+    )
+}
+
+// Request the compute unit limit and priority fee the pending transaction should be
+// executed with, or clear it (requires both owner and guardian)
+pub fn set_compute_budget(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    compute_budget: Option<ComputeBudgetRequest>,
+) -> Instruction {
+    build(
+        accounts::SetComputeBudget {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            pending_transaction: pda::pending_transaction(wallet).0,
+        },
+        instruction::SetComputeBudget { compute_budget },
+    )
+}
+
+// Give up on the pending transaction when its execution keeps failing, e.g. on an
+// always-failing transfer hook after it partly changed external state (requires both
+// owner and guardian): it is cleared as failed, with `reason` recorded in the event and
+// the history, so the wallet can approve the next one or be closed
+pub fn force_abandon_transaction(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    history: Option<Pubkey>,
+    reason: String,
+) -> Instruction {
+    build(
+        accounts::ForceAbandonTransaction {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            pending_transaction: pda::pending_transaction(wallet).0,
+            history,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ForceAbandonTransaction { reason },
+    )
+}
+
+// Approve, from the guardian's own transaction, the transaction whose data hashes to
+// `data_hash` until `expires_at`; the owner can then execute it without the guardian
+// signing live, as long as the wallet's approval TTL hasn't run out
+pub fn post_guardian_approval(
+    wallet: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    data_hash: [u8; 32],
+    expires_at: i64,
+) -> Instruction {
+    build(
+        accounts::PostGuardianApproval {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            guardian: *guardian,
+            guardian_approval: pda::guardian_approval(wallet, &data_hash).0,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::PostGuardianApproval {
+            data_hash,
+            expires_at,
+        },
+    )
+}
+
+// Post a guardian approval the guardian signed off-chain rather than as a transaction
+// signer, e.g. a threshold-signature (MPC) guardian service: the ed25519 program
+// instruction right before this one must verify `guardian_signature` over an
+// `ApproveTransaction` message for `data_hash` and `expires_at` on the wallet in its
+// current state. Anyone can relay it and pay the approval rent
+pub fn post_signed_guardian_approval(
+    wallet: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    data_hash: [u8; 32],
+    expires_at: i64,
+    guardian_signature: [u8; 64],
+) -> Instruction {
+    build(
+        accounts::PostSignedGuardianApproval {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            guardian: *guardian,
+            guardian_approval: pda::guardian_approval(wallet, &data_hash).0,
+            payer: *payer,
+            sysvar_instructions: sysvar::instructions::ID,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::PostSignedGuardianApproval {
+            data_hash,
+            expires_at,
+            guardian_signature,
+        },
+    )
+}
+
+// Withdraw an unused guardian approval, refunding its rent to the guardian
+pub fn revoke_guardian_approval(
+    wallet: &Pubkey,
+    guardian_approval: &Pubkey,
+    guardian: &Pubkey,
+    _data_hash: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::RevokeGuardianApproval {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            guardian_approval: *guardian_approval,
+            guardian: *guardian,
+        },
+        instruction::RevokeGuardianApproval { _data_hash },
+    )
+}
@@ -0,0 +1,213 @@
+// Program upgrades
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{bpf_loader_upgradeable, system_program, sysvar};
+
+use super::build;
+use crate::{accounts, instruction, pda, ID};
+
+// Propose upgrading a program to the code in `buffer`, committing to its hash
+// (requires both owner and guardian); the upgrade can run once the security period has
+// passed, and a new proposal for the same program restarts it
+#[allow(clippy::too_many_arguments)]
+pub fn propose_upgrade(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    target_program: &Pubkey,
+    payer: &Pubkey,
+    fee_ledger: Option<Pubkey>,
+    buffer: Pubkey,
+    code_hash: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::ProposeUpgrade {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            target_program: *target_program,
+            upgrade_proposal: pda::upgrade_proposal(wallet, target_program).0,
+            payer: *payer,
+            system_program: system_program::ID,
+            fee_ledger,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::ProposeUpgrade { buffer, code_hash },
+    )
+}
+
+// Upgrade the program implementation (requires both owner and guardian)
+pub fn upgrade(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    target_program: &Pubkey,
+    buffer: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::Upgrade {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            target_program: *target_program,
+            program_data: pda::program_data(target_program).0,
+            buffer: *buffer,
+            vault: pda::vault(wallet).0,
+            bpf_loader: bpf_loader_upgradeable::ID,
+            rent: sysvar::rent::ID,
+            clock: sysvar::clock::ID,
+            upgrade_proposal: pda::upgrade_proposal(wallet, target_program).0,
+            managed_programs: pda::managed_programs(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::Upgrade {},
+    )
+}
+
+// Withdraw a pending upgrade proposal with the owner's signature alone, as long as its
+// security period is running; once executable only a new dual-signed proposal replaces it
+pub fn cancel_upgrade(wallet: &Pubkey, owner: &Pubkey, target_program: &Pubkey) -> Instruction {
+    build(
+        accounts::CancelUpgrade {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            target_program: *target_program,
+            upgrade_proposal: pda::upgrade_proposal(wallet, target_program).0,
+            vault: pda::vault(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CancelUpgrade {},
+    )
+}
+
+// Transfer the upgrade authority of a program held by the wallet, or make the program
+// immutable with `None` (requires both owner and guardian)
+pub fn set_upgrade_authority(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    target_program: &Pubkey,
+    new_authority: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::SetUpgradeAuthority {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            target_program: *target_program,
+            program_data: pda::program_data(target_program).0,
+            new_authority,
+            bpf_loader: bpf_loader_upgradeable::ID,
+            managed_programs: pda::managed_programs(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::SetUpgradeAuthority { new_authority },
+    )
+}
+
+// Close a program buffer whose authority is the wallet and refund its rent to the vault
+// (requires both owner and guardian)
+pub fn close_buffer(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    buffer: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::CloseBuffer {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            buffer: *buffer,
+            vault: pda::vault(wallet).0,
+            bpf_loader: bpf_loader_upgradeable::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CloseBuffer {},
+    )
+}
+
+// Create the list of programs the wallet manages as upgrade authority
+pub fn create_managed_programs(wallet: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        accounts::CreateManagedPrograms {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            managed_programs: pda::managed_programs(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::CreateManagedPrograms {},
+    )
+}
+
+// Register a program the wallet is upgrade authority of, so it can be upgraded and its
+// authority transferred (requires both owner and guardian); the list grows as needed
+pub fn register_program(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    target_program: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::RegisterProgram {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            target_program: *target_program,
+            program_data: pda::program_data(target_program).0,
+            managed_programs: pda::managed_programs(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::RegisterProgram {},
+    )
+}
+
+// Stop managing a program (requires both owner and guardian); its authority is unchanged
+pub fn unregister_program(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    program: Pubkey,
+) -> Instruction {
+    build(
+        accounts::UnregisterProgram {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            managed_programs: pda::managed_programs(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::UnregisterProgram { program },
+    )
+}
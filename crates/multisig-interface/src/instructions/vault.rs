@@ -0,0 +1,287 @@
+// Vault outflows
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+
+use super::{build, build_with_remaining};
+use crate::{accounts, instruction, pda, SubVaultPolicy, ID};
+
+// Start the timelocked path of sweep_all to `destination` (owner alone); the sweep can run
+// without the guardian once the security period has passed, as the owner could escape the
+// guardian in that time anyway. The guardian sees the request as a SweepRequested event
+// and can cancel it until then
+pub fn request_sweep(wallet: &Pubkey, owner: &Pubkey, destination: &Pubkey) -> Instruction {
+    build(
+        accounts::RequestSweep {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            destination: *destination,
+            sweep_request: pda::sweep_request(wallet).0,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::RequestSweep {},
+    )
+}
+
+// Withdraw a sweep request (owner or guardian); the rent goes back to the owner
+pub fn cancel_sweep(wallet: &Pubkey, authority: &Pubkey, owner: &Pubkey) -> Instruction {
+    build(
+        accounts::CancelSweep {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            authority: *authority,
+            owner: *owner,
+            sweep_request: pda::sweep_request(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CancelSweep {},
+    )
+}
+
+// Move every asset out of the vault to `destination`, e.g. when migrating to a new wallet
+// or responding to a compromise: each token balance given as (vault token account, mint,
+// destination token account) remaining-account triples, then all SOL. Needs both owner
+// and guardian, or the owner alone with a sweep request to the same destination older
+// than the security period
+pub fn sweep_all(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    destination: &Pubkey,
+    token_program: &Pubkey,
+    sweep_request: Option<Pubkey>,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build_with_remaining(
+        accounts::SweepAll {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            guardian: *guardian,
+            sweep_request,
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            destination: *destination,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::SweepAll {},
+        remaining_accounts,
+    )
+}
+
+// Freeze one asset of the vault, the mint's tokens or SOL with `None`, while it is under
+// suspicion (guardian can do this alone); other assets keep moving normally. Core assets
+// are frozen by their address and compressed NFTs by their Bubblegum asset id
+pub fn freeze_asset(
+    wallet: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    mint: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::FreezeAsset {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            guardian: *guardian,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::FreezeAsset { mint },
+    )
+}
+
+// Unfreeze an asset (requires both owner and guardian); the freeze account is closed to
+// the vault once nothing is frozen
+pub fn unfreeze_asset(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    mint: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::UnfreezeAsset {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            asset_freeze: pda::asset_freeze(wallet).0,
+            vault: pda::vault(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::UnfreezeAsset { mint },
+    )
+}
+
+// Create a named sub-vault with its own balance and spending policy
+// (requires both owner and guardian)
+pub fn create_sub_vault(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    index: u8,
+    name: String,
+    policy: SubVaultPolicy,
+) -> Instruction {
+    build(
+        accounts::CreateSubVault {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            sub_vault_config: pda::sub_vault_config(wallet, index).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::CreateSubVault {
+            index,
+            name,
+            policy,
+        },
+    )
+}
+
+// Move SOL from the main vault into a sub-vault (requires both owner and guardian)
+pub fn fund_sub_vault(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    sub_vault_config: &Pubkey,
+    sub_vault: &Pubkey,
+    _index: u8,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::FundSubVault {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            sub_vault_config: *sub_vault_config,
+            vault: pda::vault(wallet).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            sub_vault: *sub_vault,
+            system_program: system_program::ID,
+        },
+        instruction::FundSubVault { _index, amount },
+    )
+}
+
+// Withdraw SOL from a sub-vault under its policy: dual-signed sub-vaults always need
+// the guardian, owner-limited sub-vaults only above their per-withdrawal limit, and
+// time-locked sub-vaults need the guardian and refuse any withdrawal before they unlock
+pub fn withdraw_sub_vault(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    destination: &Pubkey,
+    index: u8,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::WithdrawSubVault {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            guardian: *guardian,
+            sub_vault_config: pda::sub_vault_config(wallet, index).0,
+            sub_vault: pda::sub_vault(wallet, index).0,
+            asset_freeze: pda::asset_freeze(wallet).0,
+            destination: *destination,
+            system_program: system_program::ID,
+        },
+        instruction::WithdrawSubVault { index, amount },
+    )
+}
+
+// Move a sub-vault's whole balance back to the main vault (owner or guardian). A locked
+// time-locked sub-vault can only be swept within a security period after an escape
+// completed, so recovering the wallet also recovers its savings
+pub fn sweep_sub_vault(wallet: &Pubkey, authority: &Pubkey, index: u8) -> Instruction {
+    build(
+        accounts::SweepSubVault {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            authority: *authority,
+            sub_vault_config: pda::sub_vault_config(wallet, index).0,
+            vault: pda::vault(wallet).0,
+            sub_vault: pda::sub_vault(wallet, index).0,
+            system_program: system_program::ID,
+        },
+        instruction::SweepSubVault { index },
+    )
+}
+
+// Close an emptied sub-vault's configuration (requires both owner and guardian); the rent
+// goes back to the vault
+pub fn close_sub_vault(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    sub_vault_config: &Pubkey,
+    sub_vault: &Pubkey,
+    _index: u8,
+) -> Instruction {
+    build(
+        accounts::CloseSubVault {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            sub_vault_config: *sub_vault_config,
+            vault: pda::vault(wallet).0,
+            sub_vault: *sub_vault,
+        },
+        instruction::CloseSubVault { _index },
+    )
+}
+
+// Move SOL between two of the wallet's sub-vaults with the owner's signature alone; no
+// external party gains custody, so no policy allowance is consumed. The source must not be
+// locked, and the destination may not let the owner withdraw more alone than the source
+// does, so funds can't be walked out from under a stricter policy
+pub fn internal_transfer(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    from_index: u8,
+    to_index: u8,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::InternalTransfer {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            from_config: pda::sub_vault_config(wallet, from_index).0,
+            to_config: pda::sub_vault_config(wallet, to_index).0,
+            from_sub_vault: pda::sub_vault(wallet, from_index).0,
+            to_sub_vault: pda::sub_vault(wallet, to_index).0,
+            system_program: system_program::ID,
+        },
+        instruction::InternalTransfer {
+            from_index,
+            to_index,
+            amount,
+        },
+    )
+}
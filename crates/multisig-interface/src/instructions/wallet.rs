@@ -0,0 +1,423 @@
+// Wallet lifecycle
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+
+use super::{build, build_with_remaining};
+use crate::{accounts, instruction, pda, ID};
+
+// Create the wallet of `payer` at `index`
+pub fn create(
+    payer: &Pubkey,
+    index: u64,
+    owner: Pubkey,
+    guardian: Pubkey,
+    security_period: Option<i64>,
+) -> Instruction {
+    let wallet = pda::wallet(payer, index).0;
+    build(
+        accounts::Create {
+            argent_account: wallet,
+            wallet_state: pda::wallet_state(&wallet).0,
+            pending_transaction: pda::pending_transaction(&wallet).0,
+            guardian_service: None,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::Create {
+            index,
+            owner,
+            guardian,
+            security_period,
+        },
+    )
+}
+
+// Create a wallet and approve its first transaction in one instruction
+// The wallet and vault addresses can be shared and funded before the account exists;
+// owner and guardian sign here, so their keys are taken from the signers
+pub fn create_and_execute(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    guardian_service: Option<Pubkey>,
+    index: u64,
+    security_period: Option<i64>,
+    data: Vec<u8>,
+) -> Instruction {
+    let wallet = pda::wallet(payer, index).0;
+    build(
+        accounts::CreateAndExecute {
+            argent_account: wallet,
+            pending_transaction: pda::pending_transaction(&wallet).0,
+            wallet_state: pda::wallet_state(&wallet).0,
+            guardian_service,
+            owner: *owner,
+            guardian: *guardian,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CreateAndExecute {
+            index,
+            security_period,
+            data,
+        },
+    )
+}
+
+// Start the list of wallets derived from this wallet with `create_derived_wallet`
+pub fn create_controller_index(controller: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        accounts::CreateControllerIndex {
+            controller: *controller,
+            controller_index: pda::controller_index(controller).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::CreateControllerIndex {},
+    )
+}
+
+// Create the wallet at `index` under `controller`, sharing its owner and guardian
+pub fn create_derived_wallet(
+    controller: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    index: u64,
+) -> Instruction {
+    let wallet = pda::wallet(controller, index).0;
+    build(
+        accounts::CreateDerivedWallet {
+            controller: *controller,
+            controller_state: pda::wallet_state(controller).0,
+            owner: *owner,
+            guardian: *guardian,
+            argent_account: wallet,
+            wallet_state: pda::wallet_state(&wallet).0,
+            pending_transaction: pda::pending_transaction(&wallet).0,
+            controller_index: pda::controller_index(controller).0,
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::CreateDerivedWallet { index },
+    )
+}
+
+// Lock the wallet for `duration` seconds (owner alone), e.g. while traveling: until the
+// lock runs out, or the guardian co-signs unlock_wallet, only escapes, recovery and
+// cancellations go through. Locking again can extend a running lock but never shorten it
+pub fn lock_wallet(wallet: &Pubkey, owner: &Pubkey, duration: i64) -> Instruction {
+    build(
+        accounts::LockWallet {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            owner: *owner,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::LockWallet { duration },
+    )
+}
+
+// Lift an owner lock before it runs out (requires both owner and guardian)
+pub fn unlock_wallet(wallet: &Pubkey, owner: &Pubkey, guardian: &Pubkey) -> Instruction {
+    build(
+        accounts::UnlockWallet {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::UnlockWallet {},
+    )
+}
+
+// Close the wallet (requires both owner and guardian)
+#[allow(clippy::too_many_arguments)]
+pub fn close_account(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    destination: &Pubkey,
+    token_program: &Pubkey,
+    delegation_registry: Option<Pubkey>,
+    swap_router_allowlist: Option<Pubkey>,
+    quarantine: Option<Pubkey>,
+    history: Option<Pubkey>,
+    inheritance_plan: Option<Pubkey>,
+    fee_ledger: Option<Pubkey>,
+    managed_programs: Option<Pubkey>,
+    viewer_list: Option<Pubkey>,
+    label_policy: Option<Pubkey>,
+    relayer_registry: Option<Pubkey>,
+    paymaster_config: Option<Pubkey>,
+    fee_rebate_policy: Option<Pubkey>,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build_with_remaining(
+        accounts::CloseAccount {
+            argent_account: *wallet,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            pending_transaction: pda::pending_transaction(wallet).0,
+            wallet_state: pda::wallet_state(wallet).0,
+            vault: pda::vault(wallet).0,
+            delegation_registry,
+            swap_router_allowlist,
+            quarantine,
+            history,
+            inheritance_plan,
+            fee_ledger,
+            managed_programs,
+            viewer_list,
+            label_policy,
+            relayer_registry,
+            paymaster_config,
+            fee_rebate_policy,
+            destination: *destination,
+            token_program: *token_program,
+            system_program: system_program::ID,
+        },
+        instruction::CloseAccount {},
+        remaining_accounts,
+    )
+}
+
+// Upgrade an account created by an older program release to the current layout
+pub fn migrate_account(wallet: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        accounts::MigrateAccount {
+            argent_account: *wallet,
+            pending_transaction: pda::pending_transaction(wallet).0,
+            wallet_state: pda::wallet_state(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateAccount {},
+    )
+}
+
+// Put the account header in front of an account a wallet derived before the header
+// existed, growing it by the header; anyone can pay for it. Wallets go through
+// migrate_account instead, which also upgrades their body
+pub fn migrate_derived_account(account: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        accounts::MigrateDerivedAccount {
+            account: *account,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateDerivedAccount {},
+    )
+}
+
+// Establish how many program accounts a migrated wallet has open (requires both owner and
+// guardian): the remaining accounts must be every account derived from the wallet, each
+// checked to be a program account recording the wallet behind the account header (older
+// ones go through migrate_derived_account first), except its pending transaction and hot
+// state, which close_account closes along with it. Until then the wallet can't be closed
+pub fn count_open_accounts(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build_with_remaining(
+        accounts::CountOpenAccounts {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+        },
+        instruction::CountOpenAccounts {},
+        remaining_accounts,
+    )
+}
+
+// Set the wallet display name and metadata URI with both owner and guardian signatures
+pub fn set_metadata(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    name: String,
+    metadata_uri: String,
+) -> Instruction {
+    build(
+        accounts::SetMetadata {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+        },
+        instruction::SetMetadata { name, metadata_uri },
+    )
+}
+
+// Set the opaque tag included in every event of the wallet (requires both owner and
+// guardian); all zeroes clears it
+pub fn set_notification_tag(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    notification_tag: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::SetNotificationTag {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+        },
+        instruction::SetNotificationTag { notification_tag },
+    )
+}
+
+// Withdraw lamports held by the wallet account above its rent-exempt minimum
+// (requires both owner and guardian); the account itself always stays rent exempt
+pub fn withdraw_excess_lamports(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::WithdrawExcessLamports {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            destination: *destination,
+        },
+        instruction::WithdrawExcessLamports {},
+    )
+}
+
+// Create the optional history ring buffer of a wallet (anyone can pay for it)
+pub fn create_history(wallet: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        accounts::CreateHistory {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            history: pda::history(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::CreateHistory {},
+    )
+}
+
+// Refresh the owner's or guardian's last activity without doing anything else, so an owner
+// who rarely moves funds keeps the inactivity-based paths (guardian backup promotion,
+// inheritance claims) from opening. The wallet state is left alone, so off-chain messages
+// signed for the current state stay valid
+pub fn heartbeat(wallet: &Pubkey, authority: &Pubkey) -> Instruction {
+    build(
+        accounts::Heartbeat {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            authority: *authority,
+        },
+        instruction::Heartbeat {},
+    )
+}
+
+// Report when the owner and guardian last signed an instruction for the wallet
+pub fn get_last_activity(wallet: &Pubkey) -> Instruction {
+    build(
+        accounts::GetLastActivity {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        },
+        instruction::GetLastActivity {},
+    )
+}
+
+// Designate the keys allowed to read the wallet's private details through
+// get_wallet_details (requires both owner and guardian); an empty list leaves only
+// the owner and guardian
+pub fn set_viewers(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    viewers: Vec<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::SetViewers {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+            viewer_list: pda::viewer_list(wallet).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetViewers { viewers },
+    )
+}
+
+// Return the wallet's labels and routing metadata to a designated viewer or one of the
+// wallet's signers signing the (simulated) call. Unlike the public views it is never emitted;
+// it gates this program's views only, as account data itself stays readable on-chain
+pub fn get_wallet_details(
+    wallet: &Pubkey,
+    viewer: &Pubkey,
+    viewer_list: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::GetWalletDetails {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            viewer: *viewer,
+            viewer_list,
+        },
+        instruction::GetWalletDetails {},
+    )
+}
+
+// Limit how long a posted guardian approval counts, whatever its own expiry, so intents
+// left unexecuted must be re-confirmed (requires both owner and guardian); 0 lifts the limit
+pub fn set_approval_ttl(
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    approval_ttl: i64,
+) -> Instruction {
+    build(
+        accounts::SetApprovalTtl {
+            argent_account: *wallet,
+            wallet_state: pda::wallet_state(wallet).0,
+            approvers: accounts::Approvers {
+                owner: *owner,
+                guardian: *guardian,
+            },
+        },
+        instruction::SetApprovalTtl { approval_ttl },
+    )
+}
@@ -0,0 +1,65 @@
+// Interface to the Argent multisig program
+//
+// Re-exports the program's account, event and argument types so integrators share its
+// serialization, and adds PDA helpers, account filters and instruction builders on top. With
+// the `cpi` feature, `cpi` exposes the Anchor CPI helpers for driving wallets from another
+// program.
+
+pub mod audit;
+pub mod filters;
+pub mod instructions;
+pub mod message;
+pub mod pda;
+pub mod solana_pay;
+
+pub use multisig::{
+    id, AccountHeader, AssetFreeze, BackupPromotion, BalanceChange, BeneficiaryShare, BudgetAsset,
+    ComputeBudgetRequest, ControllerIndex, DelegationRegistry, ErrorCode, EscapePreauthorization,
+    EscapeRehearsal, EscapeStats, EscapeType, FeeLedger, FeeRebate, FeeRebatePolicy,
+    FlaggedDeposit, FundingPolicy, GuardianApproval, GuardianBackupChange, GuardianFee,
+    GuardianProposal, GuardianRotation, GuardianService, History, HistoryEntry, InheritancePlan,
+    KeyRotation, LabelPolicy, LimitOrder, ManagedPrograms, MessageApproval, NotificationConfig,
+    PaymasterConfig, PaymentBudget, PaymentProposal, PendingTransaction, ProposalNonce, Quarantine,
+    QuarantinedMint, RecoveryPolicy, RelayerConfig, RelayerQuota, RelayerRegistry,
+    StakeCompounding, Stream, SubVaultConfig, SubVaultPolicy, SwapRouterAllowlist, SweepRequest,
+    TokenDelegation, TypedIntent, UpgradeProposal, VaultInstruction, Vesting, ViewerList,
+    WalletConfig, WalletDetails, WalletState, WritableAllowlist, ACCOUNT_LAYOUT_VERSION,
+    CURRENT_ACCOUNT_VERSION, ID,
+};
+
+// Anchor-generated instruction arguments and account lists, for instructions built by hand
+pub use multisig::{accounts, instruction};
+
+// Pure approval, threshold, timelock and message-encoding rules the program applies, for
+// predicting off-chain what it will accept
+pub use multisig::core;
+
+// Events emitted by the program
+pub mod events {
+    pub use multisig::{
+        AssetFrozen, BufferClosed, CompressedNftTransferred, CoreAssetBurned, CoreAssetTransferred,
+        DepositFlagged, DepositReceived, DepositRefunded, DerivedWalletCreated, EscapeCancelled,
+        EscapeCompleted, EscapeKeyCommitted, EscapePreauthorized, EscapeRehearsalCancelled,
+        EscapeRehearsalCompleted, EscapeRehearsalTriggered, EscapeTriggered, FeeRebateClaimed,
+        FeeReimbursed, FeeReport, GuardianApprovalPosted, GuardianBackupChangeCancelled,
+        GuardianBackupChangeRequested, GuardianBackupChanged, GuardianChanged, GuardianFeeClaimed,
+        GuardianProposalApproved, GuardianProposalRejected, GuardianReshared,
+        GuardianRotationRequested, GuardianRotationVetoed, GuardianServiceApproved,
+        GuardianServiceRegistered, GuardianTransactionProposed, InheritanceClaimCancelled,
+        InheritanceClaimInitiated, InheritanceClaimed, InheritanceDistributed,
+        InheritancePlanRemoved, InheritancePlanSet, KeyRotationCancelled, KeyRotationRequested,
+        KeysRotated, LastActivity, LimitOrderCancelled, LimitOrderCreated, LimitOrderFilled,
+        NftTransferred, OwnerChanged, PaymentProposed, PaymentReplaced, PaymentSent,
+        ProgramRegistered, ProgramUnregistered, ProgramUpgraded, RecoveryCancelled,
+        RecoveryInitiated, RentPaid, RewardsCompounded, RewardsStaged, SolUnwrapped, SolWrapped,
+        StakePoolDeposited, StakePoolWithdrawn, StreamCancelled, StreamCreated, StreamWithdrawn,
+        Swapped, SweepCancelled, SweepRequested, TokenAccountFrozen, TokenAccountsClosed,
+        TokenDelegateApproved, TokenDelegateRevoked, TokensBurned, TokensMinted,
+        TransactionAbandoned, TransactionApproved, TransactionExecuted, TypedIntentExecuted,
+        TypedIntentProposed, UpgradeAuthorityChanged, UpgradeCancelled, VaultAccountCreated,
+        VaultSwept, VestedClaimed, VestingCreated, WalletCreated, WalletLocked, WalletUnlocked,
+    };
+}
+
+#[cfg(feature = "cpi")]
+pub use multisig::cpi;
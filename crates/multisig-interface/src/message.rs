@@ -0,0 +1,118 @@
+// Off-chain messages
+//
+// Encoders for the messages keys sign off-chain for the program, in the canonical layout the
+// program decodes (see `multisig::message`). Sign the returned bytes with an ed25519 program
+// instruction placed before the instruction that checks them (right before it for
+// `change_owner`, `post_signed_guardian_approval`, `record_guardian_reshare` and
+// `execute_from_outside_with_guardian_payload`). Typed intents are signed differently: the
+// guardian signs `typed_intent_hash` of the summary stored with the intent.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::{hash, hashv};
+
+pub use multisig::message::{
+    ApprovalPayload, OffchainAction, OffchainMessage, TransferIntent, APPROVAL_PAYLOAD_DOMAIN,
+    APPROVAL_PAYLOAD_LEN, APPROVAL_PAYLOAD_VERSION, OFFCHAIN_MESSAGE_DOMAIN, OFFCHAIN_MESSAGE_LEN,
+    OFFCHAIN_MESSAGE_VERSION, TYPED_INTENT_HEADER,
+};
+
+// Message `new_owner` signs to accept `wallet` in `change_owner`, valid while the wallet is at
+// `state_version` and until `expiry_slot`
+pub fn change_owner(
+    wallet: &Pubkey,
+    new_owner: &Pubkey,
+    state_version: u64,
+    expiry_slot: u64,
+) -> Vec<u8> {
+    register_key(
+        wallet,
+        OffchainAction::ChangeOwner,
+        new_owner,
+        state_version,
+        expiry_slot,
+    )
+}
+
+// Message `key` signs to prove possession of itself before being installed on `wallet` for
+// `action` (a new guardian, guardian backup, recovery contact, or an owner installed by an
+// escape or recovery). Any ed25519 program instruction earlier in the transaction can carry it
+pub fn register_key(
+    wallet: &Pubkey,
+    action: OffchainAction,
+    key: &Pubkey,
+    state_version: u64,
+    expiry_slot: u64,
+) -> Vec<u8> {
+    OffchainMessage {
+        wallet: *wallet,
+        nonce: state_version,
+        action,
+        payload_hash: hash(key.as_ref()).to_bytes(),
+        expiry_slot,
+    }
+    .encode()
+}
+
+// Message the guardian signs, instead of signing a transaction, to approve on `wallet` the
+// transaction whose data hashes to `data_hash` until `expires_at`; posted with
+// `post_signed_guardian_approval`
+pub fn approve_transaction(
+    wallet: &Pubkey,
+    data_hash: &[u8; 32],
+    expires_at: i64,
+    state_version: u64,
+    expiry_slot: u64,
+) -> Vec<u8> {
+    OffchainMessage {
+        wallet: *wallet,
+        nonce: state_version,
+        action: OffchainAction::ApproveTransaction,
+        payload_hash: hashv(&[data_hash, &expires_at.to_le_bytes()]).to_bytes(),
+        expiry_slot,
+    }
+    .encode()
+}
+
+// Approval payload the guardian signs air-gapped, e.g. shown to a hardware guardian as a QR
+// code, approving on `wallet` the transaction whose data hashes to `data_hash` until
+// `expires_at` with a relayer reimbursed at most `max_fee` lamports. Accepted by
+// `execute_from_outside_with_guardian_payload` and, with any `max_fee`,
+// `post_signed_guardian_approval`. `ApprovalPayload::decode` reads it back on the signing device
+pub fn approval_payload(
+    wallet: &Pubkey,
+    data_hash: &[u8; 32],
+    expires_at: i64,
+    max_fee: u64,
+    state_version: u64,
+) -> Vec<u8> {
+    ApprovalPayload {
+        wallet: *wallet,
+        nonce: state_version,
+        data_hash: *data_hash,
+        expires_at,
+        max_fee,
+    }
+    .encode()
+}
+
+// Message the guardian signs after its key shares were redistributed, recorded with
+// `record_guardian_reshare`
+pub fn guardian_reshare(
+    wallet: &Pubkey,
+    guardian: &Pubkey,
+    state_version: u64,
+    expiry_slot: u64,
+) -> Vec<u8> {
+    register_key(
+        wallet,
+        OffchainAction::GuardianReshare,
+        guardian,
+        state_version,
+        expiry_slot,
+    )
+}
+
+// What the guardian signs to approve a typed intent: the SHA-256 of its stored summary
+pub fn typed_intent_hash(summary: &str) -> [u8; 32] {
+    hash(summary.as_bytes()).to_bytes()
+}
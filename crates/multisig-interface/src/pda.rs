@@ -0,0 +1,257 @@
+// Addresses derived by the program
+//
+// Each helper mirrors the seeds the program checks and returns the address with its bump.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+
+use crate::ID;
+
+// Wallet account of `creator` at `index`
+pub fn wallet(creator: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"argent", creator.as_ref(), &index.to_le_bytes()], &ID)
+}
+
+// Wallets derived from `controller` with `create_derived_wallet`
+pub fn controller_index(controller: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"controller", controller.as_ref()], &ID)
+}
+
+// The wallet's approved transaction awaiting execution
+pub fn pending_transaction(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"pending_tx", wallet.as_ref()], &ID)
+}
+
+// The wallet's hot state: state version, activity, audit head and open accounts
+pub fn wallet_state(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"wallet_state", wallet.as_ref()], &ID)
+}
+
+// Vault holding the wallet's SOL and owning its token accounts
+pub fn vault(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", wallet.as_ref()], &ID)
+}
+
+pub fn sub_vault(wallet: &Pubkey, index: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", wallet.as_ref(), &[index]], &ID)
+}
+
+pub fn sub_vault_config(wallet: &Pubkey, index: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"sub_vault_config", wallet.as_ref(), &[index]], &ID)
+}
+
+pub fn delegation_registry(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"delegations", wallet.as_ref()], &ID)
+}
+
+pub fn quarantine(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"quarantine", wallet.as_ref()], &ID)
+}
+
+pub fn swap_routers(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"swap_routers", wallet.as_ref()], &ID)
+}
+
+pub fn history(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"history", wallet.as_ref()], &ID)
+}
+
+pub fn paymaster_config(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"paymaster", wallet.as_ref()], &ID)
+}
+
+pub fn relayer_registry(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"relayers", wallet.as_ref()], &ID)
+}
+
+pub fn viewer_list(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"viewers", wallet.as_ref()], &ID)
+}
+
+pub fn managed_programs(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"programs", wallet.as_ref()], &ID)
+}
+
+pub fn upgrade_proposal(wallet: &Pubkey, program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"upgrade", wallet.as_ref(), program.as_ref()], &ID)
+}
+
+pub fn message_approval(wallet: &Pubkey, message_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"message", wallet.as_ref(), message_hash], &ID)
+}
+
+pub fn inheritance_plan(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"inheritance", wallet.as_ref()], &ID)
+}
+
+// Stream paying `recipient` from the wallet's vault, in SOL when `mint` is None
+pub fn stream(wallet: &Pubkey, recipient: &Pubkey, mint: Option<&Pubkey>) -> (Pubkey, u8) {
+    let mint_seed: &[u8] = mint.map_or(&[], |mint| mint.as_ref());
+    Pubkey::find_program_address(
+        &[b"stream", wallet.as_ref(), recipient.as_ref(), mint_seed],
+        &ID,
+    )
+}
+
+pub fn vesting(wallet: &Pubkey, beneficiary: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"vesting",
+            wallet.as_ref(),
+            beneficiary.as_ref(),
+            mint.as_ref(),
+        ],
+        &ID,
+    )
+}
+
+pub fn guardian_approval(wallet: &Pubkey, data_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"guardian_approval", wallet.as_ref(), data_hash], &ID)
+}
+
+pub fn payment_proposal(wallet: &Pubkey, reference: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"payment", wallet.as_ref(), reference.as_ref()], &ID)
+}
+
+pub fn backup_promotion(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"backup_promotion", wallet.as_ref()], &ID)
+}
+
+// Escape targets agreed on in advance; only exists while an escape is pre-authorized
+pub fn escape_preauthorization(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"escape_preauthorization", wallet.as_ref()], &ID)
+}
+
+pub fn sweep_request(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"sweep", wallet.as_ref()], &ID)
+}
+
+pub fn label_policy(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"label_policy", wallet.as_ref()], &ID)
+}
+
+pub fn guardian_fee(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"guardian_fee", wallet.as_ref()], &ID)
+}
+
+pub fn recovery_policy(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"recovery_policy", wallet.as_ref()], &ID)
+}
+
+// Daily USD budget of payments; only exists while payments are budgeted
+pub fn payment_budget(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"payment_budget", wallet.as_ref()], &ID)
+}
+
+// Accounts vault instructions may write to; only exists while writes are restricted
+pub fn writable_allowlist(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"writable_allowlist", wallet.as_ref()], &ID)
+}
+
+// Senders allowed to fund the wallet, with flagged deposits; only exists while funding is
+// restricted
+pub fn funding_policy(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"funding_policy", wallet.as_ref()], &ID)
+}
+
+// Bounds for compounding the wallet's native stake; only exists while compounding is enabled
+pub fn stake_compounding(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"stake_compounding", wallet.as_ref()], &ID)
+}
+
+// Staging account compound_rewards delegates a vault stake account's rewards through before
+// merging them back; a seed-derived account of the vault, not a PDA
+pub fn compounding_staging_account(vault: &Pubkey, stake_account: &Pubkey) -> Pubkey {
+    Pubkey::create_with_seed(
+        vault,
+        &multisig::compounding_seed(stake_account),
+        &anchor_lang::solana_program::stake::program::ID,
+    )
+    .expect("compounding seeds are 32 characters")
+}
+
+// Transaction the guardian proposed; only exists while it awaits the owner
+pub fn guardian_proposal(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"guardian_proposal", wallet.as_ref()], &ID)
+}
+
+// Standing order `order_id` of the wallet; its escrow is the order's associated token account
+pub fn limit_order(wallet: &Pubkey, order_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"limit_order", wallet.as_ref(), &order_id.to_le_bytes()],
+        &ID,
+    )
+}
+
+// Transfer the owner proposed as a typed intent under `reference`
+pub fn typed_intent(wallet: &Pubkey, reference: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"typed_intent", wallet.as_ref(), reference.as_ref()], &ID)
+}
+
+// Shadow state of an escape rehearsal; only exists while one is in progress
+pub fn escape_rehearsal(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"escape_rehearsal", wallet.as_ref()], &ID)
+}
+
+// Guardian key rotation the guardian requested alone; only exists during the owner's veto
+// window
+pub fn guardian_rotation(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"guardian_rotation", wallet.as_ref()], &ID)
+}
+
+// Guardian backup change requested by owner and guardian; only exists until it completes or
+// is cancelled
+pub fn guardian_backup_change(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"guardian_backup_change", wallet.as_ref()], &ID)
+}
+
+// Rotation of every wallet key requested by owner and guardian; only exists until it
+// completes or is cancelled
+pub fn key_rotation(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"key_rotation", wallet.as_ref()], &ID)
+}
+
+// Program notified of the wallet's escapes; only exists once set. Escape instructions notify
+// it when that program is appended as their first remaining account
+pub fn notification_config(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"notification_config", wallet.as_ref()], &ID)
+}
+
+// Durable nonce account every execute advances first; only exists once set
+pub fn proposal_nonce(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"proposal_nonce", wallet.as_ref()], &ID)
+}
+
+// Assets the guardian froze; only exists while something is frozen
+pub fn asset_freeze(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"asset_freeze", wallet.as_ref()], &ID)
+}
+
+pub fn fee_ledger(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"fee_ledger", wallet.as_ref()], &ID)
+}
+
+// Rate and monthly cap of the wallet's fee rebates; only exists once set
+pub fn fee_rebate_policy(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"fee_rebate_policy", wallet.as_ref()], &ID)
+}
+
+// Fees `signer` spent on the wallet's operations and was rebated
+pub fn fee_rebate(wallet: &Pubkey, signer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"fee_rebate", wallet.as_ref(), signer.as_ref()], &ID)
+}
+
+// Registry entry of a guardian service, shared by all wallets
+pub fn guardian_service(guardian: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"guardian_service", guardian.as_ref()], &ID)
+}
+
+// Authority the program signs its self-CPI events with
+pub fn event_authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"__event_authority"], &ID)
+}
+
+// Program data account of an upgradeable `program`, which holds its upgrade authority
+pub fn program_data(program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::ID)
+}
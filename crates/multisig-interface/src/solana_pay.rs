@@ -0,0 +1,120 @@
+// Solana Pay transfer requests
+//
+// Parses `solana:` transfer request URLs so a wallet front-end can pay them with
+// `instructions::execute_payment`, which carries the request's reference to the merchant.
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolanaPayError {
+    InvalidScheme,
+    InvalidPubkey,
+    InvalidAmount,
+    // A transfer request needs a reference for the payment to be found on-chain
+    MissingReference,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferRequest {
+    pub recipient: Pubkey,
+    // Decimal amount as written in the URL, in SOL or whole tokens
+    pub amount: Option<String>,
+    pub spl_token: Option<Pubkey>,
+    pub references: Vec<Pubkey>,
+    pub label: Option<String>,
+    pub message: Option<String>,
+    pub memo: Option<String>,
+}
+
+impl TransferRequest {
+    pub fn parse(url: &str) -> Result<Self, SolanaPayError> {
+        let rest = url
+            .strip_prefix("solana:")
+            .ok_or(SolanaPayError::InvalidScheme)?;
+        let (recipient, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+        let mut request = TransferRequest {
+            recipient: parse_pubkey(recipient)?,
+            amount: None,
+            spl_token: None,
+            references: Vec::new(),
+            label: None,
+            message: None,
+            memo: None,
+        };
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = percent_decode(value);
+            match key {
+                "amount" => request.amount = Some(value),
+                "spl-token" => request.spl_token = Some(parse_pubkey(&value)?),
+                "reference" => request.references.push(parse_pubkey(&value)?),
+                "label" => request.label = Some(value),
+                "message" => request.message = Some(value),
+                "memo" => request.memo = Some(value),
+                _ => {}
+            }
+        }
+        Ok(request)
+    }
+
+    // The reference passed to execute_payment
+    pub fn reference(&self) -> Result<Pubkey, SolanaPayError> {
+        self.references
+            .first()
+            .copied()
+            .ok_or(SolanaPayError::MissingReference)
+    }
+
+    // Amount in base units: lamports (9 decimals) for SOL, else the mint's decimals
+    pub fn amount_in_base_units(&self, decimals: u8) -> Result<u64, SolanaPayError> {
+        let amount = self
+            .amount
+            .as_deref()
+            .ok_or(SolanaPayError::InvalidAmount)?;
+        let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+        if fraction.len() > decimals as usize
+            || !whole
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Err(SolanaPayError::InvalidAmount);
+        }
+        let digits = format!("{whole}{fraction:0<width$}", width = decimals as usize);
+        digits.parse().map_err(|_| SolanaPayError::InvalidAmount)
+    }
+}
+
+fn parse_pubkey(value: &str) -> Result<Pubkey, SolanaPayError> {
+    Pubkey::from_str(value).map_err(|_| SolanaPayError::InvalidPubkey)
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (b'+', _) => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
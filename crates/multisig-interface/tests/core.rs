@@ -0,0 +1,118 @@
+use multisig_interface::core::{self, OffchainFields, OFFCHAIN_MESSAGE_LEN};
+use multisig_interface::message::{self, OffchainMessage};
+use anchor_lang::prelude::Pubkey;
+
+#[test]
+fn approvals_count_distinct_members_only() {
+    let members = [1u8, 2, 3];
+    assert_eq!(core::distinct_approvals(&members, [1, 1, 4, 3, 3]), vec![1, 3]);
+    assert!(core::distinct_approvals(&members, []).is_empty());
+    
+    for threshold in 0..=4usize {
+        assert_eq!(
+            core::is_valid_threshold(threshold, members.len()),
+            (1..=3).contains(&threshold)
+        );
+        for approvals in 0..=4usize {
+            assert_eq!(core::meets_threshold(approvals, threshold), approvals >= threshold);
+        }
+    }
+}
+
+#[test]
+fn timelocks_elapse_exactly_at_the_period() {
+    assert!(!core::has_elapsed(100, 50, 149));
+    assert!(core::has_elapsed(100, 50, 150));
+    assert!(core::has_elapsed(100, 0, 100));
+    // A span that overflows never counts as elapsed
+    assert!(!core::has_elapsed(i64::MIN, 0, i64::MAX));
+    assert!(!core::has_elapsed(0, i64::MAX, i64::MAX - 1));
+}
+
+#[test]
+fn limit_prices_hold_at_or_above_the_minimum() {
+    // 1.5 buy units per sell unit
+    let min_price = core::LIMIT_PRICE_SCALE / 2 * 3;
+    assert!(core::meets_limit_price(100, 150, min_price));
+    assert!(core::meets_limit_price(100, 151, min_price));
+    assert!(!core::meets_limit_price(100, 149, min_price));
+    // Selling nothing is never a fill, even for nothing
+    assert!(!core::meets_limit_price(0, 0, min_price));
+    // Full-range amounts don't overflow
+    assert!(core::meets_limit_price(u64::MAX, u64::MAX, core::LIMIT_PRICE_SCALE));
+    assert!(!core::meets_limit_price(u64::MAX, u64::MAX - 1, core::LIMIT_PRICE_SCALE));
+}
+
+#[test]
+fn token_amounts_format_without_trailing_zeros() {
+    assert_eq!(core::format_token_amount(1_500_000, 6), "1.5");
+    assert_eq!(core::format_token_amount(1_000_000, 6), "1");
+    assert_eq!(core::format_token_amount(42, 6), "0.000042");
+    assert_eq!(core::format_token_amount(0, 9), "0");
+    assert_eq!(core::format_token_amount(1234, 0), "1234");
+    assert_eq!(core::format_token_amount(u64::MAX, 9), "18446744073.709551615");
+}
+
+#[test]
+fn nonce_authority_is_read_from_initialized_nonce_accounts_only() {
+    let authority = [7u8; 32];
+    let mut data = vec![1, 0, 0, 0, 1, 0, 0, 0];
+    data.extend_from_slice(&authority);
+    data.resize(core::NONCE_ACCOUNT_LEN, 0);
+    assert_eq!(core::nonce_authority(&data), Some(authority));
+    
+    // Uninitialized, legacy version or not a nonce account at all
+    let mut uninitialized = data.clone();
+    uninitialized[4] = 0;
+    assert_eq!(core::nonce_authority(&uninitialized), None);
+    let mut legacy = data.clone();
+    legacy[0] = 0;
+    assert_eq!(core::nonce_authority(&legacy), None);
+    assert_eq!(core::nonce_authority(&data[..40]), None);
+}
+
+#[test]
+fn transaction_fees_count_signatures_and_the_budgeted_priority_fee() {
+    assert_eq!(core::transaction_fee(2, 0, 1_000_000), 2 * core::LAMPORTS_PER_SIGNATURE);
+    // 200k units at 1.5 lamports (1.5M micro-lamports) each, and a fraction rounds up
+    assert_eq!(core::transaction_fee(1, 200_000, 1_500_000), 5_000 + 300_000);
+    assert_eq!(core::transaction_fee(1, 3, 1), 5_001);
+    assert_eq!(core::transaction_fee(1, u32::MAX, u64::MAX), u64::MAX);
+    
+    let mut limit = vec![2];
+    limit.extend_from_slice(&200_000u32.to_le_bytes());
+    let mut price = vec![3];
+    price.extend_from_slice(&7u64.to_le_bytes());
+    assert_eq!(
+        core::parse_compute_budget(&limit),
+        Some(core::ComputeBudgetSetting::UnitLimit(200_000))
+    );
+    assert_eq!(
+        core::parse_compute_budget(&price),
+        Some(core::ComputeBudgetSetting::UnitPrice(7))
+    );
+    // Heap frames, truncated data and the like set neither
+    assert_eq!(core::parse_compute_budget(&[1, 0, 0, 1, 0]), None);
+    assert_eq!(core::parse_compute_budget(&limit[..4]), None);
+}
+
+#[test]
+fn offchain_fields_round_trip_and_match_the_program_encoding() {
+    let (wallet, new_owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let program_id = multisig_interface::ID.to_bytes();
+    let encoded = message::change_owner(&wallet, &new_owner, 9, 2_000);
+    
+    let fields = OffchainFields::decode(&encoded, &program_id).unwrap();
+    assert_eq!(fields.wallet, wallet.to_bytes());
+    assert_eq!(fields.nonce, 9);
+    assert_eq!(fields.expiry_slot, 2_000);
+    assert_eq!(fields.encode(&program_id), encoded);
+    assert_eq!(
+        OffchainMessage::decode(&encoded).unwrap().payload_hash,
+        fields.payload_hash
+    );
+    
+    // Another program's message, or any other length, doesn't decode
+    assert!(OffchainFields::decode(&encoded, &Pubkey::new_unique().to_bytes()).is_none());
+    assert!(OffchainFields::decode(&encoded[..OFFCHAIN_MESSAGE_LEN - 1], &program_id).is_none());
+}
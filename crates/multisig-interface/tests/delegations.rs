@@ -0,0 +1,74 @@
+use anchor_lang::prelude::Pubkey;
+use multisig::MAX_TOKEN_DELEGATIONS;
+use multisig_interface::{AccountHeader, DelegationRegistry, ErrorCode, TokenDelegation};
+
+fn registry() -> DelegationRegistry {
+    DelegationRegistry {
+        header: AccountHeader::CURRENT,
+        argent_account: Pubkey::new_unique(),
+        count: 0,
+        delegations: [TokenDelegation::default(); MAX_TOKEN_DELEGATIONS],
+    }
+}
+
+fn delegation(token_account: Pubkey, amount: u64) -> TokenDelegation {
+    TokenDelegation {
+        token_account,
+        mint: Pubkey::new_unique(),
+        delegate: Pubkey::new_unique(),
+        amount,
+        approved_at: 1_000,
+    }
+}
+
+#[test]
+fn a_token_account_keeps_its_latest_delegation_only() {
+    let mut registry = registry();
+    let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+    
+    registry.upsert(delegation(first, 100)).unwrap();
+    registry.upsert(delegation(second, 200)).unwrap();
+    registry.upsert(delegation(first, 300)).unwrap();
+    
+    assert_eq!(registry.count, 2);
+    let amounts: Vec<(Pubkey, u64)> = registry
+        .delegations()
+        .iter()
+        .map(|d| (d.token_account, d.amount))
+        .collect();
+    assert_eq!(amounts, vec![(second, 200), (first, 300)]);
+}
+
+#[test]
+fn removal_moves_the_last_delegation_into_the_gap() {
+    let mut registry = registry();
+    let token_accounts: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+    for token_account in &token_accounts {
+        registry.upsert(delegation(*token_account, 1)).unwrap();
+    }
+    
+    registry.remove(&token_accounts[0]);
+    // Unknown token accounts are ignored
+    registry.remove(&Pubkey::new_unique());
+    
+    let remaining: Vec<Pubkey> = registry.delegations().iter().map(|d| d.token_account).collect();
+    assert_eq!(remaining, vec![token_accounts[2], token_accounts[1]]);
+    assert_eq!(registry.delegations[2].token_account, Pubkey::default());
+}
+
+#[test]
+fn a_full_registry_still_replaces_existing_delegations() {
+    let mut registry = registry();
+    let token_accounts: Vec<Pubkey> =
+        (0..MAX_TOKEN_DELEGATIONS).map(|_| Pubkey::new_unique()).collect();
+    for token_account in &token_accounts {
+        registry.upsert(delegation(*token_account, 1)).unwrap();
+    }
+    
+    assert_eq!(
+        registry.upsert(delegation(Pubkey::new_unique(), 1)).unwrap_err(),
+        ErrorCode::TooManyDelegations.into()
+    );
+    registry.upsert(delegation(token_accounts[0], 2)).unwrap();
+    assert_eq!(registry.count as usize, MAX_TOKEN_DELEGATIONS);
+}
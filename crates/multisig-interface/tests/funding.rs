@@ -0,0 +1,57 @@
+use anchor_lang::prelude::Pubkey;
+use multisig::{MAX_FLAGGED_DEPOSITS, MAX_FLAGGED_TOKEN_DEPOSITS, MIN_FLAGGED_SOL_DEPOSIT};
+use multisig_interface::{AccountHeader, FundingPolicy};
+
+fn policy() -> FundingPolicy {
+    FundingPolicy {
+        header: AccountHeader::CURRENT,
+        argent_account: Pubkey::new_unique(),
+        allowed_senders: vec![],
+        flagged: vec![],
+        bump: 255,
+    }
+}
+
+#[test]
+fn throwaway_mints_cannot_crowd_out_sol_refunds() {
+    let mut policy = policy();
+    
+    for _ in 0..MAX_FLAGGED_TOKEN_DEPOSITS {
+        assert!(policy.flag(Pubkey::new_unique(), Some(Pubkey::new_unique()), 1));
+    }
+    assert!(!policy.flag(Pubkey::new_unique(), Some(Pubkey::new_unique()), 1));
+    
+    // The remaining slots stay available to SOL deposits
+    for _ in MAX_FLAGGED_TOKEN_DEPOSITS..MAX_FLAGGED_DEPOSITS {
+        assert!(policy.flag(Pubkey::new_unique(), None, MIN_FLAGGED_SOL_DEPOSIT));
+    }
+    assert!(!policy.flag(Pubkey::new_unique(), None, MIN_FLAGGED_SOL_DEPOSIT));
+    assert_eq!(policy.flagged.len(), MAX_FLAGGED_DEPOSITS);
+}
+
+#[test]
+fn refunding_a_token_deposit_frees_its_slot() {
+    let mut policy = policy();
+    let (sender, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    
+    assert!(policy.flag(sender, Some(mint), 5));
+    for _ in 1..MAX_FLAGGED_TOKEN_DEPOSITS {
+        assert!(policy.flag(Pubkey::new_unique(), Some(Pubkey::new_unique()), 1));
+    }
+    // Known totals keep growing once the token slots are taken
+    assert!(policy.flag(sender, Some(mint), 5));
+    assert_eq!(policy.take_flagged(&sender, Some(mint)), Some(10));
+    
+    assert!(policy.flag(Pubkey::new_unique(), Some(Pubkey::new_unique()), 1));
+}
+
+#[test]
+fn sol_dust_is_not_held_for_a_refund() {
+    let mut policy = policy();
+    let sender = Pubkey::new_unique();
+    
+    assert!(!policy.flag(sender, None, MIN_FLAGGED_SOL_DEPOSIT - 1));
+    assert!(policy.flag(sender, None, MIN_FLAGGED_SOL_DEPOSIT));
+    assert!(policy.flag(sender, None, 1));
+    assert_eq!(policy.take_flagged(&sender, None), Some(MIN_FLAGGED_SOL_DEPOSIT + 1));
+}
@@ -0,0 +1,59 @@
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, AnchorSerialize, Discriminator};
+use multisig::ACCOUNT_LAYOUT_VERSION;
+use multisig_interface::{AccountHeader, FundingPolicy};
+use anchor_lang::prelude::Pubkey;
+
+fn funding_policy_data(header: AccountHeader) -> Vec<u8> {
+    let mut data = FundingPolicy::DISCRIMINATOR.to_vec();
+    header.serialize(&mut data).unwrap();
+    Pubkey::new_unique().serialize(&mut data).unwrap();
+    Vec::<Pubkey>::new().serialize(&mut data).unwrap();
+    Vec::<u8>::new().serialize(&mut data).unwrap();
+    data.push(255);
+    data
+}
+
+#[test]
+fn header_round_trips_behind_the_discriminator() {
+    let data = funding_policy_data(AccountHeader::CURRENT);
+    
+    assert_eq!(data[8], ACCOUNT_LAYOUT_VERSION);
+    assert!(AccountHeader::is_present(&data));
+    let policy = FundingPolicy::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(policy.header, AccountHeader::CURRENT);
+}
+
+#[test]
+fn zeroed_header_reads_as_current() {
+    let header = AccountHeader::deserialize(&mut &[0u8; AccountHeader::LEN][..]).unwrap();
+    
+    assert_eq!(header, AccountHeader::CURRENT);
+}
+
+#[test]
+fn unknown_layout_version_fails_before_the_body_is_read() {
+    let data = funding_policy_data(AccountHeader {
+        layout_version: ACCOUNT_LAYOUT_VERSION + 1,
+        ..AccountHeader::CURRENT
+    });
+    
+    assert!(FundingPolicy::try_deserialize(&mut &data[..]).is_err());
+}
+
+#[test]
+fn unknown_feature_bits_fail_to_load() {
+    let data = funding_policy_data(AccountHeader {
+        features: 1 << 63,
+        ..AccountHeader::CURRENT
+    });
+    
+    assert!(FundingPolicy::try_deserialize(&mut &data[..]).is_err());
+}
+
+#[test]
+fn accounts_from_before_the_header_are_detected() {
+    let mut data = FundingPolicy::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&Pubkey::new_unique().to_bytes());
+    
+    assert!(!AccountHeader::is_present(&data));
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::Discriminator;
+use multisig_interface::{instruction, instructions, pda, ID};
+
+#[test]
+fn builders_derive_the_wallet_pdas() {
+    let wallet = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    
+    let ix = instructions::request_sweep(&wallet, &owner, &destination);
+    
+    assert_eq!(ix.program_id, ID);
+    assert_eq!(&ix.data[..8], instruction::RequestSweep::DISCRIMINATOR);
+    let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(keys[0], wallet);
+    assert_eq!(keys[1], pda::wallet_state(&wallet).0);
+    assert!(keys.contains(&pda::sweep_request(&wallet).0));
+    assert!(ix.accounts.iter().any(|meta| meta.pubkey == owner && meta.is_signer));
+}
+
+#[test]
+fn remaining_accounts_follow_the_context() {
+    let wallet = Pubkey::new_unique();
+    let remaining: Vec<AccountMeta> = (0..3)
+        .map(|_| AccountMeta::new(Pubkey::new_unique(), false))
+        .collect();
+    
+    let ix = instructions::sweep_all(
+        &wallet,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        None,
+        remaining.clone(),
+    );
+    
+    assert_eq!(ix.accounts[ix.accounts.len() - 3..], remaining[..]);
+}
+
+#[test]
+fn wallets_created_and_executed_live_at_the_payer_index() {
+    let payer = Pubkey::new_unique();
+    
+    let ix = instructions::create_and_execute(
+        &payer,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        None,
+        7,
+        None,
+        vec![1, 2, 3],
+    );
+    
+    assert_eq!(ix.accounts[0].pubkey, pda::wallet(&payer, 7).0);
+}
@@ -0,0 +1,99 @@
+use multisig_interface::message::{
+    self, ApprovalPayload, OffchainAction, OffchainMessage, TransferIntent, APPROVAL_PAYLOAD_LEN,
+    OFFCHAIN_MESSAGE_LEN, TYPED_INTENT_HEADER,
+};
+use anchor_lang::prelude::Pubkey;
+
+#[test]
+fn change_owner_message_decodes_strictly() {
+    let (wallet, new_owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let encoded = message::change_owner(&wallet, &new_owner, 7, 1_000);
+    assert_eq!(encoded.len(), OFFCHAIN_MESSAGE_LEN);
+    
+    let decoded = OffchainMessage::decode(&encoded).unwrap();
+    assert_eq!(decoded.wallet, wallet);
+    assert_eq!(decoded.nonce, 7);
+    assert_eq!(decoded.action, OffchainAction::ChangeOwner);
+    assert_eq!(decoded.expiry_slot, 1_000);
+    
+    // Trailing bytes, another version or an unknown action are rejected
+    let mut trailing = encoded.clone();
+    trailing.push(0);
+    assert!(OffchainMessage::decode(&trailing).is_err());
+    let mut version = encoded.clone();
+    version[15] = 2;
+    assert!(OffchainMessage::decode(&version).is_err());
+    let mut action = encoded;
+    action[15 + 1 + 32 + 32 + 8] = 0xff;
+    assert!(OffchainMessage::decode(&action).is_err());
+}
+
+#[test]
+fn approval_message_binds_data_hash_and_expiry() {
+    let wallet = Pubkey::new_unique();
+    let data_hash = [7u8; 32];
+    let encoded = message::approve_transaction(&wallet, &data_hash, 1_700_000_000, 3, 500);
+    
+    let decoded = OffchainMessage::decode(&encoded).unwrap();
+    assert_eq!(decoded.action, OffchainAction::ApproveTransaction);
+    let other_expiry = message::approve_transaction(&wallet, &data_hash, 1_700_000_001, 3, 500);
+    assert_ne!(
+        decoded.payload_hash,
+        OffchainMessage::decode(&other_expiry).unwrap().payload_hash
+    );
+}
+
+#[test]
+fn approval_payload_fits_a_qr_code_and_decodes_strictly() {
+    let wallet = Pubkey::new_unique();
+    let data_hash = [9u8; 32];
+    let encoded = message::approval_payload(&wallet, &data_hash, 1_700_000_000, 5_000, 4);
+    assert_eq!(encoded.len(), APPROVAL_PAYLOAD_LEN);
+    assert!(encoded.len() < 300);
+    
+    let decoded = ApprovalPayload::decode(&encoded).unwrap();
+    assert_eq!(decoded.wallet, wallet);
+    assert_eq!(decoded.nonce, 4);
+    assert_eq!(decoded.data_hash, data_hash);
+    assert_eq!(decoded.expires_at, 1_700_000_000);
+    assert_eq!(decoded.max_fee, 5_000);
+    
+    // Neither layout is read as the other
+    assert!(OffchainMessage::decode(&encoded).is_err());
+    let approval = message::approve_transaction(&wallet, &data_hash, 1_700_000_000, 4, 500);
+    assert!(ApprovalPayload::decode(&approval).is_err());
+    let mut version = encoded;
+    version[15] = 2;
+    assert!(ApprovalPayload::decode(&version).is_err());
+}
+
+#[test]
+fn transfer_intent_summaries_are_canonical() {
+    let (wallet, recipient, mint) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let intent = TransferIntent {
+        wallet,
+        nonce: 12,
+        recipient,
+        mint: None,
+        amount: 1_500_000_000,
+        decimals: 9,
+    };
+    let summary = intent.summary();
+    let program = multisig_interface::ID;
+    assert_eq!(
+        summary,
+        format!(
+            "{TYPED_INTENT_HEADER}\nProgram: {program}\nWallet: {wallet}\nNonce: 12\nSend: 1.5 SOL\nTo: {recipient}"
+        )
+    );
+    assert!(summary.is_ascii());
+    
+    // Token transfers name the mint, and any change of field changes the signed hash
+    let token = TransferIntent { mint: Some(mint), decimals: 6, ..intent.clone() };
+    assert!(token.summary().contains(&format!("Send: 1500 {mint}")));
+    let next = TransferIntent { nonce: 13, ..intent };
+    assert_ne!(
+        message::typed_intent_hash(&summary),
+        message::typed_intent_hash(&next.summary())
+    );
+}
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::Pubkey;
+use multisig_interface::{AccountHeader, Vesting};
+
+fn vesting(amount: u64, cliff_period: i64, duration: i64) -> Vesting {
+    Vesting {
+        header: AccountHeader::CURRENT,
+        argent_account: Pubkey::new_unique(),
+        beneficiary: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        amount,
+        start_time: 1_000,
+        cliff_period,
+        duration,
+        claimed: 0,
+        bump: 255,
+    }
+}
+
+#[test]
+fn nothing_vests_before_the_cliff() {
+    let vesting = vesting(1_000, 100, 400);
+    
+    assert_eq!(vesting.vested(1_000), 0);
+    assert_eq!(vesting.vested(1_099), 0);
+    // At the cliff the time since the start has unlocked at once
+    assert_eq!(vesting.vested(1_100), 250);
+}
+
+#[test]
+fn vests_linearly_until_the_duration() {
+    let vesting = vesting(1_000, 0, 400);
+    
+    assert_eq!(vesting.vested(1_000), 0);
+    assert_eq!(vesting.vested(1_001), 2);
+    assert_eq!(vesting.vested(1_200), 500);
+    assert_eq!(vesting.vested(1_400), 1_000);
+    assert_eq!(vesting.vested(9_999), 1_000);
+}
+
+#[test]
+fn claims_only_what_is_unlocked_and_not_yet_claimed() {
+    let mut vesting = vesting(1_000, 100, 400);
+    
+    vesting.claimed += vesting.claimable(1_200);
+    assert_eq!(vesting.claimed, 500);
+    assert_eq!(vesting.claimable(1_200), 0);
+    assert!(!vesting.fully_claimed());
+    
+    // Claiming everything once vested lets the schedule and its escrow close
+    vesting.claimed += vesting.claimable(1_400);
+    assert_eq!(vesting.claimed, 1_000);
+    assert_eq!(vesting.claimable(9_999), 0);
+    assert!(vesting.fully_claimed());
+}
@@ -0,0 +1,425 @@
+// ProgramTest fixture and scenario helpers for Argent wallets
+//
+// `WalletFixture` starts a bank with the program loaded natively, creates a wallet with fresh
+// owner and guardian keys, and drives it through the common flows. Clock helpers move
+// `unix_timestamp` forward so security-period dependent flows (escapes, timelocked upgrades)
+// can be tested without waiting. Token helpers write SPL mints and token accounts straight into
+// the bank. `bpf_program_test` loads the SBF build instead, for tests that measure compute units.
+
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::solana_program::program_pack::Pack;
+use multisig::{DelegationRegistry, EscapeType, TokenDelegation, WalletConfig, WalletState};
+use multisig_interface::message::{self, OffchainAction};
+use multisig_interface::{instructions, pda};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::{Account, AccountSharedData};
+use solana_sdk::clock::Clock;
+use solana_sdk::ed25519_instruction::new_ed25519_instruction_with_signature;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+// Security period the program applies when a wallet is created without one (7 days)
+pub const DEFAULT_SECURITY_PERIOD: i64 = 604800;
+
+// Anchor's entrypoint ties the accounts to one lifetime; the native processor hands out
+// shorter-lived slices, so they are leaked for the duration of the test
+fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    multisig::entry(program_id, accounts, data)
+}
+
+// A bank with the multisig program and no wallets, for tests that need extra programs or
+// accounts before starting
+pub fn program_test() -> ProgramTest {
+    ProgramTest::new("multisig", multisig::ID, processor!(process_instruction))
+}
+
+// A bank running `multisig.so` from `anchor build` (found through `SBF_OUT_DIR` or
+// `target/deploy`), where compute units are metered as on chain
+pub fn bpf_program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(true);
+    program_test.add_program("multisig", multisig::ID, None);
+    program_test
+}
+
+// The custom error code a failed transaction's instruction returned, to compare with
+// `u32::from(ErrorCode::..)`
+pub fn custom_error(result: Result<(), BanksClientError>) -> u32 {
+    match result.expect_err("transaction succeeded").unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => code,
+        error => panic!("transaction failed without a custom error: {error}"),
+    }
+}
+
+pub struct WalletFixture {
+    pub context: ProgramTestContext,
+    pub owner: Keypair,
+    pub guardian: Keypair,
+    // Wallet created by the context payer at index 0
+    pub wallet: Pubkey,
+}
+
+impl WalletFixture {
+    pub async fn new() -> Self {
+        Self::with_security_period(None).await
+    }
+    
+    pub async fn with_security_period(security_period: Option<i64>) -> Self {
+        Self::start(program_test(), security_period).await
+    }
+    
+    // Start `program_test` and create the fixture's wallet in it
+    pub async fn start(program_test: ProgramTest, security_period: Option<i64>) -> Self {
+        let context = program_test.start_with_context().await;
+        let owner = Keypair::new();
+        let guardian = Keypair::new();
+        let wallet = pda::wallet(&context.payer.pubkey(), 0).0;
+        
+        let mut fixture = Self {
+            context,
+            owner,
+            guardian,
+            wallet,
+        };
+        let create = instructions::create(
+            &fixture.context.payer.pubkey(),
+            0,
+            fixture.owner.pubkey(),
+            fixture.guardian.pubkey(),
+            security_period,
+        );
+        fixture
+            .process(&[create], &[])
+            .await
+            .expect("wallet creation failed");
+        fixture
+    }
+    
+    // Send `instructions` paid for by the context payer and signed by `signers`
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let blockhash = self.context.get_new_latest_blockhash().await?;
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        self.context.banks_client.process_transaction(transaction).await
+    }
+    
+    // Compute units `instructions` consume, simulated without landing the transaction
+    pub async fn compute_units(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> u64 {
+        let blockhash = self
+            .context
+            .get_new_latest_blockhash()
+            .await
+            .expect("failed to fetch a blockhash");
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await
+            .expect("failed to simulate the transaction");
+        if let Some(Err(error)) = simulation.result {
+            panic!("simulated transaction failed: {error}");
+        }
+        simulation
+            .simulation_details
+            .expect("simulation returned no details")
+            .units_consumed
+    }
+    
+    pub async fn wallet_account(&mut self) -> WalletConfig {
+        let account = self
+            .context
+            .banks_client
+            .get_account(self.wallet)
+            .await
+            .expect("failed to fetch the wallet")
+            .expect("wallet does not exist");
+        WalletConfig::try_deserialize(&mut account.data.as_slice())
+            .expect("failed to deserialize the wallet")
+    }
+    
+    // The wallet's zero-copy hot state: state version, escape, activity, audit head and open
+    // accounts
+    pub async fn wallet_state(&mut self) -> WalletState {
+        let account = self
+            .context
+            .banks_client
+            .get_account(pda::wallet_state(&self.wallet).0)
+            .await
+            .expect("failed to fetch the wallet state")
+            .expect("wallet state does not exist");
+        *bytemuck::from_bytes(&account.data[8..8 + std::mem::size_of::<WalletState>()])
+    }
+    
+    // Token delegations recorded in the wallet's registry, none before the first approval
+    pub async fn delegations(&mut self) -> Vec<TokenDelegation> {
+        let account = self
+            .context
+            .banks_client
+            .get_account(pda::delegation_registry(&self.wallet).0)
+            .await
+            .expect("failed to fetch the delegation registry");
+        account
+            .map(|account| {
+                let registry: &DelegationRegistry = bytemuck::from_bytes(
+                    &account.data[8..8 + std::mem::size_of::<DelegationRegistry>()],
+                );
+                registry.delegations().to_vec()
+            })
+            .unwrap_or_default()
+    }
+    
+    // The wallet's vault PDA, holding its assets
+    pub fn vault(&self) -> Pubkey {
+        pda::vault(&self.wallet).0
+    }
+    
+    pub async fn lamports(&mut self, address: &Pubkey) -> u64 {
+        self.context
+            .banks_client
+            .get_balance(*address)
+            .await
+            .expect("failed to fetch the balance")
+    }
+    
+    // Give the system account at `address` a balance of `lamports`
+    pub fn set_lamports(&mut self, address: &Pubkey, lamports: u64) {
+        let account = AccountSharedData::new(lamports, 0, &anchor_lang::system_program::ID);
+        self.context.set_account(address, &account);
+    }
+    
+    // Write an initialized SPL token mint with 6 decimals to `address`
+    pub fn set_mint(
+        &mut self,
+        address: &Pubkey,
+        mint_authority: Option<Pubkey>,
+        freeze_authority: Option<Pubkey>,
+    ) {
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            mint_authority: mint_authority.into(),
+            supply: 1 << 40,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: freeze_authority.into(),
+        }
+        .pack_into_slice(&mut data);
+        self.set_token_program_account(address, data);
+    }
+    
+    // Write the mint of a non-fungible token (no decimals, a supply of one) to `address`
+    pub fn set_nft_mint(&mut self, address: &Pubkey) {
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            mint_authority: Some(Pubkey::new_unique()).into(),
+            supply: 1,
+            decimals: 0,
+            is_initialized: true,
+            freeze_authority: None.into(),
+        }
+        .pack_into_slice(&mut data);
+        self.set_token_program_account(address, data);
+    }
+    
+    // Write an initialized SPL token account of `owner` holding `amount` to `address`
+    pub fn set_token_account(
+        &mut self,
+        address: &Pubkey,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+    ) {
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        self.set_token_program_account(address, data);
+    }
+    
+    fn set_token_program_account(&mut self, address: &Pubkey, data: Vec<u8>) {
+        let account = Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: spl_token::ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.context.set_account(address, &AccountSharedData::from(account));
+    }
+    
+    // The SPL token account at `address`, None once it is closed
+    pub async fn token_account(&mut self, address: &Pubkey) -> Option<spl_token::state::Account> {
+        self.context
+            .banks_client
+            .get_account(*address)
+            .await
+            .expect("failed to fetch the token account")
+            .map(|account| {
+                spl_token::state::Account::unpack(&account.data)
+                    .expect("failed to unpack the token account")
+            })
+    }
+    
+    pub async fn mint(&mut self, address: &Pubkey) -> spl_token::state::Mint {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*address)
+            .await
+            .expect("failed to fetch the mint")
+            .expect("mint does not exist");
+        spl_token::state::Mint::unpack(&account.data).expect("failed to unpack the mint")
+    }
+    
+    pub async fn now(&mut self) -> i64 {
+        let clock: Clock = self
+            .context
+            .banks_client
+            .get_sysvar()
+            .await
+            .expect("failed to read the clock");
+        clock.unix_timestamp
+    }
+    
+    // Move the clock forward by `seconds`
+    pub async fn warp_by(&mut self, seconds: i64) {
+        let mut clock: Clock = self
+            .context
+            .banks_client
+            .get_sysvar()
+            .await
+            .expect("failed to read the clock");
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+    
+    // Move the clock just past the wallet's security period
+    pub async fn warp_past_security_period(&mut self) {
+        let security_period = self.wallet_account().await.security_period;
+        self.warp_by(security_period + 1).await;
+    }
+    
+    // Approve a transaction with both owner and guardian signatures
+    pub async fn approve_transaction(&mut self, data: Vec<u8>) -> Result<(), BanksClientError> {
+        let execute = instructions::execute(
+            &self.wallet,
+            &self.owner.pubkey(),
+            &self.guardian.pubkey(),
+            data,
+        );
+        let (owner, guardian) = (self.owner.insecure_clone(), self.guardian.insecure_clone());
+        self.process(&[execute], &[&owner, &guardian]).await
+    }
+    
+    // Ed25519 verification of `key`'s proof of possession for `action` on the wallet in its
+    // current state, to send before the instruction installing it
+    pub async fn register_key(&mut self, key: &Keypair, action: OffchainAction) -> Instruction {
+        let state_version = self.wallet_state().await.state_version;
+        let message =
+            message::register_key(&self.wallet, action, &key.pubkey(), state_version, u64::MAX);
+        let signature: [u8; 64] = key.sign_message(&message).into();
+        new_ed25519_instruction_with_signature(&message, &signature, &key.pubkey().to_bytes())
+    }
+    
+    // Freeze an asset of the vault (SOL with `None`), as the guardian alone can
+    pub async fn freeze_asset(&mut self, mint: Option<Pubkey>) -> Result<(), BanksClientError> {
+        let freeze = instructions::freeze_asset(
+            &self.wallet,
+            &self.guardian.pubkey(),
+            &self.context.payer.pubkey(),
+            mint,
+        );
+        let guardian = self.guardian.insecure_clone();
+        self.process(&[freeze], &[&guardian]).await
+    }
+    
+    pub async fn trigger_escape_guardian(&mut self) -> Result<(), BanksClientError> {
+        let trigger = instructions::trigger_escape_guardian(&self.wallet, &self.owner.pubkey());
+        let owner = self.owner.insecure_clone();
+        self.process(&[trigger], &[&owner]).await
+    }
+    
+    pub async fn trigger_escape_owner(&mut self) -> Result<(), BanksClientError> {
+        let trigger = instructions::trigger_escape_owner(&self.wallet, &self.guardian.pubkey());
+        let guardian = self.guardian.insecure_clone();
+        self.process(&[trigger], &[&guardian]).await
+    }
+    
+    // Commit `new_key` for the escape in progress, signed by the escaping party
+    pub async fn commit_escape_key(
+        &mut self,
+        authority: &Keypair,
+        new_key: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        let action = match self.wallet_state().await.escape_type() {
+            EscapeType::Guardian => OffchainAction::ChangeGuardian,
+            _ => OffchainAction::ChangeOwner,
+        };
+        let register = self.register_key(new_key, action).await;
+        let commit =
+            instructions::commit_escape_key(&self.wallet, &authority.pubkey(), new_key.pubkey());
+        let authority = authority.insecure_clone();
+        self.process(&[register, commit], &[&authority]).await
+    }
+    
+    // Complete an escape with its committed key, sent by the context payer alone
+    pub async fn complete_escape(&mut self) -> Result<(), BanksClientError> {
+        let complete = instructions::complete_escape(&self.wallet, &self.context.payer.pubkey());
+        self.process(&[complete], &[]).await
+    }
+    
+    // Complete a guardian escape; on success the fixture's guardian is the new key
+    pub async fn escape_guardian(&mut self, new_guardian: Keypair) -> Result<(), BanksClientError> {
+        let escape =
+            instructions::escape_guardian(&self.wallet, &self.owner.pubkey(), new_guardian.pubkey());
+        let register = self.register_key(&new_guardian, OffchainAction::ChangeGuardian).await;
+        let owner = self.owner.insecure_clone();
+        self.process(&[register, escape], &[&owner]).await?;
+        self.guardian = new_guardian;
+        Ok(())
+    }
+    
+    // Complete an owner escape; on success the fixture's owner is the new key
+    pub async fn escape_owner(&mut self, new_owner: Keypair) -> Result<(), BanksClientError> {
+        let escape =
+            instructions::escape_owner(&self.wallet, &self.guardian.pubkey(), new_owner.pubkey());
+        let register = self.register_key(&new_owner, OffchainAction::ChangeOwner).await;
+        let guardian = self.guardian.insecure_clone();
+        self.process(&[register, escape], &[&guardian]).await?;
+        self.owner = new_owner;
+        Ok(())
+    }
+}
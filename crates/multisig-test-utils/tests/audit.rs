@@ -0,0 +1,38 @@
+use anchor_lang::solana_program::hash::hash;
+use multisig::{TransactionApproved, WalletCreated};
+use multisig_interface::audit;
+use multisig_test_utils::WalletFixture;
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn audit_head_replays_from_emitted_events() {
+    let mut fixture = WalletFixture::new().await;
+    let created = WalletCreated {
+        argent_account: fixture.wallet,
+        notification_tag: [0; 32],
+        creator: fixture.context.payer.pubkey(),
+        index: 0,
+        owner: fixture.owner.pubkey(),
+        guardian: fixture.guardian.pubkey(),
+        security_period: 604800,
+        state_version: 0,
+    };
+    let head = audit::next_head_for(&[0; 32], &created);
+    assert_eq!(fixture.wallet_state().await.audit_head, head);
+    
+    let data = b"audited transaction".to_vec();
+    fixture.approve_transaction(data.clone()).await.unwrap();
+    let approved = TransactionApproved {
+        argent_account: fixture.wallet,
+        notification_tag: [0; 32],
+        owner: fixture.owner.pubkey(),
+        guardian: fixture.guardian.pubkey(),
+        data_hash: hash(&data).to_bytes(),
+        state_version: 1,
+    };
+    let head = audit::next_head_for(&head, &approved);
+    assert_eq!(fixture.wallet_state().await.audit_head, head);
+    
+    use anchor_lang::Event;
+    assert_eq!(audit::replay([created.data(), approved.data()]), head);
+}
@@ -0,0 +1,45 @@
+// Compute budgets of the hot paths, measured against the SBF build (run `anchor build` first)
+use multisig_interface::instructions;
+use multisig_test_utils::{bpf_program_test, WalletFixture};
+use solana_sdk::signature::{Keypair, Signer};
+
+// Ceiling for a plain approval or SOL payment, leaving room for the rest of the transaction
+const HOT_PATH_COMPUTE_UNITS: u64 = 30_000;
+
+#[tokio::test]
+async fn execute_stays_within_budget() {
+    let mut fixture = WalletFixture::start(bpf_program_test(), None).await;
+    let execute = instructions::execute(
+        &fixture.wallet,
+        &fixture.owner.pubkey(),
+        &fixture.guardian.pubkey(),
+        vec![0; multisig::MAX_PENDING_TX_DATA_LEN],
+    );
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    
+    let units = fixture.compute_units(&[execute], &[&owner, &guardian]).await;
+    assert!(units < HOT_PATH_COMPUTE_UNITS, "execute used {units} compute units");
+}
+
+#[tokio::test]
+async fn sol_payment_stays_within_budget() {
+    let mut fixture = WalletFixture::start(bpf_program_test(), None).await;
+    let payer = fixture.context.payer.pubkey();
+    let deposit = instructions::deposit_sol(&fixture.wallet, &payer, 1_000_000_000, String::new());
+    fixture.process(&[deposit], &[]).await.unwrap();
+    
+    let recipient = Keypair::new().pubkey();
+    let payment = instructions::execute_payment(
+        &fixture.wallet,
+        &fixture.owner.pubkey(),
+        &fixture.guardian.pubkey(),
+        &recipient,
+        Keypair::new().pubkey(),
+        100_000_000,
+        None,
+    );
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    
+    let units = fixture.compute_units(&[payment], &[&owner, &guardian]).await;
+    assert!(units < HOT_PATH_COMPUTE_UNITS, "execute_payment used {units} compute units");
+}
@@ -0,0 +1,259 @@
+use multisig::EscapeType;
+use multisig_interface::message::OffchainAction;
+use multisig_interface::{accounts, instruction, instructions, pda};
+use multisig_test_utils::WalletFixture;
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::{system_program, sysvar};
+
+#[tokio::test]
+async fn guardian_escape_completes_after_security_period() {
+    let mut fixture = WalletFixture::new().await;
+    fixture.trigger_escape_guardian().await.unwrap();
+    
+    fixture.warp_past_security_period().await;
+    let new_guardian = Keypair::new();
+    let new_guardian_key = new_guardian.pubkey();
+    fixture.escape_guardian(new_guardian).await.unwrap();
+    
+    assert_eq!(fixture.wallet_account().await.guardian, new_guardian_key);
+    assert!(fixture.wallet_state().await.escape_type() == EscapeType::None);
+}
+
+#[tokio::test]
+async fn guardian_escape_requires_the_new_guardian_to_prove_possession() {
+    let mut fixture = WalletFixture::new().await;
+    fixture.trigger_escape_guardian().await.unwrap();
+    fixture.warp_past_security_period().await;
+    
+    // A key nobody signed for, then one that signed for another role
+    let owner = fixture.owner.insecure_clone();
+    let new_guardian = Keypair::new();
+    let escape =
+        instructions::escape_guardian(&fixture.wallet, &owner.pubkey(), new_guardian.pubkey());
+    assert!(fixture.process(&[escape.clone()], &[&owner]).await.is_err());
+    let wrong_action = fixture
+        .register_key(&new_guardian, OffchainAction::RecoveryContact)
+        .await;
+    assert!(fixture
+        .process(&[wrong_action, escape], &[&owner])
+        .await
+        .is_err());
+    
+    fixture.escape_guardian(new_guardian).await.unwrap();
+}
+
+#[tokio::test]
+async fn owner_escape_fails_before_security_period() {
+    let mut fixture = WalletFixture::with_security_period(Some(3600)).await;
+    fixture.trigger_escape_owner().await.unwrap();
+    
+    fixture.warp_by(1800).await;
+    assert!(fixture.escape_owner(Keypair::new()).await.is_err());
+    
+    fixture.warp_by(1801).await;
+    fixture.escape_owner(Keypair::new()).await.unwrap();
+}
+
+#[tokio::test]
+async fn committed_owner_escape_is_completed_by_anyone() {
+    let mut fixture = WalletFixture::with_security_period(Some(3600)).await;
+    fixture.trigger_escape_owner().await.unwrap();
+    let guardian = fixture.guardian.insecure_clone();
+    let new_owner = Keypair::new();
+    fixture.commit_escape_key(&guardian, &new_owner).await.unwrap();
+    
+    assert!(fixture.complete_escape().await.is_err());
+    
+    fixture.warp_by(3601).await;
+    fixture.complete_escape().await.unwrap();
+    
+    assert_eq!(fixture.wallet_account().await.owner, new_owner.pubkey());
+    let state = fixture.wallet_state().await;
+    assert!(state.escape_type() == EscapeType::None);
+    assert_eq!(state.escape_new_key(), None);
+}
+
+#[tokio::test]
+async fn guardian_backup_is_promoted_after_guardian_inactivity() {
+    let mut fixture = WalletFixture::with_security_period(Some(3600)).await;
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let backup = Keypair::new();
+    let register_backup = fixture
+        .register_key(&backup, OffchainAction::ChangeGuardianBackup)
+        .await;
+    let payer = fixture.context.payer.pubkey();
+    let set_backup = instructions::change_guardian_backup(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &payer,
+        Some(backup.pubkey()),
+    );
+    let set_promotion = instructions::build(
+        accounts::SetBackupPromotion {
+            argent_account: fixture.wallet,
+            wallet_state: pda::wallet_state(&fixture.wallet).0,
+            approvers: accounts::Approvers {
+                owner: owner.pubkey(),
+                guardian: guardian.pubkey(),
+            },
+            backup_promotion: pda::backup_promotion(&fixture.wallet).0,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetBackupPromotion {
+            inactivity_period: 86400,
+        },
+    );
+    fixture
+        .process(&[register_backup, set_backup, set_promotion], &[&owner, &guardian])
+        .await
+        .unwrap();
+    fixture.warp_by(3600).await;
+    let complete_backup =
+        instructions::complete_guardian_backup_change(&fixture.wallet, &payer, &payer);
+    fixture.process(&[complete_backup], &[]).await.unwrap();
+    
+    let request = instructions::build(
+        accounts::RequestBackupPromotion {
+            argent_account: fixture.wallet,
+            wallet_state: pda::wallet_state(&fixture.wallet).0,
+            owner: owner.pubkey(),
+            backup_promotion: pda::backup_promotion(&fixture.wallet).0,
+            notification_config: pda::notification_config(&fixture.wallet).0,
+            event_authority: pda::event_authority().0,
+            program: multisig::ID,
+        },
+        instruction::RequestBackupPromotion {},
+    );
+    assert!(fixture.process(&[request.clone()], &[&owner]).await.is_err());
+    
+    fixture.warp_by(86400).await;
+    fixture.process(&[request], &[&owner]).await.unwrap();
+    assert!(fixture.complete_escape().await.is_err());
+    
+    fixture.warp_by(3600).await;
+    fixture.complete_escape().await.unwrap();
+    
+    let wallet = fixture.wallet_account().await;
+    assert_eq!(wallet.guardian, backup.pubkey());
+    assert_eq!(wallet.guardian_backup, None);
+}
+
+#[tokio::test]
+async fn guardian_and_contact_quorum_recover_the_owner_after_the_shorter_delay() {
+    let mut fixture = WalletFixture::with_security_period(Some(86400)).await;
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let contacts = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let mut setup = Vec::new();
+    for contact in &contacts {
+        setup.push(fixture.register_key(contact, OffchainAction::RecoveryContact).await);
+    }
+    let set_policy = instructions::build(
+        accounts::SetRecoveryPolicy {
+            argent_account: fixture.wallet,
+            wallet_state: pda::wallet_state(&fixture.wallet).0,
+            approvers: accounts::Approvers {
+                owner: owner.pubkey(),
+                guardian: guardian.pubkey(),
+            },
+            recovery_policy: pda::recovery_policy(&fixture.wallet).0,
+            payer: fixture.context.payer.pubkey(),
+            system_program: system_program::ID,
+            sysvar_instructions: sysvar::instructions::ID,
+        },
+        instruction::SetRecoveryPolicy {
+            contacts: contacts.iter().map(|contact| contact.pubkey()).collect(),
+            threshold: 2,
+            delay: 3600,
+        },
+    );
+    setup.push(set_policy);
+    fixture.process(&setup, &[&owner, &guardian]).await.unwrap();
+    
+    let new_owner = Keypair::new();
+    let register_owner = fixture.register_key(&new_owner, OffchainAction::ChangeOwner).await;
+    let wallet = fixture.wallet;
+    let initiate = |signing: &[&Keypair]| {
+        let mut ix = instructions::build(
+            accounts::InitiateQuorumRecovery {
+                argent_account: wallet,
+                wallet_state: pda::wallet_state(&wallet).0,
+                guardian: guardian.pubkey(),
+                recovery_policy: pda::recovery_policy(&wallet).0,
+                sysvar_instructions: sysvar::instructions::ID,
+                event_authority: pda::event_authority().0,
+                program: multisig::ID,
+            },
+            instruction::InitiateQuorumRecovery {
+                new_owner: new_owner.pubkey(),
+            },
+        );
+        ix.accounts.extend(
+            signing
+                .iter()
+                .map(|contact| AccountMeta::new_readonly(contact.pubkey(), true)),
+        );
+        ix
+    };
+    let one = initiate(&[&contacts[0]]);
+    assert!(fixture
+        .process(&[register_owner.clone(), one], &[&guardian, &contacts[0]])
+        .await
+        .is_err());
+    let two = initiate(&[&contacts[0], &contacts[2]]);
+    fixture
+        .process(&[register_owner, two], &[&guardian, &contacts[0], &contacts[2]])
+        .await
+        .unwrap();
+    
+    let cranker = Keypair::new();
+    let complete = instructions::build(
+        accounts::CompleteQuorumRecovery {
+            argent_account: fixture.wallet,
+            wallet_state: pda::wallet_state(&fixture.wallet).0,
+            recovery_policy: pda::recovery_policy(&fixture.wallet).0,
+            cranker: cranker.pubkey(),
+            notification_config: pda::notification_config(&fixture.wallet).0,
+            event_authority: pda::event_authority().0,
+            program: multisig::ID,
+        },
+        instruction::CompleteQuorumRecovery {},
+    );
+    assert!(fixture.process(&[complete.clone()], &[&cranker]).await.is_err());
+    
+    fixture.warp_by(3600).await;
+    fixture.process(&[complete], &[&cranker]).await.unwrap();
+    assert_eq!(fixture.wallet_account().await.owner, new_owner.pubkey());
+}
+
+#[tokio::test]
+async fn guardian_backup_change_waits_out_the_security_period() {
+    let mut fixture = WalletFixture::with_security_period(Some(3600)).await;
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let payer = fixture.context.payer.pubkey();
+    let backup = Keypair::new();
+    let register_backup = fixture
+        .register_key(&backup, OffchainAction::ChangeGuardianBackup)
+        .await;
+    let set_backup = instructions::change_guardian_backup(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &payer,
+        Some(backup.pubkey()),
+    );
+    fixture
+        .process(&[register_backup, set_backup], &[&owner, &guardian])
+        .await
+        .unwrap();
+    
+    let complete = instructions::complete_guardian_backup_change(&fixture.wallet, &payer, &payer);
+    assert!(fixture.process(&[complete.clone()], &[]).await.is_err());
+    assert_eq!(fixture.wallet_account().await.guardian_backup, None);
+    
+    fixture.warp_by(3600).await;
+    fixture.process(&[complete], &[]).await.unwrap();
+    assert_eq!(fixture.wallet_account().await.guardian_backup, Some(backup.pubkey()));
+}
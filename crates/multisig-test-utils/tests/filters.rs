@@ -0,0 +1,52 @@
+use multisig::EscapeType;
+use multisig_interface::{filters, pda, CURRENT_ACCOUNT_VERSION};
+use multisig_test_utils::WalletFixture;
+use solana_sdk::signature::Signer;
+
+fn matches(data: &[u8], (offset, bytes): (usize, Vec<u8>)) -> bool {
+    data.get(offset..offset + bytes.len()) == Some(&bytes[..])
+}
+
+#[tokio::test]
+async fn wallet_header_matches_the_published_filters() {
+    let mut fixture = WalletFixture::new().await;
+    let account = fixture
+        .context
+        .banks_client
+        .get_account(fixture.wallet)
+        .await
+        .unwrap()
+        .unwrap();
+    
+    assert!(matches(&account.data, filters::wallets()));
+    assert!(matches(&account.data, filters::owner(&fixture.owner.pubkey())));
+    assert!(matches(&account.data, filters::guardian(&fixture.guardian.pubkey())));
+    assert!(matches(&account.data, filters::version(CURRENT_ACCOUNT_VERSION)));
+    assert!(!matches(&account.data, filters::owner(&fixture.guardian.pubkey())));
+    
+    let state = fixture
+        .context
+        .banks_client
+        .get_account(pda::wallet_state(&fixture.wallet).0)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches(&state.data, filters::wallet_states()));
+    assert!(matches(&state.data, filters::escape_type(EscapeType::None)));
+}
+
+#[tokio::test]
+async fn escape_type_filter_tracks_a_triggered_escape() {
+    let mut fixture = WalletFixture::new().await;
+    fixture.trigger_escape_guardian().await.unwrap();
+    
+    let state = fixture
+        .context
+        .banks_client
+        .get_account(pda::wallet_state(&fixture.wallet).0)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches(&state.data, filters::escape_type(EscapeType::Guardian)));
+    assert!(!matches(&state.data, filters::escape_type(EscapeType::None)));
+}
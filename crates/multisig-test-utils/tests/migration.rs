@@ -0,0 +1,250 @@
+use anchor_lang::{AnchorSerialize, Discriminator};
+use multisig::{EscapeType, PrefixlessArgentAccount, UNCOUNTED_OPEN_ACCOUNTS};
+use multisig_interface::{accounts, instruction, instructions, pda, AccountHeader, WalletConfig};
+use multisig_test_utils::WalletFixture;
+use solana_sdk::account::AccountSharedData;
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_program;
+
+// Rewrite the fixture's wallet as a version 17 account, from before open accounts were counted
+// and before the account header, keeping its hot state inline as it did then
+async fn downgrade_to_version_17(fixture: &mut WalletFixture) {
+    let wallet = fixture.wallet_account().await;
+    let state = fixture.wallet_state().await;
+    let legacy = PrefixlessArgentAccount {
+        owner: wallet.owner,
+        guardian: wallet.guardian,
+        version: 17,
+        escape_type: state.escape_type(),
+        guardian_backup: wallet.guardian_backup,
+        escape_initiated_at: state.escape_initiated_at,
+        security_period: wallet.security_period,
+        pending_tx: None,
+        creator: wallet.creator,
+        index: wallet.index,
+        name: wallet.name,
+        metadata_uri: wallet.metadata_uri,
+        bump: wallet.bump,
+        state_version: state.state_version,
+        last_owner_action_at: state.last_owner_action_at,
+        last_guardian_action_at: state.last_guardian_action_at,
+        compute_budget: None,
+        message_requires_guardian: wallet.message_requires_guardian,
+        escape_new_key: state.escape_new_key(),
+        audit_head: state.audit_head,
+        notification_tag: wallet.notification_tag,
+        escape_stats: state.escape_stats,
+        approval_ttl: wallet.approval_ttl,
+        features: 0,
+        locked_until: wallet.locked_until,
+        open_accounts: 0,
+    };
+    let mut account = fixture
+        .context
+        .banks_client
+        .get_account(fixture.wallet)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut data = WalletConfig::DISCRIMINATOR.to_vec();
+    legacy.serialize(&mut data).unwrap();
+    account.data.fill(0);
+    account.data[..data.len()].copy_from_slice(&data);
+    fixture
+        .context
+        .set_account(&fixture.wallet, &AccountSharedData::from(account));
+    for address in [
+        pda::pending_transaction(&fixture.wallet).0,
+        pda::wallet_state(&fixture.wallet).0,
+    ] {
+        fixture.context.set_account(&address, &AccountSharedData::default());
+    }
+}
+
+#[tokio::test]
+async fn migrated_wallet_counts_its_open_accounts_before_closing() {
+    let mut fixture = WalletFixture::new().await;
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let payer = fixture.context.payer.pubkey();
+    
+    // An account the wallet derived before the counter existed
+    let backup_promotion = pda::backup_promotion(&fixture.wallet).0;
+    let set_promotion = instructions::build(
+        accounts::SetBackupPromotion {
+            argent_account: fixture.wallet,
+            wallet_state: pda::wallet_state(&fixture.wallet).0,
+            approvers: accounts::Approvers {
+                owner: owner.pubkey(),
+                guardian: guardian.pubkey(),
+            },
+            backup_promotion,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetBackupPromotion {
+            inactivity_period: 86400,
+        },
+    );
+    fixture
+        .process(&[set_promotion], &[&owner, &guardian])
+        .await
+        .unwrap();
+    let state_version = fixture.wallet_state().await.state_version;
+    downgrade_to_version_17(&mut fixture).await;
+    
+    let migrate = instructions::build(
+        accounts::MigrateAccount {
+            argent_account: fixture.wallet,
+            pending_transaction: pda::pending_transaction(&fixture.wallet).0,
+            wallet_state: pda::wallet_state(&fixture.wallet).0,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateAccount {},
+    );
+    fixture.process(&[migrate], &[]).await.unwrap();
+    // The inline hot state moved to the wallet state account, with the count unknown
+    let state = fixture.wallet_state().await;
+    assert_eq!(state.state_version, state_version + 1);
+    assert_eq!(state.open_accounts, UNCOUNTED_OPEN_ACCOUNTS);
+    
+    let wallet = fixture.wallet;
+    let count = |remaining: &[Pubkey]| {
+        let mut count = instructions::build(
+            accounts::CountOpenAccounts {
+                argent_account: wallet,
+                wallet_state: pda::wallet_state(&wallet).0,
+                approvers: accounts::Approvers {
+                    owner: owner.pubkey(),
+                    guardian: guardian.pubkey(),
+                },
+            },
+            instruction::CountOpenAccounts {},
+        );
+        count
+            .accounts
+            .extend(remaining.iter().map(|key| AccountMeta::new_readonly(*key, false)));
+        count
+    };
+    
+    // Only accounts recording the wallet count, each once
+    for remaining in [vec![wallet], vec![backup_promotion, backup_promotion]] {
+        let invalid = count(&remaining);
+        assert!(fixture.process(&[invalid], &[&owner, &guardian]).await.is_err());
+    }
+    let valid = count(&[backup_promotion]);
+    fixture.process(&[valid], &[&owner, &guardian]).await.unwrap();
+    assert_eq!(fixture.wallet_state().await.open_accounts, 1);
+    
+    // The count is established once; later accounts are counted as they open and close
+    fixture.warp_by(1).await;
+    let again = count(&[]);
+    assert!(fixture.process(&[again], &[&owner, &guardian]).await.is_err());
+}
+
+#[tokio::test]
+async fn escape_in_progress_moves_to_the_wallet_state() {
+    let mut fixture = WalletFixture::new().await;
+    fixture.trigger_escape_guardian().await.unwrap();
+    let triggered_at = fixture.wallet_state().await.escape_initiated_at;
+    downgrade_to_version_17(&mut fixture).await;
+    
+    let migrate = instructions::build(
+        accounts::MigrateAccount {
+            argent_account: fixture.wallet,
+            pending_transaction: pda::pending_transaction(&fixture.wallet).0,
+            wallet_state: pda::wallet_state(&fixture.wallet).0,
+            payer: fixture.context.payer.pubkey(),
+            system_program: system_program::ID,
+        },
+        instruction::MigrateAccount {},
+    );
+    fixture.process(&[migrate], &[]).await.unwrap();
+    
+    let state = fixture.wallet_state().await;
+    assert!(state.escape_type() == EscapeType::Guardian);
+    assert_eq!(state.escape_initiated_at, triggered_at);
+    assert_eq!(state.escape_stats.guardian_escapes_triggered, 1);
+    
+    // The escape completes from the migrated state
+    fixture.warp_past_security_period().await;
+    fixture.escape_guardian(Keypair::new()).await.unwrap();
+}
+
+#[tokio::test]
+async fn derived_accounts_move_behind_the_account_header() {
+    let mut fixture = WalletFixture::new().await;
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let payer = fixture.context.payer.pubkey();
+    
+    let backup_promotion = pda::backup_promotion(&fixture.wallet).0;
+    let set_promotion = instructions::build(
+        accounts::SetBackupPromotion {
+            argent_account: fixture.wallet,
+            wallet_state: pda::wallet_state(&fixture.wallet).0,
+            approvers: accounts::Approvers {
+                owner: owner.pubkey(),
+                guardian: guardian.pubkey(),
+            },
+            backup_promotion,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::SetBackupPromotion {
+            inactivity_period: 86400,
+        },
+    );
+    fixture
+        .process(&[set_promotion], &[&owner, &guardian])
+        .await
+        .unwrap();
+    
+    // Strip the header, as the account was laid out before it existed
+    let current = fixture
+        .context
+        .banks_client
+        .get_account(backup_promotion)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut legacy = current.clone();
+    legacy.data = [&current.data[..8], &current.data[8 + AccountHeader::LEN..]].concat();
+    fixture
+        .context
+        .set_account(&backup_promotion, &AccountSharedData::from(legacy));
+    
+    let migrate = || {
+        instructions::build(
+            accounts::MigrateDerivedAccount {
+                account: backup_promotion,
+                payer,
+                system_program: system_program::ID,
+            },
+            instruction::MigrateDerivedAccount {},
+        )
+    };
+    fixture.process(&[migrate()], &[]).await.unwrap();
+    let migrated = fixture
+        .context
+        .banks_client
+        .get_account(backup_promotion)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(migrated.data, current.data);
+    
+    // Wallets and accounts already behind the header are rejected
+    fixture.warp_by(1).await;
+    assert!(fixture.process(&[migrate()], &[]).await.is_err());
+    let wallet = instructions::build(
+        accounts::MigrateDerivedAccount {
+            account: fixture.wallet,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateDerivedAccount {},
+    );
+    assert!(fixture.process(&[wallet], &[]).await.is_err());
+}
@@ -0,0 +1,492 @@
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::metadata::mpl_token_metadata::accounts::{MasterEdition, Metadata};
+use anchor_spl::metadata::mpl_token_metadata::types::{Key, ProgrammableConfig, TokenStandard};
+use anchor_spl::token::spl_token;
+use borsh::BorshSerialize;
+use multisig::{ErrorCode, BUBBLEGUM_PROGRAM_ID, MPL_CORE_PROGRAM_ID};
+use multisig_interface::instructions;
+use multisig_test_utils::{custom_error, program_test, WalletFixture};
+use solana_program_test::{processor, BanksClientError};
+use solana_sdk::account::{Account, AccountSharedData};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::Signer;
+
+// Anchor discriminator of Bubblegum's `transfer`
+const BUBBLEGUM_TRANSFER: [u8; 8] = [163, 52, 200, 231, 140, 3, 69, 186];
+
+// Metaplex Core instructions and plugin arguments sent by the wallet
+const CORE_APPROVE_PLUGIN_AUTHORITY_V1: u8 = 8;
+const CORE_REVOKE_PLUGIN_AUTHORITY_V1: u8 = 10;
+const CORE_BURN_V1: u8 = 12;
+const CORE_TRANSFER_V1: u8 = 14;
+const CORE_UPDATE_DELEGATE_PLUGIN: u8 = 4;
+const CORE_PLUGIN_AUTHORITY_ADDRESS: u8 = 3;
+
+// Token Metadata stand-in: TransferV1 moves the token on the token owner's signature, as the
+// real program does once the metadata and rule set checks pass
+fn process_token_metadata(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    let [token, token_owner, destination_token, _, _, _, _, _, _, authority, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !authority.is_signer || authority.key != token_owner.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let token_program = accounts
+        .iter()
+        .find(|account| *account.key == spl_token::ID)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    invoke(
+        &spl_token::instruction::transfer(
+            &spl_token::ID,
+            token.key,
+            destination_token.key,
+            token_owner.key,
+            &[],
+            1,
+        )?,
+        &[
+            token.clone(),
+            destination_token.clone(),
+            token_owner.clone(),
+            token_program.clone(),
+        ],
+    )
+}
+
+// Bubblegum stand-in: `transfer` records the new leaf owner, nonce, index and proof length in
+// the tree account, on the leaf owner's signature
+fn process_bubblegum(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [_, leaf_owner, _, new_leaf_owner, merkle_tree, _, _, _, proof @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if data[..8] != BUBBLEGUM_TRANSFER {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    if !leaf_owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // root, data hash and creator hash come before the nonce and index
+    let nonce_and_index = &data[8 + 96..8 + 96 + 12];
+    let mut tree = merkle_tree.try_borrow_mut_data()?;
+    tree[..32].copy_from_slice(new_leaf_owner.key.as_ref());
+    tree[32..44].copy_from_slice(nonce_and_index);
+    tree[44] = proof.len() as u8;
+    Ok(())
+}
+
+// Metaplex Core stand-in over an asset laid out as owner, update delegate and a burned flag.
+// Every instruction needs the owner's signature, and burning one in a collection needs the
+// collection writable.
+fn process_core(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [asset, collection, _payer, authority, rest @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let mut asset_data = asset.try_borrow_mut_data()?;
+    if !authority.is_signer || asset_data[..32] != authority.key.to_bytes() || asset_data[64] != 0 {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    match data {
+        [CORE_TRANSFER_V1, 0] => {
+            let new_owner = rest.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            asset_data[..32].copy_from_slice(new_owner.key.as_ref());
+        }
+        [CORE_BURN_V1, 0] => {
+            if *collection.key != MPL_CORE_PROGRAM_ID && !collection.is_writable {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            asset_data[64] = 1;
+        }
+        [
+            CORE_APPROVE_PLUGIN_AUTHORITY_V1,
+            CORE_UPDATE_DELEGATE_PLUGIN,
+            CORE_PLUGIN_AUTHORITY_ADDRESS,
+            delegate @ ..,
+        ] if delegate.len() == 32 => {
+            asset_data[32..64].copy_from_slice(delegate);
+        }
+        [CORE_REVOKE_PLUGIN_AUTHORITY_V1, CORE_UPDATE_DELEGATE_PLUGIN] => {
+            asset_data[32..64].fill(0);
+        }
+        _ => return Err(ProgramError::InvalidInstructionData),
+    }
+    Ok(())
+}
+
+// Store `data` at `address` under the program `owner`
+fn set_program_data(fixture: &mut WalletFixture, address: &Pubkey, owner: &Pubkey, data: Vec<u8>) {
+    let account = Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: *owner,
+        executable: false,
+        rent_epoch: 0,
+    };
+    fixture
+        .context
+        .set_account(address, &AccountSharedData::from(account));
+}
+
+// An NFT of `token_standard` held by the vault, and an empty token account to send it to
+struct VaultNft {
+    fixture: WalletFixture,
+    mint: Pubkey,
+    vault_token_account: Pubkey,
+    destination_owner: Pubkey,
+    destination_token: Pubkey,
+}
+
+impl VaultNft {
+    async fn mint(token_standard: TokenStandard, rule_set: Option<Pubkey>) -> Self {
+        let mut program_test = program_test();
+        program_test.add_program(
+            "mpl_token_metadata",
+            anchor_spl::metadata::ID,
+            processor!(process_token_metadata),
+        );
+        let mut fixture = WalletFixture::start(program_test, None).await;
+        
+        let mint = Pubkey::new_unique();
+        fixture.set_nft_mint(&mint);
+        let vault_token_account = get_associated_token_address(&fixture.vault(), &mint);
+        let vault = fixture.vault();
+        fixture.set_token_account(&vault_token_account, &mint, &vault, 1);
+        let destination_owner = Pubkey::new_unique();
+        let destination_token = get_associated_token_address(&destination_owner, &mint);
+        fixture.set_token_account(&destination_token, &mint, &destination_owner, 0);
+        
+        let metadata = Metadata {
+            key: Key::MetadataV1,
+            update_authority: Pubkey::new_unique(),
+            mint,
+            name: "Argent".to_string(),
+            symbol: String::new(),
+            uri: String::new(),
+            seller_fee_basis_points: 0,
+            creators: None,
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: Some(token_standard),
+            collection: None,
+            uses: None,
+            collection_details: None,
+            programmable_config: rule_set.map(|rule_set| ProgrammableConfig::V1 {
+                rule_set: Some(rule_set),
+            }),
+        };
+        let data = metadata.try_to_vec().unwrap();
+        let account = Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: anchor_spl::metadata::ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        fixture
+            .context
+            .set_account(&Metadata::find_pda(&mint).0, &AccountSharedData::from(account));
+        
+        Self {
+            fixture,
+            mint,
+            vault_token_account,
+            destination_owner,
+            destination_token,
+        }
+    }
+    
+    // Send the NFT with token records when `records` is set (programmable NFTs) and
+    // `authorization_rules` as its rule set
+    async fn transfer(
+        &mut self,
+        records: bool,
+        authorization_rules: Option<Pubkey>,
+    ) -> Result<(), BanksClientError> {
+        let fixture = &self.fixture;
+        let (token_record, destination_token_record) = match records {
+            true => (Some(Pubkey::new_unique()), Some(Pubkey::new_unique())),
+            false => (None, None),
+        };
+        let transfer = instructions::transfer_nft(
+            &fixture.wallet,
+            &fixture.owner.pubkey(),
+            &fixture.guardian.pubkey(),
+            &self.mint,
+            &self.vault_token_account,
+            &self.destination_owner,
+            &self.destination_token,
+            &Metadata::find_pda(&self.mint).0,
+            &MasterEdition::find_pda(&self.mint).0,
+            &fixture.context.payer.pubkey(),
+            &spl_token::ID,
+            token_record,
+            destination_token_record,
+            authorization_rules.map(|_| Pubkey::new_unique()),
+            authorization_rules,
+        );
+        let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+        self.fixture.process(&[transfer], &[&owner, &guardian]).await
+    }
+}
+
+#[tokio::test]
+async fn nft_moves_from_the_vault_to_the_destination() {
+    let mut nft = VaultNft::mint(TokenStandard::NonFungible, None).await;
+    let state_version = nft.fixture.wallet_state().await.state_version;
+    
+    nft.transfer(false, None).await.unwrap();
+    
+    assert_eq!(nft.fixture.token_account(&nft.vault_token_account).await.unwrap().amount, 0);
+    assert_eq!(nft.fixture.token_account(&nft.destination_token).await.unwrap().amount, 1);
+    assert_eq!(nft.fixture.wallet_state().await.state_version, state_version + 1);
+}
+
+#[tokio::test]
+async fn fungible_tokens_are_not_sent_as_nfts() {
+    let mut nft = VaultNft::mint(TokenStandard::FungibleAsset, None).await;
+    
+    let result = nft.transfer(false, None).await;
+    
+    assert_eq!(custom_error(result), u32::from(ErrorCode::NotAnNft));
+}
+
+#[tokio::test]
+async fn programmable_nft_needs_its_token_records() {
+    let mut nft = VaultNft::mint(TokenStandard::ProgrammableNonFungible, None).await;
+    
+    let result = nft.transfer(false, None).await;
+    assert_eq!(custom_error(result), u32::from(ErrorCode::MissingTokenRecord));
+    
+    nft.transfer(true, None).await.unwrap();
+    assert_eq!(nft.fixture.token_account(&nft.destination_token).await.unwrap().amount, 1);
+}
+
+#[tokio::test]
+async fn programmable_nft_needs_the_rule_set_from_its_metadata() {
+    let rule_set = Pubkey::new_unique();
+    let mut nft = VaultNft::mint(TokenStandard::ProgrammableNonFungible, Some(rule_set)).await;
+    
+    let result = nft.transfer(true, None).await;
+    assert_eq!(custom_error(result), u32::from(ErrorCode::InvalidRuleSet));
+    let result = nft.transfer(true, Some(Pubkey::new_unique())).await;
+    assert_eq!(custom_error(result), u32::from(ErrorCode::InvalidRuleSet));
+    
+    nft.transfer(true, Some(rule_set)).await.unwrap();
+    assert_eq!(nft.fixture.token_account(&nft.destination_token).await.unwrap().amount, 1);
+}
+
+// A wallet whose vault owns a compressed NFT in a fresh tree
+async fn compressed_nft() -> (WalletFixture, Pubkey) {
+    let mut program_test = program_test();
+    program_test.add_program("bubblegum", BUBBLEGUM_PROGRAM_ID, processor!(process_bubblegum));
+    let mut fixture = WalletFixture::start(program_test, None).await;
+    let merkle_tree = Pubkey::new_unique();
+    set_program_data(&mut fixture, &merkle_tree, &BUBBLEGUM_PROGRAM_ID, vec![0; 64]);
+    (fixture, merkle_tree)
+}
+
+fn transfer_compressed_nft(
+    fixture: &WalletFixture,
+    merkle_tree: &Pubkey,
+    new_leaf_owner: &Pubkey,
+    nonce: u64,
+    proof: usize,
+) -> Instruction {
+    let vault = fixture.vault();
+    instructions::transfer_compressed_nft(
+        &fixture.wallet,
+        &fixture.owner.pubkey(),
+        &fixture.guardian.pubkey(),
+        &vault,
+        new_leaf_owner,
+        &Pubkey::new_unique(),
+        merkle_tree,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        [1; 32],
+        [2; 32],
+        [3; 32],
+        nonce,
+        nonce as u32,
+        (0..proof)
+            .map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false))
+            .collect(),
+    )
+}
+
+#[tokio::test]
+async fn compressed_nft_moves_on_the_vault_signature_with_its_proof() {
+    let (mut fixture, merkle_tree) = compressed_nft().await;
+    let new_leaf_owner = Pubkey::new_unique();
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    
+    let transfer = transfer_compressed_nft(&fixture, &merkle_tree, &new_leaf_owner, 7, 3);
+    fixture.process(&[transfer], &[&owner, &guardian]).await.unwrap();
+    
+    let tree = fixture
+        .context
+        .banks_client
+        .get_account(merkle_tree)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(&tree.data[..32], new_leaf_owner.as_ref());
+    assert_eq!(&tree.data[32..40], &7u64.to_le_bytes());
+    assert_eq!(&tree.data[40..44], &7u32.to_le_bytes());
+    assert_eq!(tree.data[44], 3);
+}
+
+#[tokio::test]
+async fn frozen_compressed_nft_is_not_transferred() {
+    let (mut fixture, merkle_tree) = compressed_nft().await;
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let asset_id = Pubkey::find_program_address(
+        &[b"asset", merkle_tree.as_ref(), &7u64.to_le_bytes()],
+        &BUBBLEGUM_PROGRAM_ID,
+    )
+    .0;
+    fixture.freeze_asset(Some(asset_id)).await.unwrap();
+    
+    let frozen = transfer_compressed_nft(&fixture, &merkle_tree, &Pubkey::new_unique(), 7, 3);
+    let result = fixture.process(&[frozen], &[&owner, &guardian]).await;
+    assert_eq!(custom_error(result), u32::from(ErrorCode::AssetFrozen));
+    
+    // Other leaves of the tree are not frozen with it
+    let other = transfer_compressed_nft(&fixture, &merkle_tree, &Pubkey::new_unique(), 8, 3);
+    fixture.process(&[other], &[&owner, &guardian]).await.unwrap();
+}
+
+// A wallet whose vault owns a fresh Core asset
+async fn core_asset() -> (WalletFixture, Pubkey) {
+    let mut program_test = program_test();
+    program_test.add_program("mpl_core", MPL_CORE_PROGRAM_ID, processor!(process_core));
+    let mut fixture = WalletFixture::start(program_test, None).await;
+    let asset = Pubkey::new_unique();
+    let mut data = fixture.vault().to_bytes().to_vec();
+    data.resize(65, 0);
+    set_program_data(&mut fixture, &asset, &MPL_CORE_PROGRAM_ID, data);
+    (fixture, asset)
+}
+
+async fn asset_data(fixture: &mut WalletFixture, asset: &Pubkey) -> Vec<u8> {
+    let account = fixture.context.banks_client.get_account(*asset).await.unwrap();
+    account.unwrap().data
+}
+
+#[tokio::test]
+async fn core_asset_moves_to_the_new_owner() {
+    let (mut fixture, asset) = core_asset().await;
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let new_owner = Pubkey::new_unique();
+    let payer = fixture.context.payer.pubkey();
+    
+    let transfer = instructions::transfer_core_asset(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &asset,
+        &new_owner,
+        &payer,
+        None,
+    );
+    fixture.process(&[transfer.clone()], &[&owner, &guardian]).await.unwrap();
+    
+    assert_eq!(&asset_data(&mut fixture, &asset).await[..32], new_owner.as_ref());
+    // The vault no longer owns it
+    assert!(fixture.process(&[transfer], &[&owner, &guardian]).await.is_err());
+}
+
+#[tokio::test]
+async fn core_asset_in_a_collection_is_burned() {
+    let (mut fixture, asset) = core_asset().await;
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let collection = Pubkey::new_unique();
+    let payer = fixture.context.payer.pubkey();
+    
+    let burn = instructions::burn_core_asset(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &asset,
+        &payer,
+        Some(collection),
+    );
+    fixture.process(&[burn], &[&owner, &guardian]).await.unwrap();
+    
+    assert_eq!(asset_data(&mut fixture, &asset).await[64], 1);
+}
+
+#[tokio::test]
+async fn frozen_core_asset_is_neither_transferred_nor_burned() {
+    let (mut fixture, asset) = core_asset().await;
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let payer = fixture.context.payer.pubkey();
+    fixture.freeze_asset(Some(asset)).await.unwrap();
+    
+    let transfer = instructions::transfer_core_asset(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &asset,
+        &Pubkey::new_unique(),
+        &payer,
+        None,
+    );
+    let result = fixture.process(&[transfer], &[&owner, &guardian]).await;
+    assert_eq!(custom_error(result), u32::from(ErrorCode::AssetFrozen));
+    let burn = instructions::burn_core_asset(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &asset,
+        &payer,
+        None,
+    );
+    let result = fixture.process(&[burn], &[&owner, &guardian]).await;
+    assert_eq!(custom_error(result), u32::from(ErrorCode::AssetFrozen));
+    
+    let data = asset_data(&mut fixture, &asset).await;
+    assert_eq!(&data[..32], fixture.vault().as_ref());
+    assert_eq!(data[64], 0);
+}
+
+#[tokio::test]
+async fn core_update_delegate_is_approved_then_revoked() {
+    let (mut fixture, asset) = core_asset().await;
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let delegate = Pubkey::new_unique();
+    let payer = fixture.context.payer.pubkey();
+    
+    let approve = instructions::approve_core_update_delegate(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &asset,
+        &payer,
+        None,
+        delegate,
+    );
+    fixture.process(&[approve], &[&owner, &guardian]).await.unwrap();
+    assert_eq!(&asset_data(&mut fixture, &asset).await[32..64], delegate.as_ref());
+    
+    let revoke = instructions::revoke_core_update_delegate(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &asset,
+        &payer,
+        None,
+    );
+    fixture.process(&[revoke], &[&owner, &guardian]).await.unwrap();
+    assert_eq!(&asset_data(&mut fixture, &asset).await[32..64], &[0; 32]);
+}
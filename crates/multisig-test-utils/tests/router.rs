@@ -0,0 +1,324 @@
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::solana_program::program_option::COption;
+use multisig::ErrorCode;
+use multisig_interface::core::LIMIT_PRICE_SCALE;
+use multisig_interface::{instructions, pda};
+use multisig_test_utils::{custom_error, program_test, WalletFixture};
+use solana_program_test::{processor, BanksClientError};
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+// What the router does besides the trade it was asked for
+const HONEST: u8 = 0;
+// Takes twice the input it is paid for
+const SKIM: u8 = 1;
+// Leaves the pool as delegate over the seller's remaining input
+const APPROVE_SOURCE: u8 = 2;
+// Leaves the pool as delegate over the proceeds
+const APPROVE_PROCEEDS: u8 = 3;
+
+// A router pulling `amount_in` from the seller into the pool and paying `amount_out` from the
+// pool into the proceeds account. Accounts: source, seller, pool sell account, pool buy
+// account, pool, proceeds, token program; data: mode, amount_in, amount_out
+fn process_router(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [source, seller, pool_sell, pool_buy, pool, proceeds, token_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let mode = data[0];
+    let amount_in = u64::from_le_bytes(data[1..9].try_into().unwrap());
+    let amount_out = u64::from_le_bytes(data[9..17].try_into().unwrap());
+    
+    let taken = if mode == SKIM { amount_in * 2 } else { amount_in };
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            source.key,
+            pool_sell.key,
+            seller.key,
+            &[],
+            taken,
+        )?,
+        &[source.clone(), pool_sell.clone(), seller.clone(), token_program.clone()],
+    )?;
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            pool_buy.key,
+            proceeds.key,
+            pool.key,
+            &[],
+            amount_out,
+        )?,
+        &[pool_buy.clone(), proceeds.clone(), pool.clone(), token_program.clone()],
+    )?;
+    
+    let approved = match mode {
+        APPROVE_SOURCE => source,
+        APPROVE_PROCEEDS => proceeds,
+        _ => return Ok(()),
+    };
+    invoke(
+        &spl_token::instruction::approve(
+            token_program.key,
+            approved.key,
+            pool.key,
+            seller.key,
+            &[],
+            u64::MAX,
+        )?,
+        &[approved.clone(), pool.clone(), seller.clone(), token_program.clone()],
+    )
+}
+
+fn router_data(mode: u8, amount_in: u64, amount_out: u64) -> Vec<u8> {
+    let mut data = vec![mode];
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&amount_out.to_le_bytes());
+    data
+}
+
+// A wallet with the router allowlisted, 1000 sell tokens in its vault and a pool holding
+// both tokens
+struct Market {
+    fixture: WalletFixture,
+    router: Pubkey,
+    pool: Keypair,
+    sell_mint: Pubkey,
+    buy_mint: Pubkey,
+    pool_sell: Pubkey,
+    pool_buy: Pubkey,
+    vault_sell: Pubkey,
+    vault_buy: Pubkey,
+}
+
+impl Market {
+    async fn open() -> Self {
+        let router = Pubkey::new_unique();
+        let mut program_test = program_test();
+        program_test.add_program("malicious_router", router, processor!(process_router));
+        let mut fixture = WalletFixture::start(program_test, None).await;
+        
+        let vault = fixture.vault();
+        let pool = Keypair::new();
+        let (sell_mint, buy_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        fixture.set_mint(&sell_mint, Some(Pubkey::new_unique()), None);
+        fixture.set_mint(&buy_mint, Some(Pubkey::new_unique()), None);
+        let (pool_sell, pool_buy) = (Pubkey::new_unique(), Pubkey::new_unique());
+        fixture.set_token_account(&pool_sell, &sell_mint, &pool.pubkey(), 0);
+        fixture.set_token_account(&pool_buy, &buy_mint, &pool.pubkey(), 1_000_000);
+        let vault_sell = get_associated_token_address(&vault, &sell_mint);
+        let vault_buy = get_associated_token_address(&vault, &buy_mint);
+        fixture.set_token_account(&vault_sell, &sell_mint, &vault, 1_000);
+        fixture.set_token_account(&vault_buy, &buy_mint, &vault, 0);
+        
+        let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+        let allow = instructions::set_swap_routers(
+            &fixture.wallet,
+            &owner.pubkey(),
+            &guardian.pubkey(),
+            &fixture.context.payer.pubkey(),
+            vec![router],
+        );
+        fixture.process(&[allow], &[&owner, &guardian]).await.unwrap();
+        
+        Self {
+            fixture,
+            router,
+            pool,
+            sell_mint,
+            buy_mint,
+            pool_sell,
+            pool_buy,
+            vault_sell,
+            vault_buy,
+        }
+    }
+    
+    // Accounts for the router to trade `source`, owned by `seller`, for the vault's buy tokens
+    fn router_accounts(&self, source: &Pubkey, seller: &Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(*source, false),
+            AccountMeta::new_readonly(*seller, false),
+            AccountMeta::new(self.pool_sell, false),
+            AccountMeta::new(self.pool_buy, false),
+            AccountMeta::new_readonly(self.pool.pubkey(), true),
+            AccountMeta::new(self.vault_buy, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ]
+    }
+    
+    async fn swap(
+        &mut self,
+        mode: u8,
+        amount_in: u64,
+        amount_out: u64,
+        minimum_amount_out: u64,
+    ) -> Result<(), BanksClientError> {
+        let fixture = &self.fixture;
+        let vault = fixture.vault();
+        let swap = instructions::swap(
+            &fixture.wallet,
+            &fixture.owner.pubkey(),
+            &fixture.guardian.pubkey(),
+            &self.router,
+            &self.vault_buy,
+            router_data(mode, amount_in, amount_out),
+            minimum_amount_out,
+            self.router_accounts(&self.vault_sell, &vault),
+        );
+        let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+        let pool = self.pool.insecure_clone();
+        self.fixture.process(&[swap], &[&owner, &guardian, &pool]).await
+    }
+    
+    // Escrow 100 sell tokens at 2 buy tokens each, returning the order and its escrow
+    async fn place_order(&mut self) -> (Pubkey, Pubkey) {
+        let fixture = &mut self.fixture;
+        let limit_order = pda::limit_order(&fixture.wallet, 1).0;
+        let escrow = get_associated_token_address(&limit_order, &self.sell_mint);
+        let expiry = fixture.now().await + 3600;
+        let create = instructions::create_limit_order(
+            &fixture.wallet,
+            &fixture.owner.pubkey(),
+            &fixture.guardian.pubkey(),
+            &self.sell_mint,
+            &self.vault_sell,
+            &escrow,
+            &spl_token::ID,
+            &fixture.context.payer.pubkey(),
+            1,
+            self.sell_mint,
+            self.buy_mint,
+            100,
+            2 * LIMIT_PRICE_SCALE,
+            expiry,
+        );
+        let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+        fixture.process(&[create], &[&owner, &guardian]).await.unwrap();
+        (limit_order, escrow)
+    }
+    
+    async fn fill(
+        &mut self,
+        limit_order: &Pubkey,
+        escrow: &Pubkey,
+        mode: u8,
+        amount_in: u64,
+        amount_out: u64,
+    ) -> Result<(), BanksClientError> {
+        let fill = instructions::fill_limit_order(
+            &self.fixture.wallet,
+            limit_order,
+            &self.sell_mint,
+            escrow,
+            &self.vault_buy,
+            &self.router,
+            &spl_token::ID,
+            router_data(mode, amount_in, amount_out),
+            self.router_accounts(escrow, limit_order),
+        );
+        let pool = self.pool.insecure_clone();
+        self.fixture.process(&[fill], &[&pool]).await
+    }
+}
+
+#[tokio::test]
+async fn swap_lands_when_the_minimum_arrives() {
+    let mut market = Market::open().await;
+    
+    market.swap(HONEST, 100, 200, 200).await.unwrap();
+    
+    let vault_buy = market.fixture.token_account(&market.vault_buy).await.unwrap();
+    let vault_sell = market.fixture.token_account(&market.vault_sell).await.unwrap();
+    assert_eq!(vault_buy.amount, 200);
+    assert_eq!(vault_sell.amount, 900);
+}
+
+#[tokio::test]
+async fn swap_rejects_a_router_that_underpays() {
+    let mut market = Market::open().await;
+    
+    let result = market.swap(HONEST, 100, 199, 200).await;
+    
+    assert_eq!(custom_error(result), u32::from(ErrorCode::SlippageExceeded));
+    let vault_sell = market.fixture.token_account(&market.vault_sell).await.unwrap();
+    assert_eq!(vault_sell.amount, 1_000);
+}
+
+#[tokio::test]
+async fn swap_rejects_a_router_that_delegates_the_proceeds() {
+    let mut market = Market::open().await;
+    
+    let result = market.swap(APPROVE_PROCEEDS, 100, 200, 200).await;
+    
+    assert_eq!(custom_error(result), u32::from(ErrorCode::InvalidTokenAccount));
+    let vault_buy = market.fixture.token_account(&market.vault_buy).await.unwrap();
+    assert_eq!(vault_buy.delegate, COption::None);
+}
+
+#[tokio::test]
+async fn limit_order_fills_at_its_price_and_closes() {
+    let mut market = Market::open().await;
+    let (limit_order, escrow) = market.place_order().await;
+    
+    market.fill(&limit_order, &escrow, HONEST, 40, 80).await.unwrap();
+    assert_eq!(market.fixture.token_account(&escrow).await.unwrap().amount, 60);
+    let vault_buy = market.fixture.token_account(&market.vault_buy).await.unwrap();
+    assert_eq!(vault_buy.amount, 80);
+    
+    market.fill(&limit_order, &escrow, HONEST, 60, 120).await.unwrap();
+    assert!(market.fixture.token_account(&escrow).await.is_none());
+    let vault_buy = market.fixture.token_account(&market.vault_buy).await.unwrap();
+    assert_eq!(vault_buy.amount, 200);
+    let order = market
+        .fixture
+        .context
+        .banks_client
+        .get_account(limit_order)
+        .await
+        .unwrap();
+    assert!(order.is_none());
+}
+
+#[tokio::test]
+async fn limit_order_rejects_a_router_that_underpays() {
+    let mut market = Market::open().await;
+    let (limit_order, escrow) = market.place_order().await;
+    
+    let result = market.fill(&limit_order, &escrow, HONEST, 100, 199).await;
+    
+    assert_eq!(custom_error(result), u32::from(ErrorCode::LimitPriceNotMet));
+    assert_eq!(market.fixture.token_account(&escrow).await.unwrap().amount, 100);
+}
+
+#[tokio::test]
+async fn limit_order_rejects_a_router_that_skims_the_escrow() {
+    let mut market = Market::open().await;
+    let (limit_order, escrow) = market.place_order().await;
+    
+    // Paid for 40 at the order's price while taking 80
+    let result = market.fill(&limit_order, &escrow, SKIM, 40, 80).await;
+    
+    assert_eq!(custom_error(result), u32::from(ErrorCode::LimitPriceNotMet));
+    let pool_sell = market.fixture.token_account(&market.pool_sell).await.unwrap();
+    assert_eq!(pool_sell.amount, 0);
+}
+
+#[tokio::test]
+async fn limit_order_rejects_a_router_that_delegates_the_escrow() {
+    let mut market = Market::open().await;
+    let (limit_order, escrow) = market.place_order().await;
+    
+    let result = market.fill(&limit_order, &escrow, APPROVE_SOURCE, 40, 80).await;
+    
+    assert_eq!(custom_error(result), u32::from(ErrorCode::InvalidTokenAccount));
+    let escrow_account = market.fixture.token_account(&escrow).await.unwrap();
+    assert_eq!(escrow_account.delegate, COption::None);
+    assert_eq!(escrow_account.amount, 100);
+}
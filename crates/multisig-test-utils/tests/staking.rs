@@ -0,0 +1,247 @@
+use anchor_lang::context::CpiContext;
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token;
+use multisig::{ErrorCode, SPL_STAKE_POOL_PROGRAM_ID};
+use multisig_interface::instructions;
+use multisig_test_utils::{custom_error, program_test, WalletFixture};
+use solana_program_test::{processor, BanksClientError};
+use solana_sdk::account::AccountSharedData;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+
+// SPL stake pool instructions sent by the wallet
+const DEPOSIT_SOL: u8 = 14;
+const WITHDRAW_SOL: u8 = 16;
+
+fn withdraw_authority(stake_pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[stake_pool.as_ref(), b"withdraw"], &SPL_STAKE_POOL_PROGRAM_ID)
+}
+
+// Stake pool stand-in keeping deposits in its reserve. It pays out the percentage stored in
+// the pool account's first byte: pool tokens per lamport deposited, lamports per pool token
+// withdrawn.
+fn process_stake_pool(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+    let rate = |stake_pool: &AccountInfo| -> Result<u64, ProgramError> {
+        Ok(amount * stake_pool.try_borrow_data()?[0] as u64 / 100)
+    };
+    match data[0] {
+        DEPOSIT_SOL => {
+            let [stake_pool, authority, reserve, from, pool_tokens, .., pool_mint, system, token] =
+                accounts
+            else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            system_program::transfer(
+                CpiContext::new(
+                    system.clone(),
+                    system_program::Transfer {
+                        from: from.clone(),
+                        to: reserve.clone(),
+                    },
+                ),
+                amount,
+            )?;
+            let bump = withdraw_authority(stake_pool.key).1;
+            invoke_signed(
+                &spl_token::instruction::mint_to(
+                    token.key,
+                    pool_mint.key,
+                    pool_tokens.key,
+                    authority.key,
+                    &[],
+                    rate(stake_pool)?,
+                )?,
+                &[pool_mint.clone(), pool_tokens.clone(), authority.clone(), token.clone()],
+                &[&[stake_pool.key.as_ref(), b"withdraw", &[bump]]],
+            )
+        }
+        WITHDRAW_SOL => {
+            let [stake_pool, _, authority, source, reserve, destination, _, pool_mint, .., token] =
+                accounts
+            else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            invoke(
+                &spl_token::instruction::burn(
+                    token.key,
+                    source.key,
+                    pool_mint.key,
+                    authority.key,
+                    &[],
+                    amount,
+                )?,
+                &[source.clone(), pool_mint.clone(), authority.clone(), token.clone()],
+            )?;
+            let lamports = rate(stake_pool)?;
+            **reserve.try_borrow_mut_lamports()? -= lamports;
+            **destination.try_borrow_mut_lamports()? += lamports;
+            Ok(())
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+// A wallet whose vault holds 10 SOL and 1 SOL worth of pool tokens, and a pool paying out
+// `rate` percent
+struct StakePool {
+    fixture: WalletFixture,
+    stake_pool: Pubkey,
+    reserve: Pubkey,
+    pool_mint: Pubkey,
+    vault_pool_tokens: Pubkey,
+}
+
+impl StakePool {
+    async fn open(rate: u8) -> Self {
+        let mut program_test = program_test();
+        program_test.add_program(
+            "spl_stake_pool",
+            SPL_STAKE_POOL_PROGRAM_ID,
+            processor!(process_stake_pool),
+        );
+        let mut fixture = WalletFixture::start(program_test, None).await;
+        
+        let vault = fixture.vault();
+        fixture.set_lamports(&vault, 10 * LAMPORTS_PER_SOL);
+        let (stake_pool, reserve) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut pool = AccountSharedData::new(LAMPORTS_PER_SOL, 1, &SPL_STAKE_POOL_PROGRAM_ID);
+        pool.set_data_from_slice(&[rate]);
+        fixture.context.set_account(&stake_pool, &pool);
+        let reserve_account =
+            AccountSharedData::new(10 * LAMPORTS_PER_SOL, 0, &SPL_STAKE_POOL_PROGRAM_ID);
+        fixture.context.set_account(&reserve, &reserve_account);
+        let pool_mint = Pubkey::new_unique();
+        fixture.set_mint(&pool_mint, Some(withdraw_authority(&stake_pool).0), None);
+        let vault_pool_tokens = get_associated_token_address(&vault, &pool_mint);
+        fixture.set_token_account(&vault_pool_tokens, &pool_mint, &vault, LAMPORTS_PER_SOL);
+        
+        Self {
+            fixture,
+            stake_pool,
+            reserve,
+            pool_mint,
+            vault_pool_tokens,
+        }
+    }
+    
+    async fn deposit(
+        &mut self,
+        lamports: u64,
+        minimum_pool_tokens_out: u64,
+    ) -> Result<(), BanksClientError> {
+        let fixture = &mut self.fixture;
+        let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+        let deposit = instructions::deposit_stake_pool_sol(
+            &fixture.wallet,
+            &owner.pubkey(),
+            &guardian.pubkey(),
+            &self.stake_pool,
+            &withdraw_authority(&self.stake_pool).0,
+            &self.reserve,
+            &self.pool_mint,
+            &self.vault_pool_tokens,
+            &Pubkey::new_unique(),
+            &spl_token::ID,
+            lamports,
+            minimum_pool_tokens_out,
+        );
+        fixture.process(&[deposit], &[&owner, &guardian]).await
+    }
+    
+    async fn withdraw(
+        &mut self,
+        pool_tokens: u64,
+        minimum_lamports_out: u64,
+    ) -> Result<(), BanksClientError> {
+        let fixture = &mut self.fixture;
+        let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+        let withdraw = instructions::withdraw_stake_pool_sol(
+            &fixture.wallet,
+            &owner.pubkey(),
+            &guardian.pubkey(),
+            &self.stake_pool,
+            &withdraw_authority(&self.stake_pool).0,
+            &self.reserve,
+            &self.pool_mint,
+            &self.vault_pool_tokens,
+            &Pubkey::new_unique(),
+            &spl_token::ID,
+            pool_tokens,
+            minimum_lamports_out,
+        );
+        fixture.process(&[withdraw], &[&owner, &guardian]).await
+    }
+    
+    async fn vault_balances(&mut self) -> (u64, u64) {
+        let vault = self.fixture.vault();
+        let lamports = self.fixture.lamports(&vault).await;
+        let pool_tokens = self.fixture.token_account(&self.vault_pool_tokens).await.unwrap();
+        (lamports, pool_tokens.amount)
+    }
+}
+
+#[tokio::test]
+async fn deposited_sol_mints_pool_tokens_to_the_vault() {
+    let mut pool = StakePool::open(100).await;
+    
+    pool.deposit(2 * LAMPORTS_PER_SOL, 2 * LAMPORTS_PER_SOL).await.unwrap();
+    
+    assert_eq!(pool.vault_balances().await, (8 * LAMPORTS_PER_SOL, 3 * LAMPORTS_PER_SOL));
+    let reserve = pool.reserve;
+    assert_eq!(pool.fixture.lamports(&reserve).await, 12 * LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn deposit_minting_too_few_pool_tokens_is_reverted() {
+    let mut pool = StakePool::open(90).await;
+    
+    let result = pool.deposit(2 * LAMPORTS_PER_SOL, 2 * LAMPORTS_PER_SOL).await;
+    
+    assert_eq!(custom_error(result), u32::from(ErrorCode::SlippageExceeded));
+    assert_eq!(pool.vault_balances().await, (10 * LAMPORTS_PER_SOL, LAMPORTS_PER_SOL));
+    // What the pool does pay is accepted down to the minimum
+    pool.deposit(2 * LAMPORTS_PER_SOL, 18 * LAMPORTS_PER_SOL / 10).await.unwrap();
+}
+
+#[tokio::test]
+async fn frozen_sol_is_not_deposited() {
+    let mut pool = StakePool::open(100).await;
+    pool.fixture.freeze_asset(None).await.unwrap();
+    
+    let result = pool.deposit(2 * LAMPORTS_PER_SOL, 0).await;
+    
+    assert_eq!(custom_error(result), u32::from(ErrorCode::AssetFrozen));
+    assert_eq!(pool.vault_balances().await, (10 * LAMPORTS_PER_SOL, LAMPORTS_PER_SOL));
+}
+
+#[tokio::test]
+async fn withdrawn_pool_tokens_pay_sol_into_the_vault() {
+    let mut pool = StakePool::open(100).await;
+    
+    pool.withdraw(LAMPORTS_PER_SOL, LAMPORTS_PER_SOL).await.unwrap();
+    
+    assert_eq!(pool.vault_balances().await, (11 * LAMPORTS_PER_SOL, 0));
+    let reserve = pool.reserve;
+    assert_eq!(pool.fixture.lamports(&reserve).await, 9 * LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn withdrawal_paying_too_few_lamports_is_reverted() {
+    let mut pool = StakePool::open(90).await;
+    
+    let result = pool.withdraw(LAMPORTS_PER_SOL, LAMPORTS_PER_SOL).await;
+    
+    assert_eq!(custom_error(result), u32::from(ErrorCode::SlippageExceeded));
+    assert_eq!(pool.vault_balances().await, (10 * LAMPORTS_PER_SOL, LAMPORTS_PER_SOL));
+}
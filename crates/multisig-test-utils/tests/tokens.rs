@@ -0,0 +1,609 @@
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::solana_program::program_pack::Pack;
+use multisig::ErrorCode;
+use multisig_interface::instructions;
+use multisig_test_utils::{custom_error, WalletFixture};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::program_option::COption;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signer};
+
+// A wallet and a fresh mint, returning the mint and the vault's associated token account for it
+async fn wallet_with_mint() -> (WalletFixture, Pubkey, Pubkey) {
+    let mut fixture = WalletFixture::new().await;
+    let mint = Pubkey::new_unique();
+    fixture.set_mint(&mint, Some(Pubkey::new_unique()), None);
+    let vault_token_account = get_associated_token_address(&fixture.vault(), &mint);
+    (fixture, mint, vault_token_account)
+}
+
+#[tokio::test]
+async fn vault_token_account_is_created_for_a_new_mint() {
+    let (mut fixture, mint, vault_token_account) = wallet_with_mint().await;
+    let payer = fixture.context.payer.pubkey();
+    
+    let create = instructions::create_associated_token_account(
+        &fixture.wallet,
+        &mint,
+        &vault_token_account,
+        &payer,
+        &spl_token::ID,
+    );
+    fixture.process(&[create.clone()], &[]).await.unwrap();
+    
+    let account = fixture.token_account(&vault_token_account).await.unwrap();
+    assert_eq!(account.owner, fixture.vault());
+    assert_eq!(account.mint, mint);
+    assert_eq!(account.amount, 0);
+    assert!(fixture.process(&[create], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn idempotent_creation_accepts_an_existing_vault_token_account() {
+    let (mut fixture, mint, vault_token_account) = wallet_with_mint().await;
+    let payer = fixture.context.payer.pubkey();
+    let create = instructions::create_associated_token_account_idempotent(
+        &fixture.wallet,
+        &mint,
+        &vault_token_account,
+        &payer,
+        &spl_token::ID,
+    );
+    
+    fixture.process(&[create.clone()], &[]).await.unwrap();
+    fixture.process(&[create], &[]).await.unwrap();
+    
+    assert_eq!(fixture.token_account(&vault_token_account).await.unwrap().owner, fixture.vault());
+}
+
+#[tokio::test]
+async fn only_the_vault_associated_token_account_is_created() {
+    let (mut fixture, mint, _) = wallet_with_mint().await;
+    let payer = fixture.context.payer.pubkey();
+    let payer_token_account = get_associated_token_address(&payer, &mint);
+    
+    let create = instructions::create_associated_token_account_idempotent(
+        &fixture.wallet,
+        &mint,
+        &payer_token_account,
+        &payer,
+        &spl_token::ID,
+    );
+    
+    assert!(fixture.process(&[create], &[]).await.is_err());
+    assert!(fixture.token_account(&payer_token_account).await.is_none());
+}
+
+// Fund the vault's token account for `mint`, returning it
+fn fund_vault(fixture: &mut WalletFixture, mint: &Pubkey, amount: u64) -> Pubkey {
+    let vault = fixture.vault();
+    let vault_token_account = get_associated_token_address(&vault, mint);
+    fixture.set_token_account(&vault_token_account, mint, &vault, amount);
+    vault_token_account
+}
+
+fn approve_delegate(
+    fixture: &WalletFixture,
+    mint: &Pubkey,
+    vault_token_account: &Pubkey,
+    delegate: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    instructions::approve_token_delegate(
+        &fixture.wallet,
+        &fixture.owner.pubkey(),
+        &fixture.guardian.pubkey(),
+        mint,
+        vault_token_account,
+        delegate,
+        &fixture.context.payer.pubkey(),
+        &spl_token::ID,
+        amount,
+    )
+}
+
+#[tokio::test]
+async fn approved_delegate_is_recorded_and_replaced() {
+    let (mut fixture, mint, _) = wallet_with_mint().await;
+    let vault_token_account = fund_vault(&mut fixture, &mint, 1_000);
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let open_accounts = fixture.wallet_state().await.open_accounts;
+    
+    let approve = approve_delegate(&fixture, &mint, &vault_token_account, &first, 100);
+    fixture.process(&[approve], &[&owner, &guardian]).await.unwrap();
+    
+    let account = fixture.token_account(&vault_token_account).await.unwrap();
+    assert_eq!(account.delegate, COption::Some(first));
+    assert_eq!(account.delegated_amount, 100);
+    let delegations = fixture.delegations().await;
+    assert_eq!(delegations.len(), 1);
+    assert_eq!(delegations[0].token_account, vault_token_account);
+    assert_eq!(delegations[0].mint, mint);
+    assert_eq!(delegations[0].delegate, first);
+    assert_eq!(delegations[0].amount, 100);
+    // The registry opened on the first approval is counted with the wallet's accounts
+    assert_eq!(fixture.wallet_state().await.open_accounts, open_accounts + 1);
+    
+    let approve = approve_delegate(&fixture, &mint, &vault_token_account, &second, 50);
+    fixture.process(&[approve], &[&owner, &guardian]).await.unwrap();
+    
+    let account = fixture.token_account(&vault_token_account).await.unwrap();
+    assert_eq!(account.delegate, COption::Some(second));
+    let delegations = fixture.delegations().await;
+    assert_eq!(delegations.len(), 1);
+    assert_eq!((delegations[0].delegate, delegations[0].amount), (second, 50));
+    assert_eq!(fixture.wallet_state().await.open_accounts, open_accounts + 1);
+}
+
+#[tokio::test]
+async fn frozen_mint_is_not_delegated() {
+    let (mut fixture, mint, _) = wallet_with_mint().await;
+    let vault_token_account = fund_vault(&mut fixture, &mint, 1_000);
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    fixture.freeze_asset(Some(mint)).await.unwrap();
+    
+    let approve =
+        approve_delegate(&fixture, &mint, &vault_token_account, &Pubkey::new_unique(), 100);
+    let result = fixture.process(&[approve], &[&owner, &guardian]).await;
+    
+    assert_eq!(custom_error(result), u32::from(ErrorCode::AssetFrozen));
+    assert_eq!(fixture.token_account(&vault_token_account).await.unwrap().delegate, COption::None);
+}
+
+#[tokio::test]
+async fn guardian_alone_revokes_a_delegate() {
+    let (mut fixture, mint, _) = wallet_with_mint().await;
+    let vault_token_account = fund_vault(&mut fixture, &mint, 1_000);
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let approve =
+        approve_delegate(&fixture, &mint, &vault_token_account, &Pubkey::new_unique(), 100);
+    fixture.process(&[approve], &[&owner, &guardian]).await.unwrap();
+    
+    let stranger = Keypair::new();
+    let revoke = instructions::revoke_token_delegate(
+        &fixture.wallet,
+        &stranger.pubkey(),
+        &vault_token_account,
+        &spl_token::ID,
+    );
+    let result = fixture.process(&[revoke], &[&stranger]).await;
+    assert_eq!(custom_error(result), u32::from(ErrorCode::NotOwnerOrGuardian));
+    
+    let revoke = instructions::revoke_token_delegate(
+        &fixture.wallet,
+        &guardian.pubkey(),
+        &vault_token_account,
+        &spl_token::ID,
+    );
+    fixture.process(&[revoke], &[&guardian]).await.unwrap();
+    
+    let account = fixture.token_account(&vault_token_account).await.unwrap();
+    assert_eq!(account.delegate, COption::None);
+    assert_eq!(account.delegated_amount, 0);
+    assert!(fixture.delegations().await.is_empty());
+}
+
+#[tokio::test]
+async fn every_delegate_is_revoked_at_once() {
+    let (mut fixture, first_mint, _) = wallet_with_mint().await;
+    let second_mint = Pubkey::new_unique();
+    fixture.set_mint(&second_mint, Some(Pubkey::new_unique()), None);
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let mut token_accounts = Vec::new();
+    for mint in [first_mint, second_mint] {
+        let vault_token_account = fund_vault(&mut fixture, &mint, 1_000);
+        let approve =
+            approve_delegate(&fixture, &mint, &vault_token_account, &Pubkey::new_unique(), 100);
+        fixture.process(&[approve], &[&owner, &guardian]).await.unwrap();
+        token_accounts.push(vault_token_account);
+    }
+    assert_eq!(fixture.delegations().await.len(), 2);
+    
+    // A token account the vault does not own is refused
+    let foreign = Pubkey::new_unique();
+    fixture.set_token_account(&foreign, &first_mint, &Pubkey::new_unique(), 0);
+    let revoke_all = instructions::revoke_all_token_delegates(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &spl_token::ID,
+        vec![AccountMeta::new(foreign, false)],
+    );
+    let result = fixture.process(&[revoke_all], &[&owner]).await;
+    assert_eq!(custom_error(result), u32::from(ErrorCode::InvalidTokenAccount));
+    
+    let revoke_all = instructions::revoke_all_token_delegates(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &spl_token::ID,
+        token_accounts.iter().map(|account| AccountMeta::new(*account, false)).collect(),
+    );
+    fixture.process(&[revoke_all], &[&owner]).await.unwrap();
+    
+    for vault_token_account in &token_accounts {
+        let account = fixture.token_account(vault_token_account).await.unwrap();
+        assert_eq!(account.delegate, COption::None);
+    }
+    assert!(fixture.delegations().await.is_empty());
+}
+
+// A wallet whose vault holds 10 SOL, returning the address of its wSOL account
+async fn wallet_with_sol() -> (WalletFixture, Pubkey) {
+    let mut fixture = WalletFixture::new().await;
+    let vault = fixture.vault();
+    fixture.set_lamports(&vault, 10 * LAMPORTS_PER_SOL);
+    let wsol_account = get_associated_token_address(&vault, &spl_token::native_mint::ID);
+    (fixture, wsol_account)
+}
+
+#[tokio::test]
+async fn sol_is_wrapped_in_the_vault_and_unwrapped_back() {
+    let (mut fixture, wsol_account) = wallet_with_sol().await;
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let payer = fixture.context.payer.pubkey();
+    let vault = fixture.vault();
+    
+    // The wSOL account is created on the first wrap and topped up on the next
+    for amount in [2 * LAMPORTS_PER_SOL, LAMPORTS_PER_SOL] {
+        let wrap = instructions::wrap_sol(
+            &fixture.wallet,
+            &owner.pubkey(),
+            &guardian.pubkey(),
+            &wsol_account,
+            &payer,
+            amount,
+        );
+        fixture.process(&[wrap], &[&owner, &guardian]).await.unwrap();
+    }
+    
+    let account = fixture.token_account(&wsol_account).await.unwrap();
+    assert_eq!(account.owner, vault);
+    assert_eq!(account.amount, 3 * LAMPORTS_PER_SOL);
+    // The payer funded the account's rent, the vault only the wrapped amount
+    assert_eq!(fixture.lamports(&vault).await, 7 * LAMPORTS_PER_SOL);
+    
+    let unwrap = instructions::unwrap_sol(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &wsol_account,
+    );
+    fixture.process(&[unwrap], &[&owner, &guardian]).await.unwrap();
+    
+    assert!(fixture.token_account(&wsol_account).await.is_none());
+    let rent = Rent::default().minimum_balance(spl_token::state::Account::LEN);
+    assert_eq!(fixture.lamports(&vault).await, 10 * LAMPORTS_PER_SOL + rent);
+}
+
+#[tokio::test]
+async fn frozen_sol_is_not_wrapped() {
+    let (mut fixture, wsol_account) = wallet_with_sol().await;
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let payer = fixture.context.payer.pubkey();
+    let vault = fixture.vault();
+    fixture.freeze_asset(None).await.unwrap();
+    
+    let wrap = instructions::wrap_sol(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &wsol_account,
+        &payer,
+        LAMPORTS_PER_SOL,
+    );
+    let result = fixture.process(&[wrap], &[&owner, &guardian]).await;
+    
+    assert_eq!(custom_error(result), u32::from(ErrorCode::AssetFrozen));
+    assert!(fixture.token_account(&wsol_account).await.is_none());
+    assert_eq!(fixture.lamports(&vault).await, 10 * LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn vault_mints_only_what_it_is_mint_authority_of() {
+    let mut fixture = WalletFixture::new().await;
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let (mint, foreign_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    fixture.set_mint(&mint, Some(fixture.vault()), None);
+    fixture.set_mint(&foreign_mint, Some(Pubkey::new_unique()), None);
+    let (destination, foreign_destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+    fixture.set_token_account(&destination, &mint, &Pubkey::new_unique(), 0);
+    fixture.set_token_account(&foreign_destination, &foreign_mint, &Pubkey::new_unique(), 0);
+    
+    let mint_tokens = instructions::mint_tokens(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &mint,
+        &destination,
+        &spl_token::ID,
+        500,
+    );
+    fixture.process(&[mint_tokens], &[&owner, &guardian]).await.unwrap();
+    
+    assert_eq!(fixture.token_account(&destination).await.unwrap().amount, 500);
+    assert_eq!(fixture.mint(&mint).await.supply, (1 << 40) + 500);
+    
+    let mint_tokens = instructions::mint_tokens(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &foreign_mint,
+        &foreign_destination,
+        &spl_token::ID,
+        500,
+    );
+    let result = fixture.process(&[mint_tokens], &[&owner, &guardian]).await;
+    assert_eq!(
+        custom_error(result),
+        u32::from(anchor_lang::error::ErrorCode::ConstraintMintMintAuthority)
+    );
+}
+
+#[tokio::test]
+async fn vault_burns_its_tokens_unless_the_mint_is_frozen() {
+    let (mut fixture, mint, _) = wallet_with_mint().await;
+    let vault_token_account = fund_vault(&mut fixture, &mint, 1_000);
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let burn = instructions::burn_tokens(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &mint,
+        &vault_token_account,
+        &spl_token::ID,
+        400,
+    );
+    
+    fixture.process(&[burn.clone()], &[&owner, &guardian]).await.unwrap();
+    
+    assert_eq!(fixture.token_account(&vault_token_account).await.unwrap().amount, 600);
+    assert_eq!(fixture.mint(&mint).await.supply, (1 << 40) - 400);
+    
+    fixture.freeze_asset(Some(mint)).await.unwrap();
+    let result = fixture.process(&[burn], &[&owner, &guardian]).await;
+    assert_eq!(custom_error(result), u32::from(ErrorCode::AssetFrozen));
+    assert_eq!(fixture.token_account(&vault_token_account).await.unwrap().amount, 600);
+}
+
+#[tokio::test]
+async fn vault_freezes_and_thaws_accounts_of_a_mint_it_is_freeze_authority_of() {
+    let mut fixture = WalletFixture::new().await;
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let (mint, foreign_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    fixture.set_mint(&mint, None, Some(fixture.vault()));
+    fixture.set_mint(&foreign_mint, None, Some(Pubkey::new_unique()));
+    let (holder, foreign_holder) = (Pubkey::new_unique(), Pubkey::new_unique());
+    fixture.set_token_account(&holder, &mint, &Pubkey::new_unique(), 100);
+    fixture.set_token_account(&foreign_holder, &foreign_mint, &Pubkey::new_unique(), 100);
+    
+    let freeze = instructions::freeze_token_account(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &mint,
+        &holder,
+        &spl_token::ID,
+    );
+    fixture.process(&[freeze], &[&owner, &guardian]).await.unwrap();
+    assert!(fixture.token_account(&holder).await.unwrap().is_frozen());
+    
+    let thaw = instructions::thaw_token_account(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &mint,
+        &holder,
+        &spl_token::ID,
+    );
+    fixture.process(&[thaw], &[&owner, &guardian]).await.unwrap();
+    assert!(!fixture.token_account(&holder).await.unwrap().is_frozen());
+    
+    let freeze = instructions::freeze_token_account(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &foreign_mint,
+        &foreign_holder,
+        &spl_token::ID,
+    );
+    let result = fixture.process(&[freeze], &[&owner, &guardian]).await;
+    assert_eq!(
+        custom_error(result),
+        u32::from(anchor_lang::error::ErrorCode::ConstraintMintFreezeAuthority)
+    );
+}
+
+fn close_token_accounts(
+    fixture: &WalletFixture,
+    authority: &Keypair,
+    token_accounts: &[Pubkey],
+) -> Instruction {
+    instructions::close_token_accounts(
+        &fixture.wallet,
+        &authority.pubkey(),
+        &spl_token::ID,
+        token_accounts.iter().map(|account| AccountMeta::new(*account, false)).collect(),
+    )
+}
+
+#[tokio::test]
+async fn empty_token_accounts_are_closed_into_the_vault() {
+    let (mut fixture, first_mint, _) = wallet_with_mint().await;
+    let second_mint = Pubkey::new_unique();
+    fixture.set_mint(&second_mint, Some(Pubkey::new_unique()), None);
+    let token_accounts = [
+        fund_vault(&mut fixture, &first_mint, 0),
+        fund_vault(&mut fixture, &second_mint, 0),
+    ];
+    let guardian = fixture.guardian.insecure_clone();
+    let vault = fixture.vault();
+    let lamports = fixture.lamports(&vault).await;
+    
+    let close = close_token_accounts(&fixture, &guardian, &token_accounts);
+    fixture.process(&[close], &[&guardian]).await.unwrap();
+    
+    for token_account in &token_accounts {
+        assert!(fixture.token_account(token_account).await.is_none());
+    }
+    let rent = Rent::default().minimum_balance(spl_token::state::Account::LEN);
+    assert_eq!(fixture.lamports(&vault).await, lamports + 2 * rent);
+}
+
+#[tokio::test]
+async fn token_accounts_with_a_balance_are_not_closed() {
+    let (mut fixture, first_mint, _) = wallet_with_mint().await;
+    let second_mint = Pubkey::new_unique();
+    fixture.set_mint(&second_mint, Some(Pubkey::new_unique()), None);
+    let empty = fund_vault(&mut fixture, &first_mint, 0);
+    let funded = fund_vault(&mut fixture, &second_mint, 5);
+    let owner = fixture.owner.insecure_clone();
+    
+    let close = close_token_accounts(&fixture, &owner, &[empty, funded]);
+    let result = fixture.process(&[close], &[&owner]).await;
+    
+    assert_eq!(custom_error(result), u32::from(ErrorCode::TokenAccountNotEmpty));
+    assert!(fixture.token_account(&empty).await.is_some());
+    assert_eq!(fixture.token_account(&funded).await.unwrap().amount, 5);
+}
+
+#[tokio::test]
+async fn foreign_and_frozen_token_accounts_are_not_closed() {
+    let (mut fixture, mint, _) = wallet_with_mint().await;
+    let owner = fixture.owner.insecure_clone();
+    let vault_token_account = fund_vault(&mut fixture, &mint, 0);
+    let foreign = Pubkey::new_unique();
+    fixture.set_token_account(&foreign, &mint, &Pubkey::new_unique(), 0);
+    
+    let close = close_token_accounts(&fixture, &owner, &[foreign]);
+    let result = fixture.process(&[close], &[&owner]).await;
+    assert_eq!(custom_error(result), u32::from(ErrorCode::InvalidTokenAccount));
+    
+    fixture.freeze_asset(Some(mint)).await.unwrap();
+    let close = close_token_accounts(&fixture, &owner, &[vault_token_account]);
+    let result = fixture.process(&[close], &[&owner]).await;
+    assert_eq!(custom_error(result), u32::from(ErrorCode::AssetFrozen));
+    assert!(fixture.token_account(&vault_token_account).await.is_some());
+    assert!(fixture.token_account(&foreign).await.is_some());
+}
+
+// Quarantine `mint` in the wallet, treating balances up to `dust_threshold` as dust
+async fn quarantine(fixture: &mut WalletFixture, mint: &Pubkey, dust_threshold: u64) {
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    let quarantine = instructions::quarantine_mint(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        &fixture.context.payer.pubkey(),
+        *mint,
+        dust_threshold,
+    );
+    fixture.process(&[quarantine], &[&owner, &guardian]).await.unwrap();
+}
+
+fn burn_dust(fixture: &WalletFixture, owner: &Keypair, mint: &Pubkey) -> Instruction {
+    let vault_token_account = get_associated_token_address(&fixture.vault(), mint);
+    instructions::burn_dust(
+        &fixture.wallet,
+        &owner.pubkey(),
+        mint,
+        &vault_token_account,
+        &spl_token::ID,
+    )
+}
+
+#[tokio::test]
+async fn owner_alone_burns_quarantined_dust() {
+    let (mut fixture, mint, _) = wallet_with_mint().await;
+    let vault_token_account = fund_vault(&mut fixture, &mint, 7);
+    quarantine(&mut fixture, &mint, 10).await;
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    
+    let burn = burn_dust(&fixture, &guardian, &mint);
+    let result = fixture.process(&[burn], &[&guardian]).await;
+    assert_eq!(custom_error(result), u32::from(ErrorCode::InvalidOwner));
+    
+    let burn = burn_dust(&fixture, &owner, &mint);
+    fixture.process(&[burn], &[&owner]).await.unwrap();
+    
+    assert_eq!(fixture.token_account(&vault_token_account).await.unwrap().amount, 0);
+    assert_eq!(fixture.mint(&mint).await.supply, (1 << 40) - 7);
+}
+
+#[tokio::test]
+async fn only_balances_within_a_quarantined_threshold_are_dust() {
+    let (mut fixture, mint, _) = wallet_with_mint().await;
+    let other_mint = Pubkey::new_unique();
+    fixture.set_mint(&other_mint, Some(Pubkey::new_unique()), None);
+    let vault_token_account = fund_vault(&mut fixture, &mint, 11);
+    fund_vault(&mut fixture, &other_mint, 1);
+    quarantine(&mut fixture, &mint, 10).await;
+    let (owner, guardian) = (fixture.owner.insecure_clone(), fixture.guardian.insecure_clone());
+    
+    for mint in [mint, other_mint] {
+        let burn = burn_dust(&fixture, &owner, &mint);
+        let result = fixture.process(&[burn], &[&owner]).await;
+        assert_eq!(custom_error(result), u32::from(ErrorCode::NotDust));
+    }
+    assert_eq!(fixture.token_account(&vault_token_account).await.unwrap().amount, 11);
+    
+    // A released mint is no dust even where its last threshold would cover the balance
+    quarantine(&mut fixture, &mint, 20).await;
+    let unquarantine = instructions::unquarantine_mint(
+        &fixture.wallet,
+        &owner.pubkey(),
+        &guardian.pubkey(),
+        mint,
+    );
+    fixture.process(&[unquarantine], &[&owner, &guardian]).await.unwrap();
+    let burn = burn_dust(&fixture, &owner, &mint);
+    let result = fixture.process(&[burn], &[&owner]).await;
+    assert_eq!(custom_error(result), u32::from(ErrorCode::NotDust));
+}
+
+#[tokio::test]
+async fn dust_accounts_are_burned_and_closed_into_the_vault() {
+    let (mut fixture, first_mint, _) = wallet_with_mint().await;
+    let second_mint = Pubkey::new_unique();
+    fixture.set_mint(&second_mint, Some(Pubkey::new_unique()), None);
+    let first = fund_vault(&mut fixture, &first_mint, 3);
+    let second = fund_vault(&mut fixture, &second_mint, 0);
+    quarantine(&mut fixture, &first_mint, 10).await;
+    quarantine(&mut fixture, &second_mint, 10).await;
+    let owner = fixture.owner.insecure_clone();
+    let vault = fixture.vault();
+    let lamports = fixture.lamports(&vault).await;
+    let close_dust = |pairs: &[(Pubkey, Pubkey)]| {
+        instructions::close_dust_accounts(
+            &fixture.wallet,
+            &owner.pubkey(),
+            &spl_token::ID,
+            pairs
+                .iter()
+                .flat_map(|(account, mint)| {
+                    [AccountMeta::new(*account, false), AccountMeta::new(*mint, false)]
+                })
+                .collect(),
+        )
+    };
+    
+    // Each token account comes with its own mint
+    let mismatched = close_dust(&[(first, second_mint)]);
+    let mut unpaired = close_dust(&[(first, first_mint)]);
+    unpaired.accounts.pop();
+    let close = close_dust(&[(first, first_mint), (second, second_mint)]);
+    let result = fixture.process(&[mismatched], &[&owner]).await;
+    assert_eq!(custom_error(result), u32::from(ErrorCode::InvalidTokenAccount));
+    let result = fixture.process(&[unpaired], &[&owner]).await;
+    assert_eq!(custom_error(result), u32::from(ErrorCode::InvalidRemainingAccounts));
+    
+    fixture.process(&[close], &[&owner]).await.unwrap();
+    
+    assert!(fixture.token_account(&first).await.is_none());
+    assert!(fixture.token_account(&second).await.is_none());
+    assert_eq!(fixture.mint(&first_mint).await.supply, (1 << 40) - 3);
+    let rent = Rent::default().minimum_balance(spl_token::state::Account::LEN);
+    assert_eq!(fixture.lamports(&vault).await, lamports + 2 * rent);
+}
@@ -0,0 +1,239 @@
+// Core validation rules
+//
+// The approval counting, threshold, timelock, limit price and off-chain message encoding
+// rules the instruction handlers apply, as pure functions over plain values: no Anchor types,
+// accounts or sysvars. Handlers read the clock and accounts themselves and turn a `false` or
+// `None` from here into their own error, so these rules can be tested exhaustively off-chain
+// and reused by clients to predict what the program will accept.
+
+pub const OFFCHAIN_MESSAGE_DOMAIN: &[u8; 15] = b"argent:offchain";
+pub const OFFCHAIN_MESSAGE_VERSION: u8 = 1;
+pub const OFFCHAIN_MESSAGE_LEN: usize = 15 + 1 + 32 + 32 + 8 + 1 + 32 + 8;
+pub const APPROVAL_PAYLOAD_DOMAIN: &[u8; 15] = b"argent:approval";
+pub const APPROVAL_PAYLOAD_VERSION: u8 = 1;
+pub const APPROVAL_PAYLOAD_LEN: usize = 15 + 1 + 32 + 32 + 8 + 32 + 8 + 8;
+
+// The distinct `members` among `approvers`, in order of first appearance; repeated approvers
+// and anyone who isn't a member don't count
+pub fn distinct_approvals<K: PartialEq>(
+    members: &[K],
+    approvers: impl IntoIterator<Item = K>,
+) -> Vec<K> {
+    let mut approvals = Vec::new();
+    for approver in approvers {
+        if members.contains(&approver) && !approvals.contains(&approver) {
+            approvals.push(approver);
+        }
+    }
+    approvals
+}
+
+// Whether `threshold` is a usable quorum of `members` keys: at least one, and no more than all
+pub fn is_valid_threshold(threshold: usize, members: usize) -> bool {
+    threshold > 0 && threshold <= members
+}
+
+// Whether `approvals` distinct approvals meet `threshold`
+pub fn meets_threshold(approvals: usize, threshold: usize) -> bool {
+    approvals >= threshold
+}
+
+// Whether `period` seconds have passed at `now` since `start`; a span too large to represent
+// never counts as elapsed
+pub fn has_elapsed(start: i64, period: i64, now: i64) -> bool {
+    now.checked_sub(start)
+        .is_some_and(|elapsed| elapsed >= period)
+}
+
+// Fixed-point scale of limit order prices: buy-token base units per sell-token base unit,
+// times LIMIT_PRICE_SCALE
+pub const LIMIT_PRICE_SCALE: u64 = 1_000_000_000;
+
+// Whether receiving `received` for `sold` fills a limit order at `min_price` or better;
+// nothing sold never counts as a fill
+pub fn meets_limit_price(sold: u64, received: u64, min_price: u64) -> bool {
+    sold > 0 && received as u128 * LIMIT_PRICE_SCALE as u128 >= sold as u128 * min_price as u128
+}
+
+// `amount` base units of a token with `decimals` as a decimal string, without trailing zeros
+// in the fraction (e.g. 1500000 with 6 decimals is "1.5")
+pub fn format_token_amount(amount: u64, decimals: u8) -> String {
+    let digits = amount.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (whole, fraction) = padded.split_at(padded.len() - decimals);
+    match fraction.trim_end_matches('0') {
+        "" => whole.to_string(),
+        fraction => format!("{}.{}", whole, fraction),
+    }
+}
+
+// Lamports the runtime charges for each transaction and precompile-verified signature
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+// Compute unit limit or price set by a Compute Budget program instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComputeBudgetSetting {
+    UnitLimit(u32),
+    // Micro-lamports per compute unit
+    UnitPrice(u64),
+}
+
+// The setting a Compute Budget program instruction's data makes, if it sets the unit limit
+// or price
+pub fn parse_compute_budget(data: &[u8]) -> Option<ComputeBudgetSetting> {
+    match data {
+        [2, limit @ ..] => Some(ComputeBudgetSetting::UnitLimit(u32::from_le_bytes(
+            limit.try_into().ok()?,
+        ))),
+        [3, price @ ..] => Some(ComputeBudgetSetting::UnitPrice(u64::from_le_bytes(
+            price.try_into().ok()?,
+        ))),
+        _ => None,
+    }
+}
+
+// Network fee of a transaction in lamports: LAMPORTS_PER_SIGNATURE for each of `signatures`,
+// plus a priority fee of `compute_unit_price` micro-lamports for each of `compute_unit_limit`
+// units, rounded up
+pub fn transaction_fee(signatures: u64, compute_unit_limit: u32, compute_unit_price: u64) -> u64 {
+    let priority_fee =
+        (compute_unit_limit as u128 * compute_unit_price as u128).div_ceil(1_000_000);
+    signatures
+        .saturating_mul(LAMPORTS_PER_SIGNATURE)
+        .saturating_add(u64::try_from(priority_fee).unwrap_or(u64::MAX))
+}
+
+// Size of a System program nonce account
+pub const NONCE_ACCOUNT_LEN: usize = 80;
+
+// Authority of an initialized, current-version System program nonce account from its data
+// (version and state tags, then the authority); `None` for anything else
+pub fn nonce_authority(data: &[u8]) -> Option<[u8; 32]> {
+    if data.len() != NONCE_ACCOUNT_LEN || data[..8] != [1, 0, 0, 0, 1, 0, 0, 0] {
+        return None;
+    }
+    data[8..40].try_into().ok()
+}
+
+// Fields of an off-chain message, with keys as raw bytes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OffchainFields {
+    pub wallet: [u8; 32],
+    pub nonce: u64,
+    pub action: u8,
+    pub payload_hash: [u8; 32],
+    pub expiry_slot: u64,
+}
+
+impl OffchainFields {
+    // Canonical bytes to sign for `program_id`:
+    //   domain | version | program id | wallet | nonce (u64 LE) | action | payload hash |
+    //   expiry slot (u64 LE)
+    pub fn encode(&self, program_id: &[u8; 32]) -> Vec<u8> {
+        [
+            OFFCHAIN_MESSAGE_DOMAIN.as_ref(),
+            &[OFFCHAIN_MESSAGE_VERSION],
+            program_id,
+            &self.wallet,
+            &self.nonce.to_le_bytes(),
+            &[self.action],
+            &self.payload_hash,
+            &self.expiry_slot.to_le_bytes(),
+        ]
+        .concat()
+    }
+
+    // Strict inverse of `encode`: any other length, domain, version or program id is `None`.
+    // The action byte is returned as is, for the caller to map to the actions it knows
+    pub fn decode(data: &[u8], program_id: &[u8; 32]) -> Option<Self> {
+        if data.len() != OFFCHAIN_MESSAGE_LEN {
+            return None;
+        }
+        let (domain, rest) = data.split_at(OFFCHAIN_MESSAGE_DOMAIN.len());
+        let (version, rest) = rest.split_at(1);
+        let (message_program_id, rest) = rest.split_at(32);
+        let (wallet, rest) = rest.split_at(32);
+        let (nonce, rest) = rest.split_at(8);
+        let (action, rest) = rest.split_at(1);
+        let (payload_hash, expiry_slot) = rest.split_at(32);
+
+        if domain != OFFCHAIN_MESSAGE_DOMAIN
+            || version[0] != OFFCHAIN_MESSAGE_VERSION
+            || message_program_id != program_id
+        {
+            return None;
+        }
+
+        Some(Self {
+            wallet: wallet.try_into().ok()?,
+            nonce: u64::from_le_bytes(nonce.try_into().ok()?),
+            action: action[0],
+            payload_hash: payload_hash.try_into().ok()?,
+            expiry_slot: u64::from_le_bytes(expiry_slot.try_into().ok()?),
+        })
+    }
+}
+
+// Fields of a guardian approval payload, with keys as raw bytes. Unlike an off-chain message,
+// every field is in the clear rather than hashed, so an air-gapped signer reading it from a QR
+// code can show what it approves
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApprovalFields {
+    pub wallet: [u8; 32],
+    pub nonce: u64,
+    pub data_hash: [u8; 32],
+    pub expires_at: i64,
+    pub max_fee: u64,
+}
+
+impl ApprovalFields {
+    // Canonical bytes to sign for `program_id`:
+    //   domain | version | program id | wallet | nonce (u64 LE) | data hash |
+    //   expires at (i64 LE) | max fee (u64 LE)
+    pub fn encode(&self, program_id: &[u8; 32]) -> Vec<u8> {
+        [
+            APPROVAL_PAYLOAD_DOMAIN.as_ref(),
+            &[APPROVAL_PAYLOAD_VERSION],
+            program_id,
+            &self.wallet,
+            &self.nonce.to_le_bytes(),
+            &self.data_hash,
+            &self.expires_at.to_le_bytes(),
+            &self.max_fee.to_le_bytes(),
+        ]
+        .concat()
+    }
+
+    // Strict inverse of `encode`: any other length, domain, version or program id is `None`
+    pub fn decode(data: &[u8], program_id: &[u8; 32]) -> Option<Self> {
+        if data.len() != APPROVAL_PAYLOAD_LEN {
+            return None;
+        }
+        let (domain, rest) = data.split_at(APPROVAL_PAYLOAD_DOMAIN.len());
+        let (version, rest) = rest.split_at(1);
+        let (payload_program_id, rest) = rest.split_at(32);
+        let (wallet, rest) = rest.split_at(32);
+        let (nonce, rest) = rest.split_at(8);
+        let (data_hash, rest) = rest.split_at(32);
+        let (expires_at, max_fee) = rest.split_at(8);
+
+        if domain != APPROVAL_PAYLOAD_DOMAIN
+            || version[0] != APPROVAL_PAYLOAD_VERSION
+            || payload_program_id != program_id
+        {
+            return None;
+        }
+
+        Some(Self {
+            wallet: wallet.try_into().ok()?,
+            nonce: u64::from_le_bytes(nonce.try_into().ok()?),
+            data_hash: data_hash.try_into().ok()?,
+            expires_at: i64::from_le_bytes(expires_at.try_into().ok()?),
+            max_fee: u64::from_le_bytes(max_fee.try_into().ok()?),
+        })
+    }
+}
@@ -0,0 +1,517 @@
+// Deposits
+//
+// Deposits into the vault, the funding policy that flags and refunds unexpected ones, and the
+// quarantine of unsolicited mints.
+
+use super::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DepositSol<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    /// The wallet vault PDA receiving the deposit
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    /// CHECK: The wallet's funding policy PDA, with no data when the wallet has none
+    #[account(mut, seeds = [b"funding_policy", argent_account.key().as_ref()], bump)]
+    pub funding_policy: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DepositSpl<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    /// The wallet vault PDA receiving the deposit
+    #[account(seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = sender,
+        token::token_program = token_program,
+    )]
+    pub sender_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = sender,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    /// CHECK: The wallet's funding policy PDA, with no data when the wallet has none
+    #[account(mut, seeds = [b"funding_policy", argent_account.key().as_ref()], bump)]
+    pub funding_policy: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetFundingPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = FundingPolicy::SPACE,
+        seeds = [b"funding_policy", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub funding_policy: Account<'info, FundingPolicy>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveFundingPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"funding_policy", argent_account.key().as_ref()],
+        bump = funding_policy.bump,
+    )]
+    pub funding_policy: Account<'info, FundingPolicy>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RefundDeposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        mut,
+        seeds = [b"funding_policy", argent_account.key().as_ref()],
+        bump = funding_policy.bump,
+    )]
+    pub funding_policy: Account<'info, FundingPolicy>,
+    /// The wallet vault PDA returning the deposit
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// The sender of the flagged deposit
+    #[account(mut)]
+    pub sender: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RefundSplDeposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        mut,
+        seeds = [b"funding_policy", argent_account.key().as_ref()],
+        bump = funding_policy.bump,
+    )]
+    pub funding_policy: Account<'info, FundingPolicy>,
+    /// The wallet vault PDA owning the token account
+    #[account(seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The sender of the flagged deposit, only used to look it up
+    pub sender: UncheckedAccount<'info>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = sender,
+        token::token_program = token_program,
+    )]
+    pub sender_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct QuarantineMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = Quarantine::SPACE,
+        seeds = [b"quarantine", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub quarantine: AccountLoader<'info, Quarantine>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnquarantineMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(mut, seeds = [b"quarantine", argent_account.key().as_ref()], bump)]
+    pub quarantine: AccountLoader<'info, Quarantine>,
+}
+
+pub(crate) fn deposit_sol(ctx: Context<DepositSol>, amount: u64, memo: String) -> Result<()> {
+    let wallet_state = ctx.accounts.wallet_state.load()?;
+    wallet_state.header.require_compatible()?;
+    require!(memo.len() <= MAX_MEMO_LEN, ErrorCode::MemoTooLong);
+    require!(amount > 0, ErrorCode::InvalidDepositAmount);
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.sender.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    emit_cpi!(DepositReceived {
+        argent_account: ctx.accounts.argent_account.key(),
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        sender: ctx.accounts.sender.key(),
+        amount,
+        mint: None,
+        memo,
+        state_version: wallet_state.state_version,
+    });
+    if let Some(refundable) =
+        flag_deposit(&ctx.accounts.funding_policy, &ctx.accounts.sender.key(), None, amount)?
+    {
+        emit_cpi!(DepositFlagged {
+            argent_account: ctx.accounts.argent_account.key(),
+            notification_tag: ctx.accounts.argent_account.notification_tag,
+            sender: ctx.accounts.sender.key(),
+            amount,
+            mint: None,
+            refundable,
+            state_version: wallet_state.state_version,
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn deposit_spl(ctx: Context<DepositSpl>, amount: u64, memo: String) -> Result<()> {
+    let wallet_state = ctx.accounts.wallet_state.load()?;
+    wallet_state.header.require_compatible()?;
+    require!(memo.len() <= MAX_MEMO_LEN, ErrorCode::MemoTooLong);
+    require!(amount > 0, ErrorCode::InvalidDepositAmount);
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::TransferChecked {
+                from: ctx.accounts.sender_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.sender.to_account_info(),
+            },
+        ),
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    emit_cpi!(DepositReceived {
+        argent_account: ctx.accounts.argent_account.key(),
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        sender: ctx.accounts.sender.key(),
+        amount,
+        mint: Some(ctx.accounts.mint.key()),
+        memo,
+        state_version: wallet_state.state_version,
+    });
+    let mint = ctx.accounts.mint.key();
+    if let Some(refundable) = flag_deposit(
+        &ctx.accounts.funding_policy,
+        &ctx.accounts.sender.key(),
+        Some(mint),
+        amount,
+    )? {
+        emit_cpi!(DepositFlagged {
+            argent_account: ctx.accounts.argent_account.key(),
+            notification_tag: ctx.accounts.argent_account.notification_tag,
+            sender: ctx.accounts.sender.key(),
+            amount,
+            mint: Some(mint),
+            refundable,
+            state_version: wallet_state.state_version,
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn set_funding_policy(
+    ctx: Context<SetFundingPolicy>,
+    allowed_senders: Vec<Pubkey>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require!(
+        allowed_senders.len() <= MAX_FUNDING_SOURCES,
+        ErrorCode::TooManyFundingSources
+    );
+
+    let policy = &mut ctx.accounts.funding_policy;
+    if policy.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    policy.argent_account = ctx.accounts.argent_account.key();
+    policy
+        .flagged
+        .retain(|deposit| !allowed_senders.contains(&deposit.sender));
+    policy.allowed_senders = allowed_senders;
+    policy.bump = ctx.bumps.funding_policy;
+
+    wallet_state.next_state_version();
+
+    msg!("Funding policy updated!");
+    Ok(())
+}
+
+pub(crate) fn remove_funding_policy(ctx: Context<RemoveFundingPolicy>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_closed();
+
+    wallet_state.next_state_version();
+
+    msg!("Funding policy removed!");
+    Ok(())
+}
+
+pub(crate) fn refund_deposit(ctx: Context<RefundDeposit>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    let sender = ctx.accounts.sender.key();
+    let amount = ctx
+        .accounts
+        .funding_policy
+        .take_flagged(&sender, None)
+        .ok_or(ErrorCode::NoFlaggedDeposit)?;
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.sender.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        amount,
+    )?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = DepositRefunded {
+        argent_account: argent_key,
+        notification_tag: argent_account.notification_tag,
+        sender,
+        amount,
+        mint: None,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Refunded {} lamports to {}", amount, sender);
+    Ok(())
+}
+
+pub(crate) fn refund_spl_deposit(ctx: Context<RefundSplDeposit>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    let sender = ctx.accounts.sender.key();
+    let mint = ctx.accounts.mint.key();
+    let amount = ctx
+        .accounts
+        .funding_policy
+        .take_flagged(&sender, Some(mint))
+        .ok_or(ErrorCode::NoFlaggedDeposit)?;
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.sender_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = DepositRefunded {
+        argent_account: argent_key,
+        notification_tag: argent_account.notification_tag,
+        sender,
+        amount,
+        mint: Some(mint),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Refunded {} of mint {} to {}", amount, mint, sender);
+    Ok(())
+}
+
+pub(crate) fn quarantine_mint(
+    ctx: Context<QuarantineMint>,
+    mint: Pubkey,
+    dust_threshold: u64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    // A quarantine just created by init_if_needed has no discriminator yet
+    let mut quarantine = match ctx.accounts.quarantine.load_mut() {
+        Ok(quarantine) => quarantine,
+        Err(_) => {
+            wallet_state.account_opened();
+            let mut quarantine = ctx.accounts.quarantine.load_init()?;
+            quarantine.header = AccountHeader::CURRENT;
+            quarantine
+        }
+    };
+    quarantine.header.require_compatible()?;
+    quarantine.argent_account = ctx.accounts.argent_account.key();
+    quarantine.upsert(QuarantinedMint {
+        mint,
+        dust_threshold,
+    })?;
+
+    wallet_state.next_state_version();
+
+    msg!("Mint {} quarantined", mint);
+    Ok(())
+}
+
+pub(crate) fn unquarantine_mint(ctx: Context<UnquarantineMint>, mint: Pubkey) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let mut quarantine = ctx.accounts.quarantine.load_mut()?;
+    quarantine.header.require_compatible()?;
+    quarantine.remove(&mint);
+
+    wallet_state.next_state_version();
+
+    msg!("Mint {} released from quarantine", mint);
+    Ok(())
+}
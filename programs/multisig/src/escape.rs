@@ -0,0 +1,1166 @@
+// Escapes
+//
+// Replacing an unresponsive owner or guardian after the security period: triggering,
+// committing, completing and cancelling escapes, their pre-authorizations and rehearsals,
+// backup promotion, and the notification program told about each step.
+
+use super::*;
+
+#[derive(Accounts)]
+pub struct SetNotificationProgram<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = NotificationConfig::SPACE,
+        seeds = [b"notification_config", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub notification_config: Account<'info, NotificationConfig>,
+    /// CHECK: Program receiving the escape notifications
+    #[account(executable)]
+    pub notification_program: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveNotificationProgram<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"notification_config", argent_account.key().as_ref()],
+        bump = notification_config.bump,
+    )]
+    pub notification_config: Account<'info, NotificationConfig>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TriggerEscapeGuardian<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    /// CHECK: The wallet's notification config PDA, which has no data when none is set; a
+    /// configured notification program is passed as the first remaining account
+    #[account(seeds = [b"notification_config", argent_account.key().as_ref()], bump)]
+    pub notification_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetBackupPromotion<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = BackupPromotion::SPACE,
+        seeds = [b"backup_promotion", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub backup_promotion: Account<'info, BackupPromotion>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveBackupPromotion<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"backup_promotion", argent_account.key().as_ref()],
+        bump = backup_promotion.bump,
+    )]
+    pub backup_promotion: Account<'info, BackupPromotion>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RequestBackupPromotion<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"backup_promotion", argent_account.key().as_ref()],
+        bump = backup_promotion.bump,
+    )]
+    pub backup_promotion: Account<'info, BackupPromotion>,
+    /// CHECK: The wallet's notification config PDA, which has no data when none is set; a
+    /// configured notification program is passed as the first remaining account
+    #[account(seeds = [b"notification_config", argent_account.key().as_ref()], bump)]
+    pub notification_config: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PreauthorizeEscape<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = EscapePreauthorization::SPACE,
+        seeds = [b"escape_preauthorization", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub escape_preauthorization: Account<'info, EscapePreauthorization>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Instructions sysvar, holding the new key's signature verification
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeEscapePreauthorization<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        constraint = authority.key() == argent_account.owner
+            || authority.key() == argent_account.guardian @ ErrorCode::NotOwnerOrGuardian
+    )]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"escape_preauthorization", argent_account.key().as_ref()],
+        bump = escape_preauthorization.bump,
+    )]
+    pub escape_preauthorization: Account<'info, EscapePreauthorization>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TriggerEscapeOwner<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: Signer<'info>,
+    /// CHECK: The wallet's notification config PDA, which has no data when none is set; a
+    /// configured notification program is passed as the first remaining account
+    #[account(seeds = [b"notification_config", argent_account.key().as_ref()], bump)]
+    pub notification_config: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CommitEscapeKey<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    // The escaping party, checked against the escape type in the handler
+    pub authority: Signer<'info>,
+    /// CHECK: Instructions sysvar, holding the committed key's signature verification
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CompleteEscape<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    // Anyone
+    pub cranker: Signer<'info>,
+    /// CHECK: The wallet's escape pre-authorization PDA, with no data when the wallet has none
+    #[account(seeds = [b"escape_preauthorization", argent_account.key().as_ref()], bump)]
+    pub escape_preauthorization: UncheckedAccount<'info>,
+    /// CHECK: The wallet's notification config PDA, which has no data when none is set; a
+    /// configured notification program is passed as the first remaining account
+    #[account(seeds = [b"notification_config", argent_account.key().as_ref()], bump)]
+    pub notification_config: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RehearseEscape<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    // The escaping party, checked against the escape type in the handler
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = EscapeRehearsal::SPACE,
+        seeds = [b"escape_rehearsal", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub escape_rehearsal: Account<'info, EscapeRehearsal>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Instructions sysvar, holding the rehearsed key's signature verification
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CompleteEscapeRehearsal<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    // Anyone
+    pub cranker: Signer<'info>,
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"escape_rehearsal", argent_account.key().as_ref()],
+        bump = escape_rehearsal.bump,
+        has_one = payer,
+    )]
+    pub escape_rehearsal: Account<'info, EscapeRehearsal>,
+    /// CHECK: Receives the rehearsal rent back
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    /// CHECK: The wallet's escape pre-authorization PDA, with no data when the wallet has none
+    #[account(seeds = [b"escape_preauthorization", argent_account.key().as_ref()], bump)]
+    pub escape_preauthorization: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelEscapeRehearsal<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        constraint = authority.key() == argent_account.owner
+            || authority.key() == argent_account.guardian @ ErrorCode::NotOwnerOrGuardian
+    )]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"escape_rehearsal", argent_account.key().as_ref()],
+        bump = escape_rehearsal.bump,
+        has_one = payer,
+    )]
+    pub escape_rehearsal: Account<'info, EscapeRehearsal>,
+    /// CHECK: Receives the rehearsal rent back
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct EscapeGuardian<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    /// CHECK: The wallet's escape pre-authorization PDA, with no data when the wallet has none
+    #[account(seeds = [b"escape_preauthorization", argent_account.key().as_ref()], bump)]
+    pub escape_preauthorization: UncheckedAccount<'info>,
+    /// CHECK: Instructions sysvar, holding the new guardian's signature verification
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    /// CHECK: The wallet's notification config PDA, which has no data when none is set; a
+    /// configured notification program is passed as the first remaining account
+    #[account(seeds = [b"notification_config", argent_account.key().as_ref()], bump)]
+    pub notification_config: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct EscapeOwner<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: Signer<'info>,
+    /// CHECK: The wallet's escape pre-authorization PDA, with no data when the wallet has none
+    #[account(seeds = [b"escape_preauthorization", argent_account.key().as_ref()], bump)]
+    pub escape_preauthorization: UncheckedAccount<'info>,
+    /// CHECK: Instructions sysvar, holding the new owner's signature verification
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    /// CHECK: The wallet's notification config PDA, which has no data when none is set; a
+    /// configured notification program is passed as the first remaining account
+    #[account(seeds = [b"notification_config", argent_account.key().as_ref()], bump)]
+    pub notification_config: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelEscape<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// CHECK: The wallet's notification config PDA, which has no data when none is set; a
+    /// configured notification program is passed as the first remaining account
+    #[account(seeds = [b"notification_config", argent_account.key().as_ref()], bump)]
+    pub notification_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetEscapeStats<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+}
+
+pub(crate) fn set_notification_program(ctx: Context<SetNotificationProgram>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let program = ctx.accounts.notification_program.key();
+    require_keys_neq!(program, crate::ID, ErrorCode::InvalidNotificationProgram);
+
+    let config = &mut ctx.accounts.notification_config;
+    if config.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    config.argent_account = ctx.accounts.argent_account.key();
+    config.program = program;
+    config.bump = ctx.bumps.notification_config;
+
+    wallet_state.next_state_version();
+
+    msg!("Escape notifications go to {}", program);
+    Ok(())
+}
+
+pub(crate) fn remove_notification_program(ctx: Context<RemoveNotificationProgram>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_closed();
+
+    wallet_state.next_state_version();
+
+    msg!("Escape notifications removed!");
+    Ok(())
+}
+
+pub(crate) fn trigger_escape_guardian<'info>(
+    ctx: Context<'_, '_, '_, 'info, TriggerEscapeGuardian<'info>>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+
+    let argent_account = &ctx.accounts.argent_account;
+    let clock = Clock::get()?;
+
+    // Can override an escape owner in progress
+    if wallet_state.escape_type() == EscapeType::Owner {
+        msg!("Overriding escape owner in progress");
+    }
+
+    // Set escape type and timestamp
+    wallet_state.start_escape(EscapeType::Guardian, clock.unix_timestamp, None);
+
+    wallet_state.next_state_version();
+
+    wallet_state.escape_stats.record_triggered(EscapeType::Guardian, clock.unix_timestamp);
+    let event = EscapeTriggered {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        escape_type: EscapeType::Guardian,
+        triggered_by: ctx.accounts.owner.key(),
+        initiated_at: clock.unix_timestamp,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+    notify_escape(
+        &ctx.accounts.notification_config,
+        ctx.remaining_accounts,
+        &argent_account.to_account_info(),
+        &event,
+    )?;
+
+    msg!("Guardian escape triggered!");
+    Ok(())
+}
+
+pub(crate) fn set_backup_promotion(
+    ctx: Context<SetBackupPromotion>,
+    inactivity_period: i64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require!(inactivity_period > 0, ErrorCode::InvalidInactivityPeriod);
+
+    let rule = &mut ctx.accounts.backup_promotion;
+    if rule.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    rule.argent_account = ctx.accounts.argent_account.key();
+    rule.inactivity_period = inactivity_period;
+    rule.bump = ctx.bumps.backup_promotion;
+
+    wallet_state.next_state_version();
+
+    msg!("Guardian backup promotion enabled after {}s", inactivity_period);
+    Ok(())
+}
+
+pub(crate) fn remove_backup_promotion(ctx: Context<RemoveBackupPromotion>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_closed();
+
+    wallet_state.next_state_version();
+
+    msg!("Guardian backup promotion removed!");
+    Ok(())
+}
+
+pub(crate) fn request_backup_promotion<'info>(
+    ctx: Context<'_, '_, '_, 'info, RequestBackupPromotion<'info>>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+
+    let argent_account = &ctx.accounts.argent_account;
+    let now = Clock::get()?.unix_timestamp;
+
+    let guardian_backup = argent_account
+        .guardian_backup
+        .ok_or(ErrorCode::NoGuardianBackup)?;
+    require!(
+        core::has_elapsed(
+            wallet_state.last_guardian_action_at,
+            ctx.accounts.backup_promotion.inactivity_period,
+            now,
+        ),
+        ErrorCode::GuardianNotInactive
+    );
+
+    wallet_state.start_escape(EscapeType::Guardian, now, Some(guardian_backup));
+
+    wallet_state.next_state_version();
+
+    wallet_state.escape_stats.record_triggered(EscapeType::Guardian, now);
+    let event = EscapeTriggered {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        escape_type: EscapeType::Guardian,
+        triggered_by: ctx.accounts.owner.key(),
+        initiated_at: now,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+    notify_escape(
+        &ctx.accounts.notification_config,
+        ctx.remaining_accounts,
+        &argent_account.to_account_info(),
+        &event,
+    )?;
+    let event = EscapeKeyCommitted {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        escape_type: EscapeType::Guardian,
+        new_key: guardian_backup,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Guardian backup promotion requested!");
+    Ok(())
+}
+
+pub(crate) fn preauthorize_escape(
+    ctx: Context<PreauthorizeEscape>,
+    escape_type: EscapeType,
+    new_key: Pubkey,
+    delay: i64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let argent_account = &ctx.accounts.argent_account;
+    require!(
+        delay > 0 && delay < argent_account.security_period,
+        ErrorCode::InvalidPreauthorizedDelay
+    );
+    let action = match escape_type {
+        EscapeType::Guardian => OffchainAction::ChangeGuardian,
+        EscapeType::Owner => OffchainAction::ChangeOwner,
+        EscapeType::None => return err!(ErrorCode::InvalidEscapeType),
+    };
+    require_key_registration(
+        &ctx.accounts.sysvar_instructions,
+        &argent_account.key(),
+        wallet_state.state_version,
+        action,
+        &new_key,
+    )?;
+
+    let preauthorization = &mut ctx.accounts.escape_preauthorization;
+    if preauthorization.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    preauthorization.argent_account = argent_account.key();
+    match escape_type {
+        EscapeType::Guardian => preauthorization.new_guardian = Some(new_key),
+        _ => preauthorization.new_owner = Some(new_key),
+    }
+    preauthorization.delay = delay;
+    preauthorization.bump = ctx.bumps.escape_preauthorization;
+
+    wallet_state.next_state_version();
+
+    let event = EscapePreauthorized {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        escape_type,
+        new_key,
+        delay,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Escape pre-authorized with a {}s delay", delay);
+    Ok(())
+}
+
+pub(crate) fn revoke_escape_preauthorization(
+    ctx: Context<RevokeEscapePreauthorization>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.authority.key()],
+    )?;
+    wallet_state.account_closed();
+
+    wallet_state.next_state_version();
+
+    msg!("Escape pre-authorization revoked!");
+    Ok(())
+}
+
+pub(crate) fn trigger_escape_owner<'info>(
+    ctx: Context<'_, '_, '_, 'info, TriggerEscapeOwner<'info>>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.guardian.key()])?;
+
+    let argent_account = &ctx.accounts.argent_account;
+    let clock = Clock::get()?;
+
+    // Fail if escape guardian in progress
+    require!(
+        wallet_state.escape_type() != EscapeType::Guardian,
+        ErrorCode::EscapeGuardianInProgress
+    );
+
+    // Set escape type and timestamp
+    wallet_state.start_escape(EscapeType::Owner, clock.unix_timestamp, None);
+
+    wallet_state.next_state_version();
+
+    wallet_state.escape_stats.record_triggered(EscapeType::Owner, clock.unix_timestamp);
+    let event = EscapeTriggered {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        escape_type: EscapeType::Owner,
+        triggered_by: ctx.accounts.guardian.key(),
+        initiated_at: clock.unix_timestamp,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+    notify_escape(
+        &ctx.accounts.notification_config,
+        ctx.remaining_accounts,
+        &argent_account.to_account_info(),
+        &event,
+    )?;
+
+    msg!("Owner escape triggered!");
+    Ok(())
+}
+
+pub(crate) fn escape_guardian<'info>(
+    ctx: Context<'_, '_, '_, 'info, EscapeGuardian<'info>>,
+    new_guardian: Pubkey,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    let clock = Clock::get()?;
+
+    // Verify escape type
+    require!(
+        wallet_state.escape_type() == EscapeType::Guardian,
+        ErrorCode::InvalidEscapeType
+    );
+
+    // Verify the security period, or a pre-authorized escape's delay, has elapsed
+    let delay = escape_delay(
+        argent_account,
+        &wallet_state.escape_type(),
+        &ctx.accounts.escape_preauthorization,
+        &new_guardian,
+    )?;
+    require!(
+        core::has_elapsed(wallet_state.escape_initiated_at, delay, clock.unix_timestamp),
+        ErrorCode::SecurityPeriodNotElapsed
+    );
+    require_key_registration(
+        &ctx.accounts.sysvar_instructions,
+        &argent_account.key(),
+        wallet_state.state_version,
+        OffchainAction::ChangeGuardian,
+        &new_guardian,
+    )?;
+
+    wallet_state.next_state_version();
+
+    wallet_state.escape_stats.record_completed(EscapeType::Guardian, clock.unix_timestamp);
+    let event = EscapeCompleted {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        escape_type: EscapeType::Guardian,
+        completed_by: ctx.accounts.owner.key(),
+        old_key: argent_account.guardian,
+        new_key: new_guardian,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+    notify_escape(
+        &ctx.accounts.notification_config,
+        ctx.remaining_accounts,
+        &argent_account.to_account_info(),
+        &event,
+    )?;
+
+    // Change the guardian
+    argent_account.guardian = new_guardian;
+    argent_account.require_distinct_roles()?;
+
+    // Reset escape state
+    wallet_state.clear_escape();
+
+    msg!("Guardian escaped successfully!");
+    Ok(())
+}
+
+pub(crate) fn escape_owner<'info>(
+    ctx: Context<'_, '_, '_, 'info, EscapeOwner<'info>>,
+    new_owner: Pubkey,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.guardian.key()])?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    let clock = Clock::get()?;
+
+    // Verify escape type
+    require!(
+        wallet_state.escape_type() == EscapeType::Owner,
+        ErrorCode::InvalidEscapeType
+    );
+
+    // Verify the security period, or a pre-authorized escape's delay, has elapsed
+    let delay = escape_delay(
+        argent_account,
+        &wallet_state.escape_type(),
+        &ctx.accounts.escape_preauthorization,
+        &new_owner,
+    )?;
+    require!(
+        core::has_elapsed(wallet_state.escape_initiated_at, delay, clock.unix_timestamp),
+        ErrorCode::SecurityPeriodNotElapsed
+    );
+    require_key_registration(
+        &ctx.accounts.sysvar_instructions,
+        &argent_account.key(),
+        wallet_state.state_version,
+        OffchainAction::ChangeOwner,
+        &new_owner,
+    )?;
+
+    wallet_state.next_state_version();
+
+    wallet_state.escape_stats.record_completed(EscapeType::Owner, clock.unix_timestamp);
+    let event = EscapeCompleted {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        escape_type: EscapeType::Owner,
+        completed_by: ctx.accounts.guardian.key(),
+        old_key: argent_account.owner,
+        new_key: new_owner,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+    notify_escape(
+        &ctx.accounts.notification_config,
+        ctx.remaining_accounts,
+        &argent_account.to_account_info(),
+        &event,
+    )?;
+
+    // Change the owner
+    argent_account.owner = new_owner;
+    argent_account.require_distinct_roles()?;
+
+    // Reset escape state
+    wallet_state.clear_escape();
+
+    msg!("Owner escaped successfully!");
+    Ok(())
+}
+
+pub(crate) fn cancel_escape<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelEscape<'info>>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let argent_account = &ctx.accounts.argent_account;
+
+    // Verify escape is in progress
+    require!(
+        wallet_state.escape_type() != EscapeType::None,
+        ErrorCode::NoEscapeInProgress
+    );
+
+    wallet_state.next_state_version();
+
+    let now = Clock::get()?.unix_timestamp;
+    let escape_type = wallet_state.escape_type();
+    wallet_state.escape_stats.record_cancelled(escape_type, now);
+    let event = EscapeCancelled {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        escape_type: wallet_state.escape_type(),
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+    notify_escape(
+        &ctx.accounts.notification_config,
+        ctx.remaining_accounts,
+        &argent_account.to_account_info(),
+        &event,
+    )?;
+
+    // Reset escape state
+    wallet_state.clear_escape();
+
+    msg!("Escape cancelled!");
+    Ok(())
+}
+
+pub(crate) fn commit_escape_key(ctx: Context<CommitEscapeKey>, new_key: Pubkey) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.authority.key()],
+    )?;
+
+    let argent_account = &ctx.accounts.argent_account;
+    let authority = ctx.accounts.authority.key();
+
+    // Verify the escaping party has signed
+    let action = match wallet_state.escape_type() {
+        EscapeType::Guardian => {
+            require_keys_eq!(authority, argent_account.owner, ErrorCode::InvalidOwner);
+            OffchainAction::ChangeGuardian
+        }
+        EscapeType::Owner => {
+            require_keys_eq!(authority, argent_account.guardian, ErrorCode::InvalidGuardian);
+            OffchainAction::ChangeOwner
+        }
+        EscapeType::None => return err!(ErrorCode::NoEscapeInProgress),
+    };
+    require_key_registration(
+        &ctx.accounts.sysvar_instructions,
+        &argent_account.key(),
+        wallet_state.state_version,
+        action,
+        &new_key,
+    )?;
+
+    wallet_state.escape_new_key = new_key;
+    wallet_state.next_state_version();
+
+    let event = EscapeKeyCommitted {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        escape_type: wallet_state.escape_type(),
+        new_key,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Escape key committed!");
+    Ok(())
+}
+
+pub(crate) fn complete_escape<'info>(
+    ctx: Context<'_, '_, '_, 'info, CompleteEscape<'info>>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    let argent_account = &mut ctx.accounts.argent_account;
+    let clock = Clock::get()?;
+
+    let new_key = wallet_state
+        .escape_new_key()
+        .ok_or(ErrorCode::NoEscapeKeyCommitted)?;
+
+    // Verify the security period, or a pre-authorized escape's delay, has elapsed
+    let delay = escape_delay(
+        argent_account,
+        &wallet_state.escape_type(),
+        &ctx.accounts.escape_preauthorization,
+        &new_key,
+    )?;
+    require!(
+        wallet_state.escape_type() != EscapeType::None
+            && core::has_elapsed(wallet_state.escape_initiated_at, delay, clock.unix_timestamp),
+        ErrorCode::SecurityPeriodNotElapsed
+    );
+
+    wallet_state.next_state_version();
+
+    let escape_type = wallet_state.escape_type();
+    // A promoted guardian backup leaves the backup slot
+    if escape_type == EscapeType::Guardian && argent_account.guardian_backup == Some(new_key) {
+        argent_account.guardian_backup = None;
+    }
+    let old_key = match escape_type {
+        EscapeType::Guardian => std::mem::replace(&mut argent_account.guardian, new_key),
+        EscapeType::Owner => std::mem::replace(&mut argent_account.owner, new_key),
+        EscapeType::None => return err!(ErrorCode::NoEscapeInProgress),
+    };
+    argent_account.require_distinct_roles()?;
+
+    wallet_state.escape_stats.record_completed(escape_type.clone(), clock.unix_timestamp);
+    let event = EscapeCompleted {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        escape_type,
+        completed_by: ctx.accounts.cranker.key(),
+        old_key,
+        new_key,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+    notify_escape(
+        &ctx.accounts.notification_config,
+        ctx.remaining_accounts,
+        &argent_account.to_account_info(),
+        &event,
+    )?;
+
+    // Reset escape state
+    wallet_state.clear_escape();
+
+    msg!("Escape completed!");
+    Ok(())
+}
+
+pub(crate) fn rehearse_escape(
+    ctx: Context<RehearseEscape>,
+    escape_type: EscapeType,
+    new_key: Pubkey,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.account_opened();
+    let argent_account = &ctx.accounts.argent_account;
+    let authority = ctx.accounts.authority.key();
+
+    // A rehearsal never runs alongside the real thing
+    require!(
+        wallet_state.escape_type() == EscapeType::None,
+        ErrorCode::EscapeInProgress
+    );
+    let action = match escape_type {
+        EscapeType::Guardian => {
+            require_keys_eq!(authority, argent_account.owner, ErrorCode::InvalidOwner);
+            OffchainAction::ChangeGuardian
+        }
+        EscapeType::Owner => {
+            require_keys_eq!(authority, argent_account.guardian, ErrorCode::InvalidGuardian);
+            OffchainAction::ChangeOwner
+        }
+        EscapeType::None => return err!(ErrorCode::InvalidEscapeType),
+    };
+    require_key_registration(
+        &ctx.accounts.sysvar_instructions,
+        &argent_account.key(),
+        wallet_state.state_version,
+        action,
+        &new_key,
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let rehearsal = &mut ctx.accounts.escape_rehearsal;
+    rehearsal.argent_account = argent_account.key();
+    rehearsal.escape_type = escape_type.clone();
+    rehearsal.initiated_at = now;
+    rehearsal.new_key = new_key;
+    rehearsal.payer = ctx.accounts.payer.key();
+    rehearsal.bump = ctx.bumps.escape_rehearsal;
+
+    let event = EscapeRehearsalTriggered {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        escape_type,
+        triggered_by: authority,
+        initiated_at: now,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Escape rehearsal triggered!");
+    Ok(())
+}
+
+pub(crate) fn complete_escape_rehearsal(ctx: Context<CompleteEscapeRehearsal>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.account_closed();
+    let argent_account = &ctx.accounts.argent_account;
+    let rehearsal = &ctx.accounts.escape_rehearsal;
+
+    let delay = escape_delay(
+        argent_account,
+        &rehearsal.escape_type,
+        &ctx.accounts.escape_preauthorization,
+        &rehearsal.new_key,
+    )?;
+    require!(
+        core::has_elapsed(rehearsal.initiated_at, delay, Clock::get()?.unix_timestamp),
+        ErrorCode::SecurityPeriodNotElapsed
+    );
+    let old_key = match rehearsal.escape_type {
+        EscapeType::Guardian => argent_account.guardian,
+        EscapeType::Owner => argent_account.owner,
+        EscapeType::None => return err!(ErrorCode::NoEscapeInProgress),
+    };
+
+    let event = EscapeRehearsalCompleted {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        escape_type: rehearsal.escape_type.clone(),
+        completed_by: ctx.accounts.cranker.key(),
+        old_key,
+        new_key: rehearsal.new_key,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Escape rehearsal completed!");
+    Ok(())
+}
+
+pub(crate) fn cancel_escape_rehearsal(ctx: Context<CancelEscapeRehearsal>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.account_closed();
+    let argent_account = &ctx.accounts.argent_account;
+
+    let event = EscapeRehearsalCancelled {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        escape_type: ctx.accounts.escape_rehearsal.escape_type.clone(),
+        owner: argent_account.owner,
+        guardian: argent_account.guardian,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Escape rehearsal cancelled!");
+    Ok(())
+}
+
+pub(crate) fn get_escape_stats(ctx: Context<GetEscapeStats>) -> Result<EscapeStats> {
+    let wallet_state = ctx.accounts.wallet_state.load()?;
+    wallet_state.header.require_compatible()?;
+    Ok(wallet_state.escape_stats)
+}
@@ -0,0 +1,570 @@
+// Fees
+//
+// The fee ledger, fee rebates for the keys and relayers that pay for a wallet's operations,
+// and the guardian's service fee.
+
+use super::*;
+
+#[derive(Accounts)]
+pub struct CreateFeeLedger<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        init,
+        payer = payer,
+        space = FeeLedger::SPACE,
+        seeds = [b"fee_ledger", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub fee_ledger: Account<'info, FeeLedger>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReportFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"fee_ledger", fee_ledger.argent_account.as_ref()],
+        bump,
+    )]
+    pub fee_ledger: Account<'info, FeeLedger>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeRebatePolicy<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = FeeRebatePolicy::SPACE,
+        seeds = [b"fee_rebate_policy", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub fee_rebate_policy: Account<'info, FeeRebatePolicy>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordOperationFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        seeds = [b"fee_rebate_policy", argent_account.key().as_ref()],
+        bump = fee_rebate_policy.bump,
+    )]
+    pub fee_rebate_policy: Account<'info, FeeRebatePolicy>,
+    /// CHECK: The wallet's relayer registry PDA, which has no data when it has none
+    #[account(seeds = [b"relayers", argent_account.key().as_ref()], bump)]
+    pub relayer_registry: UncheckedAccount<'info>,
+    // Pays for its own rebate account
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = FeeRebate::SPACE,
+        seeds = [b"fee_rebate", argent_account.key().as_ref(), signer.key().as_ref()],
+        bump,
+    )]
+    pub fee_rebate: Account<'info, FeeRebate>,
+    /// CHECK: Instructions sysvar, holding the wallet operation the fee is recorded for
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimFeeRebate<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        mut,
+        seeds = [b"fee_rebate_policy", argent_account.key().as_ref()],
+        bump = fee_rebate_policy.bump,
+    )]
+    pub fee_rebate_policy: Account<'info, FeeRebatePolicy>,
+    /// CHECK: The wallet's relayer registry PDA, which has no data when it has none
+    #[account(seeds = [b"relayers", argent_account.key().as_ref()], bump)]
+    pub relayer_registry: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"fee_rebate", argent_account.key().as_ref(), signer.key().as_ref()],
+        bump = fee_rebate.bump,
+    )]
+    pub fee_rebate: Account<'info, FeeRebate>,
+    /// The wallet vault PDA paying the rebate
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardianFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = GuardianFee::SPACE,
+        seeds = [b"guardian_fee", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub guardian_fee: Account<'info, GuardianFee>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveGuardianFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"guardian_fee", argent_account.key().as_ref()],
+        bump = guardian_fee.bump,
+    )]
+    pub guardian_fee: Account<'info, GuardianFee>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimGuardianFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(mut, constraint = argent_account.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: Signer<'info>,
+    // Lapses when the guardian it was set for has been replaced
+    #[account(
+        mut,
+        seeds = [b"guardian_fee", argent_account.key().as_ref()],
+        bump = guardian_fee.bump,
+        constraint = guardian_fee.guardian == guardian.key() @ ErrorCode::InvalidGuardian,
+    )]
+    pub guardian_fee: Account<'info, GuardianFee>,
+    /// The wallet vault PDA paying
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    // Token fees only; checked in the handler
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub vault_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub guardian_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn create_fee_ledger(ctx: Context<CreateFeeLedger>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.account_opened();
+    let fee_ledger = &mut ctx.accounts.fee_ledger;
+    fee_ledger.argent_account = ctx.accounts.argent_account.key();
+    fee_ledger.period_start = Clock::get()?.unix_timestamp;
+
+    wallet_state.next_state_version();
+
+    msg!("Fee ledger created!");
+    Ok(())
+}
+
+pub(crate) fn report_fees(ctx: Context<ReportFees>) -> Result<FeeReport> {
+    let now = Clock::get()?.unix_timestamp;
+    let fee_ledger = &mut ctx.accounts.fee_ledger;
+    let report = FeeReport {
+        argent_account: fee_ledger.argent_account,
+        period_start: fee_ledger.period_start,
+        period_end: now,
+        rent_lamports: fee_ledger.rent_lamports,
+        relayer_fee_lamports: fee_ledger.relayer_fee_lamports,
+        relayer_fee_tokens: fee_ledger.relayer_fee_tokens,
+        operations: fee_ledger.operations,
+    };
+
+    if now - fee_ledger.period_start >= FEE_REPORT_PERIOD {
+        emit_cpi!(report.clone());
+        fee_ledger.start_period(now);
+    }
+    Ok(report)
+}
+
+pub(crate) fn set_fee_rebate_policy(
+    ctx: Context<SetFeeRebatePolicy>,
+    max_fee_per_operation: u64,
+    monthly_cap: u64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require!(
+        max_fee_per_operation > 0 && max_fee_per_operation <= monthly_cap,
+        ErrorCode::InvalidFeeRebatePolicy
+    );
+
+    let policy = &mut ctx.accounts.fee_rebate_policy;
+    if policy.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+        policy.argent_account = ctx.accounts.argent_account.key();
+        policy.period_start = Clock::get()?.unix_timestamp;
+        policy.bump = ctx.bumps.fee_rebate_policy;
+    }
+    policy.max_fee_per_operation = max_fee_per_operation;
+    policy.monthly_cap = monthly_cap;
+
+    wallet_state.next_state_version();
+
+    msg!("Fee rebate policy set!");
+    Ok(())
+}
+
+pub(crate) fn record_operation_fee(ctx: Context<RecordOperationFee>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    use anchor_lang::solana_program::sysvar::instructions::{
+        load_current_index_checked, load_instruction_at_checked,
+    };
+    use anchor_lang::solana_program::{ed25519_program, secp256k1_program};
+
+    let wallet = ctx.accounts.argent_account.key();
+    let signer = ctx.accounts.signer.key();
+    require!(
+        is_rebate_signer(
+            &ctx.accounts.argent_account,
+            &ctx.accounts.relayer_registry,
+            &signer
+        )?,
+        ErrorCode::FeeRebateSignerNotAllowed
+    );
+
+    let sysvar_instructions = &ctx.accounts.sysvar_instructions;
+    let is_record = |instruction: &anchor_lang::solana_program::instruction::Instruction| {
+        instruction.program_id == crate::ID
+            && instruction.data.starts_with(instruction::RecordOperationFee::DISCRIMINATOR)
+    };
+    let is_relayed = |instruction: &anchor_lang::solana_program::instruction::Instruction| {
+        instruction.program_id == crate::ID
+            && [
+                instruction::ExecuteFromOutside::DISCRIMINATOR,
+                instruction::ExecuteFromOutsideWithGuardianPayload::DISCRIMINATOR,
+                instruction::ExecuteFromOutsideWithTokenFee::DISCRIMINATOR,
+            ]
+            .iter()
+            .any(|discriminator| instruction.data.starts_with(discriminator))
+    };
+
+    // Not through CPI, where the top-level instruction would be another program's
+    let current_index = load_current_index_checked(sysvar_instructions)? as usize;
+    require!(
+        is_record(&load_instruction_at_checked(current_index, sysvar_instructions)?),
+        ErrorCode::UnrecordableOperation
+    );
+
+    let mut records = 0;
+    let mut operation_signed = false;
+    let mut signers: Vec<Pubkey> = Vec::new();
+    let mut precompile_signatures = 0u64;
+    let mut compute_unit_limit = None;
+    let mut compute_unit_price = 0;
+    let mut index = 0;
+    while let Ok(instruction) = load_instruction_at_checked(index, sysvar_instructions) {
+        index += 1;
+        for meta in instruction.accounts.iter().filter(|meta| meta.is_signer) {
+            if !signers.contains(&meta.pubkey) {
+                signers.push(meta.pubkey);
+            }
+        }
+
+        if instruction.program_id == ed25519_program::ID
+            || instruction.program_id == secp256k1_program::ID
+        {
+            precompile_signatures += instruction.data.first().copied().unwrap_or(0) as u64;
+        } else if instruction.program_id == COMPUTE_BUDGET_PROGRAM_ID {
+            match core::parse_compute_budget(&instruction.data) {
+                Some(core::ComputeBudgetSetting::UnitLimit(limit)) => {
+                    compute_unit_limit = Some(limit)
+                }
+                Some(core::ComputeBudgetSetting::UnitPrice(price)) => compute_unit_price = price,
+                None => {}
+            }
+        } else if is_record(&instruction) {
+            records += 1;
+        } else if instruction.program_id == crate::ID {
+            require!(!is_relayed(&instruction), ErrorCode::UnrecordableOperation);
+            let on_wallet = instruction.accounts.iter().any(|meta| meta.pubkey == wallet);
+            let signed = instruction
+                .accounts
+                .iter()
+                .any(|meta| meta.pubkey == signer && meta.is_signer);
+            operation_signed |= on_wallet && signed;
+        }
+    }
+    require!(records == 1 && operation_signed, ErrorCode::UnrecordableOperation);
+
+    // Signers no instruction names and the default compute limit are left out, so the fee
+    // is never overestimated
+    let fee = core::transaction_fee(
+        signers.len() as u64 + precompile_signatures,
+        compute_unit_limit.unwrap_or(0),
+        compute_unit_price,
+    )
+    .min(ctx.accounts.fee_rebate_policy.max_fee_per_operation);
+    let rebate = &mut ctx.accounts.fee_rebate;
+    if rebate.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    rebate.argent_account = wallet;
+    rebate.signer = signer;
+    rebate.spent_lamports = rebate.spent_lamports.saturating_add(fee);
+    rebate.bump = ctx.bumps.fee_rebate;
+
+    msg!("Recorded {} lamports of fees for {}", fee, signer);
+    Ok(())
+}
+
+pub(crate) fn claim_fee_rebate(ctx: Context<ClaimFeeRebate>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    require!(
+        is_rebate_signer(
+            &ctx.accounts.argent_account,
+            &ctx.accounts.relayer_registry,
+            &ctx.accounts.signer.key()
+        )?,
+        ErrorCode::FeeRebateSignerNotAllowed
+    );
+    require_not_frozen(&ctx.accounts.asset_freeze, &None)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let policy = &mut ctx.accounts.fee_rebate_policy;
+    if now - policy.period_start >= FEE_REPORT_PERIOD {
+        policy.period_start = now;
+        policy.claimed_in_period = 0;
+    }
+
+    let rebate = &mut ctx.accounts.fee_rebate;
+    let amount = rebate
+        .spent_lamports
+        .saturating_sub(rebate.claimed_lamports)
+        .min(policy.monthly_cap.saturating_sub(policy.claimed_in_period));
+    require!(amount > 0, ErrorCode::NoFeeRebateDue);
+    rebate.claimed_lamports += amount;
+    policy.claimed_in_period += amount;
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.signer.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        amount,
+    )?;
+
+    // A settled rebate is closed back to the signer; its next record opens it again
+    if ctx.accounts.fee_rebate.claimed_lamports == ctx.accounts.fee_rebate.spent_lamports {
+        ctx.accounts
+            .fee_rebate
+            .close(ctx.accounts.signer.to_account_info())?;
+        wallet_state.account_closed();
+    }
+
+    emit_cpi!(FeeRebateClaimed {
+        argent_account: argent_key,
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        signer: ctx.accounts.signer.key(),
+        amount,
+        state_version: wallet_state.state_version,
+    });
+
+    msg!("Rebated {} lamports of fees", amount);
+    Ok(())
+}
+
+pub(crate) fn set_guardian_fee(
+    ctx: Context<SetGuardianFee>,
+    amount: u64,
+    mint: Option<Pubkey>,
+    interval: i64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require!(amount > 0 && interval > 0, ErrorCode::InvalidGuardianFee);
+
+    let fee = &mut ctx.accounts.guardian_fee;
+    if fee.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    fee.argent_account = ctx.accounts.argent_account.key();
+    fee.guardian = ctx.accounts.approvers.guardian.key();
+    fee.mint = mint;
+    fee.amount = amount;
+    fee.interval = interval;
+    fee.last_claimed_at = Clock::get()?.unix_timestamp;
+    fee.bump = ctx.bumps.guardian_fee;
+
+    wallet_state.next_state_version();
+
+    msg!("Guardian fee set: {} every {}s", amount, interval);
+    Ok(())
+}
+
+pub(crate) fn remove_guardian_fee(ctx: Context<RemoveGuardianFee>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_closed();
+
+    wallet_state.next_state_version();
+
+    msg!("Guardian fee removed!");
+    Ok(())
+}
+
+pub(crate) fn claim_guardian_fee(ctx: Context<ClaimGuardianFee>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.guardian.key()])?;
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    let now = Clock::get()?.unix_timestamp;
+
+    let fee = &mut ctx.accounts.guardian_fee;
+    let periods = ((now - fee.last_claimed_at) / fee.interval) as u64;
+    require!(periods > 0, ErrorCode::GuardianFeeNotDue);
+    let amount = fee
+        .amount
+        .checked_mul(periods)
+        .ok_or(ErrorCode::InvalidGuardianFee)?;
+    fee.last_claimed_at += periods as i64 * fee.interval;
+    let mint = fee.mint;
+    require_not_frozen(&ctx.accounts.asset_freeze, &mint)?;
+
+    pay_from_vault(
+        &ctx.accounts.vault,
+        vault_seeds,
+        &ctx.accounts.guardian.to_account_info(),
+        &ctx.accounts.guardian_fee.to_account_info(),
+        mint,
+        ctx.accounts.mint.as_ref(),
+        ctx.accounts.vault_token_account.as_ref(),
+        ctx.accounts.guardian_token_account.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        &ctx.accounts.system_program,
+        amount,
+    )?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = GuardianFeeClaimed {
+        argent_account: argent_key,
+        notification_tag: argent_account.notification_tag,
+        guardian: ctx.accounts.guardian.key(),
+        mint,
+        amount,
+        periods,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Guardian fee of {} claimed for {} periods", amount, periods);
+    Ok(())
+}
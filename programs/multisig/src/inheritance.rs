@@ -0,0 +1,449 @@
+// Inheritance
+//
+// The beneficiaries of an inactive wallet, their claims and the distribution of the vault.
+
+use super::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetInheritancePlan<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = InheritancePlan::SPACE,
+        seeds = [b"inheritance", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub inheritance_plan: Account<'info, InheritancePlan>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RemoveInheritancePlan<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"inheritance", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub inheritance_plan: Account<'info, InheritancePlan>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitiateInheritanceClaim<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        mut,
+        seeds = [b"inheritance", argent_account.key().as_ref()],
+        bump,
+        constraint = inheritance_plan.is_beneficiary(&beneficiary.key())
+            @ ErrorCode::InvalidBeneficiary,
+    )]
+    pub inheritance_plan: Account<'info, InheritancePlan>,
+    pub beneficiary: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelInheritanceClaim<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [b"inheritance", argent_account.key().as_ref()], bump)]
+    pub inheritance_plan: Account<'info, InheritancePlan>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimInheritance<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    // The plan is used up by the claim; its rent goes back to the vault
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"inheritance", argent_account.key().as_ref()],
+        bump,
+        constraint = inheritance_plan.beneficiaries.len() == 1
+            && inheritance_plan.is_beneficiary(&beneficiary.key())
+            @ ErrorCode::NotSoleBeneficiary,
+    )]
+    pub inheritance_plan: Account<'info, InheritancePlan>,
+    pub beneficiary: Signer<'info>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DistributeInheritance<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        seeds = [b"inheritance", argent_account.key().as_ref()],
+        bump,
+        constraint = inheritance_plan.is_beneficiary(&beneficiary.key())
+            @ ErrorCode::InvalidBeneficiary,
+    )]
+    pub inheritance_plan: Account<'info, InheritancePlan>,
+    pub beneficiary: Signer<'info>,
+    /// The wallet vault PDA being distributed
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    // Token distributions only; checked in the handler
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub vault_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn set_inheritance_plan(
+    ctx: Context<SetInheritancePlan>,
+    beneficiaries: Vec<BeneficiaryShare>,
+    mints: Vec<Pubkey>,
+    inactivity_period: i64,
+    challenge_period: i64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require!(
+        inactivity_period > 0 && challenge_period > 0,
+        ErrorCode::InvalidInheritancePlan
+    );
+    require!(
+        !beneficiaries.is_empty()
+            && beneficiaries.len() <= MAX_BENEFICIARIES
+            && mints.len() <= MAX_INHERITANCE_MINTS,
+        ErrorCode::InvalidInheritancePlan
+    );
+    let mut total_bps = 0u32;
+    for (i, share) in beneficiaries.iter().enumerate() {
+        require!(
+            share.share_bps > 0
+                && !beneficiaries[..i]
+                    .iter()
+                    .any(|other| other.beneficiary == share.beneficiary),
+            ErrorCode::InvalidInheritancePlan
+        );
+        total_bps += share.share_bps as u32;
+    }
+    require!(
+        total_bps == BASIS_POINTS as u32,
+        ErrorCode::InvalidBeneficiaryShares
+    );
+
+    let plan = &mut ctx.accounts.inheritance_plan;
+    if plan.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    plan.argent_account = ctx.accounts.argent_account.key();
+    plan.beneficiaries = beneficiaries;
+    plan.mints = mints;
+    plan.inactivity_period = inactivity_period;
+    plan.challenge_period = challenge_period;
+    plan.claim_initiated_at = 0;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = InheritancePlanSet {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        beneficiaries: plan.beneficiaries.clone(),
+        mints: plan.mints.clone(),
+        inactivity_period,
+        challenge_period,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Inheritance plan set for {} beneficiaries", plan.beneficiaries.len());
+    Ok(())
+}
+
+pub(crate) fn remove_inheritance_plan(ctx: Context<RemoveInheritancePlan>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.account_closed();
+    wallet_state.next_state_version();
+
+    let event = InheritancePlanRemoved {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Inheritance plan removed!");
+    Ok(())
+}
+
+pub(crate) fn initiate_inheritance_claim(ctx: Context<InitiateInheritanceClaim>) -> Result<()> {
+    let wallet_state = ctx.accounts.wallet_state.load()?;
+    wallet_state.header.require_compatible()?;
+    let now = Clock::get()?.unix_timestamp;
+    let argent_account = &ctx.accounts.argent_account;
+    let plan = &mut ctx.accounts.inheritance_plan;
+
+    require!(plan.claim_initiated_at == 0, ErrorCode::InheritanceClaimInProgress);
+    require!(
+        core::has_elapsed(wallet_state.last_activity_at(), plan.inactivity_period, now),
+        ErrorCode::WalletNotInactive
+    );
+
+    plan.claim_initiated_at = now;
+
+    emit_cpi!(InheritanceClaimInitiated {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        beneficiary: ctx.accounts.beneficiary.key(),
+        initiated_at: now,
+        state_version: wallet_state.state_version,
+    });
+
+    msg!("Inheritance claim initiated!");
+    Ok(())
+}
+
+pub(crate) fn cancel_inheritance_claim(ctx: Context<CancelInheritanceClaim>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+
+    let plan = &mut ctx.accounts.inheritance_plan;
+    require!(plan.claim_initiated_at != 0, ErrorCode::NoInheritanceClaim);
+    plan.claim_initiated_at = 0;
+
+    let event = InheritanceClaimCancelled {
+        argent_account: ctx.accounts.argent_account.key(),
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        owner: ctx.accounts.owner.key(),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Inheritance claim cancelled!");
+    Ok(())
+}
+
+pub(crate) fn claim_inheritance(ctx: Context<ClaimInheritance>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.account_closed();
+    let now = Clock::get()?.unix_timestamp;
+    let plan = &ctx.accounts.inheritance_plan;
+    let argent_account = &mut ctx.accounts.argent_account;
+
+    plan.require_claimable(wallet_state.last_activity_at(), now)?;
+
+    wallet_state.next_state_version();
+
+    let event = InheritanceClaimed {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        old_owner: argent_account.owner,
+        new_owner: ctx.accounts.beneficiary.key(),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    // Change the owner
+    argent_account.owner = ctx.accounts.beneficiary.key();
+    argent_account.require_distinct_roles()?;
+
+    msg!("Inheritance claimed!");
+    Ok(())
+}
+
+pub(crate) fn distribute_inheritance<'info>(
+    ctx: Context<'_, '_, 'info, 'info, DistributeInheritance<'info>>,
+    mint: Option<Pubkey>,
+) -> Result<()> {
+    let wallet_state = ctx.accounts.wallet_state.load()?;
+    wallet_state.header.require_compatible()?;
+    let now = Clock::get()?.unix_timestamp;
+    let plan = &ctx.accounts.inheritance_plan;
+    plan.require_claimable(wallet_state.last_activity_at(), now)?;
+    require_not_frozen(&ctx.accounts.asset_freeze, &mint)?;
+
+    require!(
+        ctx.remaining_accounts.len() == plan.beneficiaries.len(),
+        ErrorCode::InvalidRemainingAccounts
+    );
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_key = ctx.accounts.vault.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+
+    let total = match mint {
+        None => {
+            let total = ctx.accounts.vault.lamports();
+            for (share, (recipient, amount)) in plan
+                .beneficiaries
+                .iter()
+                .zip(ctx.remaining_accounts.iter().zip(plan.split(total)))
+            {
+                require_keys_eq!(
+                    recipient.key(),
+                    share.beneficiary,
+                    ErrorCode::InvalidBeneficiary
+                );
+                if amount > 0 {
+                    system_program::transfer(
+                        CpiContext::new_with_signer(
+                            ctx.accounts.system_program.to_account_info(),
+                            system_program::Transfer {
+                                from: ctx.accounts.vault.to_account_info(),
+                                to: recipient.clone(),
+                            },
+                            &[vault_seeds],
+                        ),
+                        amount,
+                    )?;
+                }
+            }
+            total
+        }
+        Some(mint_key) => {
+            require!(plan.mints.contains(&mint_key), ErrorCode::MintNotInPlan);
+            let accounts = &ctx.accounts;
+            let (Some(mint), Some(vault_token_account), Some(token_program)) = (
+                &accounts.mint,
+                &accounts.vault_token_account,
+                &accounts.token_program,
+            ) else {
+                return err!(ErrorCode::MissingInheritanceAccounts);
+            };
+            require_keys_eq!(mint.key(), mint_key, ErrorCode::InvalidTokenAccount);
+            require_keys_eq!(vault_token_account.owner, vault_key, ErrorCode::InvalidTokenAccount);
+            require_keys_eq!(vault_token_account.mint, mint_key, ErrorCode::InvalidTokenAccount);
+
+            let total = vault_token_account.amount;
+            for (share, (account_info, amount)) in plan
+                .beneficiaries
+                .iter()
+                .zip(ctx.remaining_accounts.iter().zip(plan.split(total)))
+            {
+                let token_account = InterfaceAccount::<TokenAccount>::try_from(account_info)?;
+                require_keys_eq!(
+                    token_account.owner,
+                    share.beneficiary,
+                    ErrorCode::InvalidBeneficiary
+                );
+                require_keys_eq!(token_account.mint, mint_key, ErrorCode::InvalidTokenAccount);
+                if amount > 0 {
+                    token_interface::transfer_checked(
+                        CpiContext::new_with_signer(
+                            token_program.to_account_info(),
+                            token_interface::TransferChecked {
+                                from: vault_token_account.to_account_info(),
+                                mint: mint.to_account_info(),
+                                to: account_info.clone(),
+                                authority: accounts.vault.to_account_info(),
+                            },
+                            &[vault_seeds],
+                        ),
+                        amount,
+                        mint.decimals,
+                    )?;
+                }
+            }
+            total
+        }
+    };
+
+    emit_cpi!(InheritanceDistributed {
+        argent_account: argent_key,
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        mint,
+        amount: total,
+        state_version: wallet_state.state_version,
+    });
+
+    msg!("Distributed {} among {} beneficiaries", total, plan.beneficiaries.len());
+    Ok(())
+}
@@ -0,0 +1,939 @@
+// Key management
+//
+// Changing the owner, guardian and guardian backup with both keys, the timelocked rotations
+// either role starts alone, and the registry of guardian services a guardian can be vetted
+// against.
+
+use super::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ChangeOwner<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// CHECK: Instructions sysvar, holding the new owner's signature verification
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ChangeGuardian<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    // When given, the new guardian must be a vetted guardian service
+    #[account(
+        seeds = [b"guardian_service", guardian_service.guardian.as_ref()],
+        bump = guardian_service.bump,
+    )]
+    pub guardian_service: Option<Account<'info, GuardianService>>,
+    /// CHECK: Instructions sysvar, holding the new guardian's signature verification
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct GuardianSelfRotate<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = GuardianRotation::SPACE,
+        seeds = [b"guardian_rotation", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub guardian_rotation: Account<'info, GuardianRotation>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Instructions sysvar, holding the new guardian's signature verification
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CompleteGuardianRotation<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    // Anyone
+    pub cranker: Signer<'info>,
+    // Lapses when the guardian who requested it has been replaced
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"guardian_rotation", argent_account.key().as_ref()],
+        bump = guardian_rotation.bump,
+        has_one = payer,
+        constraint = guardian_rotation.guardian == argent_account.guardian @ ErrorCode::InvalidGuardian,
+    )]
+    pub guardian_rotation: Account<'info, GuardianRotation>,
+    /// CHECK: Receives the request rent back
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct VetoGuardianRotation<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"guardian_rotation", argent_account.key().as_ref()],
+        bump = guardian_rotation.bump,
+        has_one = payer,
+    )]
+    pub guardian_rotation: Account<'info, GuardianRotation>,
+    /// CHECK: Receives the request rent back
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ChangeGuardianBackup<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = GuardianBackupChange::SPACE,
+        seeds = [b"guardian_backup_change", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub guardian_backup_change: Account<'info, GuardianBackupChange>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Instructions sysvar, holding the new guardian backup's signature verification
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CompleteGuardianBackupChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    // Anyone
+    pub cranker: Signer<'info>,
+    // Lapses when the owner or guardian who requested it has been replaced
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"guardian_backup_change", argent_account.key().as_ref()],
+        bump = guardian_backup_change.bump,
+        has_one = payer,
+        constraint = guardian_backup_change.owner == argent_account.owner @ ErrorCode::InvalidOwner,
+        constraint = guardian_backup_change.guardian == argent_account.guardian @ ErrorCode::InvalidGuardian,
+    )]
+    pub guardian_backup_change: Account<'info, GuardianBackupChange>,
+    /// CHECK: Receives the request rent back
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelGuardianBackupChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        constraint = authority.key() == argent_account.owner || authority.key() == argent_account.guardian @ ErrorCode::NotOwnerOrGuardian,
+    )]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"guardian_backup_change", argent_account.key().as_ref()],
+        bump = guardian_backup_change.bump,
+        has_one = payer,
+    )]
+    pub guardian_backup_change: Account<'info, GuardianBackupChange>,
+    /// CHECK: Receives the request rent back
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RotateAll<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = KeyRotation::SPACE,
+        seeds = [b"key_rotation", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub key_rotation: Account<'info, KeyRotation>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Instructions sysvar, holding the new keys' signature verifications
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CompleteKeyRotation<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    // Anyone
+    pub cranker: Signer<'info>,
+    // Lapses when the owner or guardian who requested it has been replaced
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"key_rotation", argent_account.key().as_ref()],
+        bump = key_rotation.bump,
+        has_one = payer,
+        constraint = key_rotation.owner == argent_account.owner @ ErrorCode::InvalidOwner,
+        constraint = key_rotation.guardian == argent_account.guardian @ ErrorCode::InvalidGuardian,
+    )]
+    pub key_rotation: Account<'info, KeyRotation>,
+    /// CHECK: Receives the request rent back
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelKeyRotation<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        constraint = authority.key() == argent_account.owner || authority.key() == argent_account.guardian @ ErrorCode::NotOwnerOrGuardian,
+    )]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"key_rotation", argent_account.key().as_ref()],
+        bump = key_rotation.bump,
+        has_one = payer,
+    )]
+    pub key_rotation: Account<'info, KeyRotation>,
+    /// CHECK: Receives the request rent back
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecordGuardianReshare<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    /// CHECK: Must be the wallet guardian; its signature is verified by the ed25519 program
+    #[account(constraint = argent_account.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: UncheckedAccount<'info>,
+    /// CHECK: Instructions sysvar, holding the guardian's signature verification
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RegisterGuardianService<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = GuardianService::SPACE,
+        seeds = [b"guardian_service", guardian.key().as_ref()],
+        bump,
+    )]
+    pub guardian_service: Account<'info, GuardianService>,
+    pub guardian: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ApproveGuardianService<'info> {
+    #[account(
+        mut,
+        seeds = [b"guardian_service", guardian_service.guardian.as_ref()],
+        bump = guardian_service.bump,
+    )]
+    pub guardian_service: Account<'info, GuardianService>,
+    pub authority: Signer<'info>,
+    // Program data of this program, naming the upgrade authority that vets guardian services
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ ErrorCode::InvalidUpgradeAuthority,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+}
+
+#[derive(Accounts)]
+pub struct UnregisterGuardianService<'info> {
+    #[account(
+        mut,
+        close = guardian,
+        seeds = [b"guardian_service", guardian.key().as_ref()],
+        bump = guardian_service.bump,
+    )]
+    pub guardian_service: Account<'info, GuardianService>,
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+}
+
+pub(crate) fn change_owner(
+    ctx: Context<ChangeOwner>,
+    new_owner: Pubkey,
+    new_owner_signature: [u8; 64],
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+
+    // Verify new owner signature
+    let signed = verify_ed25519_instruction(
+        &ctx.accounts.sysvar_instructions,
+        &new_owner,
+        &new_owner_signature,
+    )?;
+    OffchainMessage::decode(&signed)?.require_authorizes(
+        &argent_account.key(),
+        wallet_state.state_version,
+        OffchainAction::ChangeOwner,
+        &hash(new_owner.as_ref()).to_bytes(),
+        Clock::get()?.slot,
+    )?;
+
+    wallet_state.next_state_version();
+
+    let event = OwnerChanged {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        guardian: ctx.accounts.approvers.guardian.key(),
+        old_owner: argent_account.owner,
+        new_owner,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    // Change the owner
+    argent_account.owner = new_owner;
+    argent_account.require_distinct_roles()?;
+
+    msg!("Owner changed successfully!");
+    Ok(())
+}
+
+pub(crate) fn change_guardian(ctx: Context<ChangeGuardian>, new_guardian: Pubkey) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+
+    if let Some(guardian_service) = &ctx.accounts.guardian_service {
+        guardian_service.require_vetted(&new_guardian)?;
+    }
+    require_key_registration(
+        &ctx.accounts.sysvar_instructions,
+        &argent_account.key(),
+        wallet_state.state_version,
+        OffchainAction::ChangeGuardian,
+        &new_guardian,
+    )?;
+
+    wallet_state.next_state_version();
+
+    let event = GuardianChanged {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        old_guardian: argent_account.guardian,
+        new_guardian,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    // Change the guardian
+    argent_account.guardian = new_guardian;
+    argent_account.require_distinct_roles()?;
+
+    msg!("Guardian changed successfully!");
+    Ok(())
+}
+
+pub(crate) fn guardian_self_rotate(
+    ctx: Context<GuardianSelfRotate>,
+    new_key: Pubkey,
+    proof_sig: [u8; 64],
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.guardian.key()])?;
+    wallet_state.account_opened();
+
+    let argent_account = &ctx.accounts.argent_account;
+
+    // The guardian can't rotate away from an escape of itself
+    require!(
+        wallet_state.escape_type() != EscapeType::Guardian,
+        ErrorCode::EscapeGuardianInProgress
+    );
+    let signed = verify_ed25519_instruction(
+        &ctx.accounts.sysvar_instructions,
+        &new_key,
+        &proof_sig,
+    )?;
+    OffchainMessage::decode(&signed)?.require_authorizes(
+        &argent_account.key(),
+        wallet_state.state_version,
+        OffchainAction::ChangeGuardian,
+        &hash(new_key.as_ref()).to_bytes(),
+        Clock::get()?.slot,
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let rotation = &mut ctx.accounts.guardian_rotation;
+    rotation.argent_account = argent_account.key();
+    rotation.guardian = ctx.accounts.guardian.key();
+    rotation.new_guardian = new_key;
+    rotation.requested_at = now;
+    rotation.payer = ctx.accounts.payer.key();
+    rotation.bump = ctx.bumps.guardian_rotation;
+
+    wallet_state.next_state_version();
+
+    let event = GuardianRotationRequested {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        guardian: ctx.accounts.guardian.key(),
+        new_guardian: new_key,
+        effective_at: now.saturating_add(argent_account.security_period),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Guardian rotation to {} requested", new_key);
+    Ok(())
+}
+
+pub(crate) fn complete_guardian_rotation(ctx: Context<CompleteGuardianRotation>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.account_closed();
+    let argent_account = &mut ctx.accounts.argent_account;
+    let rotation = &ctx.accounts.guardian_rotation;
+
+    require!(
+        wallet_state.escape_type() != EscapeType::Guardian,
+        ErrorCode::EscapeGuardianInProgress
+    );
+    require!(
+        core::has_elapsed(
+            rotation.requested_at,
+            argent_account.security_period,
+            Clock::get()?.unix_timestamp
+        ),
+        ErrorCode::SecurityPeriodNotElapsed
+    );
+
+    wallet_state.next_state_version();
+
+    let event = GuardianChanged {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: argent_account.owner,
+        old_guardian: rotation.guardian,
+        new_guardian: rotation.new_guardian,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    argent_account.guardian = rotation.new_guardian;
+    argent_account.require_distinct_roles()?;
+
+    msg!("Guardian rotated successfully!");
+    Ok(())
+}
+
+pub(crate) fn veto_guardian_rotation(ctx: Context<VetoGuardianRotation>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+    wallet_state.account_closed();
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = GuardianRotationVetoed {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.owner.key(),
+        new_guardian: ctx.accounts.guardian_rotation.new_guardian,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Guardian rotation vetoed!");
+    Ok(())
+}
+
+pub(crate) fn change_guardian_backup(
+    ctx: Context<ChangeGuardianBackup>,
+    new_guardian_backup: Option<Pubkey>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_opened();
+
+    let argent_account = &mut ctx.accounts.argent_account;
+
+    if let Some(new_guardian_backup) = &new_guardian_backup {
+        require_key_registration(
+            &ctx.accounts.sysvar_instructions,
+            &argent_account.key(),
+            wallet_state.state_version,
+            OffchainAction::ChangeGuardianBackup,
+            new_guardian_backup,
+        )?;
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let change = &mut ctx.accounts.guardian_backup_change;
+    change.argent_account = argent_account.key();
+    change.owner = ctx.accounts.approvers.owner.key();
+    change.guardian = ctx.accounts.approvers.guardian.key();
+    change.new_guardian_backup = new_guardian_backup;
+    change.requested_at = now;
+    change.payer = ctx.accounts.payer.key();
+    change.bump = ctx.bumps.guardian_backup_change;
+
+    wallet_state.next_state_version();
+
+    let event = GuardianBackupChangeRequested {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        new_guardian_backup,
+        effective_at: now.saturating_add(argent_account.security_period),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Guardian backup change requested");
+    Ok(())
+}
+
+pub(crate) fn complete_guardian_backup_change(
+    ctx: Context<CompleteGuardianBackupChange>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.account_closed();
+    let argent_account = &mut ctx.accounts.argent_account;
+    let change = &ctx.accounts.guardian_backup_change;
+
+    require!(
+        core::has_elapsed(
+            change.requested_at,
+            argent_account.security_period,
+            Clock::get()?.unix_timestamp
+        ),
+        ErrorCode::SecurityPeriodNotElapsed
+    );
+
+    wallet_state.next_state_version();
+
+    let event = GuardianBackupChanged {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: change.owner,
+        guardian: change.guardian,
+        old_guardian_backup: argent_account.guardian_backup,
+        new_guardian_backup: change.new_guardian_backup,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    // Change the guardian backup
+    argent_account.guardian_backup = change.new_guardian_backup;
+    argent_account.require_distinct_roles()?;
+
+    msg!("Guardian backup changed successfully!");
+    Ok(())
+}
+
+pub(crate) fn cancel_guardian_backup_change(ctx: Context<CancelGuardianBackupChange>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.authority.key()],
+    )?;
+    wallet_state.account_closed();
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = GuardianBackupChangeCancelled {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        cancelled_by: ctx.accounts.authority.key(),
+        new_guardian_backup: ctx.accounts.guardian_backup_change.new_guardian_backup,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Guardian backup change cancelled!");
+    Ok(())
+}
+
+pub(crate) fn rotate_all(
+    ctx: Context<RotateAll>,
+    new_owner: Pubkey,
+    new_guardian: Pubkey,
+    new_backup: Option<Pubkey>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_opened();
+
+    let argent_account = &mut ctx.accounts.argent_account;
+
+    require_keys_neq!(new_owner, new_guardian, ErrorCode::OwnerIsGuardian);
+    let mut registrations = vec![
+        (OffchainAction::ChangeOwner, new_owner),
+        (OffchainAction::ChangeGuardian, new_guardian),
+    ];
+    if let Some(new_backup) = new_backup {
+        require_keys_neq!(new_backup, new_owner, ErrorCode::GuardianBackupIsOwner);
+        require_keys_neq!(new_backup, new_guardian, ErrorCode::GuardianBackupIsGuardian);
+        registrations.push((OffchainAction::ChangeGuardianBackup, new_backup));
+    }
+    for (action, key) in registrations {
+        require_key_registration(
+            &ctx.accounts.sysvar_instructions,
+            &argent_account.key(),
+            wallet_state.state_version,
+            action,
+            &key,
+        )?;
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let rotation = &mut ctx.accounts.key_rotation;
+    rotation.argent_account = argent_account.key();
+    rotation.owner = ctx.accounts.approvers.owner.key();
+    rotation.guardian = ctx.accounts.approvers.guardian.key();
+    rotation.new_owner = new_owner;
+    rotation.new_guardian = new_guardian;
+    rotation.new_guardian_backup = new_backup;
+    rotation.requested_at = now;
+    rotation.payer = ctx.accounts.payer.key();
+    rotation.bump = ctx.bumps.key_rotation;
+
+    wallet_state.next_state_version();
+
+    let event = KeyRotationRequested {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        new_owner,
+        new_guardian,
+        new_guardian_backup: new_backup,
+        effective_at: now.saturating_add(argent_account.security_period),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Rotation of all keys requested");
+    Ok(())
+}
+
+pub(crate) fn complete_key_rotation(ctx: Context<CompleteKeyRotation>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.account_closed();
+    let argent_account = &mut ctx.accounts.argent_account;
+    let rotation = &ctx.accounts.key_rotation;
+
+    require!(
+        core::has_elapsed(
+            rotation.requested_at,
+            argent_account.security_period,
+            Clock::get()?.unix_timestamp
+        ),
+        ErrorCode::SecurityPeriodNotElapsed
+    );
+
+    wallet_state.next_state_version();
+
+    let event = KeysRotated {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        old_owner: argent_account.owner,
+        new_owner: rotation.new_owner,
+        old_guardian: argent_account.guardian,
+        new_guardian: rotation.new_guardian,
+        old_guardian_backup: argent_account.guardian_backup,
+        new_guardian_backup: rotation.new_guardian_backup,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    argent_account.owner = rotation.new_owner;
+    argent_account.guardian = rotation.new_guardian;
+    argent_account.guardian_backup = rotation.new_guardian_backup;
+    argent_account.require_distinct_roles()?;
+
+    msg!("All keys rotated successfully!");
+    Ok(())
+}
+
+pub(crate) fn cancel_key_rotation(ctx: Context<CancelKeyRotation>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.authority.key()],
+    )?;
+    wallet_state.account_closed();
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = KeyRotationCancelled {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        cancelled_by: ctx.accounts.authority.key(),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Key rotation cancelled!");
+    Ok(())
+}
+
+pub(crate) fn record_guardian_reshare(
+    ctx: Context<RecordGuardianReshare>,
+    guardian_signature: [u8; 64],
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    let guardian_key = ctx.accounts.guardian.key();
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[guardian_key])?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    let signed = verify_ed25519_instruction(
+        &ctx.accounts.sysvar_instructions,
+        &guardian_key,
+        &guardian_signature,
+    )?;
+    OffchainMessage::decode(&signed)?.require_authorizes(
+        &argent_account.key(),
+        wallet_state.state_version,
+        OffchainAction::GuardianReshare,
+        &hash(guardian_key.as_ref()).to_bytes(),
+        Clock::get()?.slot,
+    )?;
+
+    wallet_state.next_state_version();
+
+    let event = GuardianReshared {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        guardian: guardian_key,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Guardian key reshared!");
+    Ok(())
+}
+
+pub(crate) fn register_guardian_service(
+    ctx: Context<RegisterGuardianService>,
+    name: String,
+    metadata_uri: String,
+) -> Result<()> {
+    require!(
+        name.len() <= MAX_WALLET_NAME_LEN && metadata_uri.len() <= MAX_METADATA_URI_LEN,
+        ErrorCode::MetadataTooLong
+    );
+
+    let guardian_service = &mut ctx.accounts.guardian_service;
+    guardian_service.guardian = ctx.accounts.guardian.key();
+    guardian_service.name = name;
+    guardian_service.metadata_uri = metadata_uri;
+    guardian_service.approved = false;
+    guardian_service.bump = ctx.bumps.guardian_service;
+
+    emit_cpi!(GuardianServiceRegistered {
+        guardian: guardian_service.guardian,
+        name: guardian_service.name.clone(),
+        metadata_uri: guardian_service.metadata_uri.clone(),
+    });
+
+    msg!("Guardian service {} registered", guardian_service.guardian);
+    Ok(())
+}
+
+pub(crate) fn approve_guardian_service(
+    ctx: Context<ApproveGuardianService>,
+    approved: bool,
+) -> Result<()> {
+    let guardian_service = &mut ctx.accounts.guardian_service;
+    guardian_service.approved = approved;
+
+    emit_cpi!(GuardianServiceApproved {
+        guardian: guardian_service.guardian,
+        approved,
+    });
+
+    msg!("Guardian service {} approved: {}", guardian_service.guardian, approved);
+    Ok(())
+}
+
+pub(crate) fn unregister_guardian_service(ctx: Context<UnregisterGuardianService>) -> Result<()> {
+    msg!("Guardian service {} unregistered", ctx.accounts.guardian.key());
+    Ok(())
+}
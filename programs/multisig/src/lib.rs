@@ -1,506 +1,5072 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
 use anchor_lang::solana_program::clock::Clock;
+use anchor_lang::solana_program::compute_units::sol_remaining_compute_units;
+use anchor_lang::solana_program::hash::{hash, hashv};
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::mpl_token_metadata::instructions::TransferV1CpiBuilder;
+use anchor_spl::metadata::mpl_token_metadata::types::{ProgrammableConfig, TokenStandard};
+use anchor_spl::metadata::{Metadata, MetadataAccount};
+use anchor_spl::token::{spl_token, Token};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
+
+pub mod core;
+pub mod deposits;
+pub mod escape;
+pub mod fees;
+pub mod inheritance;
+pub mod keys;
+pub mod message;
+pub mod migrate_from;
+pub mod nft;
+pub mod outside;
+pub mod payments;
+pub mod recovery;
+pub mod signatures;
+pub mod staking;
+pub mod streams;
+pub mod tokens;
+pub mod trading;
+pub mod transactions;
+pub mod upgrades;
+pub mod vault;
+pub mod wallet;
+
+use message::{
+    ApprovalPayload, OffchainAction, OffchainMessage, TransferIntent, APPROVAL_PAYLOAD_DOMAIN,
+};
+pub use deposits::*;
+pub use escape::*;
+pub use fees::*;
+pub use inheritance::*;
+pub use keys::*;
+pub use migrate_from::*;
+pub use nft::*;
+pub use outside::*;
+pub use payments::*;
+pub use recovery::*;
+pub use signatures::*;
+pub use staking::*;
+pub use streams::*;
+pub use tokens::*;
+pub use trading::*;
+pub use transactions::*;
+pub use upgrades::*;
+pub use vault::*;
+pub use wallet::*;
 
 declare_id!("3FfiWU89727pbcRppUBpD8ZSMpRxTPBfu324ynPffp2i");
 
+// Metaplex Bubblegum program (compressed NFTs)
+pub const BUBBLEGUM_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+// Anchor discriminator of Bubblegum's `transfer` instruction
+const BUBBLEGUM_TRANSFER_DISCRIMINATOR: [u8; 8] = [163, 52, 200, 231, 140, 3, 69, 186];
+
+// Metaplex Core program and the instruction/plugin indices we use from it
+pub const MPL_CORE_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
+const MPL_CORE_APPROVE_PLUGIN_AUTHORITY_V1: u8 = 8;
+const MPL_CORE_REVOKE_PLUGIN_AUTHORITY_V1: u8 = 10;
+const MPL_CORE_BURN_V1: u8 = 12;
+const MPL_CORE_TRANSFER_V1: u8 = 14;
+const MPL_CORE_UPDATE_DELEGATE_PLUGIN: u8 = 4;
+const MPL_CORE_PLUGIN_AUTHORITY_ADDRESS: u8 = 3;
+
+// SPL stake pool program and the instruction indices we use from it
+pub const SPL_STAKE_POOL_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
+const STAKE_POOL_DEPOSIT_SOL: u8 = 14;
+const STAKE_POOL_WITHDRAW_SOL: u8 = 16;
+
+// Pyth pull oracle receiver program, owner of the price update accounts payment budgets
+// price assets with, and the Anchor discriminator of those accounts
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+const PYTH_PRICE_UPDATE_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+// Compute Budget program, whose instructions set the priority fee fee rebates count
+const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("ComputeBudget111111111111111111111111111111");
+
+// Data of the System program's AdvanceNonceAccount instruction (its bincode enum tag)
+const ADVANCE_NONCE_ACCOUNT_DATA: [u8; 4] = [4, 0, 0, 0];
+
+// Maximum length in bytes of a deposit memo
+pub const MAX_MEMO_LEN: usize = 128;
+
+// Maximum length in bytes of the error context recorded when a transaction is abandoned
+pub const MAX_ABANDON_REASON_LEN: usize = 128;
+pub const MAX_PROPOSAL_REASON_LEN: usize = 128;
+pub const MAX_INTENT_SUMMARY_LEN: usize = 320;
+
+// Layout version written to new accounts; older accounts are upgraded by migrate_account
+pub const CURRENT_ACCOUNT_VERSION: u8 = 19;
+// First layout version with the fixed header below; older wallets start with the legacy layout
+pub const FIXED_HEADER_ACCOUNT_VERSION: u8 = 17;
+// First layout version starting with the account header; older wallets have none
+pub const ACCOUNT_HEADER_VERSION: u8 = 19;
+
+// Version of the account header every account of this program starts with, and the layout
+// feature bits this program understands (none are defined yet). Raised whenever an account's
+// layout changes, so accounts written by another release are refused rather than misparsed
+pub const ACCOUNT_LAYOUT_VERSION: u8 = 1;
+pub const SUPPORTED_ACCOUNT_FEATURES: u64 = 0;
+
+// Offsets of the wallet config's fixed header (after the discriminator and account header),
+// for getProgramAccounts memcmp filters: owner and guardian keys, layout version; and of the
+// escape type in the wallet state
+pub const WALLET_OWNER_OFFSET: usize = 24;
+pub const WALLET_GUARDIAN_OFFSET: usize = 56;
+pub const WALLET_VERSION_OFFSET: usize = 88;
+pub const WALLET_STATE_ESCAPE_TYPE_OFFSET: usize = 116;
+
+// open_accounts of a wallet migrated from before the counter existed, until count_open_accounts
+// establishes it, and the offset of the wallet key every account derived from a wallet starts
+// with
+pub const UNCOUNTED_OPEN_ACCOUNTS: u32 = u32::MAX;
+pub const DERIVED_ACCOUNT_WALLET_OFFSET: usize = 24;
+
+// Largest transaction payload a wallet account has room to store
+pub const MAX_PENDING_TX_DATA_LEN: usize = 512;
+
+// Bounds of the wallet display metadata
+pub const MAX_WALLET_NAME_LEN: usize = 32;
+pub const MAX_METADATA_URI_LEN: usize = 200;
+
+// Capacity of the zero-copy delegation registry and quarantine accounts
+pub const MAX_TOKEN_DELEGATIONS: usize = 16;
+pub const MAX_QUARANTINED_MINTS: usize = 32;
+
+// Maximum number of mints the guardian can freeze at once
+pub const MAX_FROZEN_MINTS: usize = 16;
+
+// Router programs a wallet may allowlist for swaps
+pub const MAX_SWAP_ROUTERS: usize = 8;
+
+// Relayers a wallet may allowlist for outside execution, and the length of a quota window
+pub const MAX_RELAYERS: usize = 8;
+pub const RELAYER_QUOTA_WINDOW: i64 = 86400;
+
+// Maximum number of viewer keys allowed to read a wallet's private details
+pub const MAX_VIEWERS: usize = 8;
+
+// Maximum number of recovery contacts in a quorum recovery policy
+pub const MAX_RECOVERY_CONTACTS: usize = 8;
+
+// Capacity of a label policy, and the maximum length in bytes of a proposal category
+pub const MAX_LABEL_CATEGORIES: usize = 8;
+pub const MAX_LABEL_LEN: usize = 32;
+
+// Addresses a writable allowlist can approve besides the vault and its token accounts
+pub const MAX_WRITABLE_ADDRESSES: usize = 16;
+
+// Senders a funding policy can allow, the flagged deposits it holds awaiting a refund, how many
+// of those may be token deposits, and the smallest SOL deposit (in lamports) it holds for one
+pub const MAX_FUNDING_SOURCES: usize = 16;
+pub const MAX_FLAGGED_DEPOSITS: usize = 16;
+pub const MAX_FLAGGED_TOKEN_DEPOSITS: usize = 4;
+pub const MIN_FLAGGED_SOL_DEPOSIT: u64 = 1_000_000;
+
+// Assets a payment budget can price, the length of a budget window, and the oldest price
+// (in seconds) a payment may be valued at
+pub const MAX_BUDGET_ASSETS: usize = 8;
+pub const PAYMENT_BUDGET_WINDOW: i64 = 86400;
+pub const MAX_PRICE_AGE: i64 = 60;
+
+// Runtime ceiling for a transaction's compute unit limit, and the compute a batched
+// instruction reserves per remaining-account item before starting
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+pub const BATCH_ITEM_COMPUTE_UNITS: u64 = 20_000;
+
+// Maximum length in bytes of a sub-vault name
+pub const MAX_SUB_VAULT_NAME_LEN: usize = 32;
+
+// Capacity of an inheritance plan, and the total of its basis-point shares
+pub const MAX_BENEFICIARIES: usize = 8;
+pub const MAX_INHERITANCE_MINTS: usize = 8;
+pub const BASIS_POINTS: u16 = 10_000;
+
+// Number of recent actions kept in a wallet's history ring buffer
+pub const HISTORY_LEN: usize = 32;
+
+// Kinds of action recorded in the history
+pub const HISTORY_KIND_EXECUTE: u8 = 0;
+pub const HISTORY_KIND_ABANDON: u8 = 1;
+
+// Length of a fee accounting period (30 days)
+pub const FEE_REPORT_PERIOD: i64 = 2_592_000;
+
+// Longest the owner can lock the wallet for at once (90 days)
+pub const MAX_WALLET_LOCK_DURATION: i64 = 7_776_000;
+
 #[program]
 pub mod multisig {
     use super::*;
 
     // Create a new Argent account with owner and guardian
+    // The address is derived from the creator and an index, so it survives key rotation
+    // and one creator can have many wallets
     pub fn create(
         ctx: Context<Create>,
+        index: u64,
         owner: Pubkey,
         guardian: Pubkey,
         security_period: Option<i64>,
     ) -> Result<()> {
-        let argent_account = &mut ctx.accounts.argent_account;
-        argent_account.owner = owner;
-        argent_account.guardian = guardian;
-        argent_account.guardian_backup = None;
-        argent_account.escape_type = EscapeType::None;
-        argent_account.escape_initiated_at = 0;
-        
-        // Set security period (default 7 days = 604800 seconds)
-        argent_account.security_period = security_period.unwrap_or(604800);
-        
-        // Initialize pending transaction
-        argent_account.pending_tx = None;
-        
-        Ok(())
+        wallet::create(ctx, index, owner, guardian, security_period)
     }
 
     // Execute a transaction with both owner and guardian signatures
+    // The guardian either signs live or has posted a GuardianApproval for this data, which
+    // is consumed here. Once the wallet has a proposal nonce, the transaction must advance it
+    // first, so approvals gathered over days stay valid as durable nonce transactions and
+    // each one consumes the nonce atomically
     pub fn execute(ctx: Context<Execute>, data: Vec<u8>) -> Result<()> {
-        let argent_account = &mut ctx.accounts.argent_account;
-        
-        // Verify that both owner and guardian have signed
-        let owner_signed = ctx.accounts.owner.is_signer;
-        let guardian_signed = ctx.accounts.guardian.is_signer;
-        
-        require!(
-            owner_signed && guardian_signed,
-            ErrorCode::NotEnoughApprovals
-        );
-        
-        // Store the transaction data for execution
-        argent_account.pending_tx = Some(PendingTransaction {
-            data,
-            owner_approved: true,
-            guardian_approved: true,
-        });
-        
-        msg!("Transaction approved and ready for execution!");
-        Ok(())
+        transactions::execute(ctx, data)
+    }
+    
+    // Propose a transaction from the guardian side (guardian alone), e.g. sweeping the vault
+    // to a safe address in response to fraud; nothing happens until the owner approves it
+    // with approve_guardian_proposal. `reason` is shown to the owner. One proposal at a time
+    pub fn propose_guardian_transaction(
+        ctx: Context<ProposeGuardianTransaction>,
+        data: Vec<u8>,
+        reason: String,
+    ) -> Result<()> {
+        transactions::propose_guardian_transaction(ctx, data, reason)
+    }
+    
+    // Approve the guardian's proposal with the owner's signature; it becomes the pending
+    // transaction as if both had signed `execute`, and the proposal is closed back to its
+    // payer. `data_hash` pins the proposal the owner reviewed
+    pub fn approve_guardian_proposal(
+        ctx: Context<ApproveGuardianProposal>,
+        data_hash: [u8; 32],
+    ) -> Result<()> {
+        transactions::approve_guardian_proposal(ctx, data_hash)
+    }
+    
+    // Drop the guardian's proposal, by the owner rejecting it or the guardian withdrawing it;
+    // the rent goes back to its payer
+    pub fn reject_guardian_proposal(ctx: Context<RejectGuardianProposal>) -> Result<()> {
+        transactions::reject_guardian_proposal(ctx)
+    }
+    
+    // Run `instructions` right away from the vault with both owner and guardian signatures;
+    // nothing is stored, so the transaction either executes in full or not at all. Programs
+    // and accounts are referenced by index into the remaining accounts, and the vault signs
+    // wherever it appears. Under a writable allowlist, instructions may only write to the
+    // vault, its token accounts and the allowlisted addresses
+    pub fn execute_immediate<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteImmediate<'info>>,
+        instructions: Vec<VaultInstruction>,
+    ) -> Result<()> {
+        transactions::execute_immediate(ctx, instructions)
+    }
+    
+    // Restrict the accounts `execute_immediate` instructions may mark writable to the vault,
+    // its token accounts and `addresses` (requires both owner and guardian), containing what
+    // a complex approved interaction can touch
+    pub fn set_writable_allowlist(
+        ctx: Context<SetWritableAllowlist>,
+        addresses: Vec<Pubkey>,
+    ) -> Result<()> {
+        transactions::set_writable_allowlist(ctx, addresses)
+    }
+    
+    // Lift the writable allowlist (requires both owner and guardian); the rent goes back to
+    // the vault
+    pub fn remove_writable_allowlist(ctx: Context<RemoveWritableAllowlist>) -> Result<()> {
+        transactions::remove_writable_allowlist(ctx)
+    }
+    
+    // Sign an instruction of this program as the wallet account itself (requires both owner
+    // and guardian), so the wallet can hold the guardian role of other wallets. Only the
+    // instructions a guardian takes part in are signed, see `is_guardian_role_instruction`.
+    // The instruction takes the remaining accounts in order, with the wallet account marked as
+    // signer
+    pub fn act_as_guardian<'info>(
+        ctx: Context<'_, '_, '_, 'info, ActAsGuardian<'info>>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        transactions::act_as_guardian(ctx, data)
+    }
+    
+    // Create a wallet and approve its first transaction in one instruction
+    // The wallet and vault addresses can be shared and funded before the account exists;
+    // owner and guardian sign here, so their keys are taken from the signers
+    pub fn create_and_execute(
+        ctx: Context<CreateAndExecute>,
+        index: u64,
+        security_period: Option<i64>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        wallet::create_and_execute(ctx, index, security_period, data)
+    }
+    
+    // Start the list of wallets derived from this wallet with `create_derived_wallet`
+    pub fn create_controller_index(ctx: Context<CreateControllerIndex>) -> Result<()> {
+        wallet::create_controller_index(ctx)
+    }
+    
+    // Create a wallet at `index` under this wallet (requires both owner and guardian); it
+    // starts with the same owner, guardian, guardian backup and security period but has its
+    // own vault and policies, and is listed in the controller index
+    pub fn create_derived_wallet(ctx: Context<CreateDerivedWallet>, index: u64) -> Result<()> {
+        wallet::create_derived_wallet(ctx, index)
     }
     
     // Change the owner with both owner and guardian signatures
-    // Also requires a signature from the new owner
+    // Also requires the new owner's signature over an unexpired `OffchainMessage` accepting
+    // the wallet in its current state, verified by an ed25519 program instruction placed right
+    // before this one
     pub fn change_owner(
         ctx: Context<ChangeOwner>,
         new_owner: Pubkey,
         new_owner_signature: [u8; 64],
     ) -> Result<()> {
-        let argent_account = &mut ctx.accounts.argent_account;
-        
-        // Verify that both current owner and guardian have signed
-        let owner_signed = ctx.accounts.owner.is_signer;
-        let guardian_signed = ctx.accounts.guardian.is_signer;
-        
-        require!(
-            owner_signed && guardian_signed,
-            ErrorCode::NotEnoughApprovals
-        );
-        
-        // Verify new owner signature
-        // In a real implementation, we would verify the signature here
-        // For simplicity, we're just checking that a signature was provided
-        require!(
-            new_owner_signature != [0; 64],
-            ErrorCode::InvalidSignature
-        );
-        
-        // Change the owner
-        argent_account.owner = new_owner;
-        
-        msg!("Owner changed successfully!");
-        Ok(())
+        keys::change_owner(ctx, new_owner, new_owner_signature)
     }
     
-    // Change the guardian with both owner and guardian signatures
+    // Change the guardian with both owner and guardian signatures, and the new guardian's proof
+    // of possession; passing the new guardian's registry entry additionally requires it to be
+    // a vetted guardian service
     pub fn change_guardian(ctx: Context<ChangeGuardian>, new_guardian: Pubkey) -> Result<()> {
-        let argent_account = &mut ctx.accounts.argent_account;
-        
-        // Verify that both owner and guardian have signed
-        let owner_signed = ctx.accounts.owner.is_signer;
-        let guardian_signed = ctx.accounts.guardian.is_signer;
-        
-        require!(
-            owner_signed && guardian_signed,
-            ErrorCode::NotEnoughApprovals
-        );
-        
-        // Change the guardian
-        argent_account.guardian = new_guardian;
-        
-        msg!("Guardian changed successfully!");
-        Ok(())
+        keys::change_guardian(ctx, new_guardian)
+    }
+    
+    // Let the guardian rotate its own key without the owner signing: `new_key` proves
+    // possession of itself with `proof_sig` over a ChangeGuardian off-chain message, verified
+    // by the ed25519 program instruction right before this one. The rotation only takes
+    // effect with complete_guardian_rotation after the security period, during which the
+    // owner is notified and can veto it
+    pub fn guardian_self_rotate(
+        ctx: Context<GuardianSelfRotate>,
+        new_key: Pubkey,
+        proof_sig: [u8; 64],
+    ) -> Result<()> {
+        keys::guardian_self_rotate(ctx, new_key, proof_sig)
+    }
+    
+    // Install the guardian's self-rotated key once the security period has passed without a
+    // veto (anyone can send this); the rotation lapses if the guardian was replaced meanwhile
+    pub fn complete_guardian_rotation(ctx: Context<CompleteGuardianRotation>) -> Result<()> {
+        keys::complete_guardian_rotation(ctx)
+    }
+    
+    // Veto the guardian's pending self-rotation (owner alone); the request is closed back to
+    // its payer
+    pub fn veto_guardian_rotation(ctx: Context<VetoGuardianRotation>) -> Result<()> {
+        keys::veto_guardian_rotation(ctx)
     }
     
-    // Add or change the guardian backup with both owner and guardian signatures
+    // Request adding, changing or removing the guardian backup with both owner and guardian
+    // signatures; a new backup must also prove possession of its key. Since the backup can
+    // eventually gain guardian powers, the change only takes effect with
+    // complete_guardian_backup_change after the security period, until which either party can
+    // cancel it
     pub fn change_guardian_backup(
         ctx: Context<ChangeGuardianBackup>,
         new_guardian_backup: Option<Pubkey>,
     ) -> Result<()> {
-        let argent_account = &mut ctx.accounts.argent_account;
-        
-        // Verify that both owner and guardian have signed
-        let owner_signed = ctx.accounts.owner.is_signer;
-        let guardian_signed = ctx.accounts.guardian.is_signer;
-        
-        require!(
-            owner_signed && guardian_signed,
-            ErrorCode::NotEnoughApprovals
-        );
-        
-        // Change the guardian backup
-        argent_account.guardian_backup = new_guardian_backup;
-        
-        msg!("Guardian backup changed successfully!");
-        Ok(())
+        keys::change_guardian_backup(ctx, new_guardian_backup)
+    }
+    
+    // Apply a requested guardian backup change once the security period has passed (anyone can
+    // send this); the request lapses if the owner or guardian was replaced meanwhile
+    pub fn complete_guardian_backup_change(
+        ctx: Context<CompleteGuardianBackupChange>,
+    ) -> Result<()> {
+        keys::complete_guardian_backup_change(ctx)
+    }
+    
+    // Cancel a pending guardian backup change (owner or guardian); the request is closed back
+    // to its payer
+    pub fn cancel_guardian_backup_change(ctx: Context<CancelGuardianBackupChange>) -> Result<()> {
+        keys::cancel_guardian_backup_change(ctx)
+    }
+    
+    // Replace owner, guardian and guardian backup together after a suspected key exposure,
+    // with both owner and guardian signatures. Every new key proves possession of itself with
+    // a registration for its role; the swap takes effect with complete_key_rotation after a
+    // single security period, until which either party can cancel it
+    pub fn rotate_all(
+        ctx: Context<RotateAll>,
+        new_owner: Pubkey,
+        new_guardian: Pubkey,
+        new_backup: Option<Pubkey>,
+    ) -> Result<()> {
+        keys::rotate_all(ctx, new_owner, new_guardian, new_backup)
+    }
+    
+    // Swap in the keys of a requested rotation once the security period has passed (anyone can
+    // send this); the request lapses if the owner or guardian was replaced meanwhile
+    pub fn complete_key_rotation(ctx: Context<CompleteKeyRotation>) -> Result<()> {
+        keys::complete_key_rotation(ctx)
+    }
+    
+    // Cancel a pending rotation of all keys (owner or guardian); the request is closed back to
+    // its payer
+    pub fn cancel_key_rotation(ctx: Context<CancelKeyRotation>) -> Result<()> {
+        keys::cancel_key_rotation(ctx)
+    }
+    
+    // Have every escape trigger, completion and cancellation notify `notification_program`
+    // through a CPI carrying the event, for push notifications that don't rely on log scraping
+    // (requires both owner and guardian). Escapes fail while the program does, so it should
+    // be one both parties trust not to be upgraded into rejecting them
+    pub fn set_notification_program(ctx: Context<SetNotificationProgram>) -> Result<()> {
+        escape::set_notification_program(ctx)
+    }
+    
+    // Stop escape notifications (requires both owner and guardian); the rent goes back to the
+    // vault
+    pub fn remove_notification_program(ctx: Context<RemoveNotificationProgram>) -> Result<()> {
+        escape::remove_notification_program(ctx)
+    }
+    
+    // Have every execute start by advancing `nonce_account`, a System nonce account whose
+    // authority is the owner or guardian (requires both owner and guardian). Approvals can
+    // then be signed as durable nonce transactions that outlive the blockhash while the other
+    // signature is gathered, and alternatives pre-signed against the same nonce can't all land.
+    // Only `execute` is bound to the nonce: execute_immediate and the payment paths
+    // (execute_payment, approve_payment, execute_typed_intent) are signed or cranked in one go
+    // and keep running without advancing it
+    pub fn set_proposal_nonce(ctx: Context<SetProposalNonce>) -> Result<()> {
+        transactions::set_proposal_nonce(ctx)
+    }
+    
+    // Stop requiring a nonce advance on execute (requires both owner and guardian); the rent
+    // goes back to the vault and the nonce account itself is left alone
+    pub fn remove_proposal_nonce(ctx: Context<RemoveProposalNonce>) -> Result<()> {
+        transactions::remove_proposal_nonce(ctx)
     }
     
     // Trigger escape mode for guardian (owner can do this alone)
-    pub fn trigger_escape_guardian(ctx: Context<TriggerEscapeGuardian>) -> Result<()> {
-        let argent_account = &mut ctx.accounts.argent_account;
-        let clock = Clock::get()?;
-        
-        // Verify that owner has signed
-        let owner_signed = ctx.accounts.owner.is_signer;
-        
-        require!(
-            owner_signed,
-            ErrorCode::NotEnoughApprovals
-        );
-        
-        // Can override an escape owner in progress
-        if argent_account.escape_type == EscapeType::Owner {
-            msg!("Overriding escape owner in progress");
-        }
-        
-        // Set escape type and timestamp
-        argent_account.escape_type = EscapeType::Guardian;
-        argent_account.escape_initiated_at = clock.unix_timestamp;
-        
-        msg!("Guardian escape triggered!");
-        Ok(())
+    pub fn trigger_escape_guardian<'info>(
+        ctx: Context<'_, '_, '_, 'info, TriggerEscapeGuardian<'info>>,
+    ) -> Result<()> {
+        escape::trigger_escape_guardian(ctx)
+    }
+    
+    // Opt in to promoting the guardian backup when the guardian has not signed anything for
+    // `inactivity_period` seconds (requires both owner and guardian)
+    pub fn set_backup_promotion(
+        ctx: Context<SetBackupPromotion>,
+        inactivity_period: i64,
+    ) -> Result<()> {
+        escape::set_backup_promotion(ctx, inactivity_period)
+    }
+    
+    // Opt out of guardian backup promotion (requires both owner and guardian); the rent goes
+    // back to the vault
+    pub fn remove_backup_promotion(ctx: Context<RemoveBackupPromotion>) -> Result<()> {
+        escape::remove_backup_promotion(ctx)
+    }
+    
+    // Start promoting the guardian backup once the guardian has been inactive for the opted-in
+    // period (owner alone); this is a guardian escape with the backup committed as the new
+    // guardian, so anyone can complete it with complete_escape after the security period
+    pub fn request_backup_promotion<'info>(
+        ctx: Context<'_, '_, '_, 'info, RequestBackupPromotion<'info>>,
+    ) -> Result<()> {
+        escape::request_backup_promotion(ctx)
+    }
+    
+    // Pre-authorize the key an escape may install (requires both owner and guardian, while
+    // both keys are healthy): an escape of `escape_type` to exactly `new_key` completes after
+    // `delay` seconds instead of the full security period. The key proves possession of itself
+    pub fn preauthorize_escape(
+        ctx: Context<PreauthorizeEscape>,
+        escape_type: EscapeType,
+        new_key: Pubkey,
+        delay: i64,
+    ) -> Result<()> {
+        escape::preauthorize_escape(ctx, escape_type, new_key, delay)
+    }
+    
+    // Withdraw every escape pre-authorization (owner or guardian alone, as this only brings
+    // escapes back to the full security period); the rent goes back to the vault
+    pub fn revoke_escape_preauthorization(
+        ctx: Context<RevokeEscapePreauthorization>,
+    ) -> Result<()> {
+        escape::revoke_escape_preauthorization(ctx)
+    }
+    
+    // Opt in to quorum recovery (requires both owner and guardian): the guardian together with
+    // `threshold` of the recovery contacts can replace the owner after `delay` seconds, which
+    // must be shorter than the security period of a guardian-alone owner escape. Every contact
+    // proves possession of its key
+    pub fn set_recovery_policy(
+        ctx: Context<SetRecoveryPolicy>,
+        contacts: Vec<Pubkey>,
+        threshold: u8,
+        delay: i64,
+    ) -> Result<()> {
+        recovery::set_recovery_policy(ctx, contacts, threshold, delay)
+    }
+    
+    // Opt out of quorum recovery (requires both owner and guardian); the rent goes back to
+    // the vault
+    pub fn remove_recovery_policy(ctx: Context<RemoveRecoveryPolicy>) -> Result<()> {
+        recovery::remove_recovery_policy(ctx)
+    }
+    
+    // Start replacing the owner with the guardian's signature and those of at least
+    // `threshold` recovery contacts, passed as signing remaining accounts; it completes after
+    // the policy's delay instead of the full security period. The new owner proves possession
+    // of its key
+    pub fn initiate_quorum_recovery(
+        ctx: Context<InitiateQuorumRecovery>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        recovery::initiate_quorum_recovery(ctx, new_owner)
+    }
+    
+    // Cancel a quorum recovery in progress (owner alone: an owner still able to sign
+    // doesn't need recovering)
+    pub fn cancel_quorum_recovery(ctx: Context<CancelQuorumRecovery>) -> Result<()> {
+        recovery::cancel_quorum_recovery(ctx)
+    }
+    
+    // Install the owner of a quorum recovery once its delay has elapsed; anyone can send this
+    // Any owner escape in progress is superseded
+    pub fn complete_quorum_recovery<'info>(
+        ctx: Context<'_, '_, '_, 'info, CompleteQuorumRecovery<'info>>,
+    ) -> Result<()> {
+        recovery::complete_quorum_recovery(ctx)
     }
     
     // Trigger escape mode for owner (guardian can do this alone)
-    pub fn trigger_escape_owner(ctx: Context<TriggerEscapeOwner>) -> Result<()> {
-        let argent_account = &mut ctx.accounts.argent_account;
-        let clock = Clock::get()?;
-        
-        // Verify that guardian has signed
-        let guardian_signed = ctx.accounts.guardian.is_signer;
-        
-        require!(
-            guardian_signed,
-            ErrorCode::NotEnoughApprovals
-        );
-        
-        // Fail if escape guardian in progress
-        require!(
-            argent_account.escape_type != EscapeType::Guardian,
-            ErrorCode::EscapeGuardianInProgress
-        );
-        
-        // Set escape type and timestamp
-        argent_account.escape_type = EscapeType::Owner;
-        argent_account.escape_initiated_at = clock.unix_timestamp;
-        
-        msg!("Owner escape triggered!");
-        Ok(())
+    pub fn trigger_escape_owner<'info>(
+        ctx: Context<'_, '_, '_, 'info, TriggerEscapeOwner<'info>>,
+    ) -> Result<()> {
+        escape::trigger_escape_owner(ctx)
+    }
+    
+    // Complete escape for guardian (owner can do this alone after security period); the new
+    // guardian proves possession of its key
+    pub fn escape_guardian<'info>(
+        ctx: Context<'_, '_, '_, 'info, EscapeGuardian<'info>>,
+        new_guardian: Pubkey,
+    ) -> Result<()> {
+        escape::escape_guardian(ctx, new_guardian)
+    }
+    
+    // Complete escape for owner (guardian can do this alone after security period); the new
+    // owner proves possession of its key
+    pub fn escape_owner<'info>(
+        ctx: Context<'_, '_, '_, 'info, EscapeOwner<'info>>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        escape::escape_owner(ctx, new_owner)
+    }
+    
+    // Cancel escape (requires both owner and guardian)
+    pub fn cancel_escape<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelEscape<'info>>,
+    ) -> Result<()> {
+        escape::cancel_escape(ctx)
+    }
+    
+    // Commit the key an escape in progress will install (the escaping party only: the owner
+    // for a guardian escape, the guardian for an owner escape), so that once the security
+    // period has elapsed anyone can complete it with `complete_escape`. The committed key
+    // proves possession of itself here, so `complete_escape` needs no signature from it
+    pub fn commit_escape_key(ctx: Context<CommitEscapeKey>, new_key: Pubkey) -> Result<()> {
+        escape::commit_escape_key(ctx, new_key)
+    }
+    
+    // Complete an escape whose replacement key was committed, once the security period has
+    // elapsed; anyone can send this, the escaping party need not pay for it
+    pub fn complete_escape<'info>(
+        ctx: Context<'_, '_, '_, 'info, CompleteEscape<'info>>,
+    ) -> Result<()> {
+        escape::complete_escape(ctx)
+    }
+    
+    // Rehearse an escape against a shadow state, to test recovery procedures: the escaping
+    // party (the owner for a guardian escape, the guardian for an owner escape) starts it with
+    // the key it would escape to, which proves possession of itself as in commit_escape_key,
+    // and complete_escape_rehearsal finishes it once the escape delay has passed. Rehearsal
+    // events mirroring a real escape's are emitted and audited, but no key, escape state or
+    // statistic of the wallet changes
+    pub fn rehearse_escape(
+        ctx: Context<RehearseEscape>,
+        escape_type: EscapeType,
+        new_key: Pubkey,
+    ) -> Result<()> {
+        escape::rehearse_escape(ctx, escape_type, new_key)
+    }
+    
+    // Complete an escape rehearsal once the escape delay has passed (anyone can send this);
+    // the rehearsal is closed back to its payer
+    pub fn complete_escape_rehearsal(ctx: Context<CompleteEscapeRehearsal>) -> Result<()> {
+        escape::complete_escape_rehearsal(ctx)
+    }
+    
+    // Abandon an escape rehearsal (owner or guardian); the rehearsal is closed back to its
+    // payer
+    pub fn cancel_escape_rehearsal(ctx: Context<CancelEscapeRehearsal>) -> Result<()> {
+        escape::cancel_escape_rehearsal(ctx)
+    }
+    
+    // Propose upgrading a program to the code in `buffer`, committing to its hash
+    // (requires both owner and guardian); the upgrade can run once the security period has
+    // passed, and a new proposal for the same program restarts it
+    pub fn propose_upgrade(
+        ctx: Context<ProposeUpgrade>,
+        buffer: Pubkey,
+        code_hash: [u8; 32],
+    ) -> Result<()> {
+        upgrades::propose_upgrade(ctx, buffer, code_hash)
+    }
+    
+    // Upgrade the program implementation (requires both owner and guardian)
+    pub fn upgrade(ctx: Context<Upgrade>) -> Result<()> {
+        upgrades::upgrade(ctx)
+    }
+    
+    // Withdraw a pending upgrade proposal with the owner's signature alone, as long as its
+    // security period is running; once executable only a new dual-signed proposal replaces it
+    pub fn cancel_upgrade(ctx: Context<CancelUpgrade>) -> Result<()> {
+        upgrades::cancel_upgrade(ctx)
+    }
+    
+    // Transfer the upgrade authority of a program held by the wallet, or make the program
+    // immutable with `None` (requires both owner and guardian)
+    pub fn set_upgrade_authority(
+        ctx: Context<SetUpgradeAuthority>,
+        new_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        upgrades::set_upgrade_authority(ctx, new_authority)
+    }
+    
+    // Close a program buffer whose authority is the wallet and refund its rent to the vault
+    // (requires both owner and guardian)
+    pub fn close_buffer(ctx: Context<CloseBuffer>) -> Result<()> {
+        upgrades::close_buffer(ctx)
+    }
+    
+    // Create the vault's associated token account for a mint (anyone can pay for it)
+    pub fn create_associated_token_account(
+        ctx: Context<CreateAssociatedTokenAccount>,
+    ) -> Result<()> {
+        tokens::create_associated_token_account(ctx)
+    }
+
+    // Same as create_associated_token_account but succeeds if the account already exists
+    pub fn create_associated_token_account_idempotent(
+        ctx: Context<CreateAssociatedTokenAccountIdempotent>,
+    ) -> Result<()> {
+        tokens::create_associated_token_account_idempotent(ctx)
+    }
+
+    // Transfer an NFT out of the vault (requires both owner and guardian)
+    // Programmable NFTs go through Token Metadata so their rule sets are enforced
+    pub fn transfer_nft(ctx: Context<TransferNft>) -> Result<()> {
+        nft::transfer_nft(ctx)
+    }
+
+    // Transfer a compressed NFT owned by the vault (requires both owner and guardian)
+    // The Merkle proof nodes are passed as remaining accounts
+    pub fn transfer_compressed_nft<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferCompressedNft<'info>>,
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        index: u32,
+    ) -> Result<()> {
+        nft::transfer_compressed_nft(ctx, root, data_hash, creator_hash, nonce, index)
+    }
+
+    // Transfer a Metaplex Core asset owned by the vault (requires both owner and guardian)
+    pub fn transfer_core_asset(ctx: Context<TransferCoreAsset>) -> Result<()> {
+        nft::transfer_core_asset(ctx)
+    }
+    
+    // Burn a Metaplex Core asset owned by the vault (requires both owner and guardian)
+    pub fn burn_core_asset(ctx: Context<BurnCoreAsset>) -> Result<()> {
+        nft::burn_core_asset(ctx)
+    }
+    
+    // Set the update delegate of a Metaplex Core asset whose update authority is the vault
+    // (requires both owner and guardian)
+    pub fn approve_core_update_delegate(
+        ctx: Context<ApproveCoreUpdateDelegate>,
+        delegate: Pubkey,
+    ) -> Result<()> {
+        nft::approve_core_update_delegate(ctx, delegate)
+    }
+    
+    // Revoke the update delegate of a Metaplex Core asset (requires both owner and guardian)
+    pub fn revoke_core_update_delegate(ctx: Context<RevokeCoreUpdateDelegate>) -> Result<()> {
+        nft::revoke_core_update_delegate(ctx)
+    }
+
+    // Approve a delegate on one of the vault's token accounts (requires both owner and guardian)
+    // The delegation is recorded in the wallet's delegation registry for later review
+    pub fn approve_token_delegate(ctx: Context<ApproveTokenDelegate>, amount: u64) -> Result<()> {
+        tokens::approve_token_delegate(ctx, amount)
+    }
+    
+    // Revoke the delegate of one of the vault's token accounts
+    // Either the owner or the guardian can do this alone
+    pub fn revoke_token_delegate(ctx: Context<RevokeTokenDelegate>) -> Result<()> {
+        tokens::revoke_token_delegate(ctx)
+    }
+    
+    // Revoke the delegates of all vault token accounts passed as remaining accounts
+    // Either the owner or the guardian can do this alone
+    pub fn revoke_all_token_delegates<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RevokeAllTokenDelegates<'info>>,
+    ) -> Result<()> {
+        tokens::revoke_all_token_delegates(ctx)
+    }
+
+    // Wrap SOL from the vault into the vault's wSOL token account (requires both owner and guardian)
+    pub fn wrap_sol(ctx: Context<WrapSol>, amount: u64) -> Result<()> {
+        tokens::wrap_sol(ctx, amount)
+    }
+    
+    // Unwrap all wSOL by closing the vault's wSOL token account back into the vault
+    // (requires both owner and guardian)
+    pub fn unwrap_sol(ctx: Context<UnwrapSol>) -> Result<()> {
+        tokens::unwrap_sol(ctx)
+    }
+
+    // Deposit vault SOL into an SPL stake pool (requires both owner and guardian)
+    // Fails if fewer than minimum_pool_tokens_out pool tokens are minted to the vault
+    pub fn deposit_stake_pool_sol(
+        ctx: Context<DepositStakePoolSol>,
+        lamports: u64,
+        minimum_pool_tokens_out: u64,
+    ) -> Result<()> {
+        staking::deposit_stake_pool_sol(ctx, lamports, minimum_pool_tokens_out)
+    }
+    
+    // Withdraw SOL from an SPL stake pool into the vault (requires both owner and guardian)
+    // Fails if fewer than minimum_lamports_out lamports are received by the vault
+    pub fn withdraw_stake_pool_sol(
+        ctx: Context<WithdrawStakePoolSol>,
+        pool_tokens: u64,
+        minimum_lamports_out: u64,
+    ) -> Result<()> {
+        staking::withdraw_stake_pool_sol(ctx, pool_tokens, minimum_lamports_out)
+    }
+    
+    // Let anyone compound the wallet's native stake between `min_lamports` and `max_lamports`
+    // at a time with compound_rewards (owner alone, as the lamports never leave accounts the
+    // vault controls)
+    pub fn set_stake_compounding(
+        ctx: Context<SetStakeCompounding>,
+        min_lamports: u64,
+        max_lamports: u64,
+    ) -> Result<()> {
+        staking::set_stake_compounding(ctx, min_lamports, max_lamports)
+    }
+    
+    // Stop stake compounding (owner alone); the rent goes back to the vault. A staged
+    // delegation stays delegated until the vault merges or withdraws it
+    pub fn remove_stake_compounding(ctx: Context<RemoveStakeCompounding>) -> Result<()> {
+        staking::remove_stake_compounding(ctx)
+    }
+    
+    // Compound a vault stake account's undelegated lamports (e.g. MEV tips or top-ups) into
+    // its delegation (anyone can crank this). The stake program only merges active stake into
+    // active stake, so this takes two calls an epoch apart: the first moves the lamports, up
+    // to the configured maximum, into a staging stake account derived from the vault and
+    // delegates it to the same validator; once that delegation is active, the next call
+    // merges it back into the stake account
+    pub fn compound_rewards(ctx: Context<CompoundRewards>) -> Result<()> {
+        staking::compound_rewards(ctx)
+    }
+
+    // Mint tokens of a mint whose authority is the vault (requires both owner and guardian)
+    pub fn mint_tokens(ctx: Context<MintTokens>, amount: u64) -> Result<()> {
+        tokens::mint_tokens(ctx, amount)
+    }
+    
+    // Burn tokens held by the vault (requires both owner and guardian)
+    pub fn burn_tokens(ctx: Context<BurnTokens>, amount: u64) -> Result<()> {
+        tokens::burn_tokens(ctx, amount)
+    }
+    
+    // Freeze a token account of a mint whose freeze authority is the vault
+    // (requires both owner and guardian)
+    pub fn freeze_token_account(ctx: Context<FreezeTokenAccount>) -> Result<()> {
+        tokens::freeze_token_account(ctx)
+    }
+    
+    // Thaw a token account frozen by the vault (requires both owner and guardian)
+    pub fn thaw_token_account(ctx: Context<ThawTokenAccount>) -> Result<()> {
+        tokens::thaw_token_account(ctx)
+    }
+
+    // Close empty vault token accounts passed as remaining accounts, returning their rent to the vault
+    // Either the owner or the guardian can do this alone since no assets move
+    pub fn close_token_accounts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseTokenAccounts<'info>>,
+    ) -> Result<()> {
+        tokens::close_token_accounts(ctx)
+    }
+    
+    // Lock the wallet for `duration` seconds (owner alone), e.g. while traveling: until the
+    // lock runs out, or the guardian co-signs unlock_wallet, only escapes, recovery and
+    // cancellations go through. Locking again can extend a running lock but never shorten it
+    pub fn lock_wallet(ctx: Context<LockWallet>, duration: i64) -> Result<()> {
+        wallet::lock_wallet(ctx, duration)
+    }
+    
+    // Lift an owner lock before it runs out (requires both owner and guardian)
+    pub fn unlock_wallet(ctx: Context<UnlockWallet>) -> Result<()> {
+        wallet::unlock_wallet(ctx)
+    }
+    
+    // Start the timelocked path of sweep_all to `destination` (owner alone); the sweep can run
+    // without the guardian once the security period has passed, as the owner could escape the
+    // guardian in that time anyway. The guardian sees the request as a SweepRequested event
+    // and can cancel it until then
+    pub fn request_sweep(ctx: Context<RequestSweep>) -> Result<()> {
+        vault::request_sweep(ctx)
+    }
+    
+    // Withdraw a sweep request (owner or guardian); the rent goes back to the owner
+    pub fn cancel_sweep(ctx: Context<CancelSweep>) -> Result<()> {
+        vault::cancel_sweep(ctx)
+    }
+    
+    // Move every asset out of the vault to `destination`, e.g. when migrating to a new wallet
+    // or responding to a compromise: each token balance given as (vault token account, mint,
+    // destination token account) remaining-account triples, then all SOL. Needs both owner
+    // and guardian, or the owner alone with a sweep request to the same destination older
+    // than the security period
+    pub fn sweep_all<'info>(ctx: Context<'_, '_, 'info, 'info, SweepAll<'info>>) -> Result<()> {
+        vault::sweep_all(ctx)
+    }
+
+    // Replace the list of swap router programs the wallet may swap through
+    // (requires both owner and guardian)
+    pub fn set_swap_routers(ctx: Context<SetSwapRouters>, routers: Vec<Pubkey>) -> Result<()> {
+        trading::set_swap_routers(ctx, routers)
+    }
+    
+    // Swap vault tokens through an allowlisted router (requires both owner and guardian)
+    // The router instruction accounts are passed as remaining accounts and the amount received
+    // by the destination token account is checked against minimum_amount_out. The destination
+    // must still belong to the vault, with no delegate, once the router returns
+    pub fn swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+        data: Vec<u8>,
+        minimum_amount_out: u64,
+    ) -> Result<()> {
+        trading::swap(ctx, data, minimum_amount_out)
+    }
+
+    // Place a standing order to sell `amount` of `sell_mint` for `buy_mint` at `min_price` or
+    // better until `expiry` (requires both owner and guardian). The tokens are escrowed in the
+    // order, which anyone can then fill through one of the wallet's swap routers once the
+    // market reaches the price. `min_price` is in buy-token base units per sell-token base
+    // unit, scaled by LIMIT_PRICE_SCALE
+    pub fn create_limit_order(
+        ctx: Context<CreateLimitOrder>,
+        order_id: u64,
+        sell_mint: Pubkey,
+        buy_mint: Pubkey,
+        amount: u64,
+        min_price: u64,
+        expiry: i64,
+    ) -> Result<()> {
+        trading::create_limit_order(ctx, order_id, sell_mint, buy_mint, amount, min_price, expiry)
+    }
+    
+    // Fill a limit order, in full or in part, through an allowlisted swap router (anyone can
+    // crank this). The router instruction accounts are passed as remaining accounts and the
+    // order signs for its escrow; the fill stands only if what reached the vault for what
+    // left the escrow meets the order's price. Once the escrow is empty the order is closed
+    pub fn fill_limit_order<'info>(
+        ctx: Context<'_, '_, '_, 'info, FillLimitOrder<'info>>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        trading::fill_limit_order(ctx, data)
+    }
+    
+    // Withdraw a limit order (owner or guardian): whatever is left in its escrow goes back to
+    // the vault, with the rent
+    pub fn cancel_limit_order(ctx: Context<CancelLimitOrder>) -> Result<()> {
+        trading::cancel_limit_order(ctx)
+    }
+    
+    // Deposit SOL into the vault (anyone can deposit)
+    pub fn deposit_sol(ctx: Context<DepositSol>, amount: u64, memo: String) -> Result<()> {
+        deposits::deposit_sol(ctx, amount, memo)
+    }
+    
+    // Deposit SPL tokens into the vault's associated token account (anyone can deposit)
+    pub fn deposit_spl(ctx: Context<DepositSpl>, amount: u64, memo: String) -> Result<()> {
+        deposits::deposit_spl(ctx, amount, memo)
+    }
+    
+    // Restrict which senders may fund the wallet (requires both owner and guardian). Deposits
+    // from anyone else still land in the vault but are flagged, and can be sent back with
+    // refund_deposit or refund_spl_deposit; flagged deposits from a sender allowed here stop
+    // being refundable
+    pub fn set_funding_policy(
+        ctx: Context<SetFundingPolicy>,
+        allowed_senders: Vec<Pubkey>,
+    ) -> Result<()> {
+        deposits::set_funding_policy(ctx, allowed_senders)
+    }
+    
+    // Lift the funding restriction (requires both owner and guardian); flagged deposits not yet
+    // refunded stay in the vault
+    pub fn remove_funding_policy(ctx: Context<RemoveFundingPolicy>) -> Result<()> {
+        deposits::remove_funding_policy(ctx)
+    }
+    
+    // Send a flagged SOL deposit back to its sender from the vault (anyone can crank this)
+    pub fn refund_deposit(ctx: Context<RefundDeposit>) -> Result<()> {
+        deposits::refund_deposit(ctx)
+    }
+    
+    // Send a flagged token deposit back to the sender's token account from the vault (anyone
+    // can crank this)
+    pub fn refund_spl_deposit(ctx: Context<RefundSplDeposit>) -> Result<()> {
+        deposits::refund_spl_deposit(ctx)
+    }
+
+    // Quarantine an unsolicited mint so the owner alone can burn balances up to dust_threshold
+    // (requires both owner and guardian)
+    pub fn quarantine_mint(
+        ctx: Context<QuarantineMint>,
+        mint: Pubkey,
+        dust_threshold: u64,
+    ) -> Result<()> {
+        deposits::quarantine_mint(ctx, mint, dust_threshold)
+    }
+    
+    // Remove a mint from quarantine (requires both owner and guardian)
+    pub fn unquarantine_mint(ctx: Context<UnquarantineMint>, mint: Pubkey) -> Result<()> {
+        deposits::unquarantine_mint(ctx, mint)
+    }
+    
+    // Freeze one asset of the vault, the mint's tokens or SOL with `None`, while it is under
+    // suspicion (guardian can do this alone); other assets keep moving normally. Core assets
+    // are frozen by their address and compressed NFTs by their Bubblegum asset id
+    pub fn freeze_asset(ctx: Context<FreezeAsset>, mint: Option<Pubkey>) -> Result<()> {
+        vault::freeze_asset(ctx, mint)
+    }
+    
+    // Unfreeze an asset (requires both owner and guardian); the freeze account is closed to
+    // the vault once nothing is frozen
+    pub fn unfreeze_asset(ctx: Context<UnfreezeAsset>, mint: Option<Pubkey>) -> Result<()> {
+        vault::unfreeze_asset(ctx, mint)
+    }
+    
+    // Burn the whole balance of a vault token account of a quarantined mint (owner can do this alone)
+    pub fn burn_dust(ctx: Context<BurnDust>) -> Result<()> {
+        tokens::burn_dust(ctx)
+    }
+    
+    // Burn and close vault token accounts of quarantined mints (owner can do this alone)
+    // Remaining accounts are (token account, mint) pairs; rent is returned to the vault
+    pub fn close_dust_accounts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseDustAccounts<'info>>,
+    ) -> Result<()> {
+        tokens::close_dust_accounts(ctx)
+    }
+
+    // Close the wallet (requires both owner and guardian)
+    // Token balances passed as (vault token account, mint, destination token account) triples
+    // in the remaining accounts are swept to the destination along with all vault lamports,
+    // then the state and auxiliary accounts are closed
+    pub fn close_account<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseAccount<'info>>,
+    ) -> Result<()> {
+        wallet::close_account(ctx)
+    }
+
+    // Upgrade an account created by an older program release to the current layout
+    // Anyone can pay for the migration; it only fills in defaults for new fields
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        wallet::migrate_account(ctx)
+    }
+    
+    // Put the account header in front of an account a wallet derived before the header
+    // existed, growing it by the header; anyone can pay for it. Wallets go through
+    // migrate_account instead, which also upgrades their body
+    pub fn migrate_derived_account(ctx: Context<MigrateDerivedAccount>) -> Result<()> {
+        wallet::migrate_derived_account(ctx)
+    }
+    
+    // Establish how many program accounts a migrated wallet has open (requires both owner and
+    // guardian): the remaining accounts must be every account derived from the wallet, each
+    // checked to be a program account recording the wallet behind the account header (older
+    // ones go through migrate_derived_account first), except its pending transaction and hot
+    // state, which close_account closes along with it. Until then the wallet can't be closed
+    pub fn count_open_accounts(ctx: Context<CountOpenAccounts>) -> Result<()> {
+        wallet::count_open_accounts(ctx)
+    }
+
+    // Create a named sub-vault with its own balance and spending policy
+    // (requires both owner and guardian)
+    pub fn create_sub_vault(
+        ctx: Context<CreateSubVault>,
+        index: u8,
+        name: String,
+        policy: SubVaultPolicy,
+    ) -> Result<()> {
+        vault::create_sub_vault(ctx, index, name, policy)
+    }
+    
+    // Move SOL from the main vault into a sub-vault (requires both owner and guardian)
+    pub fn fund_sub_vault(ctx: Context<FundSubVault>, _index: u8, amount: u64) -> Result<()> {
+        vault::fund_sub_vault(ctx, _index, amount)
+    }
+    
+    // Withdraw SOL from a sub-vault under its policy: dual-signed sub-vaults always need
+    // the guardian, owner-limited sub-vaults only above their per-withdrawal limit, and
+    // time-locked sub-vaults need the guardian and refuse any withdrawal before they unlock
+    pub fn withdraw_sub_vault(ctx: Context<WithdrawSubVault>, index: u8, amount: u64) -> Result<()> {
+        vault::withdraw_sub_vault(ctx, index, amount)
+    }
+    
+    // Move a sub-vault's whole balance back to the main vault (owner or guardian). A locked
+    // time-locked sub-vault can only be swept within a security period after an escape
+    // completed, so recovering the wallet also recovers its savings
+    pub fn sweep_sub_vault(ctx: Context<SweepSubVault>, index: u8) -> Result<()> {
+        vault::sweep_sub_vault(ctx, index)
+    }
+    
+    // Close an emptied sub-vault's configuration (requires both owner and guardian); the rent
+    // goes back to the vault
+    pub fn close_sub_vault(ctx: Context<CloseSubVault>, _index: u8) -> Result<()> {
+        vault::close_sub_vault(ctx, _index)
+    }
+    
+    // Move SOL between two of the wallet's sub-vaults with the owner's signature alone; no
+    // external party gains custody, so no policy allowance is consumed. The source must not be
+    // locked, and the destination may not let the owner withdraw more alone than the source
+    // does, so funds can't be walked out from under a stricter policy
+    pub fn internal_transfer(
+        ctx: Context<InternalTransfer>,
+        from_index: u8,
+        to_index: u8,
+        amount: u64,
+    ) -> Result<()> {
+        vault::internal_transfer(ctx, from_index, to_index, amount)
+    }
+
+    // Set the wallet display name and metadata URI with both owner and guardian signatures
+    // Empty strings clear the fields
+    pub fn set_metadata(
+        ctx: Context<SetMetadata>,
+        name: String,
+        metadata_uri: String,
+    ) -> Result<()> {
+        wallet::set_metadata(ctx, name, metadata_uri)
+    }
+
+    // Set the opaque tag included in every event of the wallet (requires both owner and
+    // guardian); all zeroes clears it
+    pub fn set_notification_tag(
+        ctx: Context<SetNotificationTag>,
+        notification_tag: [u8; 32],
+    ) -> Result<()> {
+        wallet::set_notification_tag(ctx, notification_tag)
+    }
+    
+    // Withdraw lamports held by the wallet account above its rent-exempt minimum
+    // (requires both owner and guardian); the account itself always stays rent exempt
+    pub fn withdraw_excess_lamports(ctx: Context<WithdrawExcessLamports>) -> Result<()> {
+        wallet::withdraw_excess_lamports(ctx)
+    }
+    
+    // Create a System program account funded by the vault and assigned to `program_owner`
+    // (requires both owner and guardian), e.g. a stake or nonce account the wallet will manage.
+    // With a seed the address is derived from the vault with create_account_with_seed and the
+    // vault signs as its base; without one the new account signs for itself
+    pub fn create_vault_account(
+        ctx: Context<CreateVaultAccount>,
+        lamports: u64,
+        space: u64,
+        program_owner: Pubkey,
+        seed: Option<String>,
+    ) -> Result<()> {
+        transactions::create_vault_account(ctx, lamports, space, program_owner, seed)
+    }
+
+    // Create a wallet from an existing SPL Token multisig the owner and guardian belong to
+    pub fn migrate_from_spl_multisig(
+        ctx: Context<MigrateFromSplMultisig>,
+        index: u64,
+        security_period: Option<i64>,
+    ) -> Result<()> {
+        migrate_from::spl_multisig(ctx, index, security_period)
+    }
+    
+    // Create a wallet from an existing Squads v4 multisig the owner and guardian belong to
+    pub fn migrate_from_squads(
+        ctx: Context<MigrateFromSquads>,
+        index: u64,
+        security_period: Option<i64>,
+    ) -> Result<()> {
+        migrate_from::squads(ctx, index, security_period)
+    }
+
+    // Create the optional history ring buffer of a wallet (anyone can pay for it)
+    // Once it exists, execute records every approved transaction in it
+    pub fn create_history(ctx: Context<CreateHistory>) -> Result<()> {
+        wallet::create_history(ctx)
+    }
+    
+    // Create the wallet's fee ledger, totalling proposal rent and relayer reimbursements of
+    // the instructions it is passed to
+    pub fn create_fee_ledger(ctx: Context<CreateFeeLedger>) -> Result<()> {
+        fees::create_fee_ledger(ctx)
+    }
+    
+    // Report the fee ledger totals of the current period. Returned to the caller so it can
+    // be read by simulation; once FEE_REPORT_PERIOD has passed the report is also emitted
+    // and a new period starts (anyone can close a period)
+    pub fn report_fees(ctx: Context<ReportFees>) -> Result<FeeReport> {
+        fees::report_fees(ctx)
+    }
+
+    // Rebate network fees that the owner, guardian and registered relayers spend on wallet
+    // operations from the vault, crediting at most `max_fee_per_operation` lamports per
+    // recorded transaction and paying out up to `monthly_cap` lamports in total per
+    // FEE_REPORT_PERIOD (requires both owner and guardian)
+    pub fn set_fee_rebate_policy(
+        ctx: Context<SetFeeRebatePolicy>,
+        max_fee_per_operation: u64,
+        monthly_cap: u64,
+    ) -> Result<()> {
+        fees::set_fee_rebate_policy(ctx, max_fee_per_operation, monthly_cap)
+    }
+    
+    // Credit the signer with the network fee of this transaction towards its rebate. The fee
+    // is worked out from the transaction itself: its signatures, including precompile-verified
+    // ones, and the priority fee of an explicit compute unit limit and price, taking the signer
+    // as the fee payer. Only counts once per transaction, sent at the top level next to an
+    // instruction of this program on the same wallet that the signer signs, and not for
+    // relayed transactions the vault already reimbursed
+    pub fn record_operation_fee(ctx: Context<RecordOperationFee>) -> Result<()> {
+        fees::record_operation_fee(ctx)
+    }
+    
+    // Reimburse the signer from the vault for the fees recorded and not yet rebated, as far
+    // as this period's cap allows; whatever is left can be claimed in a later period
+    pub fn claim_fee_rebate(ctx: Context<ClaimFeeRebate>) -> Result<()> {
+        fees::claim_fee_rebate(ctx)
+    }
+    
+    // Refresh the owner's or guardian's last activity without doing anything else, so an owner
+    // who rarely moves funds keeps the inactivity-based paths (guardian backup promotion,
+    // inheritance claims) from opening. The wallet state is left alone, so off-chain messages
+    // signed for the current state stay valid
+    pub fn heartbeat(ctx: Context<Heartbeat>) -> Result<()> {
+        wallet::heartbeat(ctx)
+    }
+    
+    // Report when the owner and guardian last signed an instruction for the wallet
+    // Returned to the caller and emitted, so inactivity monitors can poll it by simulation
+    pub fn get_last_activity(ctx: Context<GetLastActivity>) -> Result<LastActivity> {
+        wallet::get_last_activity(ctx)
+    }
+    
+    // Report how often the wallet's owner and guardian have been escaped, and when, so risk
+    // dashboards can flag wallets under repeated recovery pressure by simulating it
+    pub fn get_escape_stats(ctx: Context<GetEscapeStats>) -> Result<EscapeStats> {
+        escape::get_escape_stats(ctx)
+    }
+    
+    // Designate the keys allowed to read the wallet's private details through
+    // get_wallet_details (requires both owner and guardian); an empty list leaves only
+    // the owner and guardian
+    pub fn set_viewers(ctx: Context<SetViewers>, viewers: Vec<Pubkey>) -> Result<()> {
+        wallet::set_viewers(ctx, viewers)
+    }
+    
+    // Return the wallet's labels and routing metadata to a designated viewer or one of the
+    // wallet's signers signing the (simulated) call. Unlike the public views it is never emitted;
+    // it gates this program's views only, as account data itself stays readable on-chain
+    pub fn get_wallet_details(ctx: Context<GetWalletDetails>) -> Result<WalletDetails> {
+        wallet::get_wallet_details(ctx)
+    }
+
+    // Approve a transaction submitted by a relayer who pays the network fee
+    // Owner and guardian sign the relayed transaction; the vault reimburses the relayer up to
+    // the wallet's max_fee_reimbursement
+    pub fn execute_from_outside(
+        ctx: Context<ExecuteFromOutside>,
+        data: Vec<u8>,
+        fee: u64,
+    ) -> Result<()> {
+        outside::execute_from_outside(ctx, data, fee)
+    }
+    
+    // Approve a relayed transaction with the guardian's approval payload, signed air-gapped
+    // (e.g. from a QR code) and verified by an ed25519 program instruction right before this
+    // one, instead of the guardian signing the relayed transaction; the payload also caps the
+    // relayer's reimbursement
+    pub fn execute_from_outside_with_guardian_payload(
+        ctx: Context<ExecuteFromOutsideWithGuardianPayload>,
+        data: Vec<u8>,
+        fee: u64,
+        guardian_signature: [u8; 64],
+    ) -> Result<()> {
+        outside::execute_from_outside_with_guardian_payload(ctx, data, fee, guardian_signature)
+    }
+    
+    // Set the most a relayer can be reimbursed per relayed transaction, in lamports and
+    // optionally in an accepted token (requires both owner and guardian)
+    pub fn set_paymaster_config(
+        ctx: Context<SetPaymasterConfig>,
+        max_fee_reimbursement: u64,
+        fee_mint: Option<Pubkey>,
+        max_token_fee: u64,
+    ) -> Result<()> {
+        outside::set_paymaster_config(ctx, max_fee_reimbursement, fee_mint, max_token_fee)
+    }
+    
+    // Approve a relayed transaction and pay the relayer in the wallet's accepted fee token
+    // from the vault's token account, for wallets that hold no SOL
+    pub fn execute_from_outside_with_token_fee(
+        ctx: Context<ExecuteFromOutsideWithTokenFee>,
+        data: Vec<u8>,
+        fee: u64,
+    ) -> Result<()> {
+        outside::execute_from_outside_with_token_fee(ctx, data, fee)
+    }
+    
+    // Replace the relayers allowed to be reimbursed for outside execution, with their daily
+    // quotas (requires both owner and guardian); spending counters restart
+    pub fn set_relayers(ctx: Context<SetRelayers>, relayers: Vec<RelayerConfig>) -> Result<()> {
+        outside::set_relayers(ctx, relayers)
+    }
+    
+    // Request the compute unit limit and priority fee the pending transaction should be
+    // executed with, or clear it (requires both owner and guardian)
+    pub fn set_compute_budget(
+        ctx: Context<SetComputeBudget>,
+        compute_budget: Option<ComputeBudgetRequest>,
+    ) -> Result<()> {
+        transactions::set_compute_budget(ctx, compute_budget)
+    }
+    
+    // Give up on the pending transaction when its execution keeps failing, e.g. on an
+    // always-failing transfer hook after it partly changed external state (requires both
+    // owner and guardian): it is cleared as failed, with `reason` recorded in the event and
+    // the history, so the wallet can approve the next one or be closed
+    pub fn force_abandon_transaction(
+        ctx: Context<ForceAbandonTransaction>,
+        reason: String,
+    ) -> Result<()> {
+        transactions::force_abandon_transaction(ctx, reason)
+    }
+    
+    // Create the list of programs the wallet manages as upgrade authority
+    pub fn create_managed_programs(ctx: Context<CreateManagedPrograms>) -> Result<()> {
+        upgrades::create_managed_programs(ctx)
+    }
+    
+    // Register a program the wallet is upgrade authority of, so it can be upgraded and its
+    // authority transferred (requires both owner and guardian); the list grows as needed
+    pub fn register_program(ctx: Context<RegisterProgram>) -> Result<()> {
+        upgrades::register_program(ctx)
+    }
+    
+    // Stop managing a program (requires both owner and guardian); its authority is unchanged
+    pub fn unregister_program(ctx: Context<UnregisterProgram>, program: Pubkey) -> Result<()> {
+        upgrades::unregister_program(ctx, program)
+    }
+    
+    // Choose whether approved messages also need the guardian (requires both owner and guardian)
+    pub fn set_message_policy(
+        ctx: Context<SetMessagePolicy>,
+        requires_guardian: bool,
+    ) -> Result<()> {
+        signatures::set_message_policy(ctx, requires_guardian)
+    }
+    
+    // Approve an off-chain message (e.g. a sign-in message) by its hash so dApps can check it
+    // with is_valid_signature; the owner signs, and the guardian too when the policy requires it
+    pub fn approve_message(ctx: Context<ApproveMessage>, message_hash: [u8; 32]) -> Result<()> {
+        signatures::approve_message(ctx, message_hash)
+    }
+    
+    // Withdraw a message approval (requires the owner); the rent goes back to the vault
+    pub fn revoke_message(ctx: Context<RevokeMessage>, _message_hash: [u8; 32]) -> Result<()> {
+        signatures::revoke_message(ctx, _message_hash)
+    }
+    
+    // Whether the wallet currently vouches for a message: it must have been approved by the
+    // current owner, and by the current guardian if the policy requires it, so rotating a key
+    // invalidates earlier approvals
+    pub fn is_valid_signature(
+        ctx: Context<IsValidSignature>,
+        _message_hash: [u8; 32],
+    ) -> Result<bool> {
+        signatures::is_valid_signature(ctx, _message_hash)
+    }
+    
+    // Cap what payments (execute_payment, approve_payment and execute_typed_intent) can take
+    // out of the vault each day across assets (requires both owner and guardian): each payment
+    // is valued in USD (6 decimals) at execution time and counted against one shared
+    // `daily_limit_usd`, so a drain can't be split across assets. Assets without a price feed
+    // are valued at $1 a unit (stablecoins); while a budget exists, payments in assets it
+    // doesn't list are refused. Other vault outflows, such as immediate execution, swaps,
+    // sweeps, streams, sub-vaults and the guardian fee, are not counted
+    pub fn set_payment_budget(
+        ctx: Context<SetPaymentBudget>,
+        daily_limit_usd: u64,
+        assets: Vec<BudgetAsset>,
+    ) -> Result<()> {
+        payments::set_payment_budget(ctx, daily_limit_usd, assets)
+    }
+    
+    // Drop the payment budget (requires both owner and guardian); the rent goes back to the
+    // vault
+    pub fn remove_payment_budget(ctx: Context<RemovePaymentBudget>) -> Result<()> {
+        payments::remove_payment_budget(ctx)
+    }
+    
+    // Pay a Solana Pay transfer request from the vault (requires both owner and guardian)
+    // The reference key is appended to the transfer instruction itself, so merchants find
+    // and validate the payment exactly as for a regular wallet; `mint` selects an SPL token
+    // payment, `None` pays in SOL
+    pub fn execute_payment(
+        ctx: Context<ExecutePayment>,
+        reference: Pubkey,
+        amount: u64,
+        mint: Option<Pubkey>,
+    ) -> Result<()> {
+        payments::execute_payment(ctx, reference, amount, mint)
+    }
+    
+    // Set the categories payment proposals are labelled with for treasury reporting, e.g.
+    // "payroll", "vendor", "investment" (requires both owner and guardian); with `required`,
+    // proposals without one of them are rejected
+    pub fn set_label_policy(
+        ctx: Context<SetLabelPolicy>,
+        required: bool,
+        categories: Vec<String>,
+    ) -> Result<()> {
+        payments::set_label_policy(ctx, required, categories)
+    }
+    
+    // Propose a Solana Pay payment from the vault with the owner's signature alone; the
+    // proposal records the balance changes it will make so the guardian's wallet can show
+    // them from chain data before approving. `category` labels it for treasury reporting
+    // and must satisfy the wallet's label policy, if any
+    pub fn propose_payment(
+        ctx: Context<ProposePayment>,
+        reference: Pubkey,
+        amount: u64,
+        mint: Option<Pubkey>,
+        category: String,
+    ) -> Result<()> {
+        payments::propose_payment(ctx, reference, amount, mint, category)
+    }
+    
+    // Approve and send a proposed payment with the guardian's signature; the proposal is
+    // closed back to its payer
+    pub fn approve_payment(ctx: Context<ApprovePayment>) -> Result<()> {
+        payments::approve_payment(ctx)
+    }
+    
+    // Propose a transfer from the vault as a typed intent (owner alone), for guardians on
+    // hardware wallets that can't parse transactions to this program: the canonical summary
+    // of the transfer is rendered and stored on-chain, and the guardian approves it by
+    // signing the summary's SHA-256 once its device has shown it
+    pub fn propose_typed_intent(
+        ctx: Context<ProposeTypedIntent>,
+        reference: Pubkey,
+        amount: u64,
+        mint: Option<Pubkey>,
+    ) -> Result<()> {
+        payments::propose_typed_intent(ctx, reference, amount, mint)
+    }
+    
+    // Execute a typed intent with the guardian's signature over its summary hash, verified by
+    // the ed25519 program instruction right before this one; anyone can submit it, and the
+    // intent is closed back to its payer
+    pub fn execute_typed_intent(
+        ctx: Context<ExecuteTypedIntent>,
+        guardian_signature: [u8; 64],
+    ) -> Result<()> {
+        payments::execute_typed_intent(ctx, guardian_signature)
+    }
+    
+    // Withdraw a typed intent before the guardian's signature is used (owner alone)
+    pub fn cancel_typed_intent(ctx: Context<CancelTypedIntent>, _reference: Pubkey) -> Result<()> {
+        payments::cancel_typed_intent(ctx, _reference)
+    }
+    
+    // Withdraw a proposed payment before the guardian approves it (owner alone)
+    pub fn cancel_payment(ctx: Context<CancelPayment>, _reference: Pubkey) -> Result<()> {
+        payments::cancel_payment(ctx, _reference)
+    }
+    
+    // Amend a proposed payment in one step (owner alone): the proposal under `old_reference`
+    // is closed and a new one under `reference` takes its place with the same category and
+    // rent payer, so the guardian can never approve both the original and the amendment
+    pub fn replace_payment(
+        ctx: Context<ReplacePayment>,
+        old_reference: Pubkey,
+        reference: Pubkey,
+        amount: u64,
+        mint: Option<Pubkey>,
+    ) -> Result<()> {
+        payments::replace_payment(ctx, old_reference, reference, amount, mint)
+    }
+    
+    // Pay the current guardian `amount` of SOL or `mint` from the vault every `interval`
+    // seconds, starting now (requires both owner and guardian); replacing the fee forfeits
+    // unclaimed periods, and the fee lapses once the guardian is changed
+    pub fn set_guardian_fee(
+        ctx: Context<SetGuardianFee>,
+        amount: u64,
+        mint: Option<Pubkey>,
+        interval: i64,
+    ) -> Result<()> {
+        fees::set_guardian_fee(ctx, amount, mint, interval)
+    }
+    
+    // Stop paying the guardian fee (requires both owner and guardian); the rent goes back to
+    // the vault
+    pub fn remove_guardian_fee(ctx: Context<RemoveGuardianFee>) -> Result<()> {
+        fees::remove_guardian_fee(ctx)
+    }
+    
+    // Claim the guardian fee for every full interval elapsed since the last claim (guardian
+    // alone); it is paid from the vault like a Solana Pay payment with the fee account as
+    // the reference
+    pub fn claim_guardian_fee(ctx: Context<ClaimGuardianFee>) -> Result<()> {
+        fees::claim_guardian_fee(ctx)
+    }
+    
+    // Name the beneficiaries who inherit the wallet once neither owner nor guardian has acted
+    // for `inactivity_period` seconds, after a further `challenge_period` in which the owner
+    // can cancel the claim (requires both owner and guardian); shares are in basis points and
+    // must add up to 10,000, and `mints` lists the tokens distributed besides SOL. Replacing
+    // the plan drops any claim in progress
+    pub fn set_inheritance_plan(
+        ctx: Context<SetInheritancePlan>,
+        beneficiaries: Vec<BeneficiaryShare>,
+        mints: Vec<Pubkey>,
+        inactivity_period: i64,
+        challenge_period: i64,
+    ) -> Result<()> {
+        inheritance::set_inheritance_plan(
+            ctx,
+            beneficiaries,
+            mints,
+            inactivity_period,
+            challenge_period,
+        )
+    }
+    
+    // Remove the inheritance plan (requires both owner and guardian); the rent goes back to
+    // the vault
+    pub fn remove_inheritance_plan(ctx: Context<RemoveInheritancePlan>) -> Result<()> {
+        inheritance::remove_inheritance_plan(ctx)
+    }
+    
+    // Start an inheritance claim (any beneficiary alone) once neither owner nor guardian has
+    // acted for the plan's inactivity period; this opens the challenge period
+    pub fn initiate_inheritance_claim(ctx: Context<InitiateInheritanceClaim>) -> Result<()> {
+        inheritance::initiate_inheritance_claim(ctx)
+    }
+    
+    // Cancel an inheritance claim in progress (owner can do this alone)
+    pub fn cancel_inheritance_claim(ctx: Context<CancelInheritanceClaim>) -> Result<()> {
+        inheritance::cancel_inheritance_claim(ctx)
+    }
+    
+    // Complete an inheritance claim by making the sole beneficiary of the plan the owner
+    // (beneficiary alone, after the challenge period); plans with several beneficiaries are
+    // settled with distribute_inheritance instead
+    pub fn claim_inheritance(ctx: Context<ClaimInheritance>) -> Result<()> {
+        inheritance::claim_inheritance(ctx)
+    }
+    
+    // Distribute the vault's SOL (`mint` of None) or its balance of a listed token among the
+    // beneficiaries by their shares (any beneficiary, after the challenge period); called once
+    // per asset. The receiving accounts are passed as remaining accounts in plan order: the
+    // beneficiaries themselves for SOL, their token accounts for the mint otherwise
+    pub fn distribute_inheritance<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DistributeInheritance<'info>>,
+        mint: Option<Pubkey>,
+    ) -> Result<()> {
+        inheritance::distribute_inheritance(ctx, mint)
+    }
+    
+    // Stream `rate_per_second` of SOL (`mint` of None) or of a token from the vault to
+    // `recipient` until `end_time` (requires both owner and guardian); the whole amount is
+    // escrowed in the stream up front and accrues to the recipient second by second
+    pub fn create_stream(
+        ctx: Context<CreateStream>,
+        recipient: Pubkey,
+        rate_per_second: u64,
+        mint: Option<Pubkey>,
+        end_time: i64,
+    ) -> Result<()> {
+        streams::create_stream(ctx, recipient, rate_per_second, mint, end_time)
+    }
+    
+    // Pay the recipient what the stream has accrued so far (anyone can crank this, the
+    // funds only ever go to the recipient)
+    pub fn withdraw_stream(ctx: Context<WithdrawStream>) -> Result<()> {
+        streams::withdraw_stream(ctx)
+    }
+    
+    // Stop a stream (requires both owner and guardian): the recipient is paid what has accrued
+    // and the unstreamed remainder, with the stream's rent, goes back to the vault
+    pub fn cancel_stream(ctx: Context<CancelStream>) -> Result<()> {
+        streams::cancel_stream(ctx)
+    }
+    
+    // Vest `amount` of the mint from the vault to `beneficiary` (requires both owner and
+    // guardian): nothing unlocks before `cliff_period` has passed, then the amount unlocks
+    // linearly until `duration` after creation. The tokens are escrowed up front
+    pub fn create_vesting(
+        ctx: Context<CreateVesting>,
+        beneficiary: Pubkey,
+        amount: u64,
+        cliff_period: i64,
+        duration: i64,
+    ) -> Result<()> {
+        streams::create_vesting(ctx, beneficiary, amount, cliff_period, duration)
+    }
+    
+    // Pay the beneficiary everything unlocked so far and not yet claimed
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        streams::claim_vested(ctx)
+    }
+    
+    // Limit how long a posted guardian approval counts, whatever its own expiry, so intents
+    // left unexecuted must be re-confirmed (requires both owner and guardian); 0 lifts the limit
+    pub fn set_approval_ttl(ctx: Context<SetApprovalTtl>, approval_ttl: i64) -> Result<()> {
+        wallet::set_approval_ttl(ctx, approval_ttl)
+    }
+    
+    // Approve, from the guardian's own transaction, the transaction whose data hashes to
+    // `data_hash` until `expires_at`; the owner can then execute it without the guardian
+    // signing live, as long as the wallet's approval TTL hasn't run out
+    pub fn post_guardian_approval(
+        ctx: Context<PostGuardianApproval>,
+        data_hash: [u8; 32],
+        expires_at: i64,
+    ) -> Result<()> {
+        transactions::post_guardian_approval(ctx, data_hash, expires_at)
+    }
+    
+    // Post a guardian approval the guardian signed off-chain rather than as a transaction
+    // signer, e.g. a threshold-signature (MPC) guardian service: the ed25519 program
+    // instruction right before this one must verify `guardian_signature` over an
+    // `ApproveTransaction` message for `data_hash` and `expires_at` on the wallet in its
+    // current state. Anyone can relay it and pay the approval rent
+    pub fn post_signed_guardian_approval(
+        ctx: Context<PostSignedGuardianApproval>,
+        data_hash: [u8; 32],
+        expires_at: i64,
+        guardian_signature: [u8; 64],
+    ) -> Result<()> {
+        transactions::post_signed_guardian_approval(ctx, data_hash, expires_at, guardian_signature)
+    }
+    
+    // Record that the guardian's key shares were redistributed while its public key stayed
+    // the same (an MPC resharing), from a `GuardianReshare` message the guardian signed
+    // off-chain and the ed25519 program instruction right before this one verifies. Moving
+    // the wallet to a new state voids every off-chain message signed before the resharing
+    pub fn record_guardian_reshare(
+        ctx: Context<RecordGuardianReshare>,
+        guardian_signature: [u8; 64],
+    ) -> Result<()> {
+        keys::record_guardian_reshare(ctx, guardian_signature)
+    }
+    
+    // Withdraw an unused guardian approval, refunding its rent to the guardian
+    pub fn revoke_guardian_approval(
+        ctx: Context<RevokeGuardianApproval>,
+        _data_hash: [u8; 32],
+    ) -> Result<()> {
+        transactions::revoke_guardian_approval(ctx, _data_hash)
+    }
+    
+    // List a guardian service in the program-wide registry (signed by the guardian key); it
+    // only counts as vetted once the program upgrade authority approves it
+    pub fn register_guardian_service(
+        ctx: Context<RegisterGuardianService>,
+        name: String,
+        metadata_uri: String,
+    ) -> Result<()> {
+        keys::register_guardian_service(ctx, name, metadata_uri)
+    }
+    
+    // Vet or revoke a registered guardian service (program upgrade authority only)
+    pub fn approve_guardian_service(
+        ctx: Context<ApproveGuardianService>,
+        approved: bool,
+    ) -> Result<()> {
+        keys::approve_guardian_service(ctx, approved)
+    }
+    
+    // Remove a guardian service from the registry (signed by the guardian key); wallets
+    // already guarded by it keep it as their guardian
+    pub fn unregister_guardian_service(ctx: Context<UnregisterGuardianService>) -> Result<()> {
+        keys::unregister_guardian_service(ctx)
+    }
+}
+
+// Check, through the instructions sysvar, that the instruction right before the current one
+// is an ed25519 program verification of exactly `signature` by `pubkey`, and return the message
+// it verified
+fn verify_ed25519_instruction(
+    sysvar_instructions: &AccountInfo,
+    pubkey: &Pubkey,
+    signature: &[u8; 64],
+) -> Result<Vec<u8>> {
+    use anchor_lang::solana_program::sysvar::instructions::{
+        load_current_index_checked, load_instruction_at_checked,
+    };
+    
+    let current_index = load_current_index_checked(sysvar_instructions)? as usize;
+    require!(current_index > 0, ErrorCode::MissingSignatureVerification);
+    let verification = load_instruction_at_checked(current_index - 1, sysvar_instructions)?;
+    let (signer, verified_signature, message) =
+        parse_ed25519_verification(&verification).ok_or(ErrorCode::MissingSignatureVerification)?;
+    require!(
+        signer == *pubkey && verified_signature == *signature,
+        ErrorCode::InvalidSignature
+    );
+    Ok(message)
+}
+
+// Require proof of possession from a key about to be installed on the wallet: an ed25519
+// program instruction earlier in the transaction must verify `key`'s signature over an
+// unexpired `OffchainMessage` for `action`, whose payload is the key itself, on the wallet in
+// its current state. A typo'd key, or one whose secret nobody at hand holds, can't sign it
+fn require_key_registration(
+    sysvar_instructions: &AccountInfo,
+    wallet: &Pubkey,
+    state_version: u64,
+    action: OffchainAction,
+    key: &Pubkey,
+) -> Result<()> {
+    use anchor_lang::solana_program::sysvar::instructions::{
+        load_current_index_checked, load_instruction_at_checked,
+    };
+    
+    let current_index = load_current_index_checked(sysvar_instructions)? as usize;
+    for index in 0..current_index {
+        let instruction = load_instruction_at_checked(index, sysvar_instructions)?;
+        let Some((signer, _, message)) = parse_ed25519_verification(&instruction) else {
+            continue;
+        };
+        if signer == *key {
+            return OffchainMessage::decode(&message)?.require_authorizes(
+                wallet,
+                state_version,
+                action,
+                &hash(key.as_ref()).to_bytes(),
+                Clock::get()?.slot,
+            );
+        }
+    }
+    err!(ErrorCode::MissingSignatureVerification)
+}
+
+// Signer, signature and message of an ed25519 program instruction verifying a single
+// signature. Every offset must point into that instruction itself, so a verification of
+// other data elsewhere in the transaction cannot be passed off as this one; anything else
+// yields `None`
+fn parse_ed25519_verification(
+    instruction: &anchor_lang::solana_program::instruction::Instruction,
+) -> Option<(Pubkey, [u8; 64], Vec<u8>)> {
+    use anchor_lang::solana_program::ed25519_program;
+    
+    if instruction.program_id != ed25519_program::ID {
+        return None;
+    }
+    
+    // A single signature: count and padding, then its seven u16 offsets
+    let data = &instruction.data;
+    if data.len() < 16 || data[0] != 1 {
+        return None;
+    }
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let [
+        signature_offset,
+        signature_ix,
+        pubkey_offset,
+        pubkey_ix,
+        message_offset,
+        message_size,
+        message_ix,
+    ] = [2, 4, 6, 8, 10, 12, 14].map(read_u16);
+    if [signature_ix, pubkey_ix, message_ix].iter().any(|&ix| ix != u16::MAX) {
+        return None;
+    }
+    
+    let slice = |offset: u16, len: usize| data.get(offset as usize..offset as usize + len);
+    let pubkey = Pubkey::try_from(slice(pubkey_offset, 32)?).ok()?;
+    let signature = slice(signature_offset, 64)?.try_into().ok()?;
+    let message = slice(message_offset, message_size as usize)?.to_vec();
+    Some((pubkey, signature, message))
+}
+
+// Seed a stream address takes from its mint; SOL streams have none
+pub fn stream_mint_seed(mint: &Option<Pubkey>) -> &[u8] {
+    mint.as_ref().map_or(&[], |mint| mint.as_ref())
+}
+
+// Move `amount` out of a stream's escrow to its recipient: lamports straight from the stream
+// account, tokens from its escrow token account to the recipient's
+#[allow(clippy::too_many_arguments)]
+fn pay_stream<'info>(
+    argent_account: &Pubkey,
+    stream: &Account<'info, Stream>,
+    recipient: &AccountInfo<'info>,
+    stream_mint: Option<&InterfaceAccount<'info, Mint>>,
+    escrow_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    recipient_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    token_program: Option<&Interface<'info, TokenInterface>>,
+    amount: u64,
+) -> Result<()> {
+    let Some(mint_key) = stream.mint else {
+        // The stream account is owned by this program, so its lamports can be moved directly
+        stream.to_account_info().sub_lamports(amount)?;
+        recipient.add_lamports(amount)?;
+        return Ok(());
+    };
+    
+    let (
+        Some(stream_mint),
+        Some(escrow_token_account),
+        Some(recipient_token_account),
+        Some(token_program),
+    ) = (stream_mint, escrow_token_account, recipient_token_account, token_program)
+    else {
+        return err!(ErrorCode::MissingStreamAccounts);
+    };
+    require_keys_eq!(stream_mint.key(), mint_key, ErrorCode::InvalidTokenAccount);
+    require_keys_eq!(
+        recipient_token_account.owner,
+        stream.recipient,
+        ErrorCode::InvalidTokenAccount
+    );
+    
+    let stream_seeds: &[&[u8]] = &[
+        b"stream",
+        argent_account.as_ref(),
+        stream.recipient.as_ref(),
+        mint_key.as_ref(),
+        &[stream.bump],
+    ];
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token_interface::TransferChecked {
+                from: escrow_token_account.to_account_info(),
+                mint: stream_mint.to_account_info(),
+                to: recipient_token_account.to_account_info(),
+                authority: stream.to_account_info(),
+            },
+            &[stream_seeds],
+        ),
+        amount,
+        stream_mint.decimals,
+    )
+}
+
+// Balance changes of a payment of `amount` of SOL, or of `mint` (whose account gives the
+// decimals), from the vault to `recipient`
+fn payment_balance_changes(
+    vault: Pubkey,
+    recipient: Pubkey,
+    mint: Option<Pubkey>,
+    mint_account: Option<&InterfaceAccount<Mint>>,
+    amount: u64,
+) -> Result<Vec<BalanceChange>> {
+    let delta = i64::try_from(amount)
+        .ok()
+        .filter(|delta| *delta > 0)
+        .ok_or(ErrorCode::InvalidPaymentAmount)?;
+    let decimals = match mint {
+        None => 9,
+        Some(mint_key) => {
+            let Some(mint_account) = mint_account else {
+                return err!(ErrorCode::MissingPaymentAccounts);
+            };
+            require_keys_eq!(mint_account.key(), mint_key, ErrorCode::InvalidTokenAccount);
+            mint_account.decimals
+        }
+    };
+    Ok(vec![
+        BalanceChange {
+            account: vault,
+            mint,
+            decimals,
+            delta: -delta,
+        },
+        BalanceChange {
+            account: recipient,
+            mint,
+            decimals,
+            delta,
+        },
+    ])
+}
+
+// Pay `amount` of SOL, or of `mint` between associated token accounts, from the vault to a
+// Solana Pay recipient; the reference key is appended to the transfer instruction so the
+// merchant finds the payment
+#[allow(clippy::too_many_arguments)]
+fn pay_from_vault<'info>(
+    vault: &SystemAccount<'info>,
+    vault_seeds: &[&[u8]],
+    recipient: &AccountInfo<'info>,
+    reference: &AccountInfo<'info>,
+    mint: Option<Pubkey>,
+    mint_account: Option<&InterfaceAccount<'info, Mint>>,
+    vault_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    recipient_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    token_program: Option<&Interface<'info, TokenInterface>>,
+    system_program: &Program<'info, System>,
+    amount: u64,
+) -> Result<()> {
+    use anchor_lang::solana_program::{instruction::AccountMeta, program::invoke_signed};
+    use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+    use anchor_spl::token_2022::spl_token_2022;
+    
+    let vault_key = vault.key();
+    let recipient_key = recipient.key();
+    
+    let Some(mint_key) = mint else {
+        let mut transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &vault_key,
+            &recipient_key,
+            amount,
+        );
+        transfer_ix.accounts.push(AccountMeta::new_readonly(reference.key(), false));
+        invoke_signed(
+            &transfer_ix,
+            &[
+                vault.to_account_info(),
+                recipient.clone(),
+                reference.clone(),
+                system_program.to_account_info(),
+            ],
+            &[vault_seeds],
+        )?;
+        return Ok(());
+    };
+    
+    let (
+        Some(mint),
+        Some(vault_token_account),
+        Some(recipient_token_account),
+        Some(token_program),
+    ) = (mint_account, vault_token_account, recipient_token_account, token_program)
+    else {
+        return err!(ErrorCode::MissingPaymentAccounts);
+    };
+    let token_program_key = token_program.key();
+    require_keys_eq!(mint.key(), mint_key, ErrorCode::InvalidTokenAccount);
+    require_keys_eq!(
+        vault_token_account.key(),
+        get_associated_token_address_with_program_id(&vault_key, &mint_key, &token_program_key),
+        ErrorCode::InvalidTokenAccount
+    );
+    // Solana Pay pays into the recipient's associated token account
+    require_keys_eq!(
+        recipient_token_account.key(),
+        get_associated_token_address_with_program_id(&recipient_key, &mint_key, &token_program_key),
+        ErrorCode::InvalidTokenAccount
+    );
+    
+    let mut transfer_ix = spl_token_2022::instruction::transfer_checked(
+        &token_program_key,
+        &vault_token_account.key(),
+        &mint_key,
+        &recipient_token_account.key(),
+        &vault_key,
+        &[],
+        amount,
+        mint.decimals,
+    )?;
+    transfer_ix.accounts.push(AccountMeta::new_readonly(reference.key(), false));
+    invoke_signed(
+        &transfer_ix,
+        &[
+            vault_token_account.to_account_info(),
+            mint.to_account_info(),
+            recipient_token_account.to_account_info(),
+            vault.to_account_info(),
+            reference.clone(),
+            token_program.to_account_info(),
+        ],
+        &[vault_seeds],
+    )?;
+    Ok(())
+}
+
+// Reject moving `mint`, or SOL for `None`, out of the vault while the guardian has it frozen;
+// `asset_freeze` is the wallet's freeze PDA, which has no data when nothing is frozen
+fn require_not_frozen(asset_freeze: &AccountInfo, mint: &Option<Pubkey>) -> Result<()> {
+    if asset_freeze.data_is_empty() {
+        return Ok(());
+    }
+    let freeze = AssetFreeze::try_deserialize(&mut &asset_freeze.try_borrow_data()?[..])?;
+    require!(!freeze.is_frozen(mint), ErrorCode::AssetFrozen);
+    Ok(())
+}
+
+// Whether `key` earns fee rebates from the wallet: its owner, its guardian or a relayer in its
+// registry, which has no data when the wallet has none
+fn is_rebate_signer(
+    argent_account: &WalletConfig,
+    relayer_registry: &AccountInfo,
+    key: &Pubkey,
+) -> Result<bool> {
+    if *key == argent_account.owner || *key == argent_account.guardian {
+        return Ok(true);
+    }
+    if relayer_registry.data_is_empty() {
+        return Ok(false);
+    }
+    let registry = RelayerRegistry::try_deserialize(&mut &relayer_registry.try_borrow_data()?[..])?;
+    Ok(registry.relayers.iter().any(|quota| quota.relayer == *key))
+}
+
+// Require the transaction to start by advancing the wallet's proposal nonce, when it has one;
+// the System program then checks the nonce authority's signature, and the runtime treats the
+// transaction as a durable nonce one. Only `execute` calls this
+fn require_proposal_nonce_advance(
+    proposal_nonce: &AccountInfo,
+    sysvar_instructions: &AccountInfo,
+) -> Result<()> {
+    use anchor_lang::solana_program::sysvar::instructions::load_instruction_at_checked;
+    
+    if proposal_nonce.data_is_empty() {
+        return Ok(());
+    }
+    let proposal_nonce =
+        ProposalNonce::try_deserialize(&mut &proposal_nonce.try_borrow_data()?[..])?;
+    let first = load_instruction_at_checked(0, sysvar_instructions)?;
+    require!(
+        first.program_id == anchor_lang::solana_program::system_program::ID
+            && first.data == ADVANCE_NONCE_ACCOUNT_DATA
+            && first
+                .accounts
+                .first()
+                .is_some_and(|meta| meta.pubkey == proposal_nonce.nonce_account),
+        ErrorCode::NonceNotAdvanced
+    );
+    Ok(())
+}
+
+// Forward an escape event to the wallet's notification program, when one is configured: a
+// CPI with the wallet as its only, read-only account and the event's Anchor encoding as data.
+// Notifications never hold up recovery: they are skipped unless the configured program is
+// passed, still executable, as the first remaining account. A failed CPI can't be caught, so an
+// escape is sent without a program that rejects the call
+fn notify_escape<'info>(
+    notification_config: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    wallet: &AccountInfo<'info>,
+    event: &impl anchor_lang::Event,
+) -> Result<()> {
+    use anchor_lang::solana_program::{instruction::AccountMeta, program::invoke};
+    
+    if notification_config.data_is_empty() {
+        return Ok(());
+    }
+    let config =
+        NotificationConfig::try_deserialize(&mut &notification_config.try_borrow_data()?[..])?;
+    let Some(program) = remaining_accounts
+        .first()
+        .filter(|program| program.key() == config.program && program.executable)
+    else {
+        msg!("Escape notification to {} skipped", config.program);
+        return Ok(());
+    };
+    
+    invoke(
+        &anchor_lang::solana_program::instruction::Instruction {
+            program_id: config.program,
+            accounts: vec![AccountMeta::new_readonly(wallet.key(), false)],
+            data: event.data(),
+        },
+        &[wallet.clone(), program.clone()],
+    )?;
+    Ok(())
+}
+
+// Value a payment of `amount` of `mint` (SOL for `None`) in USD and charge it to the wallet's
+// payment budget PDA, which has no data when the wallet has none
+fn charge_payment_budget(
+    payment_budget: &AccountInfo,
+    price_feed: Option<&UncheckedAccount>,
+    mint: &Option<Pubkey>,
+    decimals: u8,
+    amount: u64,
+) -> Result<()> {
+    if payment_budget.data_is_empty() {
+        return Ok(());
+    }
+    let mut budget = PaymentBudget::try_deserialize(&mut &payment_budget.try_borrow_data()?[..])?;
+    let asset = budget
+        .assets
+        .iter()
+        .find(|asset| asset.mint == *mint)
+        .ok_or(ErrorCode::AssetNotBudgeted)?;
+    
+    let now = Clock::get()?.unix_timestamp;
+    let (price, exponent) = match asset.price_feed {
+        None => (1, 0),
+        Some(feed) => {
+            let price_feed = price_feed.ok_or(ErrorCode::InvalidPriceFeed)?;
+            require_keys_eq!(price_feed.key(), feed, ErrorCode::InvalidPriceFeed);
+            read_pyth_price(price_feed, now)?
+        }
+    };
+    
+    // amount * price * 10^(exponent - decimals), in USD micro-units, rounded up
+    let value = (amount as u128)
+        .checked_mul(price as u128)
+        .ok_or(ErrorCode::InvalidPriceFeed)?;
+    let scale = exponent + 6 - decimals as i32;
+    let usd = if scale >= 0 {
+        10u128
+            .checked_pow(scale as u32)
+            .and_then(|factor| value.checked_mul(factor))
+    } else {
+        10u128
+            .checked_pow(scale.unsigned_abs())
+            .map(|divisor| value.div_ceil(divisor))
+    };
+    let usd = usd
+        .and_then(|usd| u64::try_from(usd).ok())
+        .ok_or(ErrorCode::PaymentBudgetExceeded)?;
+    
+    budget.charge(usd, now)?;
+    budget.try_serialize(&mut &mut payment_budget.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+// Price and exponent of a fully verified Pyth price update no older than MAX_PRICE_AGE
+fn read_pyth_price(price_feed: &AccountInfo, now: i64) -> Result<(u64, i32)> {
+    // Leading fields of the receiver's PriceUpdateV2 account, after its discriminator
+    #[derive(AnchorDeserialize)]
+    enum VerificationLevel {
+        Partial { _num_signatures: u8 },
+        Full,
+    }
+    #[derive(AnchorDeserialize)]
+    struct PriceUpdate {
+        _write_authority: Pubkey,
+        verification_level: VerificationLevel,
+        _feed_id: [u8; 32],
+        price: i64,
+        _conf: u64,
+        exponent: i32,
+        publish_time: i64,
+    }
+    
+    require_keys_eq!(
+        *price_feed.owner,
+        PYTH_RECEIVER_PROGRAM_ID,
+        ErrorCode::InvalidPriceFeed
+    );
+    let data = price_feed.try_borrow_data()?;
+    require!(
+        data.get(..8) == Some(PYTH_PRICE_UPDATE_DISCRIMINATOR.as_ref()),
+        ErrorCode::InvalidPriceFeed
+    );
+    let update = PriceUpdate::deserialize(&mut &data[8..])
+        .map_err(|_| error!(ErrorCode::InvalidPriceFeed))?;
+    require!(
+        matches!(update.verification_level, VerificationLevel::Full)
+            && update.price > 0
+            && now - update.publish_time <= MAX_PRICE_AGE,
+        ErrorCode::InvalidPriceFeed
+    );
+    Ok((update.price as u64, update.exponent))
+}
+
+// The wallet's writable allowlist, from its PDA, or `None` when the wallet has none
+fn load_writable_allowlist(writable_allowlist: &AccountInfo) -> Result<Option<WritableAllowlist>> {
+    if writable_allowlist.data_is_empty() {
+        return Ok(None);
+    }
+    let allowlist =
+        WritableAllowlist::try_deserialize(&mut &writable_allowlist.try_borrow_data()?[..])?;
+    Ok(Some(allowlist))
+}
+
+// Seed of the staging stake account compound_rewards derives from the vault for
+// `stake_account`: the hex of its first 16 bytes, the longest seed create_with_seed accepts
+pub fn compounding_seed(stake_account: &Pubkey) -> String {
+    stake_account.to_bytes()[..16]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+// Create the seeded `account` owned by `owner` with `lamports` from the vault, its base. An
+// address anyone already sent lamports to can't be created over, so it is topped up, allocated
+// and assigned instead
+#[allow(clippy::too_many_arguments)]
+fn create_staging_account<'info>(
+    system_program: &Program<'info, System>,
+    vault: &SystemAccount<'info>,
+    account: &UncheckedAccount<'info>,
+    owner: &Pubkey,
+    vault_seeds: &[&[u8]],
+    seed: &str,
+    lamports: u64,
+    space: u64,
+) -> Result<()> {
+    let system = system_program.to_account_info();
+    if account.lamports() == 0 {
+        return system_program::create_account_with_seed(
+            CpiContext::new_with_signer(
+                system,
+                system_program::CreateAccountWithSeed {
+                    from: vault.to_account_info(),
+                    to: account.to_account_info(),
+                    base: vault.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            seed,
+            lamports,
+            space,
+            owner,
+        );
+    }
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            system.clone(),
+            system_program::Transfer {
+                from: vault.to_account_info(),
+                to: account.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        lamports,
+    )?;
+    system_program::allocate_with_seed(
+        CpiContext::new_with_signer(
+            system.clone(),
+            system_program::AllocateWithSeed {
+                account_to_allocate: account.to_account_info(),
+                base: vault.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        seed,
+        space,
+        owner,
+    )?;
+    system_program::assign_with_seed(
+        CpiContext::new_with_signer(
+            system,
+            system_program::AssignWithSeed {
+                account_to_assign: account.to_account_info(),
+                base: vault.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        seed,
+        owner,
+    )
+}
+
+// Whether `data` is an instruction of this program a guardian takes part in, the only ones a
+// wallet signs through act_as_guardian: approving, proposing or rejecting transactions and
+// payments, owner escapes and quorum recovery, and keeping its own role alive. Anything else
+// would let the wallet's signature stand in for it in another role, e.g. as an owner or payer
+fn is_guardian_role_instruction(data: &[u8]) -> bool {
+    [
+        instruction::Execute::DISCRIMINATOR,
+        instruction::ProposeGuardianTransaction::DISCRIMINATOR,
+        instruction::ApproveGuardianProposal::DISCRIMINATOR,
+        instruction::RejectGuardianProposal::DISCRIMINATOR,
+        instruction::PostGuardianApproval::DISCRIMINATOR,
+        instruction::RevokeGuardianApproval::DISCRIMINATOR,
+        instruction::ApprovePayment::DISCRIMINATOR,
+        instruction::TriggerEscapeOwner::DISCRIMINATOR,
+        instruction::EscapeOwner::DISCRIMINATOR,
+        instruction::CommitEscapeKey::DISCRIMINATOR,
+        instruction::CancelEscape::DISCRIMINATOR,
+        instruction::InitiateQuorumRecovery::DISCRIMINATOR,
+        instruction::GuardianSelfRotate::DISCRIMINATOR,
+        instruction::Heartbeat::DISCRIMINATOR,
+    ]
+    .iter()
+    .any(|discriminator| data.starts_with(discriminator))
+}
+
+// Seconds an escape of `escape_type` must wait before installing `new_key`: the delay of a
+// matching pre-authorization in the wallet's escape pre-authorization PDA, which has no data
+// when the wallet has none, or else the security period
+fn escape_delay(
+    argent_account: &WalletConfig,
+    escape_type: &EscapeType,
+    escape_preauthorization: &AccountInfo,
+    new_key: &Pubkey,
+) -> Result<i64> {
+    if escape_preauthorization.data_is_empty() {
+        return Ok(argent_account.security_period);
+    }
+    let preauthorization = EscapePreauthorization::try_deserialize(
+        &mut &escape_preauthorization.try_borrow_data()?[..],
+    )?;
+    let target = match escape_type {
+        EscapeType::Guardian => preauthorization.new_guardian,
+        EscapeType::Owner => preauthorization.new_owner,
+        EscapeType::None => None,
+    };
+    // A security period shortened since then still caps the delay
+    Ok(if target == Some(*new_key) {
+        preauthorization.delay.min(argent_account.security_period)
+    } else {
+        argent_account.security_period
+    })
+}
+
+// Record a deposit in the wallet's funding policy PDA, which has no data when the wallet has
+// none, if its sender is not allowed. Returns None for a deposit that isn't flagged, and
+// otherwise whether it was held for a refund; a flagged deposit never fails the deposit itself
+fn flag_deposit(
+    funding_policy: &AccountInfo,
+    sender: &Pubkey,
+    mint: Option<Pubkey>,
+    amount: u64,
+) -> Result<Option<bool>> {
+    if funding_policy.data_is_empty() {
+        return Ok(None);
+    }
+    let mut policy = FundingPolicy::try_deserialize(&mut &funding_policy.try_borrow_data()?[..])?;
+    if policy.allowed_senders.contains(sender) {
+        return Ok(None);
+    }
+    let refundable = policy.flag(*sender, mint, amount);
+    if refundable {
+        policy.try_serialize(&mut &mut funding_policy.try_borrow_mut_data()?[..])?;
+    }
+    Ok(Some(refundable))
+}
+
+// Check a proposal category against the wallet's label policy PDA, which has no data when the
+// wallet has none: categories must come from the policy's set, and may only be left empty
+// when the policy doesn't require one
+fn require_label(label_policy: &AccountInfo, category: &str) -> Result<()> {
+    require!(category.len() <= MAX_LABEL_LEN, ErrorCode::InvalidLabelCategory);
+    if label_policy.data_is_empty() {
+        return Ok(());
+    }
+    let policy = LabelPolicy::try_deserialize(&mut &label_policy.try_borrow_data()?[..])?;
+    if category.is_empty() {
+        require!(!policy.required, ErrorCode::LabelRequired);
+        return Ok(());
+    }
+    require!(
+        policy.categories.iter().any(|allowed| allowed == category),
+        ErrorCode::LabelRequired
+    );
+    Ok(())
+}
+
+// Fail early, with the units needed, when too little compute is left to finish a batch
+// of `items`, rather than running out midway through its CPIs
+fn require_compute_units(items: usize) -> Result<()> {
+    let needed = (items as u64).saturating_mul(BATCH_ITEM_COMPUTE_UNITS);
+    let remaining = sol_remaining_compute_units();
+    if remaining < needed {
+        return Err(error!(ErrorCode::InsufficientComputeBudget).with_values((needed, remaining)));
+    }
+    Ok(())
+}
+
+// Leading accounts shared by Metaplex Core asset instructions: asset, collection,
+// payer and authority. Absent optional accounts are replaced by the Core program id.
+fn core_asset_metas(
+    asset: Pubkey,
+    collection: Option<Pubkey>,
+    collection_writable: bool,
+    payer: Pubkey,
+    vault: Pubkey,
+) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+    use anchor_lang::solana_program::instruction::AccountMeta;
+    
+    let collection = match collection {
+        Some(collection) if collection_writable => AccountMeta::new(collection, false),
+        Some(collection) => AccountMeta::new_readonly(collection, false),
+        None => AccountMeta::new_readonly(MPL_CORE_PROGRAM_ID, false),
+    };
+    vec![
+        AccountMeta::new(asset, false),
+        collection,
+        AccountMeta::new(payer, true),
+        AccountMeta::new_readonly(vault, true),
+    ]
+}
+
+// Account contexts
+
+// The wallet's owner and guardian, both signing: the approvals of every dual-signed instruction.
+// A context binds them to its wallet with
+// `#[account(constraint = approvers.belong_to(&argent_account)?)]`
+#[derive(Accounts)]
+pub struct Approvers<'info> {
+    pub owner: Signer<'info>,
+    pub guardian: Signer<'info>,
+}
+
+impl Approvers<'_> {
+    // Whether the signers are the wallet's owner and guardian; fails with the role that isn't,
+    // so a mismatch still reports InvalidOwner or InvalidGuardian
+    pub fn belong_to(&self, wallet: &WalletConfig) -> Result<bool> {
+        require_keys_eq!(self.owner.key(), wallet.owner, ErrorCode::InvalidOwner);
+        require_keys_eq!(self.guardian.key(), wallet.guardian, ErrorCode::InvalidGuardian);
+        Ok(true)
+    }
+}
+
+// Account data structure
+
+// Prefix of every account after its discriminator. Decoding it checks the layout version and
+// feature bits before any of the body is read, so an account this program doesn't understand
+// fails to load instead of being misparsed; a zeroed header is an account being initialized
+#[zero_copy]
+#[derive(InitSpace, Default, PartialEq, Eq, Debug)]
+pub struct AccountHeader {
+    pub layout_version: u8,
+    pub reserved: [u8; 7],
+    pub features: u64,
+}
+
+impl AccountHeader {
+    pub const LEN: usize = 16;
+    pub const CURRENT: Self = Self {
+        layout_version: ACCOUNT_LAYOUT_VERSION,
+        reserved: [0; 7],
+        features: 0,
+    };
+    
+    pub fn is_compatible(&self) -> bool {
+        self.layout_version == ACCOUNT_LAYOUT_VERSION
+            && self.reserved == [0; 7]
+            && self.features & !SUPPORTED_ACCOUNT_FEATURES == 0
+    }
+    
+    // Whether the account data carries a header at all: every body starts with a key, which is
+    // where accounts from before the header have the reserved bytes, never all zero in practice
+    pub fn is_present(data: &[u8]) -> bool {
+        data.get(9..16).is_some_and(|reserved| reserved.iter().all(|b| *b == 0))
+    }
+    
+    // Check the header of a zero-copy account, which is loaded without being decoded
+    pub fn require_compatible(&self) -> Result<()> {
+        require!(self.is_compatible(), ErrorCode::IncompatibleAccountLayout);
+        Ok(())
+    }
+}
+
+impl AnchorSerialize for AccountHeader {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&[self.layout_version])?;
+        writer.write_all(&self.reserved)?;
+        writer.write_all(&self.features.to_le_bytes())
+    }
+}
+
+impl AnchorDeserialize for AccountHeader {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let mut bytes = [0u8; Self::LEN];
+        reader.read_exact(&mut bytes)?;
+        let header = Self {
+            layout_version: bytes[0],
+            reserved: bytes[1..8].try_into().unwrap(),
+            features: u64::from_le_bytes(bytes[8..].try_into().unwrap()),
+        };
+        if header == Self::default() {
+            return Ok(Self::CURRENT);
+        }
+        if !header.is_compatible() {
+            msg!("Incompatible account layout {}", header.layout_version);
+            return Err(std::io::ErrorKind::InvalidData.into());
+        }
+        Ok(header)
+    }
+}
+
+// The wallet's configuration: keys, periods and policies, which change rarely. It lives at the
+// wallet address and keeps the discriminator of the ArgentAccount it was split from, so existing
+// wallets load as their config; the hot state (state version, escape progress, activity) is in
+// the wallet's WalletState account, which execution and escapes write instead
+#[account(discriminator = [215, 104, 176, 122, 141, 15, 26, 150])]
+#[derive(InitSpace)]
+pub struct WalletConfig {
+    pub header: AccountHeader,
+    // Fixed header ahead of any variable-length field, so indexers can filter wallets by
+    // these at the WALLET_*_OFFSET offsets
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub version: u8,
+    pub guardian_backup: Option<Pubkey>,
+    pub security_period: i64,
+    // Seeds the wallet address is derived from
+    pub creator: Pubkey,
+    pub index: u64,
+    // Display metadata for front-ends and indexers, empty when unset
+    #[max_len(MAX_WALLET_NAME_LEN)]
+    pub name: String,
+    #[max_len(MAX_METADATA_URI_LEN)]
+    pub metadata_uri: String,
+    // Canonical bump of the [b"argent", creator, index] address
+    pub bump: u8,
+    // Whether approved messages also need the guardian's signature to be valid
+    pub message_requires_guardian: bool,
+    // Opaque tag copied into every wallet event so notification services can route alerts,
+    // all zeroes when unset
+    pub notification_tag: [u8; 32],
+    // Seconds a posted guardian approval counts for before it must be re-confirmed, 0 to rely
+    // on each approval's own expiry only
+    pub approval_ttl: i64,
+    // Time until which the owner locked the wallet, 0 when it never was
+    pub locked_until: i64,
+}
+
+// Wallet account layout before version 17, when the version was appended after the pending
+// transaction and escape_type followed the guardian backup; only decoded by migrate_account.
+// Accounts older still read the fields they lack from the zeroed tail
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyArgentAccount {
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub guardian_backup: Option<Pubkey>,
+    pub escape_type: EscapeType,
+    pub escape_initiated_at: i64,
+    pub security_period: i64,
+    pub pending_tx: Option<LegacyPendingTransaction>,
+    pub version: u8,
+    pub creator: Pubkey,
+    pub index: u64,
+    pub name: String,
+    pub metadata_uri: String,
+    pub bump: u8,
+    pub state_version: u64,
+    pub last_owner_action_at: i64,
+    pub last_guardian_action_at: i64,
+    pub compute_budget: Option<ComputeBudgetRequest>,
+    pub message_requires_guardian: bool,
+    pub escape_new_key: Option<Pubkey>,
+    pub audit_head: [u8; 32],
+    pub notification_tag: [u8; 32],
+    pub escape_stats: EscapeStats,
+    pub approval_ttl: i64,
+    pub features: u64,
+    pub locked_until: i64,
+}
+
+impl From<LegacyArgentAccount> for WalletConfig {
+    fn from(legacy: LegacyArgentAccount) -> Self {
+        Self {
+            header: AccountHeader::CURRENT,
+            owner: legacy.owner,
+            guardian: legacy.guardian,
+            version: legacy.version,
+            guardian_backup: legacy.guardian_backup,
+            security_period: legacy.security_period,
+            creator: legacy.creator,
+            index: legacy.index,
+            name: legacy.name,
+            metadata_uri: legacy.metadata_uri,
+            bump: legacy.bump,
+            message_requires_guardian: legacy.message_requires_guardian,
+            notification_tag: legacy.notification_tag,
+            approval_ttl: legacy.approval_ttl,
+            locked_until: legacy.locked_until,
+        }
+    }
+}
+
+impl From<&LegacyArgentAccount> for InlineWalletState {
+    fn from(legacy: &LegacyArgentAccount) -> Self {
+        Self {
+            state_version: legacy.state_version,
+            last_owner_action_at: legacy.last_owner_action_at,
+            last_guardian_action_at: legacy.last_guardian_action_at,
+            audit_head: legacy.audit_head,
+            open_accounts: 0,
+            escape_type: legacy.escape_type.clone(),
+            escape_initiated_at: legacy.escape_initiated_at,
+            escape_new_key: legacy.escape_new_key,
+            escape_stats: legacy.escape_stats,
+            compute_budget: legacy.compute_budget.clone(),
+            pending_tx: legacy.pending_tx.clone(),
+        }
+    }
+}
+
+// Wallet account layout of versions 17 and 18, with the fixed header but before the account
+// header prefix, when the layout features were kept after the approval TTL; only decoded by
+// migrate_account. Version 17 accounts read open_accounts from the zeroed tail. As the
+// largest older layout, its space bounds what migrate_account decodes
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct PrefixlessArgentAccount {
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub version: u8,
+    pub escape_type: EscapeType,
+    pub guardian_backup: Option<Pubkey>,
+    pub escape_initiated_at: i64,
+    pub security_period: i64,
+    pub pending_tx: Option<LegacyPendingTransaction>,
+    pub creator: Pubkey,
+    pub index: u64,
+    #[max_len(MAX_WALLET_NAME_LEN)]
+    pub name: String,
+    #[max_len(MAX_METADATA_URI_LEN)]
+    pub metadata_uri: String,
+    pub bump: u8,
+    pub state_version: u64,
+    pub last_owner_action_at: i64,
+    pub last_guardian_action_at: i64,
+    pub compute_budget: Option<ComputeBudgetRequest>,
+    pub message_requires_guardian: bool,
+    pub escape_new_key: Option<Pubkey>,
+    pub audit_head: [u8; 32],
+    pub notification_tag: [u8; 32],
+    pub escape_stats: EscapeStats,
+    pub approval_ttl: i64,
+    pub features: u64,
+    pub locked_until: i64,
+    pub open_accounts: u32,
+}
+
+impl PrefixlessArgentAccount {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+impl From<PrefixlessArgentAccount> for WalletConfig {
+    fn from(prefixless: PrefixlessArgentAccount) -> Self {
+        Self {
+            header: AccountHeader {
+                features: prefixless.features,
+                ..AccountHeader::CURRENT
+            },
+            owner: prefixless.owner,
+            guardian: prefixless.guardian,
+            version: prefixless.version,
+            guardian_backup: prefixless.guardian_backup,
+            security_period: prefixless.security_period,
+            creator: prefixless.creator,
+            index: prefixless.index,
+            name: prefixless.name,
+            metadata_uri: prefixless.metadata_uri,
+            bump: prefixless.bump,
+            message_requires_guardian: prefixless.message_requires_guardian,
+            notification_tag: prefixless.notification_tag,
+            approval_ttl: prefixless.approval_ttl,
+            locked_until: prefixless.locked_until,
+        }
+    }
+}
+
+impl From<&PrefixlessArgentAccount> for InlineWalletState {
+    fn from(prefixless: &PrefixlessArgentAccount) -> Self {
+        Self {
+            state_version: prefixless.state_version,
+            last_owner_action_at: prefixless.last_owner_action_at,
+            last_guardian_action_at: prefixless.last_guardian_action_at,
+            audit_head: prefixless.audit_head,
+            open_accounts: prefixless.open_accounts,
+            escape_type: prefixless.escape_type.clone(),
+            escape_initiated_at: prefixless.escape_initiated_at,
+            escape_new_key: prefixless.escape_new_key,
+            escape_stats: prefixless.escape_stats,
+            compute_budget: prefixless.compute_budget.clone(),
+            pending_tx: prefixless.pending_tx.clone(),
+        }
+    }
+}
+
+// Hot state the older layouts kept inside the wallet account; migrate_account moves it to the
+// wallet's WalletState and PendingTransaction accounts
+pub struct InlineWalletState {
+    pub state_version: u64,
+    pub last_owner_action_at: i64,
+    pub last_guardian_action_at: i64,
+    pub audit_head: [u8; 32],
+    pub open_accounts: u32,
+    pub escape_type: EscapeType,
+    pub escape_initiated_at: i64,
+    pub escape_new_key: Option<Pubkey>,
+    pub escape_stats: EscapeStats,
+    pub compute_budget: Option<ComputeBudgetRequest>,
+    pub pending_tx: Option<LegacyPendingTransaction>,
+}
+
+impl WalletConfig {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    
+    // Whether this program reads the wallet's layout as written: the current version (the
+    // account header was already checked when the account was decoded). Older accounts go
+    // through migrate_account first
+    pub fn is_compatible(&self) -> bool {
+        self.version == CURRENT_ACCOUNT_VERSION
+    }
+    
+    // Whether an owner lock is still running at `now`
+    pub fn is_locked(&self, now: i64) -> bool {
+        now < self.locked_until
+    }
+    
+    // Reject role assignments that would let one key act as two of owner, guardian and
+    // guardian backup, which would reduce the 2-of-2 to a single signature
+    pub fn require_distinct_roles(&self) -> Result<()> {
+        require_keys_neq!(self.owner, self.guardian, ErrorCode::OwnerIsGuardian);
+        if let Some(guardian_backup) = self.guardian_backup {
+            require_keys_neq!(guardian_backup, self.owner, ErrorCode::GuardianBackupIsOwner);
+            require_keys_neq!(guardian_backup, self.guardian, ErrorCode::GuardianBackupIsGuardian);
+        }
+        Ok(())
+    }
+    
+    // Whether `key` is the owner, guardian or guardian backup
+    pub fn is_signer_role(&self, key: &Pubkey) -> bool {
+        *key == self.owner || *key == self.guardian || self.guardian_backup == Some(*key)
+    }
+}
+
+// The wallet's hot state, which nearly every instruction updates, kept in its own zero-copy
+// account at `[b"wallet_state", wallet]` from the wallet's creation on. Instructions update it
+// in place instead of re-serializing the wallet's config, which paths such as execute and the
+// escapes only read
+#[account(zero_copy)]
+#[derive(InitSpace)]
+pub struct WalletState {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Incremented by every instruction that changes the wallet state (activity stamps aside)
+    pub state_version: u64,
+    // When the owner and guardian last signed an instruction for this wallet
+    pub last_owner_action_at: i64,
+    pub last_guardian_action_at: i64,
+    // Head of the SHA-256 chain over every emitted wallet event, see `record_audit`
+    pub audit_head: [u8; 32],
+    // Program accounts derived from the wallet's address that are still open, which
+    // close_account requires to be none so a wallet re-created at the address starts clean;
+    // UNCOUNTED_OPEN_ACCOUNTS for a migrated wallet until count_open_accounts. The wallet's
+    // own pending transaction and state accounts aren't counted
+    pub open_accounts: u32,
+    // Escape in progress as an EscapeType, see `escape_type`; kept at
+    // WALLET_STATE_ESCAPE_TYPE_OFFSET for getProgramAccounts filters
+    pub escape_type: u8,
+    pub padding: [u8; 3],
+    pub escape_initiated_at: i64,
+    // Replacement key committed for the escape in progress, letting anyone complete it; the
+    // default key when none is, see `escape_new_key`
+    pub escape_new_key: Pubkey,
+    // Escapes triggered, completed and cancelled over the wallet's life, for risk monitoring
+    pub escape_stats: EscapeStats,
+}
+
+impl WalletState {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    
+    pub fn initialize(&mut self, argent_account: Pubkey) {
+        self.header = AccountHeader::CURRENT;
+        self.argent_account = argent_account;
+    }
+    
+    pub fn next_state_version(&mut self) {
+        self.state_version += 1;
+    }
+    
+    // Count a program account derived from the wallet's address as opened or closed; an
+    // uncounted wallet stays so until count_open_accounts
+    pub fn account_opened(&mut self) {
+        if self.open_accounts != UNCOUNTED_OPEN_ACCOUNTS {
+            self.open_accounts += 1;
+        }
+    }
+    
+    pub fn account_closed(&mut self) {
+        if self.open_accounts != UNCOUNTED_OPEN_ACCOUNTS {
+            self.open_accounts = self.open_accounts.saturating_sub(1);
+        }
+    }
+    
+    // Fold an event about to be emitted into the audit chain:
+    // audit_head = sha256(audit_head || event discriminator || event data), so replaying the
+    // wallet's events from the start must end at the stored head
+    pub fn record_audit<E: anchor_lang::Event>(&mut self, event: &E) {
+        self.audit_head = hashv(&[&self.audit_head, &event.data()]).to_bytes();
+    }
+    
+    // Stamp the last action time of the wallet's owner and guardian among the signers
+    pub fn record_activity(
+        &mut self,
+        argent_account: &WalletConfig,
+        signers: &[Pubkey],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        if signers.contains(&argent_account.owner) {
+            self.last_owner_action_at = now;
+        }
+        if signers.contains(&argent_account.guardian) {
+            self.last_guardian_action_at = now;
+        }
+        Ok(())
+    }
+    
+    // Most recent time the owner or guardian signed an instruction
+    pub fn last_activity_at(&self) -> i64 {
+        self.last_owner_action_at.max(self.last_guardian_action_at)
+    }
+    
+    pub fn escape_type(&self) -> EscapeType {
+        match self.escape_type {
+            1 => EscapeType::Guardian,
+            2 => EscapeType::Owner,
+            _ => EscapeType::None,
+        }
+    }
+    
+    pub fn escape_new_key(&self) -> Option<Pubkey> {
+        (self.escape_new_key != Pubkey::default()).then_some(self.escape_new_key)
+    }
+    
+    // Start an escape of `escape_type` at `now`, with the replacement key already committed
+    // when it is known up front
+    pub fn start_escape(&mut self, escape_type: EscapeType, now: i64, new_key: Option<Pubkey>) {
+        self.escape_type = escape_type as u8;
+        self.escape_initiated_at = now;
+        self.escape_new_key = new_key.unwrap_or_default();
+    }
+    
+    pub fn clear_escape(&mut self) {
+        self.start_escape(EscapeType::None, 0, None);
+    }
+    
+    // Whether an escape completed, actually rotating a key, within `security_period` before
+    // `now`
+    pub fn recently_escaped(&self, security_period: i64, now: i64) -> bool {
+        let completed_at = self.escape_stats.last_completed_at;
+        completed_at != 0 && !core::has_elapsed(completed_at, security_period, now)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
+pub enum EscapeType {
+    None,
+    Guardian,
+    Owner,
+}
+
+// Escape counters per escaped role, with when the last escape of each outcome happened. Kept
+// zero-copy in the wallet state; its Borsh encoding, as returned by get_escape_stats, is the same
+// bytes
+#[zero_copy]
+#[derive(Default, InitSpace)]
+pub struct EscapeStats {
+    pub owner_escapes_triggered: u32,
+    pub owner_escapes_completed: u32,
+    pub owner_escapes_cancelled: u32,
+    pub guardian_escapes_triggered: u32,
+    pub guardian_escapes_completed: u32,
+    pub guardian_escapes_cancelled: u32,
+    pub last_triggered_at: i64,
+    pub last_completed_at: i64,
+    pub last_cancelled_at: i64,
+}
+
+impl EscapeStats {
+    pub fn record_triggered(&mut self, escape_type: EscapeType, now: i64) {
+        Self::count(
+            &escape_type,
+            &mut self.owner_escapes_triggered,
+            &mut self.guardian_escapes_triggered,
+        );
+        self.last_triggered_at = now;
+    }
+    
+    pub fn record_completed(&mut self, escape_type: EscapeType, now: i64) {
+        Self::count(
+            &escape_type,
+            &mut self.owner_escapes_completed,
+            &mut self.guardian_escapes_completed,
+        );
+        self.last_completed_at = now;
+    }
+    
+    pub fn record_cancelled(&mut self, escape_type: EscapeType, now: i64) {
+        Self::count(
+            &escape_type,
+            &mut self.owner_escapes_cancelled,
+            &mut self.guardian_escapes_cancelled,
+        );
+        self.last_cancelled_at = now;
+    }
+    
+    fn count(escape_type: &EscapeType, owner: &mut u32, guardian: &mut u32) {
+        match escape_type {
+            EscapeType::Owner => *owner = owner.saturating_add(1),
+            EscapeType::Guardian => *guardian = guardian.saturating_add(1),
+            EscapeType::None => {}
+        }
+    }
+}
+
+impl AnchorSerialize for EscapeStats {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(bytemuck::bytes_of(self))
+    }
+}
+
+impl AnchorDeserialize for EscapeStats {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let mut bytes = [0u8; Self::INIT_SPACE];
+        reader.read_exact(&mut bytes)?;
+        Ok(bytemuck::pod_read_unaligned(&bytes))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
+pub struct ComputeBudgetRequest {
+    pub compute_unit_limit: u32,
+    // Priority fee in micro-lamports per compute unit
+    pub compute_unit_price: u64,
+}
+
+// Instruction run by execute_immediate, referencing its program and accounts by index into
+// the remaining accounts; their signer and writable flags are taken from the transaction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VaultInstruction {
+    pub program_index: u8,
+    pub account_indexes: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+// Pending transaction as older layouts kept it inside the wallet account; only decoded by
+// migrate_account, which moves it to the wallet's PendingTransaction account
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LegacyPendingTransaction {
+    #[max_len(MAX_PENDING_TX_DATA_LEN)]
+    pub data: Vec<u8>,
+    pub owner_approved: bool,
+    pub guardian_approved: bool,
+}
+
+// The wallet's approved transaction awaiting execution, kept in its own zero-copy account at
+// `[b"pending_tx", wallet]` from the wallet's creation on. Only the instructions that store,
+// inspect or drop the payload load it, instead of every instruction decoding it along with
+// the wallet
+#[account(zero_copy)]
+#[derive(InitSpace)]
+pub struct PendingTransaction {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Compute budget the transaction asks to be executed with, a zero limit when none was set
+    pub compute_unit_price: u64,
+    pub compute_unit_limit: u32,
+    pub data_len: u16,
+    pub pending: u8,
+    pub owner_approved: u8,
+    pub guardian_approved: u8,
+    pub padding: [u8; 7],
+    pub data: [u8; MAX_PENDING_TX_DATA_LEN],
+}
+
+impl PendingTransaction {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    
+    pub fn initialize(&mut self, argent_account: Pubkey) {
+        self.header = AccountHeader::CURRENT;
+        self.argent_account = argent_account;
+    }
+    
+    pub fn is_pending(&self) -> bool {
+        self.pending != 0
+    }
+    
+    pub fn data(&self) -> &[u8] {
+        &self.data[..self.data_len as usize]
+    }
+    
+    pub fn compute_budget(&self) -> Option<ComputeBudgetRequest> {
+        (self.compute_unit_limit != 0).then_some(ComputeBudgetRequest {
+            compute_unit_limit: self.compute_unit_limit,
+            compute_unit_price: self.compute_unit_price,
+        })
+    }
+    
+    pub fn set_compute_budget(&mut self, compute_budget: Option<ComputeBudgetRequest>) {
+        let compute_budget = compute_budget.unwrap_or(ComputeBudgetRequest {
+            compute_unit_limit: 0,
+            compute_unit_price: 0,
+        });
+        self.compute_unit_limit = compute_budget.compute_unit_limit;
+        self.compute_unit_price = compute_budget.compute_unit_price;
+    }
+    
+    // Store a transaction approved by both owner and guardian, replacing the previous one
+    // along with its compute budget
+    pub fn store(&mut self, data: &[u8]) -> Result<()> {
+        require!(
+            data.len() <= MAX_PENDING_TX_DATA_LEN,
+            ErrorCode::TransactionTooLarge
+        );
+        self.data[..data.len()].copy_from_slice(data);
+        self.data[data.len()..].fill(0);
+        self.data_len = data.len() as u16;
+        self.pending = 1;
+        self.owner_approved = 1;
+        self.guardian_approved = 1;
+        self.set_compute_budget(None);
+        Ok(())
+    }
+    
+    pub fn clear(&mut self) {
+        self.set_compute_budget(None);
+        self.data = [0; MAX_PENDING_TX_DATA_LEN];
+        self.data_len = 0;
+        self.pending = 0;
+        self.owner_approved = 0;
+        self.guardian_approved = 0;
+    }
+}
+
+// Token delegations granted by the vault, kept for review and bulk revocation
+#[account(zero_copy)]
+#[derive(InitSpace)]
+pub struct DelegationRegistry {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    pub count: u64,
+    pub delegations: [TokenDelegation; MAX_TOKEN_DELEGATIONS],
+}
+
+impl DelegationRegistry {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    
+    pub fn delegations(&self) -> &[TokenDelegation] {
+        &self.delegations[..self.count as usize]
+    }
+    
+    // Record a delegation, replacing any existing one on the same token account
+    pub fn upsert(&mut self, delegation: TokenDelegation) -> Result<()> {
+        self.remove(&delegation.token_account);
+        let count = self.count as usize;
+        require!(count < MAX_TOKEN_DELEGATIONS, ErrorCode::TooManyDelegations);
+        self.delegations[count] = delegation;
+        self.count += 1;
+        Ok(())
+    }
+    
+    pub fn remove(&mut self, token_account: &Pubkey) {
+        let count = self.count as usize;
+        if let Some(i) = self.delegations[..count]
+            .iter()
+            .position(|d| d.token_account == *token_account)
+        {
+            self.delegations.swap(i, count - 1);
+            self.delegations[count - 1] = TokenDelegation::default();
+            self.count -= 1;
+        }
+    }
+}
+
+#[zero_copy]
+#[derive(Default, InitSpace)]
+pub struct TokenDelegation {
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    pub delegate: Pubkey,
+    pub amount: u64,
+    pub approved_at: i64,
+}
+
+// Router programs the vault may swap through
+#[account]
+#[derive(InitSpace)]
+pub struct SwapRouterAllowlist {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    #[max_len(MAX_SWAP_ROUTERS)]
+    pub routers: Vec<Pubkey>,
+}
+
+impl SwapRouterAllowlist {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Standing order to sell escrowed tokens through the wallet's swap routers at a minimum price
+#[account]
+#[derive(InitSpace)]
+pub struct LimitOrder {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    pub order_id: u64,
+    pub sell_mint: Pubkey,
+    pub buy_mint: Pubkey,
+    // Amount escrowed when the order was placed
+    pub amount: u64,
+    // Buy-token base units per sell-token base unit, scaled by LIMIT_PRICE_SCALE
+    pub min_price: u64,
+    pub expiry: i64,
+    // Filled so far, and what the fills brought in
+    pub sold: u64,
+    pub received: u64,
+    pub bump: u8,
+}
+
+impl LimitOrder {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Accounts instructions run from the vault may write to, besides the vault itself and its
+// token accounts
+#[account]
+#[derive(InitSpace)]
+pub struct WritableAllowlist {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    #[max_len(MAX_WRITABLE_ADDRESSES)]
+    pub addresses: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl WritableAllowlist {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    
+    // Whether `account` may be passed writable: the vault, a token account the vault owns,
+    // or an allowlisted address
+    pub fn allows(&self, account: &AccountInfo, vault: &Pubkey) -> bool {
+        if account.key == vault || self.addresses.contains(account.key) {
+            return true;
+        }
+        let is_token_program = *account.owner == spl_token::ID
+            || *account.owner == anchor_spl::token_2022::spl_token_2022::ID;
+        is_token_program
+            && account.try_borrow_data().is_ok_and(|data| {
+                TokenAccount::try_deserialize(&mut &data[..])
+                    .is_ok_and(|token_account| token_account.owner == *vault)
+            })
+    }
+}
+
+// Senders allowed to fund the wallet, and deposits from anyone else awaiting a refund
+#[account]
+#[derive(InitSpace)]
+pub struct FundingPolicy {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    #[max_len(MAX_FUNDING_SOURCES)]
+    pub allowed_senders: Vec<Pubkey>,
+    #[max_len(MAX_FLAGGED_DEPOSITS)]
+    pub flagged: Vec<FlaggedDeposit>,
+    pub bump: u8,
+}
+
+impl FundingPolicy {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    
+    // Add a deposit to the sender's flagged total for the asset, returning whether it is held
+    // for a refund. A sender without a total only gets one while the list has room, with a SOL
+    // deposit of at least MIN_FLAGGED_SOL_DEPOSIT or a token deposit while fewer than
+    // MAX_FLAGGED_TOKEN_DEPOSITS are held; tokens can't be priced, so throwaway mints are
+    // confined to those slots and can't crowd out SOL refunds
+    pub fn flag(&mut self, sender: Pubkey, mint: Option<Pubkey>, amount: u64) -> bool {
+        if let Some(deposit) = self
+            .flagged
+            .iter_mut()
+            .find(|deposit| deposit.sender == sender && deposit.mint == mint)
+        {
+            deposit.amount = deposit.amount.saturating_add(amount);
+            return true;
+        }
+        let has_room = match mint {
+            None => amount >= MIN_FLAGGED_SOL_DEPOSIT,
+            Some(_) => {
+                self.flagged.iter().filter(|deposit| deposit.mint.is_some()).count()
+                    < MAX_FLAGGED_TOKEN_DEPOSITS
+            }
+        };
+        if !has_room || self.flagged.len() >= MAX_FLAGGED_DEPOSITS {
+            return false;
+        }
+        self.flagged.push(FlaggedDeposit {
+            sender,
+            mint,
+            amount,
+        });
+        true
+    }
+    
+    // Remove the sender's flagged total for the asset, returning it
+    pub fn take_flagged(&mut self, sender: &Pubkey, mint: Option<Pubkey>) -> Option<u64> {
+        let position = self
+            .flagged
+            .iter()
+            .position(|deposit| deposit.sender == *sender && deposit.mint == mint)?;
+        Some(self.flagged.remove(position).amount)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct FlaggedDeposit {
+    pub sender: Pubkey,
+    // None for SOL deposits
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+// Events
+
+#[event]
+pub struct WalletCreated {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub creator: Pubkey,
+    pub index: u64,
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub security_period: i64,
+    pub state_version: u64,
+}
+
+// Emitted on the controller when it creates a derived wallet, alongside the new wallet's
+// WalletCreated
+#[event]
+pub struct DerivedWalletCreated {
+    pub controller: Pubkey,
+    pub argent_account: Pubkey,
+    pub index: u64,
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub state_version: u64,
+}
+
+// Emitted instead of TransactionApproved for transactions the guardian initiated
+#[event]
+pub struct GuardianTransactionProposed {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub guardian: Pubkey,
+    pub data_hash: [u8; 32],
+    pub reason: String,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct GuardianProposalApproved {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub data_hash: [u8; 32],
+    pub state_version: u64,
+}
+
+#[event]
+pub struct GuardianProposalRejected {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    // Owner rejecting the proposal or guardian withdrawing it
+    pub authority: Pubkey,
+    pub data_hash: [u8; 32],
+    pub state_version: u64,
+}
+
+#[event]
+pub struct TransactionApproved {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    // SHA-256 of the approved transaction data
+    pub data_hash: [u8; 32],
+    pub state_version: u64,
+}
+
+#[event]
+pub struct OwnerChanged {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub guardian: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct GuardianChanged {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub old_guardian: Pubkey,
+    pub new_guardian: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct GuardianRotationRequested {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub guardian: Pubkey,
+    pub new_guardian: Pubkey,
+    // When the rotation can complete unless the owner vetoes it
+    pub effective_at: i64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct GuardianRotationVetoed {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub new_guardian: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct GuardianBackupChangeRequested {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub new_guardian_backup: Option<Pubkey>,
+    // When the change can complete unless it is cancelled
+    pub effective_at: i64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct GuardianBackupChangeCancelled {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub cancelled_by: Pubkey,
+    pub new_guardian_backup: Option<Pubkey>,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct GuardianBackupChanged {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub old_guardian_backup: Option<Pubkey>,
+    pub new_guardian_backup: Option<Pubkey>,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct KeyRotationRequested {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub new_owner: Pubkey,
+    pub new_guardian: Pubkey,
+    pub new_guardian_backup: Option<Pubkey>,
+    // When the rotation can complete unless it is cancelled
+    pub effective_at: i64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct KeyRotationCancelled {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub cancelled_by: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct KeysRotated {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub old_guardian: Pubkey,
+    pub new_guardian: Pubkey,
+    pub old_guardian_backup: Option<Pubkey>,
+    pub new_guardian_backup: Option<Pubkey>,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct EscapeKeyCommitted {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub escape_type: EscapeType,
+    pub new_key: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct RewardsStaged {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub stake_account: Pubkey,
+    pub staging_stake_account: Pubkey,
+    pub lamports: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct RewardsCompounded {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub stake_account: Pubkey,
+    // Staged lamports merged into the delegation, including the staging account's rent
+    pub lamports: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct EscapePreauthorized {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub escape_type: EscapeType,
+    pub new_key: Pubkey,
+    pub delay: i64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct EscapeTriggered {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub escape_type: EscapeType,
+    pub triggered_by: Pubkey,
+    pub initiated_at: i64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct EscapeCompleted {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub escape_type: EscapeType,
+    pub completed_by: Pubkey,
+    // The escaped key and its replacement
+    pub old_key: Pubkey,
+    pub new_key: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct EscapeCancelled {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub escape_type: EscapeType,
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub state_version: u64,
+}
+
+// Events of an escape rehearsal, mirroring those of a real escape so procedures can be tested
+// end to end, under their own names so monitors never mistake a drill for a takeover
+#[event]
+pub struct EscapeRehearsalTriggered {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub escape_type: EscapeType,
+    pub triggered_by: Pubkey,
+    pub initiated_at: i64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct EscapeRehearsalCompleted {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub escape_type: EscapeType,
+    pub completed_by: Pubkey,
+    pub old_key: Pubkey,
+    pub new_key: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct EscapeRehearsalCancelled {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub escape_type: EscapeType,
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct WalletLocked {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub locked_until: i64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct WalletUnlocked {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub state_version: u64,
+}
+
+#[event]
+pub struct FeeReimbursed {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub relayer: Pubkey,
+    pub amount: u64,
+    // None for SOL reimbursements
+    pub mint: Option<Pubkey>,
+    pub state_version: u64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct LastActivity {
+    pub argent_account: Pubkey,
+    pub last_owner_action_at: i64,
+    pub last_guardian_action_at: i64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct ProgramUpgraded {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub program: Pubkey,
+    pub buffer: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct UpgradeCancelled {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub program: Pubkey,
+    pub buffer: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct PaymentProposed {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub reference: Pubkey,
+    pub category: String,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct TypedIntentProposed {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub reference: Pubkey,
+    pub summary: String,
+    // What the guardian signs
+    pub summary_hash: [u8; 32],
+    pub state_version: u64,
+}
+
+#[event]
+pub struct TypedIntentExecuted {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub guardian: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub reference: Pubkey,
+    pub summary_hash: [u8; 32],
+    pub state_version: u64,
+}
+
+#[event]
+pub struct PaymentReplaced {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    // Reference of the closed proposal, and of the proposal replacing it
+    pub old_reference: Pubkey,
+    pub reference: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct GuardianReshared {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub guardian: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct TransactionAbandoned {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    // SHA-256 of the abandoned transaction data
+    pub data_hash: [u8; 32],
+    // Error context given by the owner and guardian
+    pub reason: String,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct PaymentSent {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub reference: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct ProgramRegistered {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub program: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct ProgramUnregistered {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub program: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct UpgradeAuthorityChanged {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub program: Pubkey,
+    pub new_authority: Option<Pubkey>,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct InheritancePlanSet {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub beneficiaries: Vec<BeneficiaryShare>,
+    // Tokens distributed besides SOL
+    pub mints: Vec<Pubkey>,
+    pub inactivity_period: i64,
+    pub challenge_period: i64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct InheritancePlanRemoved {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct InheritanceClaimInitiated {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub beneficiary: Pubkey,
+    pub initiated_at: i64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct InheritanceClaimCancelled {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct InheritanceClaimed {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct InheritanceDistributed {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    // None for SOL distributions
+    pub mint: Option<Pubkey>,
+    // Total split among the beneficiaries
+    pub amount: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct LimitOrderCreated {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub order_id: u64,
+    pub sell_mint: Pubkey,
+    pub buy_mint: Pubkey,
+    pub amount: u64,
+    pub min_price: u64,
+    pub expiry: i64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct LimitOrderFilled {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub order_id: u64,
+    pub router: Pubkey,
+    pub sold: u64,
+    pub received: u64,
+    // Still escrowed; the order is closed when this reaches zero
+    pub remaining: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct LimitOrderCancelled {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub order_id: u64,
+    // Unsold tokens returned to the vault
+    pub refunded: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct StreamCreated {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub recipient: Pubkey,
+    // None for SOL streams
+    pub mint: Option<Pubkey>,
+    pub rate_per_second: u64,
+    pub end_time: i64,
+    pub deposited: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct StreamWithdrawn {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct StreamCancelled {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    // Accrued amount paid to the recipient, and the remainder returned to the vault
+    pub paid: u64,
+    pub refunded: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct VestingCreated {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub beneficiary: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub start_time: i64,
+    pub cliff_period: i64,
+    pub duration: i64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct VestedClaimed {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub beneficiary: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct GuardianServiceRegistered {
+    pub guardian: Pubkey,
+    pub name: String,
+    pub metadata_uri: String,
+}
+
+#[event]
+pub struct GuardianServiceApproved {
+    pub guardian: Pubkey,
+    pub approved: bool,
+}
+
+#[event]
+pub struct TransactionExecuted {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    // Hash of the executed instructions' data, in order
+    pub data_hash: [u8; 32],
+    pub instruction_count: u32,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct RentPaid {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    // Account the rent was paid for, and who paid it
+    pub account: Pubkey,
+    pub payer: Pubkey,
+    pub lamports: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct FeeRebateClaimed {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub signer: Pubkey,
+    pub amount: u64,
+    pub state_version: u64,
+}
+
+// Fee ledger totals of an accounting period
+#[event]
+#[derive(Clone)]
+pub struct FeeReport {
+    pub argent_account: Pubkey,
+    pub period_start: i64,
+    pub period_end: i64,
+    pub rent_lamports: u64,
+    pub relayer_fee_lamports: u64,
+    pub relayer_fee_tokens: u64,
+    pub operations: u64,
+}
+
+#[event]
+pub struct GuardianApprovalPosted {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub guardian: Pubkey,
+    pub data_hash: [u8; 32],
+    pub expires_at: i64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct VaultAccountCreated {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub account: Pubkey,
+    pub program_owner: Pubkey,
+    pub lamports: u64,
+    pub space: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct BufferClosed {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub buffer: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct NftTransferred {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub mint: Pubkey,
+    pub destination_owner: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct CompressedNftTransferred {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub merkle_tree: Pubkey,
+    // Leaf index of the asset in the tree
+    pub index: u32,
+    pub new_leaf_owner: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct CoreAssetTransferred {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub asset: Pubkey,
+    pub new_owner: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct CoreAssetBurned {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub asset: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct TokenDelegateApproved {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    pub delegate: Pubkey,
+    pub amount: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct TokenDelegateRevoked {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    // Owner or guardian that revoked it
+    pub authority: Pubkey,
+    pub token_account: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct SolWrapped {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub amount: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct SolUnwrapped {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub amount: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct StakePoolDeposited {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub stake_pool: Pubkey,
+    pub lamports: u64,
+    // Pool tokens that reached the vault
+    pub pool_tokens_out: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct StakePoolWithdrawn {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub stake_pool: Pubkey,
+    pub pool_tokens: u64,
+    // Lamports that reached the vault
+    pub lamports_out: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct TokensMinted {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct TokensBurned {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct TokenAccountFrozen {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    // False when the account was thawed
+    pub frozen: bool,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct TokenAccountsClosed {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    // Owner or guardian that closed them
+    pub authority: Pubkey,
+    pub token_accounts: Vec<Pubkey>,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct Swapped {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub router: Pubkey,
+    pub destination_token_account: Pubkey,
+    pub mint: Pubkey,
+    // Tokens that reached the destination token account
+    pub amount_out: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct AssetFrozen {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    // None for SOL
+    pub mint: Option<Pubkey>,
+    // False when the asset was unfrozen
+    pub frozen: bool,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct SweepRequested {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub destination: Pubkey,
+    // When the owner can sweep without the guardian
+    pub executable_at: i64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct SweepCancelled {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub cancelled_by: Pubkey,
+    pub destination: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct VaultSwept {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub destination: Pubkey,
+    pub lamports: u64,
+    // Token accounts passed to the sweep, empty ones included
+    pub token_accounts: u32,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct GuardianFeeClaimed {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub guardian: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    // Fee intervals paid for
+    pub periods: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct RecoveryInitiated {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub guardian: Pubkey,
+    // Recovery contacts that signed
+    pub contacts: Vec<Pubkey>,
+    pub new_owner: Pubkey,
+    pub initiated_at: i64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct RecoveryCancelled {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub owner: Pubkey,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct DepositReceived {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub sender: Pubkey,
+    pub amount: u64,
+    // None for SOL deposits
+    pub mint: Option<Pubkey>,
+    pub memo: String,
+    pub state_version: u64,
+}
+
+// A deposit from a sender outside the wallet's funding policy, held for refund
+#[event]
+pub struct DepositFlagged {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub sender: Pubkey,
+    pub amount: u64,
+    // None for SOL deposits
+    pub mint: Option<Pubkey>,
+    // Whether the deposit is held in the funding policy for refund_deposit or refund_spl_deposit
+    pub refundable: bool,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct DepositRefunded {
+    pub argent_account: Pubkey,
+    pub notification_tag: [u8; 32],
+    pub sender: Pubkey,
+    pub amount: u64,
+    // None for SOL deposits
+    pub mint: Option<Pubkey>,
+    pub state_version: u64,
+}
+
+// Unsolicited mints the owner may burn alone while their vault balance stays below a threshold
+#[account(zero_copy)]
+#[derive(InitSpace)]
+pub struct Quarantine {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    pub count: u64,
+    pub mints: [QuarantinedMint; MAX_QUARANTINED_MINTS],
+}
+
+impl Quarantine {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    
+    pub fn mints(&self) -> &[QuarantinedMint] {
+        &self.mints[..self.count as usize]
+    }
+    
+    pub fn is_dust(&self, mint: &Pubkey, amount: u64) -> bool {
+        self.mints()
+            .iter()
+            .any(|m| m.mint == *mint && amount <= m.dust_threshold)
+    }
+    
+    // Quarantine a mint, replacing its threshold if it is already quarantined
+    pub fn upsert(&mut self, quarantined: QuarantinedMint) -> Result<()> {
+        self.remove(&quarantined.mint);
+        let count = self.count as usize;
+        require!(count < MAX_QUARANTINED_MINTS, ErrorCode::TooManyQuarantinedMints);
+        self.mints[count] = quarantined;
+        self.count += 1;
+        Ok(())
+    }
+    
+    pub fn remove(&mut self, mint: &Pubkey) {
+        let count = self.count as usize;
+        if let Some(i) = self.mints[..count].iter().position(|m| m.mint == *mint) {
+            self.mints.swap(i, count - 1);
+            self.mints[count - 1] = QuarantinedMint::default();
+            self.count -= 1;
+        }
+    }
+}
+
+#[zero_copy]
+#[derive(Default, InitSpace)]
+pub struct QuarantinedMint {
+    pub mint: Pubkey,
+    // Largest balance (in base units) still considered dust
+    pub dust_threshold: u64,
+}
+
+// Named sub-vault of a wallet; its SOL lives at [b"vault", wallet, index]
+#[account]
+#[derive(InitSpace)]
+pub struct SubVaultConfig {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    pub index: u8,
+    #[max_len(MAX_SUB_VAULT_NAME_LEN)]
+    pub name: String,
+    pub policy: SubVaultPolicy,
+}
+
+impl SubVaultConfig {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
+pub enum SubVaultPolicy {
+    // Every withdrawal needs both owner and guardian
+    DualSigned,
+    // The owner alone may withdraw up to `limit` lamports at a time
+    OwnerUnderLimit { limit: u64 },
+    // Savings: nothing can be withdrawn before `unlock_at`, then both owner and guardian
+    // are needed. Only a completable escape lets the balance be swept back early
+    TimeLocked { unlock_at: i64 },
+}
+
+impl SubVaultPolicy {
+    // Lamports the owner may withdraw alone at a time
+    pub fn owner_limit(&self) -> u64 {
+        match self {
+            Self::OwnerUnderLimit { limit } => *limit,
+            Self::DualSigned | Self::TimeLocked { .. } => 0,
+        }
+    }
+}
+
+// Most recent actions of a wallet, oldest overwritten first
+#[account(zero_copy)]
+#[derive(InitSpace)]
+pub struct History {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Slot the next entry is written to
+    pub head: u64,
+    pub count: u64,
+    pub entries: [HistoryEntry; HISTORY_LEN],
+}
+
+impl History {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    
+    pub fn record(&mut self, entry: HistoryEntry) {
+        let head = self.head as usize;
+        self.entries[head] = entry;
+        self.head = ((head + 1) % HISTORY_LEN) as u64;
+        if (self.count as usize) < HISTORY_LEN {
+            self.count += 1;
+        }
+    }
+}
+
+#[zero_copy]
+#[derive(Default, InitSpace)]
+pub struct HistoryEntry {
+    pub timestamp: i64,
+    // SHA-256 of the action payload
+    pub payload_hash: [u8; 32],
+    // Signers of the action, unused slots left as the default key
+    pub actors: [Pubkey; 2],
+    // One of the HISTORY_KIND_* constants
+    pub kind: u8,
+    pub _padding: [u8; 7],
+}
+
+// Programs the wallet holds the upgrade authority of; sized to the number registered
+#[account]
+pub struct ManagedPrograms {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    pub programs: Vec<Pubkey>,
+}
+
+impl ManagedPrograms {
+    pub fn space(programs: usize) -> usize {
+        8 + AccountHeader::LEN + 32 + 4 + 32 * programs
+    }
+}
+
+// Off-chain message approved by the wallet, checked by dApps through is_valid_signature
+#[account]
+#[derive(InitSpace)]
+pub struct MessageApproval {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    pub message_hash: [u8; 32],
+    // Keys that approved the message
+    pub owner: Pubkey,
+    pub guardian: Option<Pubkey>,
+    pub approved_at: i64,
+}
+
+impl MessageApproval {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Pending program upgrade, executable once the wallet's security period has passed
+#[account]
+#[derive(InitSpace)]
+pub struct UpgradeProposal {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    pub program: Pubkey,
+    pub buffer: Pubkey,
+    // SHA-256 of the buffer's program bytes, after the loader's buffer header
+    pub code_hash: [u8; 32],
+    pub proposed_at: i64,
+}
+
+impl UpgradeProposal {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Beneficiaries who inherit the wallet after a period of owner and guardian inactivity
+#[account]
+#[derive(InitSpace)]
+pub struct InheritancePlan {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    #[max_len(MAX_BENEFICIARIES)]
+    pub beneficiaries: Vec<BeneficiaryShare>,
+    // Tokens distributed along with the vault's SOL
+    #[max_len(MAX_INHERITANCE_MINTS)]
+    pub mints: Vec<Pubkey>,
+    // Seconds without owner or guardian activity before the beneficiary can start a claim
+    pub inactivity_period: i64,
+    // Seconds the owner has to cancel a started claim
+    pub challenge_period: i64,
+    // When the current claim was started, 0 when there is none
+    pub claim_initiated_at: i64,
+}
+
+impl InheritancePlan {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    
+    pub fn is_beneficiary(&self, key: &Pubkey) -> bool {
+        self.beneficiaries.iter().any(|share| share.beneficiary == *key)
     }
     
-    // Complete escape for guardian (owner can do this alone after security period)
-    pub fn escape_guardian(ctx: Context<EscapeGuardian>, new_guardian: Pubkey) -> Result<()> {
-        let argent_account = &mut ctx.accounts.argent_account;
-        let clock = Clock::get()?;
-        
-        // Verify that owner has signed
-        let owner_signed = ctx.accounts.owner.is_signer;
-        
-        require!(
-            owner_signed,
-            ErrorCode::NotEnoughApprovals
-        );
-        
-        // Verify escape type
+    // A claim can complete once its challenge period is over, provided neither owner nor
+    // guardian has acted since it started
+    pub fn require_claimable(&self, last_activity_at: i64, now: i64) -> Result<()> {
+        require!(self.claim_initiated_at != 0, ErrorCode::NoInheritanceClaim);
         require!(
-            argent_account.escape_type == EscapeType::Guardian,
-            ErrorCode::InvalidEscapeType
+            last_activity_at < self.claim_initiated_at,
+            ErrorCode::WalletNotInactive
         );
-        
-        // Verify security period has elapsed
-        let elapsed = clock.unix_timestamp - argent_account.escape_initiated_at;
         require!(
-            elapsed >= argent_account.security_period,
-            ErrorCode::SecurityPeriodNotElapsed
+            core::has_elapsed(self.claim_initiated_at, self.challenge_period, now),
+            ErrorCode::ChallengePeriodNotElapsed
         );
-        
-        // Change the guardian
-        argent_account.guardian = new_guardian;
-        
-        // Reset escape state
-        argent_account.escape_type = EscapeType::None;
-        argent_account.escape_initiated_at = 0;
-        
-        msg!("Guardian escaped successfully!");
         Ok(())
     }
     
-    // Complete escape for owner (guardian can do this alone after security period)
-    pub fn escape_owner(ctx: Context<EscapeOwner>, new_owner: Pubkey) -> Result<()> {
-        let argent_account = &mut ctx.accounts.argent_account;
-        let clock = Clock::get()?;
-        
-        // Verify that guardian has signed
-        let guardian_signed = ctx.accounts.guardian.is_signer;
-        
-        require!(
-            guardian_signed,
-            ErrorCode::NotEnoughApprovals
-        );
-        
-        // Verify escape type
-        require!(
-            argent_account.escape_type == EscapeType::Owner,
-            ErrorCode::InvalidEscapeType
-        );
-        
-        // Verify security period has elapsed
-        let elapsed = clock.unix_timestamp - argent_account.escape_initiated_at;
+    // Split `total` by the beneficiaries' shares; the last one also receives the rounding
+    // remainder so nothing is left behind
+    pub fn split(&self, total: u64) -> Vec<u64> {
+        let mut remaining = total;
+        let mut amounts: Vec<u64> = self
+            .beneficiaries
+            .iter()
+            .map(|share| {
+                let amount = (total as u128 * share.share_bps as u128 / BASIS_POINTS as u128) as u64;
+                remaining -= amount;
+                amount
+            })
+            .collect();
+        if let Some(last) = amounts.last_mut() {
+            *last += remaining;
+        }
+        amounts
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct BeneficiaryShare {
+    pub beneficiary: Pubkey,
+    // Share of each distributed balance, in basis points
+    pub share_bps: u16,
+}
+
+// Payment streamed from the vault to a recipient, escrowed in full when created
+#[account]
+#[derive(InitSpace)]
+pub struct Stream {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    pub recipient: Pubkey,
+    // None for SOL streams, whose escrow is the stream account itself
+    pub mint: Option<Pubkey>,
+    pub rate_per_second: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    // Amount escrowed for the whole stream, and how much the recipient has withdrawn
+    pub deposited: u64,
+    pub withdrawn: u64,
+    pub bump: u8,
+}
+
+impl Stream {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    
+    // Amount accrued to the recipient by `now` and not yet withdrawn
+    pub fn withdrawable(&self, now: i64) -> u64 {
+        let elapsed = now.clamp(self.start_time, self.end_time) - self.start_time;
+        let streamed = self
+            .rate_per_second
+            .saturating_mul(elapsed as u64)
+            .min(self.deposited);
+        streamed - self.withdrawn
+    }
+}
+
+// Token vesting schedule funded from the vault, escrowed in full when created
+#[account]
+#[derive(InitSpace)]
+pub struct Vesting {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub start_time: i64,
+    // Seconds after `start_time` before anything unlocks, and until everything has
+    pub cliff_period: i64,
+    pub duration: i64,
+    pub claimed: u64,
+    pub bump: u8,
+}
+
+impl Vesting {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    
+    // Amount unlocked by `now`: zero before the cliff, then linear over the duration
+    pub fn vested(&self, now: i64) -> u64 {
+        let elapsed = now - self.start_time;
+        if elapsed < self.cliff_period {
+            return 0;
+        }
+        let elapsed = elapsed.min(self.duration);
+        (self.amount as u128 * elapsed as u128 / self.duration as u128) as u64
+    }
+    
+    // Amount unlocked by `now` that the beneficiary has not claimed yet
+    pub fn claimable(&self, now: i64) -> u64 {
+        self.vested(now) - self.claimed
+    }
+    
+    // Whether the whole amount has been paid out, so the schedule and its escrow can close
+    pub fn fully_claimed(&self) -> bool {
+        self.claimed == self.amount
+    }
+}
+
+// Transaction proposed by the guardian, waiting for the owner's approval
+#[account]
+#[derive(InitSpace)]
+pub struct GuardianProposal {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Guardian that proposed it; the proposal lapses if the guardian changes
+    pub guardian: Pubkey,
+    #[max_len(MAX_PENDING_TX_DATA_LEN)]
+    pub data: Vec<u8>,
+    // Why the guardian proposes it, for the owner's wallet to show
+    #[max_len(MAX_PROPOSAL_REASON_LEN)]
+    pub reason: String,
+    pub proposed_at: i64,
+    // Refunded the proposal rent once it is approved or rejected
+    pub payer: Pubkey,
+    pub bump: u8,
+}
+
+impl GuardianProposal {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Guardian approval of one transaction, posted ahead of its execution
+#[account]
+#[derive(InitSpace)]
+pub struct GuardianApproval {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Guardian at the time of posting; the approval lapses if the guardian changes
+    pub guardian: Pubkey,
+    // SHA-256 of the approved transaction data
+    pub data_hash: [u8; 32],
+    pub expires_at: i64,
+    pub posted_at: i64,
+    pub bump: u8,
+}
+
+impl GuardianApproval {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    
+    // Require this approval to stand for `guardian` approving `data_hash` at `now`, and to be
+    // younger than the wallet's `approval_ttl` when one is set
+    pub fn require_valid(
+        &self,
+        guardian: &Pubkey,
+        data_hash: &[u8; 32],
+        now: i64,
+        approval_ttl: i64,
+    ) -> Result<()> {
+        require_keys_eq!(self.guardian, *guardian, ErrorCode::InvalidGuardian);
+        require!(self.data_hash == *data_hash, ErrorCode::ApprovalMismatch);
+        require!(now < self.expires_at, ErrorCode::ApprovalExpired);
         require!(
-            elapsed >= argent_account.security_period,
-            ErrorCode::SecurityPeriodNotElapsed
+            approval_ttl == 0 || now - self.posted_at < approval_ttl,
+            ErrorCode::ApprovalExpired
         );
-        
-        // Change the owner
-        argent_account.owner = new_owner;
-        
-        // Reset escape state
-        argent_account.escape_type = EscapeType::None;
-        argent_account.escape_initiated_at = 0;
-        
-        msg!("Owner escaped successfully!");
         Ok(())
     }
+}
+
+// Entry of the program-wide guardian service registry
+#[account]
+#[derive(InitSpace)]
+pub struct GuardianService {
+    pub header: AccountHeader,
+    pub guardian: Pubkey,
+    #[max_len(MAX_WALLET_NAME_LEN)]
+    pub name: String,
+    #[max_len(MAX_METADATA_URI_LEN)]
+    pub metadata_uri: String,
+    // Set by the program upgrade authority once the service has been vetted
+    pub approved: bool,
+    pub bump: u8,
+}
+
+impl GuardianService {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
     
-    // Cancel escape (requires both owner and guardian)
-    pub fn cancel_escape(ctx: Context<CancelEscape>) -> Result<()> {
-        let argent_account = &mut ctx.accounts.argent_account;
-        
-        // Verify that both owner and guardian have signed
-        let owner_signed = ctx.accounts.owner.is_signer;
-        let guardian_signed = ctx.accounts.guardian.is_signer;
-        
-        require!(
-            owner_signed && guardian_signed,
-            ErrorCode::NotEnoughApprovals
-        );
-        
-        // Verify escape is in progress
-        require!(
-            argent_account.escape_type != EscapeType::None,
-            ErrorCode::NoEscapeInProgress
-        );
-        
-        // Reset escape state
-        argent_account.escape_type = EscapeType::None;
-        argent_account.escape_initiated_at = 0;
-        
-        msg!("Escape cancelled!");
+    // Require this entry to be the vetted registration of `guardian`
+    pub fn require_vetted(&self, guardian: &Pubkey) -> Result<()> {
+        require_keys_eq!(self.guardian, *guardian, ErrorCode::GuardianNotRegistered);
+        require!(self.approved, ErrorCode::GuardianServiceNotApproved);
         Ok(())
     }
+}
+
+// Lamports and tokens spent on a wallet's operations during the current accounting period
+#[account]
+#[derive(InitSpace)]
+pub struct FeeLedger {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    pub period_start: i64,
+    // Rent paid by the payers of proposal accounts
+    pub rent_lamports: u64,
+    // Relayer reimbursements from the vault, in lamports and in the fee token
+    pub relayer_fee_lamports: u64,
+    pub relayer_fee_tokens: u64,
+    // Operations that spent rent or fees
+    pub operations: u64,
+}
+
+impl FeeLedger {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
     
-    // Upgrade the program implementation (requires both owner and guardian)
-    pub fn upgrade(ctx: Context<Upgrade>) -> Result<()> {
-        // Verify that both owner and guardian have signed
-        let owner_signed = ctx.accounts.owner.is_signer;
-        let guardian_signed = ctx.accounts.guardian.is_signer;
-        
+    pub fn record(
+        &mut self,
+        rent_lamports: u64,
+        relayer_fee_lamports: u64,
+        relayer_fee_tokens: u64,
+    ) {
+        self.rent_lamports = self.rent_lamports.saturating_add(rent_lamports);
+        self.relayer_fee_lamports = self.relayer_fee_lamports.saturating_add(relayer_fee_lamports);
+        self.relayer_fee_tokens = self.relayer_fee_tokens.saturating_add(relayer_fee_tokens);
+        self.operations += 1;
+    }
+    
+    pub fn start_period(&mut self, now: i64) {
+        self.period_start = now;
+        self.rent_lamports = 0;
+        self.relayer_fee_lamports = 0;
+        self.relayer_fee_tokens = 0;
+        self.operations = 0;
+    }
+}
+
+// Owner- and guardian-set limits of the fee rebates signers claim from the vault
+#[account]
+#[derive(InitSpace)]
+pub struct FeeRebatePolicy {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Most lamports credited per recorded transaction, whatever its fee
+    pub max_fee_per_operation: u64,
+    // Most rebated to all signers together per FEE_REPORT_PERIOD
+    pub monthly_cap: u64,
+    pub period_start: i64,
+    pub claimed_in_period: u64,
+    pub bump: u8,
+}
+
+impl FeeRebatePolicy {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Fees one signer spent on a wallet's operations, and how much of them was rebated
+#[account]
+#[derive(InitSpace)]
+pub struct FeeRebate {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    pub signer: Pubkey,
+    // Cumulative, never reset
+    pub spent_lamports: u64,
+    pub claimed_lamports: u64,
+    pub bump: u8,
+}
+
+impl FeeRebate {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Wallets created under a controller wallet; sized to the number derived
+#[account]
+pub struct ControllerIndex {
+    pub header: AccountHeader,
+    pub controller: Pubkey,
+    pub wallets: Vec<Pubkey>,
+}
+
+impl ControllerIndex {
+    pub fn space(wallets: usize) -> usize {
+        8 + AccountHeader::LEN + 32 + 4 + 32 * wallets
+    }
+}
+
+// Owner's request to sweep the vault without the guardian once the security period passes
+#[account]
+#[derive(InitSpace)]
+pub struct SweepRequest {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    pub destination: Pubkey,
+    pub requested_at: i64,
+    pub bump: u8,
+}
+
+impl SweepRequest {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Daily USD budget shared by the payments of every listed asset
+#[account]
+#[derive(InitSpace)]
+pub struct PaymentBudget {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // USD with 6 decimals
+    pub daily_limit_usd: u64,
+    #[max_len(MAX_BUDGET_ASSETS)]
+    pub assets: Vec<BudgetAsset>,
+    pub window_start: i64,
+    pub spent_usd: u64,
+    pub bump: u8,
+}
+
+impl PaymentBudget {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    
+    // Count `usd` against the budget for the current window
+    pub fn charge(&mut self, usd: u64, now: i64) -> Result<()> {
+        if now - self.window_start >= PAYMENT_BUDGET_WINDOW {
+            self.window_start = now;
+            self.spent_usd = 0;
+        }
+        self.spent_usd = self.spent_usd.saturating_add(usd);
         require!(
-            owner_signed && guardian_signed,
-            ErrorCode::NotEnoughApprovals
+            self.spent_usd <= self.daily_limit_usd,
+            ErrorCode::PaymentBudgetExceeded
         );
-        
-        // Create the upgrade instruction for the BPF Loader
-        use anchor_lang::solana_program::{
-            instruction::{Instruction, AccountMeta},
-            program::invoke,
-        };
-        
-        // Create the upgrade instruction manually
-        let upgrade_ix = Instruction {
-            program_id: ctx.accounts.bpf_loader.key(),
-            accounts: vec![
-                AccountMeta::new(*ctx.accounts.program.key, false),
-                AccountMeta::new(*ctx.accounts.program_data.key, false),
-                AccountMeta::new(*ctx.accounts.buffer.key, false),
-                AccountMeta::new_readonly(*ctx.accounts.upgrade_authority.key, true),
-                AccountMeta::new_readonly(*ctx.accounts.rent.key, false),
-                AccountMeta::new_readonly(*ctx.accounts.clock.key, false),
-                AccountMeta::new_readonly(*ctx.accounts.spl_token_program.key, false),
-                AccountMeta::new_readonly(*ctx.accounts.system_program.key, false),
-            ],
-            data: vec![3], // 3 is the instruction index for upgrade
-        };
-        
-        // Invoke the upgrade instruction
-        invoke(
-            &upgrade_ix,
-            &[
-                ctx.accounts.program.to_account_info(),
-                ctx.accounts.program_data.to_account_info(),
-                ctx.accounts.buffer.to_account_info(),
-                ctx.accounts.upgrade_authority.to_account_info(),
-                ctx.accounts.rent.to_account_info(),
-                ctx.accounts.clock.to_account_info(),
-                ctx.accounts.spl_token_program.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-        )?;
-        
-        msg!("Program implementation upgraded successfully!");
         Ok(())
     }
-    
-    // This functionality is removed as Solana handles this directly
-    // External execution with signatures is handled by the Solana runtime
 }
 
-// Account contexts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct BudgetAsset {
+    // `None` for SOL
+    pub mint: Option<Pubkey>,
+    // Pyth price update account quoting the asset in USD; `None` values it at $1 a unit
+    pub price_feed: Option<Pubkey>,
+}
 
-#[derive(Accounts)]
-#[instruction(owner: Pubkey, guardian: Pubkey, security_period: Option<i64>)]
-pub struct Create<'info> {
-    #[account(
-        init,
-        seeds = [b"argent", owner.as_ref(), guardian.as_ref()],
-        bump,
-        payer = payer,
-        space = 8 + 32 + 32 + 33 + 1 + 8 + 1 + 200 // Extra space for pending tx
-    )]
-    pub argent_account: Account<'info, ArgentAccount>,
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    pub system_program: Program<'info, System>,
+// Categories payment proposals of a wallet are labelled with
+#[account]
+#[derive(InitSpace)]
+pub struct LabelPolicy {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Whether proposals must carry one of the categories
+    pub required: bool,
+    #[max_len(MAX_LABEL_CATEGORIES, MAX_LABEL_LEN)]
+    pub categories: Vec<String>,
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct Execute<'info> {
-    #[account(mut)]
-    pub argent_account: Account<'info, ArgentAccount>,
-    #[account(constraint = argent_account.owner == owner.key())]
-    pub owner: Signer<'info>,
-    #[account(constraint = argent_account.guardian == guardian.key())]
-    pub guardian: Signer<'info>,
+impl LabelPolicy {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
 }
 
-#[derive(Accounts)]
-pub struct ChangeOwner<'info> {
-    #[account(mut)]
-    pub argent_account: Account<'info, ArgentAccount>,
-    #[account(constraint = argent_account.owner == owner.key())]
-    pub owner: Signer<'info>,
-    #[account(constraint = argent_account.guardian == guardian.key())]
-    pub guardian: Signer<'info>,
+// Payment proposed by the owner, sent once the guardian approves it
+#[account]
+#[derive(InitSpace)]
+pub struct PaymentProposal {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Owner who proposed the payment
+    pub owner: Pubkey,
+    pub recipient: Pubkey,
+    pub reference: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    // What the payment will do to balances, for wallet UIs to render from chain data
+    #[max_len(2)]
+    pub balance_changes: Vec<BalanceChange>,
+    // Reporting category from the wallet's label policy, empty when unlabelled
+    #[max_len(MAX_LABEL_LEN)]
+    pub category: String,
+    // Refunded the proposal rent once it is approved or cancelled
+    pub payer: Pubkey,
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct ChangeGuardian<'info> {
-    #[account(mut)]
-    pub argent_account: Account<'info, ArgentAccount>,
-    #[account(constraint = argent_account.owner == owner.key())]
-    pub owner: Signer<'info>,
-    #[account(constraint = argent_account.guardian == guardian.key())]
-    pub guardian: Signer<'info>,
+impl PaymentProposal {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
 }
 
-#[derive(Accounts)]
-pub struct ChangeGuardianBackup<'info> {
-    #[account(mut)]
-    pub argent_account: Account<'info, ArgentAccount>,
-    #[account(constraint = argent_account.owner == owner.key())]
-    pub owner: Signer<'info>,
-    #[account(constraint = argent_account.guardian == guardian.key())]
-    pub guardian: Signer<'info>,
+// Transfer proposed by the owner for the guardian to approve by signing its summary's hash
+#[account]
+#[derive(InitSpace)]
+pub struct TypedIntent {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Owner who proposed the intent
+    pub owner: Pubkey,
+    pub recipient: Pubkey,
+    pub reference: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    // Canonical summary rendered by the program, see `message::TransferIntent`
+    #[max_len(MAX_INTENT_SUMMARY_LEN)]
+    pub summary: String,
+    // Refunded the intent rent once it is executed or cancelled
+    pub payer: Pubkey,
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct TriggerEscapeGuardian<'info> {
-    #[account(mut)]
-    pub argent_account: Account<'info, ArgentAccount>,
-    #[account(constraint = argent_account.owner == owner.key())]
-    pub owner: Signer<'info>,
+impl TypedIntent {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
 }
 
-#[derive(Accounts)]
-pub struct TriggerEscapeOwner<'info> {
-    #[account(mut)]
-    pub argent_account: Account<'info, ArgentAccount>,
-    #[account(constraint = argent_account.guardian == guardian.key())]
-    pub guardian: Signer<'info>,
+// Expected change of one account's SOL (`mint` None) or token balance, in base units
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct BalanceChange {
+    // Wallet or owner whose balance changes; token balances are of its associated account
+    pub account: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub decimals: u8,
+    pub delta: i64,
 }
 
-#[derive(Accounts)]
-pub struct EscapeGuardian<'info> {
-    #[account(mut)]
-    pub argent_account: Account<'info, ArgentAccount>,
-    #[account(constraint = argent_account.owner == owner.key())]
-    pub owner: Signer<'info>,
+// Recurring fee the wallet pays its guardian from the vault
+#[account]
+#[derive(InitSpace)]
+pub struct GuardianFee {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Guardian the fee was set for; it lapses once the wallet changes guardian
+    pub guardian: Pubkey,
+    // SOL when None
+    pub mint: Option<Pubkey>,
+    // Paid per full `interval` seconds, counted from `last_claimed_at`
+    pub amount: u64,
+    pub interval: i64,
+    pub last_claimed_at: i64,
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct EscapeOwner<'info> {
-    #[account(mut)]
-    pub argent_account: Account<'info, ArgentAccount>,
-    #[account(constraint = argent_account.guardian == guardian.key())]
-    pub guardian: Signer<'info>,
+impl GuardianFee {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
 }
 
-#[derive(Accounts)]
-pub struct CancelEscape<'info> {
-    #[account(mut)]
-    pub argent_account: Account<'info, ArgentAccount>,
-    #[account(constraint = argent_account.owner == owner.key())]
-    pub owner: Signer<'info>,
-    #[account(constraint = argent_account.guardian == guardian.key())]
-    pub guardian: Signer<'info>,
+// Opt-in rule letting the owner promote the guardian backup when the guardian goes silent
+#[account]
+#[derive(InitSpace)]
+pub struct BackupPromotion {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Seconds without a guardian signature before the owner can request the promotion
+    pub inactivity_period: i64,
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct Upgrade<'info> {
-    #[account(mut)]
-    pub argent_account: Account<'info, ArgentAccount>,
-    #[account(constraint = argent_account.owner == owner.key())]
-    pub owner: Signer<'info>,
-    #[account(constraint = argent_account.guardian == guardian.key())]
-    pub guardian: Signer<'info>,
-    /// CHECK: This is the program to upgrade
-    #[account(mut)]
-    pub program: AccountInfo<'info>,
-    /// CHECK: This is the program data account
-    #[account(mut)]
-    pub program_data: AccountInfo<'info>,
-    /// CHECK: This is the buffer with the new program code
-    pub buffer: AccountInfo<'info>,
-    /// CHECK: Upgrade authority of the program
-    pub upgrade_authority: Signer<'info>,
-    /// CHECK: The BPF Loader program
-    pub bpf_loader: AccountInfo<'info>,
-    /// CHECK: Rent sysvar
-    pub rent: AccountInfo<'info>,
-    /// CHECK: Clock sysvar
-    pub clock: AccountInfo<'info>,
-    /// CHECK: SPL Token program
-    pub spl_token_program: AccountInfo<'info>,
-    /// CHECK: System program
-    pub system_program: AccountInfo<'info>,
-}
-
-// ExecuteFromOutside context removed as Solana handles this directly
+impl BackupPromotion {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
 
-// Account data structure
+// Escape targets both keys agreed on in advance, which an escape can install after a shorter
+// delay than the security period
+#[account]
+#[derive(InitSpace)]
+pub struct EscapePreauthorization {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Guardian the owner may escape to
+    pub new_guardian: Option<Pubkey>,
+    // Owner the guardian may escape to
+    pub new_owner: Option<Pubkey>,
+    // Seconds a pre-authorized escape waits instead of the security period
+    pub delay: i64,
+    pub bump: u8,
+}
+
+impl EscapePreauthorization {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Shadow escape state of a rehearsal, kept apart from the wallet's own escape fields
+#[account]
+#[derive(InitSpace)]
+pub struct EscapeRehearsal {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    pub escape_type: EscapeType,
+    pub initiated_at: i64,
+    // Key the rehearsed escape would install
+    pub new_key: Pubkey,
+    // Refunded the rehearsal rent once it is completed or cancelled
+    pub payer: Pubkey,
+    pub bump: u8,
+}
+
+impl EscapeRehearsal {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Guardian key rotation requested by the guardian alone, waiting out the owner's veto window
+#[account]
+#[derive(InitSpace)]
+pub struct GuardianRotation {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Guardian that requested the rotation
+    pub guardian: Pubkey,
+    pub new_guardian: Pubkey,
+    pub requested_at: i64,
+    // Refunded the request rent once it is completed or vetoed
+    pub payer: Pubkey,
+    pub bump: u8,
+}
+
+impl GuardianRotation {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
 
+// Guardian backup change approved by owner and guardian, waiting out the security period
 #[account]
-pub struct ArgentAccount {
+#[derive(InitSpace)]
+pub struct GuardianBackupChange {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Owner and guardian that requested the change
     pub owner: Pubkey,
     pub guardian: Pubkey,
-    pub guardian_backup: Option<Pubkey>,
-    pub escape_type: EscapeType,
-    pub escape_initiated_at: i64,
-    pub security_period: i64,
-    pub pending_tx: Option<PendingTransaction>,
+    // None removes the current backup
+    pub new_guardian_backup: Option<Pubkey>,
+    pub requested_at: i64,
+    // Refunded the request rent once it is completed or cancelled
+    pub payer: Pubkey,
+    pub bump: u8,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
-pub enum EscapeType {
-    None,
-    Guardian,
-    Owner,
+impl GuardianBackupChange {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Coordinated replacement of every wallet key, waiting out a single security period
+#[account]
+#[derive(InitSpace)]
+pub struct KeyRotation {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Owner and guardian that requested the rotation
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub new_owner: Pubkey,
+    pub new_guardian: Pubkey,
+    // None leaves the wallet without a backup
+    pub new_guardian_backup: Option<Pubkey>,
+    pub requested_at: i64,
+    // Refunded the request rent once it is completed or cancelled
+    pub payer: Pubkey,
+    pub bump: u8,
 }
 
+impl KeyRotation {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Program notified through a CPI of every escape trigger, completion and cancellation
+#[account]
+#[derive(InitSpace)]
+pub struct NotificationConfig {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    pub program: Pubkey,
+    pub bump: u8,
+}
+
+impl NotificationConfig {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Durable nonce account every execute of the wallet advances first
+#[account]
+#[derive(InitSpace)]
+pub struct ProposalNonce {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    pub nonce_account: Pubkey,
+    pub bump: u8,
+}
+
+impl ProposalNonce {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Owner-set bounds on how many undelegated stake lamports compound_rewards moves at a time
+#[account]
+#[derive(InitSpace)]
+pub struct StakeCompounding {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Smallest amount worth compounding, below which the crank refuses
+    pub min_lamports: u64,
+    // Most compounded per round; any excess waits for the next one
+    pub max_lamports: u64,
+    pub bump: u8,
+}
+
+impl StakeCompounding {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Opt-in policy letting the guardian and a quorum of recovery contacts replace the owner
+// faster than a guardian-alone escape
+#[account]
+#[derive(InitSpace)]
+pub struct RecoveryPolicy {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    #[max_len(MAX_RECOVERY_CONTACTS)]
+    pub contacts: Vec<Pubkey>,
+    // Contacts that must sign alongside the guardian
+    pub threshold: u8,
+    // Seconds before a recovery can complete, shorter than the security period
+    pub delay: i64,
+    // Owner being recovered to and when the recovery started, if one is in progress
+    pub pending_owner: Option<Pubkey>,
+    pub initiated_at: i64,
+    pub bump: u8,
+}
+
+impl RecoveryPolicy {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Assets the guardian froze in the vault; the account only exists while something is frozen
+#[account]
+#[derive(InitSpace)]
+pub struct AssetFreeze {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Whether SOL transfers out of the vault are frozen
+    pub sol_frozen: bool,
+    #[max_len(MAX_FROZEN_MINTS)]
+    pub mints: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl AssetFreeze {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    
+    // Whether `mint`, or SOL for `None`, is frozen
+    pub fn is_frozen(&self, mint: &Option<Pubkey>) -> bool {
+        match mint {
+            None => self.sol_frozen,
+            Some(mint) => self.mints.contains(mint),
+        }
+    }
+}
+
+// Relayer fee reimbursement settings of a wallet
+#[account]
+#[derive(InitSpace)]
+pub struct PaymasterConfig {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    // Most lamports the vault pays a relayer per relayed transaction
+    pub max_fee_reimbursement: u64,
+    // Token relayers may be paid in instead, and the most base units paid per transaction
+    pub fee_mint: Option<Pubkey>,
+    pub max_token_fee: u64,
+}
+
+impl PaymasterConfig {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Keys allowed to read the wallet's private details through get_wallet_details
+#[account]
+#[derive(InitSpace)]
+pub struct ViewerList {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    #[max_len(MAX_VIEWERS)]
+    pub viewers: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl ViewerList {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+// Private wallet details returned by get_wallet_details
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct PendingTransaction {
-    pub data: Vec<u8>,
-    pub owner_approved: bool,
-    pub guardian_approved: bool,
+pub struct WalletDetails {
+    pub argent_account: Pubkey,
+    pub name: String,
+    pub metadata_uri: String,
+    pub notification_tag: [u8; 32],
+    pub state_version: u64,
+}
+
+// Relayers allowed to be reimbursed for outside execution
+#[account]
+#[derive(InitSpace)]
+pub struct RelayerRegistry {
+    pub header: AccountHeader,
+    pub argent_account: Pubkey,
+    #[max_len(MAX_RELAYERS)]
+    pub relayers: Vec<RelayerQuota>,
+}
+
+impl RelayerRegistry {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    
+    // Count a reimbursement against the relayer's quota for the current window
+    pub fn charge(&mut self, relayer: &Pubkey, lamports: u64, tokens: u64, now: i64) -> Result<()> {
+        let quota = self
+            .relayers
+            .iter_mut()
+            .find(|quota| quota.relayer == *relayer)
+            .ok_or(ErrorCode::RelayerNotAllowed)?;
+        
+        if now - quota.window_start >= RELAYER_QUOTA_WINDOW {
+            quota.window_start = now;
+            quota.lamports_spent = 0;
+            quota.tokens_spent = 0;
+        }
+        quota.lamports_spent = quota.lamports_spent.saturating_add(lamports);
+        quota.tokens_spent = quota.tokens_spent.saturating_add(tokens);
+        
+        // A quota of 0 means unlimited
+        require!(
+            quota.daily_lamport_quota == 0 || quota.lamports_spent <= quota.daily_lamport_quota,
+            ErrorCode::RelayerQuotaExceeded
+        );
+        require!(
+            quota.daily_token_quota == 0 || quota.tokens_spent <= quota.daily_token_quota,
+            ErrorCode::RelayerQuotaExceeded
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct RelayerQuota {
+    pub relayer: Pubkey,
+    pub daily_lamport_quota: u64,
+    pub daily_token_quota: u64,
+    pub window_start: i64,
+    pub lamports_spent: u64,
+    pub tokens_spent: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RelayerConfig {
+    pub relayer: Pubkey,
+    // Daily reimbursement limits in lamports and fee token base units, 0 for unlimited
+    pub daily_lamport_quota: u64,
+    pub daily_token_quota: u64,
 }
 
 #[error_code]
 pub enum ErrorCode {
+    // No longer returned; kept so later error codes stay stable
     #[msg("Not enough approvals")]
     NotEnoughApprovals,
     #[msg("Invalid owner")]
@@ -517,4 +5083,296 @@ pub enum ErrorCode {
     SecurityPeriodNotElapsed,
     #[msg("No escape in progress")]
     NoEscapeInProgress,
+    #[msg("Mint is not a non-fungible token")]
+    NotAnNft,
+    #[msg("Metadata does not match the mint")]
+    InvalidMetadata,
+    #[msg("Programmable NFT transfer requires token records")]
+    MissingTokenRecord,
+    #[msg("Authorization rules do not match the NFT rule set")]
+    InvalidRuleSet,
+    #[msg("Too many token delegations")]
+    TooManyDelegations,
+    #[msg("Token account is not owned by the vault")]
+    InvalidTokenAccount,
+    #[msg("Received amount is below the minimum out")]
+    SlippageExceeded,
+    #[msg("Token account still holds tokens")]
+    TokenAccountNotEmpty,
+    #[msg("Too many swap routers")]
+    TooManySwapRouters,
+    #[msg("Swap router is not allowlisted")]
+    SwapRouterNotAllowed,
+    #[msg("Memo is too long")]
+    MemoTooLong,
+    #[msg("Too many quarantined mints")]
+    TooManyQuarantinedMints,
+    #[msg("Balance is not quarantined dust")]
+    NotDust,
+    #[msg("Invalid remaining accounts")]
+    InvalidRemainingAccounts,
+    #[msg("A pending transaction exists")]
+    PendingTransactionExists,
+    #[msg("An escape is in progress")]
+    EscapeInProgress,
+    #[msg("Account is already at the current version")]
+    AlreadyMigrated,
+    #[msg("Transaction data exceeds the space reserved for it")]
+    TransactionTooLarge,
+    #[msg("Sub-vault name is too long")]
+    SubVaultNameTooLong,
+    #[msg("Withdrawal exceeds the sub-vault limit without guardian approval")]
+    SubVaultLimitExceeded,
+    #[msg("Sub-vault is time-locked")]
+    SubVaultLocked,
+    #[msg("Unlock time must be in the future")]
+    InvalidUnlockTime,
+    #[msg("Wallet name or metadata URI is too long")]
+    MetadataTooLong,
+    #[msg("Account holds no lamports above its rent-exempt minimum")]
+    NoExcessLamports,
+    #[msg("Source account is not a supported multisig")]
+    InvalidSourceMultisig,
+    #[msg("Owner and guardian must both be members of the source multisig")]
+    NotMultisigMember,
+    #[msg("Account address does not match its creator and index")]
+    InvalidAccountAddress,
+    #[msg("The wallet owner must sign this instruction")]
+    OwnerSignatureMissing,
+    #[msg("The wallet guardian must sign this instruction")]
+    GuardianSignatureMissing,
+    #[msg("Fewer signers than the required threshold")]
+    ThresholdNotMet,
+    #[msg("Signer is neither the wallet owner nor the guardian")]
+    NotOwnerOrGuardian,
+    #[msg("The source multisig config authority must sign the import")]
+    ConfigAuthoritySignatureMissing,
+    #[msg("Requested fee exceeds the wallet's maximum reimbursement")]
+    FeeReimbursementTooHigh,
+    #[msg("Fee token is not the wallet's accepted fee mint")]
+    FeeMintNotAccepted,
+    #[msg("Too many relayers")]
+    TooManyRelayers,
+    #[msg("Relayer is not allowlisted for this wallet")]
+    RelayerNotAllowed,
+    #[msg("Relayer reimbursement quota exceeded for today")]
+    RelayerQuotaExceeded,
+    #[msg("No pending transaction")]
+    NoPendingTransaction,
+    #[msg("Compute unit limit must be between 1 and 1,400,000")]
+    InvalidComputeBudget,
+    #[msg("Not enough compute units left to finish the batch")]
+    InsufficientComputeBudget,
+    #[msg("Account is not the expected upgradeable loader account")]
+    InvalidProgramAccount,
+    #[msg("Wallet is not the program's upgrade authority")]
+    InvalidUpgradeAuthority,
+    #[msg("New authority account does not match the requested authority")]
+    NewAuthorityMismatch,
+    #[msg("Buffer is not the one proposed for this upgrade")]
+    UpgradeBufferMismatch,
+    #[msg("Upgrade security period has not elapsed")]
+    UpgradeNotReady,
+    #[msg("Buffer contents do not match the proposed code hash")]
+    UpgradeHashMismatch,
+    #[msg("Program is not managed by this wallet")]
+    ProgramNotManaged,
+    #[msg("Program is already managed by this wallet")]
+    ProgramAlreadyManaged,
+    #[msg("Reference account does not match the payment reference")]
+    InvalidPaymentReference,
+    #[msg("Token payments need the mint, token accounts and token program")]
+    MissingPaymentAccounts,
+    #[msg("Inactivity and challenge periods must be positive")]
+    InvalidInheritancePlan,
+    #[msg("Signer is not the inheritance beneficiary")]
+    InvalidBeneficiary,
+    #[msg("Owner or guardian acted within the inactivity period")]
+    WalletNotInactive,
+    #[msg("An inheritance claim is already in progress")]
+    InheritanceClaimInProgress,
+    #[msg("No inheritance claim in progress")]
+    NoInheritanceClaim,
+    #[msg("Inheritance challenge period not elapsed")]
+    ChallengePeriodNotElapsed,
+    #[msg("Beneficiary shares must add up to 10,000 basis points")]
+    InvalidBeneficiaryShares,
+    #[msg("Ownership can only pass to the sole beneficiary of a plan")]
+    NotSoleBeneficiary,
+    #[msg("Mint is not listed in the inheritance plan")]
+    MintNotInPlan,
+    #[msg("Token distributions need the mint, vault token account and token program")]
+    MissingInheritanceAccounts,
+    #[msg("Stream needs a positive rate and an end time in the future")]
+    InvalidStream,
+    #[msg("Token streams need the mint, token accounts and token program")]
+    MissingStreamAccounts,
+    #[msg("Account is not the stream recipient")]
+    InvalidStreamRecipient,
+    #[msg("Nothing has accrued since the last withdrawal")]
+    NothingToWithdraw,
+    #[msg("Vesting needs a positive amount and duration, with the cliff within the duration")]
+    InvalidVesting,
+    #[msg("Nothing has vested since the last claim")]
+    NothingToClaim,
+    #[msg("Owner and guardian must be different keys")]
+    OwnerIsGuardian,
+    #[msg("Guardian backup must not be the owner")]
+    GuardianBackupIsOwner,
+    #[msg("Guardian backup must not be the guardian")]
+    GuardianBackupIsGuardian,
+    #[msg("Relayer must not be the owner, guardian or guardian backup")]
+    RelayerIsWalletSigner,
+    #[msg("Relayer is listed more than once")]
+    DuplicateRelayer,
+    #[msg("Expected an ed25519 signature verification right before this instruction")]
+    MissingSignatureVerification,
+    #[msg("Guardian service entry belongs to another key")]
+    GuardianNotRegistered,
+    #[msg("Guardian service has not been approved")]
+    GuardianServiceNotApproved,
+    #[msg("No replacement key has been committed for this escape")]
+    NoEscapeKeyCommitted,
+    #[msg("Account index is out of range of the remaining accounts")]
+    InvalidAccountIndex,
+    #[msg("Executed instructions cannot call the wallet program itself")]
+    SelfCpiNotAllowed,
+    #[msg("Approval expiry must be in the future")]
+    InvalidApprovalExpiry,
+    #[msg("Guardian approval is for other transaction data")]
+    ApprovalMismatch,
+    #[msg("Guardian approval has expired")]
+    ApprovalExpired,
+    #[msg("Upgrade is past its security period and can no longer be cancelled")]
+    UpgradeDelayElapsed,
+    #[msg("Account space exceeds the maximum account size")]
+    InvalidAccountSpace,
+    #[msg("Lamports do not cover the rent-exempt minimum for the account space")]
+    AccountNotRentExempt,
+    #[msg("New account address is not derived from the vault with this seed and owner")]
+    SeedAddressMismatch,
+    #[msg("New account must sign when it is not derived with a seed")]
+    NewAccountSignatureMissing,
+    #[msg("Payment amount must be positive and fit a signed balance change")]
+    InvalidPaymentAmount,
+    #[msg("Inactivity period must be positive")]
+    InvalidInactivityPeriod,
+    #[msg("The wallet has no guardian backup")]
+    NoGuardianBackup,
+    #[msg("The guardian has signed within the inactivity period")]
+    GuardianNotInactive,
+    #[msg("The asset is frozen by the guardian")]
+    AssetFrozen,
+    #[msg("Too many frozen mints")]
+    TooManyFrozenMints,
+    #[msg("Too many viewers")]
+    TooManyViewers,
+    #[msg("Signer is not a designated viewer of the wallet")]
+    ViewerNotAllowed,
+    #[msg("Too many recovery contacts")]
+    TooManyRecoveryContacts,
+    #[msg("Recovery threshold must be between 1 and the number of contacts")]
+    InvalidRecoveryThreshold,
+    #[msg("Recovery delay must be positive and shorter than the security period")]
+    InvalidRecoveryDelay,
+    #[msg("Recovery contacts must be distinct and not wallet signers")]
+    InvalidRecoveryContact,
+    #[msg("A quorum recovery is already in progress")]
+    RecoveryInProgress,
+    #[msg("No quorum recovery in progress")]
+    NoRecoveryInProgress,
+    #[msg("Not enough recovery contacts signed")]
+    RecoveryQuorumNotMet,
+    #[msg("Signed message does not follow the off-chain message schema")]
+    InvalidOffchainMessage,
+    #[msg("Signed message authorizes another wallet, state or action")]
+    OffchainMessageMismatch,
+    #[msg("Signed message has expired")]
+    OffchainMessageExpired,
+    #[msg("Guardian fee amount and interval must be positive")]
+    InvalidGuardianFee,
+    #[msg("No full guardian fee interval has elapsed since the last claim")]
+    GuardianFeeNotDue,
+    #[msg("Too many label categories")]
+    TooManyLabelCategories,
+    #[msg("Label categories must be non-empty, distinct and at most 32 bytes")]
+    InvalidLabelCategory,
+    #[msg("Proposal needs a category from the wallet's label policy")]
+    LabelRequired,
+    #[msg("Destination differs from the sweep request")]
+    InvalidSweepDestination,
+    #[msg("Too many addresses in the writable allowlist")]
+    TooManyWritableAddresses,
+    #[msg("Instruction writes to an account outside the writable allowlist")]
+    WritableAccountNotAllowed,
+    #[msg("Internal transfers need two sub-vaults, the destination no looser than the source")]
+    InvalidInternalTransfer,
+    #[msg("Too many or duplicate assets in the payment budget")]
+    TooManyBudgetAssets,
+    #[msg("Payment asset is not covered by the payment budget")]
+    AssetNotBudgeted,
+    #[msg("Price feed is missing, stale, unverified or not the budgeted one")]
+    InvalidPriceFeed,
+    #[msg("Payment exceeds the daily payment budget")]
+    PaymentBudgetExceeded,
+    #[msg("Wallet account layout is outdated or uses unknown features; migrate it first")]
+    IncompatibleAccountLayout,
+    #[msg("Lock duration must be positive and at most 90 days")]
+    InvalidLockDuration,
+    #[msg("The wallet is locked by its owner")]
+    WalletLockActive,
+    #[msg("Payer does not match the proposal's rent payer")]
+    InvalidPayer,
+    #[msg("Abandon reason too long")]
+    AbandonReasonTooLong,
+    #[msg("Too many senders in the funding policy")]
+    TooManyFundingSources,
+    #[msg("No flagged deposit from this sender for this asset")]
+    NoFlaggedDeposit,
+    #[msg("Pre-authorized escape delay must be positive and shorter than the security period")]
+    InvalidPreauthorizedDelay,
+    #[msg("Compounding bounds must be positive with the minimum no larger than the maximum")]
+    InvalidCompoundingBounds,
+    #[msg("Not a delegated stake account of the vault, or another vote account")]
+    InvalidStakeAccount,
+    #[msg("Fewer undelegated lamports than the compounding minimum")]
+    NothingToCompound,
+    #[msg("Proposal reason is too long")]
+    ProposalReasonTooLong,
+    #[msg("Guardian proposal differs from the one reviewed")]
+    GuardianProposalMismatch,
+    #[msg("Limit order needs an amount, a price, a future expiry and two different mints")]
+    InvalidLimitOrder,
+    #[msg("Limit order has expired")]
+    LimitOrderExpired,
+    #[msg("Fill is below the limit order price")]
+    LimitPriceNotMet,
+    #[msg("Typed intent summary is too long")]
+    IntentSummaryTooLong,
+    #[msg("Fee rebates need a positive fee per operation within the monthly cap")]
+    InvalidFeeRebatePolicy,
+    #[msg("Fees are recorded once per transaction, next to a wallet operation the signer signs")]
+    UnrecordableOperation,
+    #[msg("No fee rebate is owed, or this period's rebate cap is used up")]
+    NoFeeRebateDue,
+    #[msg("The notification program can't be this program")]
+    InvalidNotificationProgram,
+    #[msg("Nonce account must be an initialized System nonce account of the owner or guardian")]
+    InvalidNonceAccount,
+    #[msg("The transaction must start by advancing the wallet's proposal nonce")]
+    NonceNotAdvanced,
+    #[msg("Only the owner, guardian or a registered relayer earn fee rebates")]
+    FeeRebateSignerNotAllowed,
+    #[msg("Accounts of the wallet are still open; close them first")]
+    WalletAccountsOpen,
+    #[msg("The sub-vault still holds funds")]
+    SubVaultNotEmpty,
+    #[msg("Deposits must be of a positive amount")]
+    InvalidDepositAmount,
+    #[msg("The wallet's open accounts were not counted since its migration")]
+    OpenAccountsUncounted,
+    #[msg("The wallet's open accounts are already counted")]
+    OpenAccountsAlreadyCounted,
+    #[msg("A wallet acting as guardian can only sign guardian instructions")]
+    NotAGuardianInstruction,
 }
@@ -0,0 +1,224 @@
+// Off-chain message schema
+//
+// Every message a key signs off-chain for the program uses one canonical, versioned layout,
+// so a signature made for one program, wallet, action or wallet state can never be accepted
+// for another:
+//
+//   domain ("argent:offchain") | version (u8) | program id | wallet | nonce (u64 LE) |
+//   action (u8) | payload hash (32 bytes) | expiry slot (u64 LE)
+//
+// The nonce is the wallet's `state_version` when the message is signed, and the payload hash
+// is the SHA-256 of the action's arguments. The on-chain decoder is strict: any other length,
+// domain, version, program id or action is rejected rather than skipped over.
+//
+// Guardian transaction approvals can also be signed as an approval payload, which carries the
+// approval in the clear for hardware guardians signing air-gapped from a QR code:
+//
+//   domain ("argent:approval") | version (u8) | program id | wallet | nonce (u64 LE) |
+//   data hash (32 bytes) | expires at (i64 LE) | max fee (u64 LE)
+//
+// At 136 bytes it fits a single QR code, and the distinct domain keeps the two layouts from
+// ever being read as one another.
+//
+// Hardware guardians that can't parse either layout approve typed intents instead: the program
+// renders a canonical, human-readable summary of the intent, stores it with the proposal, and
+// the guardian signs the summary's SHA-256 after checking it against the text on its screen.
+
+use anchor_lang::prelude::*;
+
+use crate::core::{self, ApprovalFields, OffchainFields};
+use crate::ErrorCode;
+
+pub use crate::core::{
+    APPROVAL_PAYLOAD_DOMAIN, APPROVAL_PAYLOAD_LEN, APPROVAL_PAYLOAD_VERSION,
+    OFFCHAIN_MESSAGE_DOMAIN, OFFCHAIN_MESSAGE_LEN, OFFCHAIN_MESSAGE_VERSION,
+};
+
+// First line of every typed intent summary
+pub const TYPED_INTENT_HEADER: &str = "Argent typed intent v1";
+
+// Action a message authorizes; new actions are only ever appended
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum OffchainAction {
+    // The new owner accepting the wallet in `change_owner`, or in an owner escape or quorum
+    // recovery; payload: the new owner's key
+    ChangeOwner = 0,
+    // The new guardian accepting the wallet in `change_guardian` or a guardian escape;
+    // payload: the new guardian's key
+    ChangeGuardian = 1,
+    // The new guardian backup accepting the wallet; payload: its key
+    ChangeGuardianBackup = 2,
+    // A recovery contact accepting its place in the wallet's recovery policy; payload: its key
+    RecoveryContact = 3,
+    // The guardian approving the transaction whose data hashes to `data_hash` until
+    // `expires_at`, without signing a transaction itself; payload: SHA-256 of the data hash
+    // and the expiry (i64 LE)
+    ApproveTransaction = 4,
+    // The guardian announcing that its key shares were redistributed (e.g. an MPC resharing);
+    // payload: the guardian's key
+    GuardianReshare = 5,
+}
+
+impl OffchainAction {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::ChangeOwner),
+            1 => Some(Self::ChangeGuardian),
+            2 => Some(Self::ChangeGuardianBackup),
+            3 => Some(Self::RecoveryContact),
+            4 => Some(Self::ApproveTransaction),
+            5 => Some(Self::GuardianReshare),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OffchainMessage {
+    pub wallet: Pubkey,
+    pub nonce: u64,
+    pub action: OffchainAction,
+    pub payload_hash: [u8; 32],
+    pub expiry_slot: u64,
+}
+
+impl OffchainMessage {
+    // Canonical bytes to sign, bound to this program
+    pub fn encode(&self) -> Vec<u8> {
+        OffchainFields {
+            wallet: self.wallet.to_bytes(),
+            nonce: self.nonce,
+            action: self.action as u8,
+            payload_hash: self.payload_hash,
+            expiry_slot: self.expiry_slot,
+        }
+        .encode(&crate::ID.to_bytes())
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let fields = OffchainFields::decode(data, &crate::ID.to_bytes())
+            .ok_or(ErrorCode::InvalidOffchainMessage)?;
+        let action =
+            OffchainAction::from_u8(fields.action).ok_or(ErrorCode::InvalidOffchainMessage)?;
+
+        Ok(Self {
+            wallet: Pubkey::new_from_array(fields.wallet),
+            nonce: fields.nonce,
+            action,
+            payload_hash: fields.payload_hash,
+            expiry_slot: fields.expiry_slot,
+        })
+    }
+
+    // Check the message authorizes `action` with `payload_hash` on `wallet` in its current
+    // state, and has not expired at `slot`
+    pub fn require_authorizes(
+        &self,
+        wallet: &Pubkey,
+        state_version: u64,
+        action: OffchainAction,
+        payload_hash: &[u8; 32],
+        slot: u64,
+    ) -> Result<()> {
+        require!(
+            self.wallet == *wallet
+                && self.nonce == state_version
+                && self.action == action
+                && self.payload_hash == *payload_hash,
+            ErrorCode::OffchainMessageMismatch
+        );
+        require!(slot <= self.expiry_slot, ErrorCode::OffchainMessageExpired);
+        Ok(())
+    }
+}
+
+// Guardian approval of the transaction whose data hashes to `data_hash`, until `expires_at`,
+// for relayers reimbursed at most `max_fee` lamports
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApprovalPayload {
+    pub wallet: Pubkey,
+    pub nonce: u64,
+    pub data_hash: [u8; 32],
+    pub expires_at: i64,
+    pub max_fee: u64,
+}
+
+impl ApprovalPayload {
+    // Canonical bytes to sign, bound to this program
+    pub fn encode(&self) -> Vec<u8> {
+        ApprovalFields {
+            wallet: self.wallet.to_bytes(),
+            nonce: self.nonce,
+            data_hash: self.data_hash,
+            expires_at: self.expires_at,
+            max_fee: self.max_fee,
+        }
+        .encode(&crate::ID.to_bytes())
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let fields = ApprovalFields::decode(data, &crate::ID.to_bytes())
+            .ok_or(ErrorCode::InvalidOffchainMessage)?;
+
+        Ok(Self {
+            wallet: Pubkey::new_from_array(fields.wallet),
+            nonce: fields.nonce,
+            data_hash: fields.data_hash,
+            expires_at: fields.expires_at,
+            max_fee: fields.max_fee,
+        })
+    }
+
+    // Check the payload approves `data_hash` on `wallet` in its current state, has not expired
+    // at `now`, and covers a relayer `fee`
+    pub fn require_authorizes(
+        &self,
+        wallet: &Pubkey,
+        state_version: u64,
+        data_hash: &[u8; 32],
+        fee: u64,
+        now: i64,
+    ) -> Result<()> {
+        require!(
+            self.wallet == *wallet
+                && self.nonce == state_version
+                && self.data_hash == *data_hash
+                && fee <= self.max_fee,
+            ErrorCode::OffchainMessageMismatch
+        );
+        require!(now < self.expires_at, ErrorCode::OffchainMessageExpired);
+        Ok(())
+    }
+}
+
+// Transfer of `amount` SOL (`mint` None) or tokens from the vault to `recipient`, proposed as
+// a typed intent while the wallet was at `nonce`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferIntent {
+    pub wallet: Pubkey,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+impl TransferIntent {
+    // Canonical summary, one field per line, bound to this program
+    pub fn summary(&self) -> String {
+        let asset = self
+            .mint
+            .map_or_else(|| "SOL".to_string(), |mint| mint.to_string());
+        format!(
+            "{}\nProgram: {}\nWallet: {}\nNonce: {}\nSend: {} {}\nTo: {}",
+            TYPED_INTENT_HEADER,
+            crate::ID,
+            self.wallet,
+            self.nonce,
+            core::format_token_amount(self.amount, self.decimals),
+            asset,
+            self.recipient,
+        )
+    }
+}
@@ -0,0 +1,329 @@
+// Import existing multisigs as Argent wallets
+//
+// A source multisig is read as an input account and mapped onto a new wallet: the owner and
+// guardian must both be members of it, and enough members to meet its threshold must sign
+// the import, which proves the caller controls the source. The resulting wallet is always
+// owner + guardian; the source account itself is left untouched.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+
+use crate::{
+    ErrorCode, GuardianService, PendingTransaction, WalletConfig, WalletCreated, WalletState,
+    CURRENT_ACCOUNT_VERSION,
+};
+
+// Squads v4 program and the Anchor discriminator of its `Multisig` account
+pub const SQUADS_V4_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf");
+const SQUADS_V4_MULTISIG_DISCRIMINATOR: [u8; 8] = [224, 116, 121, 186, 68, 161, 79, 236];
+
+// Default security period when the source has no time lock to carry over (7 days)
+const DEFAULT_SECURITY_PERIOD: i64 = 604800;
+
+// Leading fields of a Squads v4 `Multisig` account
+#[derive(AnchorDeserialize)]
+struct SquadsV4Multisig {
+    _create_key: Pubkey,
+    config_authority: Pubkey,
+    threshold: u16,
+    time_lock: u32,
+    _transaction_index: u64,
+    _stale_transaction_index: u64,
+    _rent_collector: Option<Pubkey>,
+    _bump: u8,
+    members: Vec<SquadsV4Member>,
+}
+
+#[derive(AnchorDeserialize)]
+struct SquadsV4Member {
+    key: Pubkey,
+    _permissions: u8,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct MigrateFromSplMultisig<'info> {
+    #[account(
+        init,
+        seeds = [b"argent", payer.key().as_ref(), index.to_le_bytes().as_ref()],
+        bump,
+        payer = payer,
+        space = WalletConfig::SPACE
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(
+        init,
+        seeds = [b"pending_tx", argent_account.key().as_ref()],
+        bump,
+        payer = payer,
+        space = PendingTransaction::SPACE
+    )]
+    pub pending_transaction: AccountLoader<'info, PendingTransaction>,
+    #[account(
+        init,
+        seeds = [b"wallet_state", argent_account.key().as_ref()],
+        bump,
+        payer = payer,
+        space = WalletState::SPACE
+    )]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    // When given, the guardian must be a vetted guardian service
+    #[account(
+        seeds = [b"guardian_service", guardian_service.guardian.as_ref()],
+        bump = guardian_service.bump,
+    )]
+    pub guardian_service: Option<Account<'info, GuardianService>>,
+    /// CHECK: Parsed as an SPL Token (or Token-2022) multisig in the handler
+    #[account(
+        constraint = *source_multisig.owner == spl_token::ID
+            || *source_multisig.owner == anchor_spl::token_2022::ID
+            @ ErrorCode::InvalidSourceMultisig
+    )]
+    pub source_multisig: UncheckedAccount<'info>,
+    pub owner: Signer<'info>,
+    pub guardian: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct MigrateFromSquads<'info> {
+    #[account(
+        init,
+        seeds = [b"argent", payer.key().as_ref(), index.to_le_bytes().as_ref()],
+        bump,
+        payer = payer,
+        space = WalletConfig::SPACE
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(
+        init,
+        seeds = [b"pending_tx", argent_account.key().as_ref()],
+        bump,
+        payer = payer,
+        space = PendingTransaction::SPACE
+    )]
+    pub pending_transaction: AccountLoader<'info, PendingTransaction>,
+    #[account(
+        init,
+        seeds = [b"wallet_state", argent_account.key().as_ref()],
+        bump,
+        payer = payer,
+        space = WalletState::SPACE
+    )]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    // When given, the guardian must be a vetted guardian service
+    #[account(
+        seeds = [b"guardian_service", guardian_service.guardian.as_ref()],
+        bump = guardian_service.bump,
+    )]
+    pub guardian_service: Option<Account<'info, GuardianService>>,
+    /// CHECK: Parsed as a Squads v4 multisig in the handler
+    #[account(owner = SQUADS_V4_PROGRAM_ID @ ErrorCode::InvalidSourceMultisig)]
+    pub source_multisig: UncheckedAccount<'info>,
+    pub owner: Signer<'info>,
+    pub guardian: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Import an SPL Token multisig; additional member signers are passed as remaining accounts
+pub fn spl_multisig(
+    ctx: Context<MigrateFromSplMultisig>,
+    index: u64,
+    security_period: Option<i64>,
+) -> Result<()> {
+    let multisig = {
+        let data = ctx.accounts.source_multisig.try_borrow_data()?;
+        let len = spl_token::state::Multisig::LEN;
+        require!(data.len() >= len, ErrorCode::InvalidSourceMultisig);
+        spl_token::state::Multisig::unpack(&data[..len])
+            .map_err(|_| error!(ErrorCode::InvalidSourceMultisig))?
+    };
+    let members = &multisig.signers[..multisig.n as usize];
+
+    check_control(
+        members,
+        multisig.m as usize,
+        &ctx.accounts.owner,
+        &ctx.accounts.guardian,
+        ctx.remaining_accounts,
+    )?;
+    if let Some(guardian_service) = &ctx.accounts.guardian_service {
+        guardian_service.require_vetted(&ctx.accounts.guardian.key())?;
+    }
+
+    initialize(
+        &mut ctx.accounts.argent_account,
+        ctx.accounts.owner.key(),
+        ctx.accounts.guardian.key(),
+        security_period.unwrap_or(DEFAULT_SECURITY_PERIOD),
+        ctx.accounts.payer.key(),
+        index,
+        ctx.bumps.argent_account,
+    );
+    ctx.accounts
+        .pending_transaction
+        .load_init()?
+        .initialize(ctx.accounts.argent_account.key());
+    let mut wallet_state = ctx.accounts.wallet_state.load_init()?;
+    wallet_state.initialize(ctx.accounts.argent_account.key());
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.owner.key(), ctx.accounts.guardian.key()],
+    )?;
+
+    let event = WalletCreated {
+        argent_account: ctx.accounts.argent_account.key(),
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        creator: ctx.accounts.payer.key(),
+        index,
+        owner: ctx.accounts.owner.key(),
+        guardian: ctx.accounts.guardian.key(),
+        security_period: ctx.accounts.argent_account.security_period,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Imported SPL multisig {}", ctx.accounts.source_multisig.key());
+    Ok(())
+}
+
+// Import a Squads v4 multisig; additional member signers are passed as remaining accounts
+// A controlled multisig also needs its config authority among the signers, and its time
+// lock becomes the default security period
+pub fn squads(
+    ctx: Context<MigrateFromSquads>,
+    index: u64,
+    security_period: Option<i64>,
+) -> Result<()> {
+    let multisig = {
+        let data = ctx.accounts.source_multisig.try_borrow_data()?;
+        require!(
+            data.len() > 8 && data[..8] == SQUADS_V4_MULTISIG_DISCRIMINATOR,
+            ErrorCode::InvalidSourceMultisig
+        );
+        SquadsV4Multisig::deserialize(&mut &data[8..])
+            .map_err(|_| error!(ErrorCode::InvalidSourceMultisig))?
+    };
+    let members: Vec<Pubkey> = multisig.members.iter().map(|member| member.key).collect();
+
+    check_control(
+        &members,
+        multisig.threshold as usize,
+        &ctx.accounts.owner,
+        &ctx.accounts.guardian,
+        ctx.remaining_accounts,
+    )?;
+    if let Some(guardian_service) = &ctx.accounts.guardian_service {
+        guardian_service.require_vetted(&ctx.accounts.guardian.key())?;
+    }
+
+    if multisig.config_authority != Pubkey::default() {
+        let authority_signed = ctx.accounts.owner.key() == multisig.config_authority
+            || ctx.accounts.guardian.key() == multisig.config_authority
+            || ctx
+                .remaining_accounts
+                .iter()
+                .any(|account| account.is_signer && account.key() == multisig.config_authority);
+        require!(authority_signed, ErrorCode::ConfigAuthoritySignatureMissing);
+    }
+
+    let default_period = if multisig.time_lock > 0 {
+        multisig.time_lock as i64
+    } else {
+        DEFAULT_SECURITY_PERIOD
+    };
+
+    initialize(
+        &mut ctx.accounts.argent_account,
+        ctx.accounts.owner.key(),
+        ctx.accounts.guardian.key(),
+        security_period.unwrap_or(default_period),
+        ctx.accounts.payer.key(),
+        index,
+        ctx.bumps.argent_account,
+    );
+    ctx.accounts
+        .pending_transaction
+        .load_init()?
+        .initialize(ctx.accounts.argent_account.key());
+    let mut wallet_state = ctx.accounts.wallet_state.load_init()?;
+    wallet_state.initialize(ctx.accounts.argent_account.key());
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.owner.key(), ctx.accounts.guardian.key()],
+    )?;
+
+    let event = WalletCreated {
+        argent_account: ctx.accounts.argent_account.key(),
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        creator: ctx.accounts.payer.key(),
+        index,
+        owner: ctx.accounts.owner.key(),
+        guardian: ctx.accounts.guardian.key(),
+        security_period: ctx.accounts.argent_account.security_period,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Imported Squads multisig {}", ctx.accounts.source_multisig.key());
+    Ok(())
+}
+
+// Owner and guardian must be members, and distinct member signatures must meet the threshold
+fn check_control(
+    members: &[Pubkey],
+    threshold: usize,
+    owner: &Signer,
+    guardian: &Signer,
+    remaining_accounts: &[AccountInfo],
+) -> Result<()> {
+    require!(
+        members.contains(&owner.key()) && members.contains(&guardian.key()),
+        ErrorCode::NotMultisigMember
+    );
+
+    let signers = crate::core::distinct_approvals(
+        members,
+        [owner.key(), guardian.key()].into_iter().chain(
+            remaining_accounts
+                .iter()
+                .filter(|account| account.is_signer)
+                .map(|account| account.key()),
+        ),
+    );
+    if !crate::core::meets_threshold(signers.len(), threshold) {
+        return Err(error!(ErrorCode::ThresholdNotMet).with_values((threshold, signers.len())));
+    }
+    Ok(())
+}
+
+fn initialize(
+    argent_account: &mut WalletConfig,
+    owner: Pubkey,
+    guardian: Pubkey,
+    security_period: i64,
+    creator: Pubkey,
+    index: u64,
+    bump: u8,
+) {
+    argent_account.owner = owner;
+    argent_account.guardian = guardian;
+    argent_account.guardian_backup = None;
+    argent_account.security_period = security_period;
+    argent_account.message_requires_guardian = false;
+    argent_account.version = CURRENT_ACCOUNT_VERSION;
+    argent_account.creator = creator;
+    argent_account.index = index;
+    argent_account.bump = bump;
+}
@@ -0,0 +1,712 @@
+// NFTs
+//
+// Moving the vault's Metaplex NFTs, compressed NFTs and Core assets, and delegating the update
+// authority of its Core assets.
+
+use super::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TransferNft<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// The wallet vault PDA holding the NFT
+    #[account(seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    #[account(
+        constraint = mint.decimals == 0 && mint.supply == 1 @ ErrorCode::NotAnNft,
+        mint::token_program = token_program,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = vault,
+        token::token_program = token_program,
+    )]
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: Any wallet can receive the NFT
+    pub destination_owner: UncheckedAccount<'info>,
+    /// CHECK: Destination ATA, validated (and created if needed) by Token Metadata
+    #[account(mut)]
+    pub destination_token: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), mint.key().as_ref()],
+        seeds::program = token_metadata_program.key(),
+        bump,
+        constraint = metadata.mint == mint.key() @ ErrorCode::InvalidMetadata,
+    )]
+    pub metadata: Box<Account<'info, MetadataAccount>>,
+    /// CHECK: Master edition PDA, validated by Token Metadata
+    #[account(
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), mint.key().as_ref(), b"edition"],
+        seeds::program = token_metadata_program.key(),
+        bump,
+    )]
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: Source token record (programmable NFTs only), validated by Token Metadata
+    #[account(mut)]
+    pub token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: Destination token record (programmable NFTs only), validated by Token Metadata
+    #[account(mut)]
+    pub destination_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token Auth Rules program (programmable NFTs with a rule set only)
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: Rule set account, checked against the metadata's programmable config
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: Instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TransferCompressedNft<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// The wallet vault PDA, leaf owner of the compressed NFT
+    #[account(seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    /// CHECK: Leaf delegate (the vault itself if none is set), part of the leaf hash checked by Bubblegum
+    pub leaf_delegate: UncheckedAccount<'info>,
+    /// CHECK: Any wallet can receive the compressed NFT
+    pub new_leaf_owner: UncheckedAccount<'info>,
+    /// CHECK: Tree config PDA, validated by Bubblegum
+    pub tree_config: UncheckedAccount<'info>,
+    /// CHECK: Merkle tree account, validated by Bubblegum
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// CHECK: Noop log wrapper program, validated by Bubblegum
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// CHECK: Account compression program, validated by Bubblegum
+    pub compression_program: UncheckedAccount<'info>,
+    /// CHECK: The Bubblegum program
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TransferCoreAsset<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// The wallet vault PDA, authority over the asset
+    #[account(seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    /// CHECK: Core asset account, validated by Metaplex Core
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: Collection of the asset if it has one, validated by Metaplex Core
+    #[account(mut)]
+    pub collection: Option<UncheckedAccount<'info>>,
+    /// CHECK: Any wallet can receive the asset
+    pub new_owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: The Metaplex Core program
+    #[account(address = MPL_CORE_PROGRAM_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BurnCoreAsset<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// The wallet vault PDA, authority over the asset
+    #[account(seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    /// CHECK: Core asset account, validated by Metaplex Core
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: Collection of the asset if it has one, validated by Metaplex Core
+    #[account(mut)]
+    pub collection: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: The Metaplex Core program
+    #[account(address = MPL_CORE_PROGRAM_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveCoreUpdateDelegate<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// The wallet vault PDA, authority over the asset
+    #[account(seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: Core asset account, validated by Metaplex Core
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: Collection of the asset if it has one, validated by Metaplex Core
+    #[account(mut)]
+    pub collection: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: The Metaplex Core program
+    #[account(address = MPL_CORE_PROGRAM_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeCoreUpdateDelegate<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// The wallet vault PDA, authority over the asset
+    #[account(seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: Core asset account, validated by Metaplex Core
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: Collection of the asset if it has one, validated by Metaplex Core
+    #[account(mut)]
+    pub collection: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: The Metaplex Core program
+    #[account(address = MPL_CORE_PROGRAM_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+}
+
+pub(crate) fn transfer_nft(ctx: Context<TransferNft>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require_not_frozen(&ctx.accounts.asset_freeze, &Some(ctx.accounts.mint.key()))?;
+
+    // Only (programmable) non-fungibles are handled here
+    let metadata = &ctx.accounts.metadata;
+    let is_programmable = match metadata.token_standard {
+        None | Some(TokenStandard::NonFungible) | Some(TokenStandard::NonFungibleEdition) => {
+            false
+        }
+        Some(TokenStandard::ProgrammableNonFungible)
+        | Some(TokenStandard::ProgrammableNonFungibleEdition) => true,
+        Some(_) => return err!(ErrorCode::NotAnNft),
+    };
+
+    // Programmable NFTs need both token records and, if configured, the rule set
+    if is_programmable {
+        require!(
+            ctx.accounts.token_record.is_some()
+                && ctx.accounts.destination_token_record.is_some(),
+            ErrorCode::MissingTokenRecord
+        );
+        if let Some(ProgrammableConfig::V1 {
+            rule_set: Some(rule_set),
+        }) = metadata.programmable_config
+        {
+            let rules_match = ctx
+                .accounts
+                .authorization_rules
+                .as_ref()
+                .is_some_and(|rules| rules.key() == rule_set);
+            require!(
+                rules_match && ctx.accounts.authorization_rules_program.is_some(),
+                ErrorCode::InvalidRuleSet
+            );
+        }
+    }
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+
+    let token_metadata_program = ctx.accounts.token_metadata_program.to_account_info();
+    let vault = ctx.accounts.vault.to_account_info();
+    let vault_token_account = ctx.accounts.vault_token_account.to_account_info();
+    let destination_token = ctx.accounts.destination_token.to_account_info();
+    let destination_owner = ctx.accounts.destination_owner.to_account_info();
+    let mint = ctx.accounts.mint.to_account_info();
+    let metadata = ctx.accounts.metadata.to_account_info();
+    let edition = ctx.accounts.edition.to_account_info();
+    let token_record = ctx.accounts.token_record.as_ref().map(|a| a.to_account_info());
+    let destination_token_record = ctx
+        .accounts
+        .destination_token_record
+        .as_ref()
+        .map(|a| a.to_account_info());
+    let authorization_rules_program = ctx
+        .accounts
+        .authorization_rules_program
+        .as_ref()
+        .map(|a| a.to_account_info());
+    let authorization_rules = ctx
+        .accounts
+        .authorization_rules
+        .as_ref()
+        .map(|a| a.to_account_info());
+    let payer = ctx.accounts.payer.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let sysvar_instructions = ctx.accounts.sysvar_instructions.to_account_info();
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let associated_token_program = ctx.accounts.associated_token_program.to_account_info();
+
+    TransferV1CpiBuilder::new(&token_metadata_program)
+        .token(&vault_token_account)
+        .token_owner(&vault)
+        .destination_token(&destination_token)
+        .destination_owner(&destination_owner)
+        .mint(&mint)
+        .metadata(&metadata)
+        .edition(Some(&edition))
+        .token_record(token_record.as_ref())
+        .destination_token_record(destination_token_record.as_ref())
+        .authority(&vault)
+        .payer(&payer)
+        .system_program(&system_program)
+        .sysvar_instructions(&sysvar_instructions)
+        .spl_token_program(&token_program)
+        .spl_ata_program(&associated_token_program)
+        .authorization_rules_program(authorization_rules_program.as_ref())
+        .authorization_rules(authorization_rules.as_ref())
+        .amount(1)
+        .invoke_signed(&[vault_seeds])?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = NftTransferred {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        mint: mint.key(),
+        destination_owner: destination_owner.key(),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("NFT {} transferred to {}", mint.key(), destination_owner.key());
+    Ok(())
+}
+
+pub(crate) fn transfer_compressed_nft<'info>(
+    ctx: Context<'_, '_, '_, 'info, TransferCompressedNft<'info>>,
+    root: [u8; 32],
+    data_hash: [u8; 32],
+    creator_hash: [u8; 32],
+    nonce: u64,
+    index: u32,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    // A compressed NFT is frozen under its Bubblegum asset id
+    let (asset_id, _) = Pubkey::find_program_address(
+        &[b"asset", ctx.accounts.merkle_tree.key().as_ref(), &nonce.to_le_bytes()],
+        &BUBBLEGUM_PROGRAM_ID,
+    );
+    require_not_frozen(&ctx.accounts.asset_freeze, &Some(asset_id))?;
+
+    use anchor_lang::solana_program::{
+        instruction::{Instruction, AccountMeta},
+        program::invoke_signed,
+    };
+
+    let mut data = BUBBLEGUM_TRANSFER_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&root);
+    data.extend_from_slice(&data_hash);
+    data.extend_from_slice(&creator_hash);
+    data.extend_from_slice(&nonce.to_le_bytes());
+    data.extend_from_slice(&index.to_le_bytes());
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(ctx.accounts.tree_config.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.vault.key(), true),
+        AccountMeta::new_readonly(ctx.accounts.leaf_delegate.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.new_leaf_owner.key(), false),
+        AccountMeta::new(ctx.accounts.merkle_tree.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.log_wrapper.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.compression_program.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+    ];
+    let mut account_infos = vec![
+        ctx.accounts.tree_config.to_account_info(),
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.leaf_delegate.to_account_info(),
+        ctx.accounts.new_leaf_owner.to_account_info(),
+        ctx.accounts.merkle_tree.to_account_info(),
+        ctx.accounts.log_wrapper.to_account_info(),
+        ctx.accounts.compression_program.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.bubblegum_program.to_account_info(),
+    ];
+    for proof_node in ctx.remaining_accounts {
+        accounts.push(AccountMeta::new_readonly(proof_node.key(), false));
+        account_infos.push(proof_node.clone());
+    }
+
+    let transfer_ix = Instruction {
+        program_id: BUBBLEGUM_PROGRAM_ID,
+        accounts,
+        data,
+    };
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    invoke_signed(&transfer_ix, &account_infos, &[vault_seeds])?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = CompressedNftTransferred {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        merkle_tree: ctx.accounts.merkle_tree.key(),
+        index,
+        new_leaf_owner: ctx.accounts.new_leaf_owner.key(),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!(
+        "Compressed NFT {} transferred to {}",
+        index,
+        ctx.accounts.new_leaf_owner.key()
+    );
+    Ok(())
+}
+
+pub(crate) fn transfer_core_asset(ctx: Context<TransferCoreAsset>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require_not_frozen(&ctx.accounts.asset_freeze, &Some(ctx.accounts.asset.key()))?;
+
+    use anchor_lang::solana_program::{
+        instruction::{Instruction, AccountMeta},
+        program::invoke_signed,
+    };
+
+    let mut accounts = core_asset_metas(
+        ctx.accounts.asset.key(),
+        ctx.accounts.collection.as_ref().map(|c| c.key()),
+        false,
+        ctx.accounts.payer.key(),
+        ctx.accounts.vault.key(),
+    );
+    accounts.push(AccountMeta::new_readonly(ctx.accounts.new_owner.key(), false));
+    accounts.push(AccountMeta::new_readonly(ctx.accounts.system_program.key(), false));
+    accounts.push(AccountMeta::new_readonly(MPL_CORE_PROGRAM_ID, false));
+
+    // TransferV1Args { compression_proof: None }
+    let transfer_ix = Instruction {
+        program_id: MPL_CORE_PROGRAM_ID,
+        accounts,
+        data: vec![MPL_CORE_TRANSFER_V1, 0],
+    };
+
+    let mut account_infos = vec![
+        ctx.accounts.asset.to_account_info(),
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.new_owner.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.mpl_core_program.to_account_info(),
+    ];
+    if let Some(collection) = &ctx.accounts.collection {
+        account_infos.push(collection.to_account_info());
+    }
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    invoke_signed(&transfer_ix, &account_infos, &[vault_seeds])?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = CoreAssetTransferred {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        asset: ctx.accounts.asset.key(),
+        new_owner: ctx.accounts.new_owner.key(),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!(
+        "Core asset {} transferred to {}",
+        ctx.accounts.asset.key(),
+        ctx.accounts.new_owner.key()
+    );
+    Ok(())
+}
+
+pub(crate) fn burn_core_asset(ctx: Context<BurnCoreAsset>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require_not_frozen(&ctx.accounts.asset_freeze, &Some(ctx.accounts.asset.key()))?;
+
+    use anchor_lang::solana_program::{
+        instruction::{Instruction, AccountMeta},
+        program::invoke_signed,
+    };
+
+    let mut accounts = core_asset_metas(
+        ctx.accounts.asset.key(),
+        ctx.accounts.collection.as_ref().map(|c| c.key()),
+        true,
+        ctx.accounts.payer.key(),
+        ctx.accounts.vault.key(),
+    );
+    accounts.push(AccountMeta::new_readonly(ctx.accounts.system_program.key(), false));
+    accounts.push(AccountMeta::new_readonly(MPL_CORE_PROGRAM_ID, false));
+
+    // BurnV1Args { compression_proof: None }
+    let burn_ix = Instruction {
+        program_id: MPL_CORE_PROGRAM_ID,
+        accounts,
+        data: vec![MPL_CORE_BURN_V1, 0],
+    };
+
+    let mut account_infos = vec![
+        ctx.accounts.asset.to_account_info(),
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.mpl_core_program.to_account_info(),
+    ];
+    if let Some(collection) = &ctx.accounts.collection {
+        account_infos.push(collection.to_account_info());
+    }
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    invoke_signed(&burn_ix, &account_infos, &[vault_seeds])?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = CoreAssetBurned {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        asset: ctx.accounts.asset.key(),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Core asset {} burned", ctx.accounts.asset.key());
+    Ok(())
+}
+
+pub(crate) fn approve_core_update_delegate(
+    ctx: Context<ApproveCoreUpdateDelegate>,
+    delegate: Pubkey,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    use anchor_lang::solana_program::{
+        instruction::{Instruction, AccountMeta},
+        program::invoke_signed,
+    };
+
+    let mut accounts = core_asset_metas(
+        ctx.accounts.asset.key(),
+        ctx.accounts.collection.as_ref().map(|c| c.key()),
+        false,
+        ctx.accounts.payer.key(),
+        ctx.accounts.vault.key(),
+    );
+    accounts.push(AccountMeta::new_readonly(ctx.accounts.system_program.key(), false));
+    accounts.push(AccountMeta::new_readonly(MPL_CORE_PROGRAM_ID, false));
+
+    // ApprovePluginAuthorityV1Args { plugin_type: UpdateDelegate, new_authority: Address }
+    let mut data = vec![
+        MPL_CORE_APPROVE_PLUGIN_AUTHORITY_V1,
+        MPL_CORE_UPDATE_DELEGATE_PLUGIN,
+        MPL_CORE_PLUGIN_AUTHORITY_ADDRESS,
+    ];
+    data.extend_from_slice(delegate.as_ref());
+    let approve_ix = Instruction {
+        program_id: MPL_CORE_PROGRAM_ID,
+        accounts,
+        data,
+    };
+
+    let mut account_infos = vec![
+        ctx.accounts.asset.to_account_info(),
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.mpl_core_program.to_account_info(),
+    ];
+    if let Some(collection) = &ctx.accounts.collection {
+        account_infos.push(collection.to_account_info());
+    }
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    invoke_signed(&approve_ix, &account_infos, &[vault_seeds])?;
+
+    msg!(
+        "Core asset {} update delegate set to {}",
+        ctx.accounts.asset.key(),
+        delegate
+    );
+    Ok(())
+}
+
+pub(crate) fn revoke_core_update_delegate(ctx: Context<RevokeCoreUpdateDelegate>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    use anchor_lang::solana_program::{
+        instruction::{Instruction, AccountMeta},
+        program::invoke_signed,
+    };
+
+    let mut accounts = core_asset_metas(
+        ctx.accounts.asset.key(),
+        ctx.accounts.collection.as_ref().map(|c| c.key()),
+        false,
+        ctx.accounts.payer.key(),
+        ctx.accounts.vault.key(),
+    );
+    accounts.push(AccountMeta::new_readonly(ctx.accounts.system_program.key(), false));
+    accounts.push(AccountMeta::new_readonly(MPL_CORE_PROGRAM_ID, false));
+
+    // RevokePluginAuthorityV1Args { plugin_type: UpdateDelegate }
+    let revoke_ix = Instruction {
+        program_id: MPL_CORE_PROGRAM_ID,
+        accounts,
+        data: vec![MPL_CORE_REVOKE_PLUGIN_AUTHORITY_V1, MPL_CORE_UPDATE_DELEGATE_PLUGIN],
+    };
+
+    let mut account_infos = vec![
+        ctx.accounts.asset.to_account_info(),
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.mpl_core_program.to_account_info(),
+    ];
+    if let Some(collection) = &ctx.accounts.collection {
+        account_infos.push(collection.to_account_info());
+    }
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    invoke_signed(&revoke_ix, &account_infos, &[vault_seeds])?;
+
+    msg!("Core asset {} update delegate revoked", ctx.accounts.asset.key());
+    Ok(())
+}
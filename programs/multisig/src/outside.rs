@@ -0,0 +1,511 @@
+// Outside execution
+//
+// Relayers executing a wallet's pending transaction on its behalf, and the paymaster that
+// reimburses them from the vault.
+
+use super::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteFromOutside<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(mut, seeds = [b"pending_tx", argent_account.key().as_ref()], bump)]
+    pub pending_transaction: AccountLoader<'info, PendingTransaction>,
+    #[account(seeds = [b"paymaster", argent_account.key().as_ref()], bump)]
+    pub paymaster_config: Account<'info, PaymasterConfig>,
+    #[account(mut, seeds = [b"relayers", argent_account.key().as_ref()], bump)]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+    /// The wallet vault PDA paying the reimbursement
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// The relayer submitting and paying for the transaction
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(mut, seeds = [b"fee_ledger", argent_account.key().as_ref()], bump)]
+    pub fee_ledger: Option<Account<'info, FeeLedger>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteFromOutsideWithGuardianPayload<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    /// CHECK: Must be the wallet guardian; its signature is verified by the ed25519 program
+    #[account(constraint = argent_account.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"pending_tx", argent_account.key().as_ref()], bump)]
+    pub pending_transaction: AccountLoader<'info, PendingTransaction>,
+    #[account(seeds = [b"paymaster", argent_account.key().as_ref()], bump)]
+    pub paymaster_config: Account<'info, PaymasterConfig>,
+    #[account(mut, seeds = [b"relayers", argent_account.key().as_ref()], bump)]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+    /// The wallet vault PDA paying the reimbursement
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// The relayer submitting and paying for the transaction
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    /// CHECK: Instructions sysvar, holding the guardian's signature verification
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(mut, seeds = [b"fee_ledger", argent_account.key().as_ref()], bump)]
+    pub fee_ledger: Option<Account<'info, FeeLedger>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteFromOutsideWithTokenFee<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(mut, seeds = [b"pending_tx", argent_account.key().as_ref()], bump)]
+    pub pending_transaction: AccountLoader<'info, PendingTransaction>,
+    #[account(seeds = [b"paymaster", argent_account.key().as_ref()], bump)]
+    pub paymaster_config: Account<'info, PaymasterConfig>,
+    #[account(mut, seeds = [b"relayers", argent_account.key().as_ref()], bump)]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+    /// The wallet vault PDA owning the fee token account
+    #[account(seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mint::token_program = token_program)]
+    pub fee_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = fee_mint,
+        token::authority = relayer,
+        token::token_program = token_program,
+    )]
+    pub relayer_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// The relayer submitting and paying for the transaction
+    pub relayer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut, seeds = [b"fee_ledger", argent_account.key().as_ref()], bump)]
+    pub fee_ledger: Option<Account<'info, FeeLedger>>,
+}
+
+#[derive(Accounts)]
+pub struct SetRelayers<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = RelayerRegistry::SPACE,
+        seeds = [b"relayers", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaymasterConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = PaymasterConfig::SPACE,
+        seeds = [b"paymaster", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub paymaster_config: Account<'info, PaymasterConfig>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn execute_from_outside(
+    ctx: Context<ExecuteFromOutside>,
+    data: Vec<u8>,
+    fee: u64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require!(
+        data.len() <= MAX_PENDING_TX_DATA_LEN,
+        ErrorCode::TransactionTooLarge
+    );
+    require!(
+        fee <= ctx.accounts.paymaster_config.max_fee_reimbursement,
+        ErrorCode::FeeReimbursementTooHigh
+    );
+    ctx.accounts.relayer_registry.charge(
+        &ctx.accounts.relayer.key(),
+        fee,
+        0,
+        Clock::get()?.unix_timestamp,
+    )?;
+    if let Some(fee_ledger) = &mut ctx.accounts.fee_ledger {
+        fee_ledger.record(0, fee, 0);
+    }
+
+    let argent_account = &ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = TransactionApproved {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        data_hash: hash(&data).to_bytes(),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    // Store the transaction data for execution
+    let mut pending_transaction = ctx.accounts.pending_transaction.load_mut()?;
+    pending_transaction.header.require_compatible()?;
+    pending_transaction.store(&data)?;
+
+    // Reimburse the relayer from the vault
+    if fee > 0 {
+        let argent_key = ctx.accounts.argent_account.key();
+        let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.relayer.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            fee,
+        )?;
+
+        let event = FeeReimbursed {
+            argent_account: argent_key,
+            notification_tag: ctx.accounts.argent_account.notification_tag,
+            relayer: ctx.accounts.relayer.key(),
+            amount: fee,
+            mint: None,
+            state_version: wallet_state.state_version,
+        };
+        wallet_state.record_audit(&event);
+        emit_cpi!(event);
+    }
+
+    msg!("Relayed transaction approved and ready for execution!");
+    Ok(())
+}
+
+pub(crate) fn execute_from_outside_with_guardian_payload(
+    ctx: Context<ExecuteFromOutsideWithGuardianPayload>,
+    data: Vec<u8>,
+    fee: u64,
+    guardian_signature: [u8; 64],
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.owner.key(), ctx.accounts.guardian.key()],
+    )?;
+
+    require!(
+        data.len() <= MAX_PENDING_TX_DATA_LEN,
+        ErrorCode::TransactionTooLarge
+    );
+    let data_hash = hash(&data).to_bytes();
+    let signed = verify_ed25519_instruction(
+        &ctx.accounts.sysvar_instructions,
+        &ctx.accounts.guardian.key(),
+        &guardian_signature,
+    )?;
+    ApprovalPayload::decode(&signed)?.require_authorizes(
+        &ctx.accounts.argent_account.key(),
+        wallet_state.state_version,
+        &data_hash,
+        fee,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    require!(
+        fee <= ctx.accounts.paymaster_config.max_fee_reimbursement,
+        ErrorCode::FeeReimbursementTooHigh
+    );
+    ctx.accounts.relayer_registry.charge(
+        &ctx.accounts.relayer.key(),
+        fee,
+        0,
+        Clock::get()?.unix_timestamp,
+    )?;
+    if let Some(fee_ledger) = &mut ctx.accounts.fee_ledger {
+        fee_ledger.record(0, fee, 0);
+    }
+
+    let argent_account = &ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = TransactionApproved {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.owner.key(),
+        guardian: ctx.accounts.guardian.key(),
+        data_hash,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    let mut pending_transaction = ctx.accounts.pending_transaction.load_mut()?;
+    pending_transaction.header.require_compatible()?;
+    pending_transaction.store(&data)?;
+
+    if fee > 0 {
+        let argent_key = ctx.accounts.argent_account.key();
+        let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.relayer.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            fee,
+        )?;
+
+        let event = FeeReimbursed {
+            argent_account: argent_key,
+            notification_tag: ctx.accounts.argent_account.notification_tag,
+            relayer: ctx.accounts.relayer.key(),
+            amount: fee,
+            mint: None,
+            state_version: wallet_state.state_version,
+        };
+        wallet_state.record_audit(&event);
+        emit_cpi!(event);
+    }
+
+    msg!("Relayed transaction approved with the guardian's payload!");
+    Ok(())
+}
+
+pub(crate) fn set_paymaster_config(
+    ctx: Context<SetPaymasterConfig>,
+    max_fee_reimbursement: u64,
+    fee_mint: Option<Pubkey>,
+    max_token_fee: u64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let config = &mut ctx.accounts.paymaster_config;
+    if config.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    config.argent_account = ctx.accounts.argent_account.key();
+    config.max_fee_reimbursement = max_fee_reimbursement;
+    config.fee_mint = fee_mint;
+    config.max_token_fee = max_token_fee;
+
+    wallet_state.next_state_version();
+
+    msg!("Paymaster config updated!");
+    Ok(())
+}
+
+pub(crate) fn execute_from_outside_with_token_fee(
+    ctx: Context<ExecuteFromOutsideWithTokenFee>,
+    data: Vec<u8>,
+    fee: u64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require!(
+        data.len() <= MAX_PENDING_TX_DATA_LEN,
+        ErrorCode::TransactionTooLarge
+    );
+
+    let config = &ctx.accounts.paymaster_config;
+    require!(
+        config.fee_mint == Some(ctx.accounts.fee_mint.key()),
+        ErrorCode::FeeMintNotAccepted
+    );
+    require!(fee <= config.max_token_fee, ErrorCode::FeeReimbursementTooHigh);
+    ctx.accounts.relayer_registry.charge(
+        &ctx.accounts.relayer.key(),
+        0,
+        fee,
+        Clock::get()?.unix_timestamp,
+    )?;
+    if let Some(fee_ledger) = &mut ctx.accounts.fee_ledger {
+        fee_ledger.record(0, 0, fee);
+    }
+
+    let argent_account = &ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = TransactionApproved {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        data_hash: hash(&data).to_bytes(),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    // Store the transaction data for execution
+    let mut pending_transaction = ctx.accounts.pending_transaction.load_mut()?;
+    pending_transaction.header.require_compatible()?;
+    pending_transaction.store(&data)?;
+
+    // Pay the relayer from the vault's fee token account
+    if fee > 0 {
+        let argent_key = ctx.accounts.argent_account.key();
+        let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    mint: ctx.accounts.fee_mint.to_account_info(),
+                    to: ctx.accounts.relayer_token_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            fee,
+            ctx.accounts.fee_mint.decimals,
+        )?;
+
+        let event = FeeReimbursed {
+            argent_account: argent_key,
+            notification_tag: ctx.accounts.argent_account.notification_tag,
+            relayer: ctx.accounts.relayer.key(),
+            amount: fee,
+            mint: Some(ctx.accounts.fee_mint.key()),
+            state_version: wallet_state.state_version,
+        };
+        wallet_state.record_audit(&event);
+        emit_cpi!(event);
+    }
+
+    msg!("Relayed transaction approved and ready for execution!");
+    Ok(())
+}
+
+pub(crate) fn set_relayers(ctx: Context<SetRelayers>, relayers: Vec<RelayerConfig>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require!(relayers.len() <= MAX_RELAYERS, ErrorCode::TooManyRelayers);
+    let argent_account = &ctx.accounts.argent_account;
+    for (i, config) in relayers.iter().enumerate() {
+        require!(
+            !argent_account.is_signer_role(&config.relayer),
+            ErrorCode::RelayerIsWalletSigner
+        );
+        require!(
+            relayers[..i].iter().all(|other| other.relayer != config.relayer),
+            ErrorCode::DuplicateRelayer
+        );
+    }
+
+    let registry = &mut ctx.accounts.relayer_registry;
+    if registry.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    registry.argent_account = ctx.accounts.argent_account.key();
+    registry.relayers = relayers
+        .into_iter()
+        .map(|config| RelayerQuota {
+            relayer: config.relayer,
+            daily_lamport_quota: config.daily_lamport_quota,
+            daily_token_quota: config.daily_token_quota,
+            window_start: 0,
+            lamports_spent: 0,
+            tokens_spent: 0,
+        })
+        .collect();
+
+    wallet_state.next_state_version();
+
+    msg!("Relayers updated!");
+    Ok(())
+}
@@ -0,0 +1,900 @@
+// Payments
+//
+// Solana Pay payments from the vault, the payment proposals and typed intents a guardian
+// approves, and the daily budget and label policy they are checked against.
+
+use super::*;
+
+#[derive(Accounts)]
+pub struct SetPaymentBudget<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = PaymentBudget::SPACE,
+        seeds = [b"payment_budget", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub payment_budget: Account<'info, PaymentBudget>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemovePaymentBudget<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"payment_budget", argent_account.key().as_ref()],
+        bump = payment_budget.bump,
+    )]
+    pub payment_budget: Account<'info, PaymentBudget>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecutePayment<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// The wallet vault PDA paying
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    /// CHECK: The wallet's payment budget PDA, read and charged in the handler; absent when
+    /// payments are unbudgeted
+    #[account(mut, seeds = [b"payment_budget", argent_account.key().as_ref()], bump)]
+    pub payment_budget: UncheckedAccount<'info>,
+    /// CHECK: Price update account of the paid asset, checked against the payment budget;
+    /// only needed for budgeted assets priced by a feed
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: Payment recipient from the transfer request; receives SOL directly or owns the
+    /// associated token account receiving tokens
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+    /// CHECK: Solana Pay reference key, only added to the transfer instruction
+    pub reference: UncheckedAccount<'info>,
+    // Token payments only; checked in the handler
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub vault_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub recipient_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetLabelPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = LabelPolicy::SPACE,
+        seeds = [b"label_policy", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub label_policy: Account<'info, LabelPolicy>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(reference: Pubkey)]
+pub struct ProposePayment<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    /// The wallet vault PDA that will pay
+    #[account(seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: Payment recipient from the transfer request, only recorded
+    pub recipient: UncheckedAccount<'info>,
+    // Token payments only, for the amount's decimals
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    /// CHECK: The wallet's label policy PDA, read in the handler; absent when it has none
+    #[account(seeds = [b"label_policy", argent_account.key().as_ref()], bump)]
+    pub label_policy: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = PaymentProposal::SPACE,
+        seeds = [b"payment", argent_account.key().as_ref(), reference.as_ref()],
+        bump,
+    )]
+    pub payment_proposal: Account<'info, PaymentProposal>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ApprovePayment<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: Signer<'info>,
+    // Lapses when the owner who proposed it has been replaced
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"payment", argent_account.key().as_ref(), reference.key().as_ref()],
+        bump = payment_proposal.bump,
+        has_one = payer,
+        constraint = payment_proposal.owner == argent_account.owner @ ErrorCode::InvalidOwner,
+    )]
+    pub payment_proposal: Account<'info, PaymentProposal>,
+    /// The wallet vault PDA paying
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    /// CHECK: The wallet's payment budget PDA, read and charged in the handler; absent when
+    /// payments are unbudgeted
+    #[account(mut, seeds = [b"payment_budget", argent_account.key().as_ref()], bump)]
+    pub payment_budget: UncheckedAccount<'info>,
+    /// CHECK: Price update account of the paid asset, checked against the payment budget;
+    /// only needed for budgeted assets priced by a feed
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: The proposed recipient
+    #[account(mut, address = payment_proposal.recipient @ ErrorCode::InvalidPaymentReference)]
+    pub recipient: UncheckedAccount<'info>,
+    /// CHECK: Solana Pay reference key the proposal is for, added to the transfer instruction
+    pub reference: UncheckedAccount<'info>,
+    // Token payments only; checked in the handler
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub vault_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub recipient_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    /// CHECK: Receives the proposal rent back
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(reference: Pubkey)]
+pub struct ProposeTypedIntent<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    /// CHECK: Transfer recipient, only recorded
+    pub recipient: UncheckedAccount<'info>,
+    // Token transfers only, for the amount's decimals
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = payer,
+        space = TypedIntent::SPACE,
+        seeds = [b"typed_intent", argent_account.key().as_ref(), reference.as_ref()],
+        bump,
+    )]
+    pub typed_intent: Account<'info, TypedIntent>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteTypedIntent<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    /// CHECK: Must be the wallet guardian; its signature is checked through the instructions
+    /// sysvar instead of on the transaction
+    #[account(constraint = argent_account.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: UncheckedAccount<'info>,
+    // Lapses when the owner who proposed it has been replaced
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"typed_intent", argent_account.key().as_ref(), reference.key().as_ref()],
+        bump = typed_intent.bump,
+        has_one = payer,
+        constraint = typed_intent.owner == argent_account.owner @ ErrorCode::InvalidOwner,
+    )]
+    pub typed_intent: Account<'info, TypedIntent>,
+    /// The wallet vault PDA paying
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    /// CHECK: The wallet's payment budget PDA, read and charged in the handler; absent when
+    /// payments are unbudgeted
+    #[account(mut, seeds = [b"payment_budget", argent_account.key().as_ref()], bump)]
+    pub payment_budget: UncheckedAccount<'info>,
+    /// CHECK: Price update account of the paid asset, checked against the payment budget;
+    /// only needed for budgeted assets priced by a feed
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: The intended recipient
+    #[account(mut, address = typed_intent.recipient @ ErrorCode::InvalidPaymentReference)]
+    pub recipient: UncheckedAccount<'info>,
+    /// CHECK: Reference key the intent is for, added to the transfer instruction
+    pub reference: UncheckedAccount<'info>,
+    // Token transfers only; checked in the handler
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub vault_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub recipient_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    /// CHECK: Receives the intent rent back
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    /// CHECK: Instructions sysvar, holding the guardian's signature verification
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(reference: Pubkey)]
+pub struct CancelTypedIntent<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"typed_intent", argent_account.key().as_ref(), reference.as_ref()],
+        bump = typed_intent.bump,
+        has_one = payer,
+    )]
+    pub typed_intent: Account<'info, TypedIntent>,
+    /// CHECK: Receives the intent rent back
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(reference: Pubkey)]
+pub struct CancelPayment<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"payment", argent_account.key().as_ref(), reference.as_ref()],
+        bump = payment_proposal.bump,
+        has_one = payer,
+    )]
+    pub payment_proposal: Account<'info, PaymentProposal>,
+    /// CHECK: Receives the proposal rent back
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(old_reference: Pubkey, reference: Pubkey)]
+pub struct ReplacePayment<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    /// The wallet vault PDA that will pay
+    #[account(seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: Payment recipient from the amended transfer request, only recorded
+    pub recipient: UncheckedAccount<'info>,
+    // Token payments only, for the amount's decimals
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    // Proposal being replaced, closed back to its payer
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"payment", argent_account.key().as_ref(), old_reference.as_ref()],
+        bump = old_payment_proposal.bump,
+        constraint = old_payment_proposal.payer == payer.key() @ ErrorCode::InvalidPayer,
+    )]
+    pub old_payment_proposal: Account<'info, PaymentProposal>,
+    #[account(
+        init,
+        payer = payer,
+        space = PaymentProposal::SPACE,
+        seeds = [b"payment", argent_account.key().as_ref(), reference.as_ref()],
+        bump,
+    )]
+    pub payment_proposal: Account<'info, PaymentProposal>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn set_payment_budget(
+    ctx: Context<SetPaymentBudget>,
+    daily_limit_usd: u64,
+    assets: Vec<BudgetAsset>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require!(
+        assets.len() <= MAX_BUDGET_ASSETS,
+        ErrorCode::TooManyBudgetAssets
+    );
+    for (i, asset) in assets.iter().enumerate() {
+        require!(
+            !assets[..i].iter().any(|other| other.mint == asset.mint),
+            ErrorCode::TooManyBudgetAssets
+        );
+    }
+
+    let budget = &mut ctx.accounts.payment_budget;
+    if budget.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    budget.argent_account = ctx.accounts.argent_account.key();
+    budget.daily_limit_usd = daily_limit_usd;
+    budget.assets = assets;
+    budget.bump = ctx.bumps.payment_budget;
+
+    wallet_state.next_state_version();
+
+    msg!("Payment budget set: {} USD micro-units a day", daily_limit_usd);
+    Ok(())
+}
+
+pub(crate) fn remove_payment_budget(ctx: Context<RemovePaymentBudget>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_closed();
+
+    wallet_state.next_state_version();
+
+    msg!("Payment budget removed!");
+    Ok(())
+}
+
+pub(crate) fn execute_payment(
+    ctx: Context<ExecutePayment>,
+    reference: Pubkey,
+    amount: u64,
+    mint: Option<Pubkey>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require_keys_eq!(
+        ctx.accounts.reference.key(),
+        reference,
+        ErrorCode::InvalidPaymentReference
+    );
+    require_not_frozen(&ctx.accounts.asset_freeze, &mint)?;
+    charge_payment_budget(
+        &ctx.accounts.payment_budget,
+        ctx.accounts.price_feed.as_ref(),
+        &mint,
+        ctx.accounts.mint.as_ref().map_or(9, |mint| mint.decimals),
+        amount,
+    )?;
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let recipient_key = ctx.accounts.recipient.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+
+    pay_from_vault(
+        &ctx.accounts.vault,
+        vault_seeds,
+        &ctx.accounts.recipient,
+        &ctx.accounts.reference,
+        mint,
+        ctx.accounts.mint.as_ref(),
+        ctx.accounts.vault_token_account.as_ref(),
+        ctx.accounts.recipient_token_account.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        &ctx.accounts.system_program,
+        amount,
+    )?;
+
+    let argent_account = &ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = PaymentSent {
+        argent_account: argent_key,
+        notification_tag: argent_account.notification_tag,
+        recipient: recipient_key,
+        mint,
+        amount,
+        reference,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Payment of {} sent to {}", amount, recipient_key);
+    Ok(())
+}
+
+pub(crate) fn set_label_policy(
+    ctx: Context<SetLabelPolicy>,
+    required: bool,
+    categories: Vec<String>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require!(
+        categories.len() <= MAX_LABEL_CATEGORIES,
+        ErrorCode::TooManyLabelCategories
+    );
+    for (i, category) in categories.iter().enumerate() {
+        require!(
+            !category.is_empty()
+                && category.len() <= MAX_LABEL_LEN
+                && !categories[..i].contains(category),
+            ErrorCode::InvalidLabelCategory
+        );
+    }
+    require!(!required || !categories.is_empty(), ErrorCode::InvalidLabelCategory);
+
+    let policy = &mut ctx.accounts.label_policy;
+    if policy.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    policy.argent_account = ctx.accounts.argent_account.key();
+    policy.required = required;
+    policy.categories = categories;
+    policy.bump = ctx.bumps.label_policy;
+
+    wallet_state.next_state_version();
+
+    msg!("Label policy updated!");
+    Ok(())
+}
+
+pub(crate) fn propose_payment(
+    ctx: Context<ProposePayment>,
+    reference: Pubkey,
+    amount: u64,
+    mint: Option<Pubkey>,
+    category: String,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+    wallet_state.account_opened();
+
+    require_label(&ctx.accounts.label_policy, &category)?;
+    let recipient_key = ctx.accounts.recipient.key();
+    let balance_changes = payment_balance_changes(
+        ctx.accounts.vault.key(),
+        recipient_key,
+        mint,
+        ctx.accounts.mint.as_ref(),
+        amount,
+    )?;
+
+    let proposal = &mut ctx.accounts.payment_proposal;
+    proposal.argent_account = ctx.accounts.argent_account.key();
+    proposal.owner = ctx.accounts.owner.key();
+    proposal.recipient = recipient_key;
+    proposal.reference = reference;
+    proposal.mint = mint;
+    proposal.amount = amount;
+    proposal.balance_changes = balance_changes;
+    proposal.category = category.clone();
+    proposal.payer = ctx.accounts.payer.key();
+    proposal.bump = ctx.bumps.payment_proposal;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = PaymentProposed {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.owner.key(),
+        recipient: recipient_key,
+        mint,
+        amount,
+        reference,
+        category,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Payment of {} to {} proposed", amount, recipient_key);
+    Ok(())
+}
+
+pub(crate) fn approve_payment(ctx: Context<ApprovePayment>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.guardian.key()])?;
+    wallet_state.account_closed();
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    let proposal = &ctx.accounts.payment_proposal;
+    require_not_frozen(&ctx.accounts.asset_freeze, &proposal.mint)?;
+    charge_payment_budget(
+        &ctx.accounts.payment_budget,
+        ctx.accounts.price_feed.as_ref(),
+        &proposal.mint,
+        ctx.accounts.mint.as_ref().map_or(9, |mint| mint.decimals),
+        proposal.amount,
+    )?;
+
+    pay_from_vault(
+        &ctx.accounts.vault,
+        vault_seeds,
+        &ctx.accounts.recipient,
+        &ctx.accounts.reference,
+        proposal.mint,
+        ctx.accounts.mint.as_ref(),
+        ctx.accounts.vault_token_account.as_ref(),
+        ctx.accounts.recipient_token_account.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        &ctx.accounts.system_program,
+        proposal.amount,
+    )?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = PaymentSent {
+        argent_account: argent_key,
+        notification_tag: argent_account.notification_tag,
+        recipient: proposal.recipient,
+        mint: proposal.mint,
+        amount: proposal.amount,
+        reference: proposal.reference,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Payment of {} sent to {}", proposal.amount, proposal.recipient);
+    Ok(())
+}
+
+pub(crate) fn propose_typed_intent(
+    ctx: Context<ProposeTypedIntent>,
+    reference: Pubkey,
+    amount: u64,
+    mint: Option<Pubkey>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+    wallet_state.account_opened();
+
+    require!(amount > 0, ErrorCode::InvalidPaymentAmount);
+    let decimals = match mint {
+        None => 9,
+        Some(mint_key) => {
+            let Some(mint_account) = &ctx.accounts.mint else {
+                return err!(ErrorCode::MissingPaymentAccounts);
+            };
+            require_keys_eq!(mint_account.key(), mint_key, ErrorCode::InvalidTokenAccount);
+            mint_account.decimals
+        }
+    };
+    let argent_key = ctx.accounts.argent_account.key();
+    let recipient_key = ctx.accounts.recipient.key();
+    let summary = TransferIntent {
+        wallet: argent_key,
+        nonce: wallet_state.state_version,
+        recipient: recipient_key,
+        mint,
+        amount,
+        decimals,
+    }
+    .summary();
+    require!(
+        summary.len() <= MAX_INTENT_SUMMARY_LEN,
+        ErrorCode::IntentSummaryTooLong
+    );
+    let summary_hash = hash(summary.as_bytes()).to_bytes();
+
+    let intent = &mut ctx.accounts.typed_intent;
+    intent.argent_account = argent_key;
+    intent.owner = ctx.accounts.owner.key();
+    intent.recipient = recipient_key;
+    intent.reference = reference;
+    intent.mint = mint;
+    intent.amount = amount;
+    intent.summary = summary.clone();
+    intent.payer = ctx.accounts.payer.key();
+    intent.bump = ctx.bumps.typed_intent;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = TypedIntentProposed {
+        argent_account: argent_key,
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.owner.key(),
+        reference,
+        summary,
+        summary_hash,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Typed intent {} proposed", reference);
+    Ok(())
+}
+
+pub(crate) fn execute_typed_intent(
+    ctx: Context<ExecuteTypedIntent>,
+    guardian_signature: [u8; 64],
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.account_closed();
+    let intent = &ctx.accounts.typed_intent;
+    let signed = verify_ed25519_instruction(
+        &ctx.accounts.sysvar_instructions,
+        &ctx.accounts.guardian.key(),
+        &guardian_signature,
+    )?;
+    let summary_hash = hash(intent.summary.as_bytes()).to_bytes();
+    require!(
+        signed == summary_hash,
+        ErrorCode::OffchainMessageMismatch
+    );
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.guardian.key()])?;
+
+    require_not_frozen(&ctx.accounts.asset_freeze, &intent.mint)?;
+    charge_payment_budget(
+        &ctx.accounts.payment_budget,
+        ctx.accounts.price_feed.as_ref(),
+        &intent.mint,
+        ctx.accounts.mint.as_ref().map_or(9, |mint| mint.decimals),
+        intent.amount,
+    )?;
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    pay_from_vault(
+        &ctx.accounts.vault,
+        vault_seeds,
+        &ctx.accounts.recipient,
+        &ctx.accounts.reference,
+        intent.mint,
+        ctx.accounts.mint.as_ref(),
+        ctx.accounts.vault_token_account.as_ref(),
+        ctx.accounts.recipient_token_account.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        &ctx.accounts.system_program,
+        intent.amount,
+    )?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = TypedIntentExecuted {
+        argent_account: argent_key,
+        notification_tag: argent_account.notification_tag,
+        guardian: ctx.accounts.guardian.key(),
+        recipient: intent.recipient,
+        mint: intent.mint,
+        amount: intent.amount,
+        reference: intent.reference,
+        summary_hash,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Typed intent {} executed", intent.reference);
+    Ok(())
+}
+
+pub(crate) fn cancel_typed_intent(ctx: Context<CancelTypedIntent>, _reference: Pubkey) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+    wallet_state.account_closed();
+    wallet_state.next_state_version();
+
+    msg!("Typed intent cancelled!");
+    Ok(())
+}
+
+pub(crate) fn cancel_payment(ctx: Context<CancelPayment>, _reference: Pubkey) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+    wallet_state.account_closed();
+    wallet_state.next_state_version();
+
+    msg!("Payment proposal cancelled!");
+    Ok(())
+}
+
+pub(crate) fn replace_payment(
+    ctx: Context<ReplacePayment>,
+    old_reference: Pubkey,
+    reference: Pubkey,
+    amount: u64,
+    mint: Option<Pubkey>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+
+    let recipient_key = ctx.accounts.recipient.key();
+    let balance_changes = payment_balance_changes(
+        ctx.accounts.vault.key(),
+        recipient_key,
+        mint,
+        ctx.accounts.mint.as_ref(),
+        amount,
+    )?;
+    let category = ctx.accounts.old_payment_proposal.category.clone();
+
+    let proposal = &mut ctx.accounts.payment_proposal;
+    proposal.argent_account = ctx.accounts.argent_account.key();
+    proposal.owner = ctx.accounts.owner.key();
+    proposal.recipient = recipient_key;
+    proposal.reference = reference;
+    proposal.mint = mint;
+    proposal.amount = amount;
+    proposal.balance_changes = balance_changes;
+    proposal.category = category.clone();
+    proposal.payer = ctx.accounts.payer.key();
+    proposal.bump = ctx.bumps.payment_proposal;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let proposed = PaymentProposed {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.owner.key(),
+        recipient: recipient_key,
+        mint,
+        amount,
+        reference,
+        category,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&proposed);
+    emit_cpi!(proposed);
+    let replaced = PaymentReplaced {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        old_reference,
+        reference,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&replaced);
+    emit_cpi!(replaced);
+
+    msg!("Payment proposal {} replaced by {}", old_reference, reference);
+    Ok(())
+}
@@ -0,0 +1,350 @@
+// Quorum recovery
+//
+// Replacing the owner through a quorum of recovery keys set in the wallet's recovery policy,
+// after its own delay.
+
+use super::*;
+
+#[derive(Accounts)]
+pub struct SetRecoveryPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = RecoveryPolicy::SPACE,
+        seeds = [b"recovery_policy", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub recovery_policy: Account<'info, RecoveryPolicy>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: Instructions sysvar, holding the recovery contacts' signature verifications
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveRecoveryPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"recovery_policy", argent_account.key().as_ref()],
+        bump = recovery_policy.bump,
+    )]
+    pub recovery_policy: Account<'info, RecoveryPolicy>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitiateQuorumRecovery<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"recovery_policy", argent_account.key().as_ref()],
+        bump = recovery_policy.bump,
+    )]
+    pub recovery_policy: Account<'info, RecoveryPolicy>,
+    /// CHECK: Instructions sysvar, holding the new owner's signature verification
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelQuorumRecovery<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"recovery_policy", argent_account.key().as_ref()],
+        bump = recovery_policy.bump,
+    )]
+    pub recovery_policy: Account<'info, RecoveryPolicy>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CompleteQuorumRecovery<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        mut,
+        seeds = [b"recovery_policy", argent_account.key().as_ref()],
+        bump = recovery_policy.bump,
+    )]
+    pub recovery_policy: Account<'info, RecoveryPolicy>,
+    // Anyone
+    pub cranker: Signer<'info>,
+    /// CHECK: The wallet's notification config PDA, which has no data when none is set; a
+    /// configured notification program is passed as the first remaining account
+    #[account(seeds = [b"notification_config", argent_account.key().as_ref()], bump)]
+    pub notification_config: UncheckedAccount<'info>,
+}
+
+pub(crate) fn set_recovery_policy(
+    ctx: Context<SetRecoveryPolicy>,
+    contacts: Vec<Pubkey>,
+    threshold: u8,
+    delay: i64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require!(
+        contacts.len() <= MAX_RECOVERY_CONTACTS,
+        ErrorCode::TooManyRecoveryContacts
+    );
+    require!(
+        core::is_valid_threshold(threshold as usize, contacts.len()),
+        ErrorCode::InvalidRecoveryThreshold
+    );
+    require!(
+        delay > 0 && delay < ctx.accounts.argent_account.security_period,
+        ErrorCode::InvalidRecoveryDelay
+    );
+    let argent_account = &ctx.accounts.argent_account;
+    for (i, contact) in contacts.iter().enumerate() {
+        require!(
+            !argent_account.is_signer_role(contact) && !contacts[..i].contains(contact),
+            ErrorCode::InvalidRecoveryContact
+        );
+        require_key_registration(
+            &ctx.accounts.sysvar_instructions,
+            &argent_account.key(),
+            wallet_state.state_version,
+            OffchainAction::RecoveryContact,
+            contact,
+        )?;
+    }
+
+    let policy = &mut ctx.accounts.recovery_policy;
+    if policy.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    policy.argent_account = ctx.accounts.argent_account.key();
+    policy.contacts = contacts;
+    policy.threshold = threshold;
+    policy.delay = delay;
+    // A new policy drops any recovery started under the previous one
+    policy.pending_owner = None;
+    policy.initiated_at = 0;
+    policy.bump = ctx.bumps.recovery_policy;
+
+    wallet_state.next_state_version();
+
+    msg!(
+        "Recovery policy set: {} of {} contacts, {}s delay",
+        threshold,
+        policy.contacts.len(),
+        delay
+    );
+    Ok(())
+}
+
+pub(crate) fn remove_recovery_policy(ctx: Context<RemoveRecoveryPolicy>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_closed();
+
+    wallet_state.next_state_version();
+
+    msg!("Recovery policy removed!");
+    Ok(())
+}
+
+pub(crate) fn initiate_quorum_recovery(
+    ctx: Context<InitiateQuorumRecovery>,
+    new_owner: Pubkey,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.guardian.key()])?;
+
+    let argent_account = &ctx.accounts.argent_account;
+    let policy = &mut ctx.accounts.recovery_policy;
+    let now = Clock::get()?.unix_timestamp;
+
+    // Fail if escape guardian in progress
+    require!(
+        wallet_state.escape_type() != EscapeType::Guardian,
+        ErrorCode::EscapeGuardianInProgress
+    );
+    require!(policy.pending_owner.is_none(), ErrorCode::RecoveryInProgress);
+
+    let approvals = core::distinct_approvals(
+        &policy.contacts,
+        ctx.remaining_accounts
+            .iter()
+            .filter(|contact| contact.is_signer)
+            .map(|contact| contact.key()),
+    );
+    require!(
+        core::meets_threshold(approvals.len(), policy.threshold as usize),
+        ErrorCode::RecoveryQuorumNotMet
+    );
+    require_key_registration(
+        &ctx.accounts.sysvar_instructions,
+        &argent_account.key(),
+        wallet_state.state_version,
+        OffchainAction::ChangeOwner,
+        &new_owner,
+    )?;
+
+    policy.pending_owner = Some(new_owner);
+    policy.initiated_at = now;
+
+    wallet_state.next_state_version();
+
+    wallet_state.escape_stats.record_triggered(EscapeType::Owner, now);
+    let event = RecoveryInitiated {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        guardian: ctx.accounts.guardian.key(),
+        contacts: approvals,
+        new_owner,
+        initiated_at: now,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Quorum recovery initiated!");
+    Ok(())
+}
+
+pub(crate) fn cancel_quorum_recovery(ctx: Context<CancelQuorumRecovery>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+
+    let argent_account = &ctx.accounts.argent_account;
+    let policy = &mut ctx.accounts.recovery_policy;
+
+    require!(policy.pending_owner.is_some(), ErrorCode::NoRecoveryInProgress);
+    policy.pending_owner = None;
+    policy.initiated_at = 0;
+
+    wallet_state.next_state_version();
+
+    let now = Clock::get()?.unix_timestamp;
+    wallet_state.escape_stats.record_cancelled(EscapeType::Owner, now);
+    let event = RecoveryCancelled {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.owner.key(),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Quorum recovery cancelled!");
+    Ok(())
+}
+
+pub(crate) fn complete_quorum_recovery<'info>(
+    ctx: Context<'_, '_, '_, 'info, CompleteQuorumRecovery<'info>>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    let argent_account = &mut ctx.accounts.argent_account;
+    let policy = &mut ctx.accounts.recovery_policy;
+    let now = Clock::get()?.unix_timestamp;
+
+    let new_owner = policy.pending_owner.ok_or(ErrorCode::NoRecoveryInProgress)?;
+    require!(
+        core::has_elapsed(policy.initiated_at, policy.delay, now),
+        ErrorCode::SecurityPeriodNotElapsed
+    );
+
+    policy.pending_owner = None;
+    policy.initiated_at = 0;
+
+    wallet_state.next_state_version();
+
+    let old_key = std::mem::replace(&mut argent_account.owner, new_owner);
+    argent_account.require_distinct_roles()?;
+
+    wallet_state.escape_stats.record_completed(EscapeType::Owner, now);
+    let event = EscapeCompleted {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        escape_type: EscapeType::Owner,
+        completed_by: ctx.accounts.cranker.key(),
+        old_key,
+        new_key: new_owner,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+    notify_escape(
+        &ctx.accounts.notification_config,
+        ctx.remaining_accounts,
+        &argent_account.to_account_info(),
+        &event,
+    )?;
+
+    if wallet_state.escape_type() == EscapeType::Owner {
+        wallet_state.clear_escape();
+    }
+
+    msg!("Owner recovered by quorum!");
+    Ok(())
+}
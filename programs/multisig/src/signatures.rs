@@ -0,0 +1,168 @@
+// Message signatures
+//
+// Messages the wallet approves as signed by it, and the policy on who must approve them.
+
+use super::*;
+
+#[derive(Accounts)]
+pub struct SetMessagePolicy<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_hash: [u8; 32])]
+pub struct ApproveMessage<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    #[account(constraint = argent_account.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: Option<Signer<'info>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = MessageApproval::SPACE,
+        seeds = [b"message", argent_account.key().as_ref(), message_hash.as_ref()],
+        bump,
+    )]
+    pub message_approval: Account<'info, MessageApproval>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_hash: [u8; 32])]
+pub struct RevokeMessage<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"message", argent_account.key().as_ref(), message_hash.as_ref()],
+        bump,
+    )]
+    pub message_approval: Account<'info, MessageApproval>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_hash: [u8; 32])]
+pub struct IsValidSignature<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    // Omitted when the message was never approved
+    #[account(seeds = [b"message", argent_account.key().as_ref(), message_hash.as_ref()], bump)]
+    pub message_approval: Option<Account<'info, MessageApproval>>,
+}
+
+pub(crate) fn set_message_policy(
+    ctx: Context<SetMessagePolicy>,
+    requires_guardian: bool,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+
+    argent_account.message_requires_guardian = requires_guardian;
+
+    wallet_state.next_state_version();
+
+    msg!("Message policy updated!");
+    Ok(())
+}
+
+pub(crate) fn approve_message(ctx: Context<ApproveMessage>, message_hash: [u8; 32]) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    let mut signers = vec![ctx.accounts.owner.key()];
+    signers.extend(ctx.accounts.guardian.as_ref().map(|guardian| guardian.key()));
+    wallet_state.record_activity(&ctx.accounts.argent_account, &signers)?;
+
+    if ctx.accounts.argent_account.message_requires_guardian {
+        require!(
+            ctx.accounts.guardian.is_some(),
+            ErrorCode::GuardianSignatureMissing
+        );
+    }
+
+    let approval = &mut ctx.accounts.message_approval;
+    if approval.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    approval.argent_account = ctx.accounts.argent_account.key();
+    approval.message_hash = message_hash;
+    approval.owner = ctx.accounts.owner.key();
+    approval.guardian = ctx.accounts.guardian.as_ref().map(|guardian| guardian.key());
+    approval.approved_at = Clock::get()?.unix_timestamp;
+
+    wallet_state.next_state_version();
+
+    msg!("Message approved!");
+    Ok(())
+}
+
+pub(crate) fn revoke_message(ctx: Context<RevokeMessage>, _message_hash: [u8; 32]) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+    wallet_state.account_closed();
+
+    wallet_state.next_state_version();
+
+    msg!("Message approval revoked!");
+    Ok(())
+}
+
+pub(crate) fn is_valid_signature(
+    ctx: Context<IsValidSignature>,
+    _message_hash: [u8; 32],
+) -> Result<bool> {
+    let argent_account = &ctx.accounts.argent_account;
+    let Some(approval) = &ctx.accounts.message_approval else {
+        return Ok(false);
+    };
+
+    let owner_valid = approval.owner == argent_account.owner;
+    let guardian_valid = !argent_account.message_requires_guardian
+        || approval.guardian == Some(argent_account.guardian);
+    Ok(owner_valid && guardian_valid)
+}
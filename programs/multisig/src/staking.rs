@@ -0,0 +1,574 @@
+// Staking
+//
+// Depositing vault SOL into stake pools and withdrawing it, and compounding the rewards of the
+// vault's stake accounts.
+
+use super::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DepositStakePoolSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// The wallet vault PDA funding the deposit
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    /// CHECK: Stake pool account, validated by the stake pool program
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: Stake pool withdraw authority PDA, validated by the stake pool program
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: Reserve stake account, validated by the stake pool program
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+    #[account(mut, mint::token_program = token_program)]
+    pub pool_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = pool_mint,
+        token::authority = vault,
+        token::token_program = token_program,
+    )]
+    pub pool_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: Manager fee account, validated by the stake pool program
+    #[account(mut)]
+    pub manager_fee_account: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    /// CHECK: The SPL stake pool program
+    #[account(address = SPL_STAKE_POOL_PROGRAM_ID)]
+    pub stake_pool_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetStakeCompounding<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = StakeCompounding::SPACE,
+        seeds = [b"stake_compounding", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub stake_compounding: Account<'info, StakeCompounding>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveStakeCompounding<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"stake_compounding", argent_account.key().as_ref()],
+        bump = stake_compounding.bump,
+    )]
+    pub stake_compounding: Account<'info, StakeCompounding>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CompoundRewards<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        seeds = [b"stake_compounding", argent_account.key().as_ref()],
+        bump = stake_compounding.bump,
+    )]
+    pub stake_compounding: Account<'info, StakeCompounding>,
+    /// The wallet vault PDA, staker and withdrawer of both stake accounts
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: Stake account delegated with the vault as its authorities, checked by the handler
+    #[account(mut, owner = anchor_lang::solana_program::stake::program::ID)]
+    pub stake_account: UncheckedAccount<'info>,
+    /// CHECK: Staging stake account derived from the vault with compounding_seed, checked by
+    /// the handler
+    #[account(mut)]
+    pub staging_stake_account: UncheckedAccount<'info>,
+    /// CHECK: Vote account the stake account is delegated to, checked by the handler
+    pub vote_account: UncheckedAccount<'info>,
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+    pub stake_history: Sysvar<'info, StakeHistory>,
+    /// CHECK: The stake config account, still passed to DelegateStake
+    #[account(address = anchor_lang::solana_program::stake::config::ID)]
+    pub stake_config: UncheckedAccount<'info>,
+    /// CHECK: The native stake program
+    #[account(address = anchor_lang::solana_program::stake::program::ID)]
+    pub stake_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawStakePoolSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// The wallet vault PDA receiving the withdrawn SOL
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: Stake pool account, validated by the stake pool program
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: Stake pool withdraw authority PDA, validated by the stake pool program
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: Reserve stake account, validated by the stake pool program
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+    #[account(mut, mint::token_program = token_program)]
+    pub pool_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = pool_mint,
+        token::authority = vault,
+        token::token_program = token_program,
+    )]
+    pub pool_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: Manager fee account, validated by the stake pool program
+    #[account(mut)]
+    pub manager_fee_account: UncheckedAccount<'info>,
+    pub clock: Sysvar<'info, Clock>,
+    pub stake_history: Sysvar<'info, StakeHistory>,
+    /// CHECK: The native stake program
+    #[account(address = anchor_lang::solana_program::stake::program::ID)]
+    pub stake_program: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    /// CHECK: The SPL stake pool program
+    #[account(address = SPL_STAKE_POOL_PROGRAM_ID)]
+    pub stake_pool_program: UncheckedAccount<'info>,
+}
+
+pub(crate) fn deposit_stake_pool_sol(
+    ctx: Context<DepositStakePoolSol>,
+    lamports: u64,
+    minimum_pool_tokens_out: u64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require_not_frozen(&ctx.accounts.asset_freeze, &None)?;
+
+    use anchor_lang::solana_program::{
+        instruction::{Instruction, AccountMeta},
+        program::invoke_signed,
+    };
+
+    let pool_tokens_before = ctx.accounts.pool_token_account.amount;
+
+    let mut data = vec![STAKE_POOL_DEPOSIT_SOL];
+    data.extend_from_slice(&lamports.to_le_bytes());
+    let deposit_ix = Instruction {
+        program_id: SPL_STAKE_POOL_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(ctx.accounts.stake_pool.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.stake_pool_withdraw_authority.key(), false),
+            AccountMeta::new(ctx.accounts.reserve_stake.key(), false),
+            AccountMeta::new(ctx.accounts.vault.key(), true),
+            AccountMeta::new(ctx.accounts.pool_token_account.key(), false),
+            AccountMeta::new(ctx.accounts.manager_fee_account.key(), false),
+            AccountMeta::new(ctx.accounts.pool_token_account.key(), false),
+            AccountMeta::new(ctx.accounts.pool_mint.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+        ],
+        data,
+    };
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    invoke_signed(
+        &deposit_ix,
+        &[
+            ctx.accounts.stake_pool.to_account_info(),
+            ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+            ctx.accounts.reserve_stake.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.pool_token_account.to_account_info(),
+            ctx.accounts.manager_fee_account.to_account_info(),
+            ctx.accounts.pool_mint.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.stake_pool_program.to_account_info(),
+        ],
+        &[vault_seeds],
+    )?;
+
+    // Enforce slippage on what actually arrived in the vault
+    ctx.accounts.pool_token_account.reload()?;
+    let pool_tokens_out = ctx
+        .accounts
+        .pool_token_account
+        .amount
+        .saturating_sub(pool_tokens_before);
+    require!(
+        pool_tokens_out >= minimum_pool_tokens_out,
+        ErrorCode::SlippageExceeded
+    );
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = StakePoolDeposited {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        stake_pool: ctx.accounts.stake_pool.key(),
+        lamports,
+        pool_tokens_out,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Deposited {} lamports for {} pool tokens", lamports, pool_tokens_out);
+    Ok(())
+}
+
+pub(crate) fn withdraw_stake_pool_sol(
+    ctx: Context<WithdrawStakePoolSol>,
+    pool_tokens: u64,
+    minimum_lamports_out: u64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    use anchor_lang::solana_program::{
+        instruction::{Instruction, AccountMeta},
+        program::invoke_signed,
+        sysvar,
+    };
+
+    let lamports_before = ctx.accounts.vault.lamports();
+
+    let mut data = vec![STAKE_POOL_WITHDRAW_SOL];
+    data.extend_from_slice(&pool_tokens.to_le_bytes());
+    let withdraw_ix = Instruction {
+        program_id: SPL_STAKE_POOL_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(ctx.accounts.stake_pool.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.stake_pool_withdraw_authority.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.vault.key(), true),
+            AccountMeta::new(ctx.accounts.pool_token_account.key(), false),
+            AccountMeta::new(ctx.accounts.reserve_stake.key(), false),
+            AccountMeta::new(ctx.accounts.vault.key(), false),
+            AccountMeta::new(ctx.accounts.manager_fee_account.key(), false),
+            AccountMeta::new(ctx.accounts.pool_mint.key(), false),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(sysvar::stake_history::ID, false),
+            AccountMeta::new_readonly(ctx.accounts.stake_program.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+        ],
+        data,
+    };
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    invoke_signed(
+        &withdraw_ix,
+        &[
+            ctx.accounts.stake_pool.to_account_info(),
+            ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.pool_token_account.to_account_info(),
+            ctx.accounts.reserve_stake.to_account_info(),
+            ctx.accounts.manager_fee_account.to_account_info(),
+            ctx.accounts.pool_mint.to_account_info(),
+            ctx.accounts.clock.to_account_info(),
+            ctx.accounts.stake_history.to_account_info(),
+            ctx.accounts.stake_program.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.stake_pool_program.to_account_info(),
+        ],
+        &[vault_seeds],
+    )?;
+
+    // Enforce slippage on what actually arrived in the vault
+    let lamports_out = ctx.accounts.vault.lamports().saturating_sub(lamports_before);
+    require!(
+        lamports_out >= minimum_lamports_out,
+        ErrorCode::SlippageExceeded
+    );
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = StakePoolWithdrawn {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        stake_pool: ctx.accounts.stake_pool.key(),
+        pool_tokens,
+        lamports_out,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Withdrew {} pool tokens for {} lamports", pool_tokens, lamports_out);
+    Ok(())
+}
+
+pub(crate) fn set_stake_compounding(
+    ctx: Context<SetStakeCompounding>,
+    min_lamports: u64,
+    max_lamports: u64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+
+    require!(
+        min_lamports > 0 && min_lamports <= max_lamports,
+        ErrorCode::InvalidCompoundingBounds
+    );
+
+    let compounding = &mut ctx.accounts.stake_compounding;
+    if compounding.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    compounding.argent_account = ctx.accounts.argent_account.key();
+    compounding.min_lamports = min_lamports;
+    compounding.max_lamports = max_lamports;
+    compounding.bump = ctx.bumps.stake_compounding;
+
+    wallet_state.next_state_version();
+
+    msg!("Stake compounding enabled!");
+    Ok(())
+}
+
+pub(crate) fn remove_stake_compounding(ctx: Context<RemoveStakeCompounding>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+    wallet_state.account_closed();
+
+    wallet_state.next_state_version();
+
+    msg!("Stake compounding removed!");
+    Ok(())
+}
+
+pub(crate) fn compound_rewards(ctx: Context<CompoundRewards>) -> Result<()> {
+    let wallet_state = ctx.accounts.wallet_state.load()?;
+    wallet_state.header.require_compatible()?;
+    use anchor_lang::solana_program::program::invoke_signed;
+    use anchor_lang::solana_program::stake::{instruction as stake_instruction, state};
+
+    let vault_key = ctx.accounts.vault.key();
+    let (meta, delegation) = {
+        let data = ctx.accounts.stake_account.try_borrow_data()?;
+        match state::StakeStateV2::deserialize(&mut &data[..]) {
+            Ok(state::StakeStateV2::Stake(meta, stake, _)) => (meta, stake.delegation),
+            _ => return err!(ErrorCode::InvalidStakeAccount),
+        }
+    };
+    require!(
+        meta.authorized.staker == vault_key && meta.authorized.withdrawer == vault_key,
+        ErrorCode::InvalidStakeAccount
+    );
+    require_keys_eq!(
+        ctx.accounts.vote_account.key(),
+        delegation.voter_pubkey,
+        ErrorCode::InvalidStakeAccount
+    );
+    let seed = compounding_seed(&ctx.accounts.stake_account.key());
+    require_keys_eq!(
+        ctx.accounts.staging_stake_account.key(),
+        Pubkey::create_with_seed(&vault_key, &seed, &ctx.accounts.stake_program.key())
+            .map_err(|_| ErrorCode::SeedAddressMismatch)?,
+        ErrorCode::SeedAddressMismatch
+    );
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    let staging = &ctx.accounts.staging_stake_account;
+
+    // Only a staging account the vault created and handed to the stake program holds a
+    // delegation to merge; lamports sent to the bare address don't
+    if *staging.owner == ctx.accounts.stake_program.key() {
+        let staged = staging.lamports();
+        // Merge the staged delegation back; the stake program refuses while it is still
+        // activating
+        for merge_ix in stake_instruction::merge(
+            &ctx.accounts.stake_account.key(),
+            &ctx.accounts.staging_stake_account.key(),
+            &vault_key,
+        ) {
+            invoke_signed(
+                &merge_ix,
+                &[
+                    ctx.accounts.stake_account.to_account_info(),
+                    ctx.accounts.staging_stake_account.to_account_info(),
+                    ctx.accounts.clock.to_account_info(),
+                    ctx.accounts.stake_history.to_account_info(),
+                    ctx.accounts.vault.to_account_info(),
+                    ctx.accounts.stake_program.to_account_info(),
+                ],
+                &[vault_seeds],
+            )?;
+        }
+
+        emit_cpi!(RewardsCompounded {
+            argent_account: argent_key,
+            notification_tag: ctx.accounts.argent_account.notification_tag,
+            stake_account: ctx.accounts.stake_account.key(),
+            lamports: staged,
+            state_version: wallet_state.state_version,
+        });
+        msg!("Merged {} staged lamports", staged);
+        return Ok(());
+    }
+
+    // The staging account's own rent reserve comes out of the undelegated lamports too
+    let staging_rent = Rent::get()?.minimum_balance(state::StakeStateV2::size_of());
+    let undelegated = ctx
+        .accounts
+        .stake_account
+        .lamports()
+        .saturating_sub(delegation.stake)
+        .saturating_sub(meta.rent_exempt_reserve)
+        .saturating_sub(staging_rent);
+    let compounding = &ctx.accounts.stake_compounding;
+    require!(
+        undelegated >= compounding.min_lamports,
+        ErrorCode::NothingToCompound
+    );
+    let lamports = undelegated.min(compounding.max_lamports);
+
+    invoke_signed(
+        &stake_instruction::withdraw(
+            &ctx.accounts.stake_account.key(),
+            &vault_key,
+            &vault_key,
+            lamports + staging_rent,
+            None,
+        ),
+        &[
+            ctx.accounts.stake_account.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.clock.to_account_info(),
+            ctx.accounts.stake_history.to_account_info(),
+            ctx.accounts.stake_program.to_account_info(),
+        ],
+        &[vault_seeds],
+    )?;
+    create_staging_account(
+        &ctx.accounts.system_program,
+        &ctx.accounts.vault,
+        &ctx.accounts.staging_stake_account,
+        &ctx.accounts.stake_program.key(),
+        vault_seeds,
+        &seed,
+        lamports + staging_rent,
+        state::StakeStateV2::size_of() as u64,
+    )?;
+    // Same authorities and lockup as the stake account, which merging requires
+    invoke_signed(
+        &stake_instruction::initialize(
+            &ctx.accounts.staging_stake_account.key(),
+            &meta.authorized,
+            &meta.lockup,
+        ),
+        &[
+            ctx.accounts.staging_stake_account.to_account_info(),
+            ctx.accounts.rent.to_account_info(),
+            ctx.accounts.stake_program.to_account_info(),
+        ],
+        &[vault_seeds],
+    )?;
+    invoke_signed(
+        &stake_instruction::delegate_stake(
+            &ctx.accounts.staging_stake_account.key(),
+            &vault_key,
+            &delegation.voter_pubkey,
+        ),
+        &[
+            ctx.accounts.staging_stake_account.to_account_info(),
+            ctx.accounts.vote_account.to_account_info(),
+            ctx.accounts.clock.to_account_info(),
+            ctx.accounts.stake_history.to_account_info(),
+            ctx.accounts.stake_config.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.stake_program.to_account_info(),
+        ],
+        &[vault_seeds],
+    )?;
+
+    emit_cpi!(RewardsStaged {
+        argent_account: argent_key,
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        stake_account: ctx.accounts.stake_account.key(),
+        staging_stake_account: ctx.accounts.staging_stake_account.key(),
+        lamports,
+        state_version: wallet_state.state_version,
+    });
+    msg!("Staged {} lamports for compounding", lamports);
+    Ok(())
+}
@@ -0,0 +1,629 @@
+// Streams and vesting
+//
+// Payment streams and vesting schedules paid out of escrows funded by the vault.
+
+use super::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(recipient: Pubkey, rate_per_second: u64, mint: Option<Pubkey>)]
+pub struct CreateStream<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = Stream::SPACE,
+        seeds = [b"stream", argent_account.key().as_ref(), recipient.as_ref(), stream_mint_seed(&mint)],
+        bump,
+    )]
+    pub stream: Account<'info, Stream>,
+    /// The wallet vault PDA funding the stream
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    // Token streams only
+    #[account(mint::token_program = token_program)]
+    pub stream_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = stream_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = stream_mint,
+        associated_token::authority = stream,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawStream<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        mut,
+        seeds = [
+            b"stream",
+            argent_account.key().as_ref(),
+            stream.recipient.as_ref(),
+            stream_mint_seed(&stream.mint),
+        ],
+        bump = stream.bump,
+        has_one = recipient @ ErrorCode::InvalidStreamRecipient,
+    )]
+    pub stream: Account<'info, Stream>,
+    /// CHECK: The stream recipient, receiving SOL directly or owning the token account
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+    // Token streams only; checked in the handler
+    pub stream_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = stream_mint,
+        associated_token::authority = stream,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub recipient_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelStream<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [
+            b"stream",
+            argent_account.key().as_ref(),
+            stream.recipient.as_ref(),
+            stream_mint_seed(&stream.mint),
+        ],
+        bump = stream.bump,
+        has_one = recipient @ ErrorCode::InvalidStreamRecipient,
+    )]
+    pub stream: Account<'info, Stream>,
+    /// CHECK: The stream recipient, receiving SOL directly or owning the token account
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+    /// The wallet vault PDA receiving the unstreamed remainder
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    // Token streams only; checked in the handler
+    pub stream_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = stream_mint,
+        associated_token::authority = stream,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub recipient_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = stream_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(beneficiary: Pubkey)]
+pub struct CreateVesting<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = Vesting::SPACE,
+        seeds = [b"vesting", argent_account.key().as_ref(), beneficiary.as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub vesting: Account<'info, Vesting>,
+    /// The wallet vault PDA funding the schedule
+    #[account(seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        mut,
+        seeds = [
+            b"vesting",
+            argent_account.key().as_ref(),
+            beneficiary.key().as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump = vesting.bump,
+    )]
+    pub vesting: Account<'info, Vesting>,
+    pub beneficiary: Signer<'info>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = beneficiary,
+        token::token_program = token_program,
+    )]
+    pub beneficiary_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// The wallet vault PDA, receiving the rent of a fully claimed schedule
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub(crate) fn create_stream(
+    ctx: Context<CreateStream>,
+    recipient: Pubkey,
+    rate_per_second: u64,
+    mint: Option<Pubkey>,
+    end_time: i64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_opened();
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(rate_per_second > 0 && end_time > now, ErrorCode::InvalidStream);
+    require_not_frozen(&ctx.accounts.asset_freeze, &mint)?;
+    let deposited = rate_per_second
+        .checked_mul((end_time - now) as u64)
+        .ok_or(ErrorCode::InvalidStream)?;
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+
+    // Escrow the whole stream
+    match mint {
+        None => {
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.vault.to_account_info(),
+                        to: ctx.accounts.stream.to_account_info(),
+                    },
+                    &[vault_seeds],
+                ),
+                deposited,
+            )?;
+        }
+        Some(mint_key) => {
+            let accounts = &ctx.accounts;
+            let (
+                Some(stream_mint),
+                Some(vault_token_account),
+                Some(escrow_token_account),
+                Some(token_program),
+            ) = (
+                &accounts.stream_mint,
+                &accounts.vault_token_account,
+                &accounts.escrow_token_account,
+                &accounts.token_program,
+            )
+            else {
+                return err!(ErrorCode::MissingStreamAccounts);
+            };
+            require_keys_eq!(stream_mint.key(), mint_key, ErrorCode::InvalidTokenAccount);
+
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    token_interface::TransferChecked {
+                        from: vault_token_account.to_account_info(),
+                        mint: stream_mint.to_account_info(),
+                        to: escrow_token_account.to_account_info(),
+                        authority: accounts.vault.to_account_info(),
+                    },
+                    &[vault_seeds],
+                ),
+                deposited,
+                stream_mint.decimals,
+            )?;
+        }
+    }
+
+    let stream = &mut ctx.accounts.stream;
+    stream.argent_account = argent_key;
+    stream.recipient = recipient;
+    stream.mint = mint;
+    stream.rate_per_second = rate_per_second;
+    stream.start_time = now;
+    stream.end_time = end_time;
+    stream.deposited = deposited;
+    stream.withdrawn = 0;
+    stream.bump = ctx.bumps.stream;
+
+    wallet_state.next_state_version();
+
+    let event = StreamCreated {
+        argent_account: argent_key,
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        recipient,
+        mint,
+        rate_per_second,
+        end_time,
+        deposited,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Stream of {} per second created for {}", rate_per_second, recipient);
+    Ok(())
+}
+
+pub(crate) fn withdraw_stream(ctx: Context<WithdrawStream>) -> Result<()> {
+    let wallet_state = ctx.accounts.wallet_state.load()?;
+    wallet_state.header.require_compatible()?;
+    let now = Clock::get()?.unix_timestamp;
+    let amount = ctx.accounts.stream.withdrawable(now);
+    require!(amount > 0, ErrorCode::NothingToWithdraw);
+
+    pay_stream(
+        &ctx.accounts.argent_account.key(),
+        &ctx.accounts.stream,
+        &ctx.accounts.recipient,
+        ctx.accounts.stream_mint.as_ref(),
+        ctx.accounts.escrow_token_account.as_ref(),
+        ctx.accounts.recipient_token_account.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        amount,
+    )?;
+    ctx.accounts.stream.withdrawn += amount;
+
+    emit_cpi!(StreamWithdrawn {
+        argent_account: ctx.accounts.argent_account.key(),
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        recipient: ctx.accounts.recipient.key(),
+        mint: ctx.accounts.stream.mint,
+        amount,
+        state_version: wallet_state.state_version,
+    });
+
+    msg!("Withdrew {} from stream", amount);
+    Ok(())
+}
+
+pub(crate) fn cancel_stream(ctx: Context<CancelStream>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_closed();
+
+    let now = Clock::get()?.unix_timestamp;
+    let stream = &ctx.accounts.stream;
+    let accrued = stream.withdrawable(now);
+    let remainder = stream.deposited - stream.withdrawn - accrued;
+    let argent_key = ctx.accounts.argent_account.key();
+
+    if accrued > 0 {
+        pay_stream(
+            &argent_key,
+            stream,
+            &ctx.accounts.recipient,
+            ctx.accounts.stream_mint.as_ref(),
+            ctx.accounts.escrow_token_account.as_ref(),
+            ctx.accounts.recipient_token_account.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+            accrued,
+        )?;
+    }
+
+    // SOL left in the stream goes back with the account when it closes; token escrows are
+    // emptied into the vault and closed
+    if stream.mint.is_some() {
+        let accounts = &ctx.accounts;
+        let (
+            Some(stream_mint),
+            Some(escrow_token_account),
+            Some(vault_token_account),
+            Some(token_program),
+        ) = (
+            &accounts.stream_mint,
+            &accounts.escrow_token_account,
+            &accounts.vault_token_account,
+            &accounts.token_program,
+        )
+        else {
+            return err!(ErrorCode::MissingStreamAccounts);
+        };
+        let mint_seed = stream_mint_seed(&stream.mint);
+        let stream_seeds: &[&[u8]] = &[
+            b"stream",
+            argent_key.as_ref(),
+            stream.recipient.as_ref(),
+            mint_seed,
+            &[stream.bump],
+        ];
+
+        if remainder > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    token_interface::TransferChecked {
+                        from: escrow_token_account.to_account_info(),
+                        mint: stream_mint.to_account_info(),
+                        to: vault_token_account.to_account_info(),
+                        authority: stream.to_account_info(),
+                    },
+                    &[stream_seeds],
+                ),
+                remainder,
+                stream_mint.decimals,
+            )?;
+        }
+        token_interface::close_account(CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token_interface::CloseAccount {
+                account: escrow_token_account.to_account_info(),
+                destination: accounts.vault.to_account_info(),
+                authority: stream.to_account_info(),
+            },
+            &[stream_seeds],
+        ))?;
+    }
+
+    wallet_state.next_state_version();
+
+    let event = StreamCancelled {
+        argent_account: argent_key,
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        recipient: stream.recipient,
+        mint: stream.mint,
+        paid: accrued,
+        refunded: remainder,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Stream cancelled, {} returned to the vault", remainder);
+    Ok(())
+}
+
+pub(crate) fn create_vesting(
+    ctx: Context<CreateVesting>,
+    beneficiary: Pubkey,
+    amount: u64,
+    cliff_period: i64,
+    duration: i64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_opened();
+
+    require!(
+        amount > 0 && duration > 0 && (0..=duration).contains(&cliff_period),
+        ErrorCode::InvalidVesting
+    );
+    require_not_frozen(&ctx.accounts.asset_freeze, &Some(ctx.accounts.mint.key()))?;
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+
+    // Escrow the whole schedule
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let vesting = &mut ctx.accounts.vesting;
+    vesting.argent_account = argent_key;
+    vesting.beneficiary = beneficiary;
+    vesting.mint = ctx.accounts.mint.key();
+    vesting.amount = amount;
+    vesting.start_time = now;
+    vesting.cliff_period = cliff_period;
+    vesting.duration = duration;
+    vesting.claimed = 0;
+    vesting.bump = ctx.bumps.vesting;
+
+    wallet_state.next_state_version();
+
+    let event = VestingCreated {
+        argent_account: argent_key,
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        beneficiary,
+        mint: vesting.mint,
+        amount,
+        start_time: now,
+        cliff_period,
+        duration,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Vesting of {} created for {}", amount, beneficiary);
+    Ok(())
+}
+
+pub(crate) fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    let now = Clock::get()?.unix_timestamp;
+    let vesting = &ctx.accounts.vesting;
+    let amount = vesting.claimable(now);
+    require!(amount > 0, ErrorCode::NothingToClaim);
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let (beneficiary_key, mint_key) = (vesting.beneficiary, vesting.mint);
+    let vesting_seeds: &[&[u8]] = &[
+        b"vesting",
+        argent_key.as_ref(),
+        beneficiary_key.as_ref(),
+        mint_key.as_ref(),
+        &[vesting.bump],
+    ];
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::TransferChecked {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.beneficiary_token_account.to_account_info(),
+                authority: vesting.to_account_info(),
+            },
+            &[vesting_seeds],
+        ),
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+    ctx.accounts.vesting.claimed += amount;
+
+    emit_cpi!(VestedClaimed {
+        argent_account: argent_key,
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        beneficiary: ctx.accounts.beneficiary.key(),
+        mint: ctx.accounts.mint.key(),
+        amount,
+        state_version: wallet_state.state_version,
+    });
+
+    // A fully claimed schedule hands its escrow's and its own rent back to the vault
+    let vesting = &ctx.accounts.vesting;
+    if vesting.fully_claimed() {
+        token_interface::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::CloseAccount {
+                account: ctx.accounts.escrow_token_account.to_account_info(),
+                destination: ctx.accounts.vault.to_account_info(),
+                authority: vesting.to_account_info(),
+            },
+            &[vesting_seeds],
+        ))?;
+        ctx.accounts
+            .vesting
+            .close(ctx.accounts.vault.to_account_info())?;
+        wallet_state.account_closed();
+    }
+
+    msg!("Claimed {} vested tokens", amount);
+    Ok(())
+}
@@ -0,0 +1,580 @@
+// Trading
+//
+// Swaps through the wallet's allowlisted routers, and limit orders filled through them from
+// an escrow.
+
+use super::*;
+
+#[derive(Accounts)]
+pub struct SetSwapRouters<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = SwapRouterAllowlist::SPACE,
+        seeds = [b"swap_routers", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub swap_router_allowlist: Account<'info, SwapRouterAllowlist>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// The wallet vault PDA, authority over the swapped tokens
+    #[account(seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    #[account(seeds = [b"swap_routers", argent_account.key().as_ref()], bump)]
+    pub swap_router_allowlist: Account<'info, SwapRouterAllowlist>,
+    /// CHECK: Checked against the swap router allowlist
+    #[account(executable)]
+    pub router_program: UncheckedAccount<'info>,
+    #[account(token::authority = vault)]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(order_id: u64, sell_mint: Pubkey)]
+pub struct CreateLimitOrder<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = LimitOrder::SPACE,
+        seeds = [b"limit_order", argent_account.key().as_ref(), order_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub limit_order: Account<'info, LimitOrder>,
+    /// The wallet vault PDA, authority over the tokens sold
+    #[account(seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        address = sell_mint @ ErrorCode::InvalidTokenAccount,
+        mint::token_program = token_program,
+    )]
+    pub sell_token_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = sell_token_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = sell_token_mint,
+        associated_token::authority = limit_order,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FillLimitOrder<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        mut,
+        seeds = [b"limit_order", argent_account.key().as_ref(), limit_order.order_id.to_le_bytes().as_ref()],
+        bump = limit_order.bump,
+    )]
+    pub limit_order: Account<'info, LimitOrder>,
+    /// The wallet vault PDA, receiving the order's rent once it is filled
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    #[account(
+        address = limit_order.sell_mint @ ErrorCode::InvalidTokenAccount,
+        mint::token_program = token_program,
+    )]
+    pub sell_token_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = sell_token_mint,
+        associated_token::authority = limit_order,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    // Vault token account the proceeds must arrive in
+    #[account(
+        mut,
+        token::mint = limit_order.buy_mint,
+        token::authority = vault,
+    )]
+    pub vault_buy_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(seeds = [b"swap_routers", argent_account.key().as_ref()], bump)]
+    pub swap_router_allowlist: Account<'info, SwapRouterAllowlist>,
+    /// CHECK: Checked against the swap router allowlist
+    #[account(executable)]
+    pub router_program: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelLimitOrder<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        constraint = authority.key() == argent_account.owner
+            || authority.key() == argent_account.guardian @ ErrorCode::NotOwnerOrGuardian
+    )]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"limit_order", argent_account.key().as_ref(), limit_order.order_id.to_le_bytes().as_ref()],
+        bump = limit_order.bump,
+    )]
+    pub limit_order: Account<'info, LimitOrder>,
+    /// The wallet vault PDA receiving the unsold tokens and the rent
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        address = limit_order.sell_mint @ ErrorCode::InvalidTokenAccount,
+        mint::token_program = token_program,
+    )]
+    pub sell_token_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = sell_token_mint,
+        associated_token::authority = limit_order,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = sell_token_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub(crate) fn set_swap_routers(ctx: Context<SetSwapRouters>, routers: Vec<Pubkey>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require!(
+        routers.len() <= MAX_SWAP_ROUTERS,
+        ErrorCode::TooManySwapRouters
+    );
+
+    let allowlist = &mut ctx.accounts.swap_router_allowlist;
+    if allowlist.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    allowlist.argent_account = ctx.accounts.argent_account.key();
+    allowlist.routers = routers;
+
+    wallet_state.next_state_version();
+
+    msg!("Swap routers updated!");
+    Ok(())
+}
+
+pub(crate) fn swap<'info>(
+    ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+    data: Vec<u8>,
+    minimum_amount_out: u64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    // The router is signed for by the vault and could move any of its assets
+    require!(ctx.accounts.asset_freeze.data_is_empty(), ErrorCode::AssetFrozen);
+
+    let router_key = ctx.accounts.router_program.key();
+    require!(
+        ctx.accounts.swap_router_allowlist.routers.contains(&router_key),
+        ErrorCode::SwapRouterNotAllowed
+    );
+
+    use anchor_lang::solana_program::{
+        instruction::{Instruction, AccountMeta},
+        program::invoke_signed,
+    };
+
+    let vault_key = ctx.accounts.vault.key();
+    let accounts = ctx
+        .remaining_accounts
+        .iter()
+        .map(|info| AccountMeta {
+            pubkey: info.key(),
+            is_signer: info.is_signer || info.key() == vault_key,
+            is_writable: info.is_writable,
+        })
+        .collect();
+    let swap_ix = Instruction {
+        program_id: router_key,
+        accounts,
+        data,
+    };
+
+    let amount_before = ctx.accounts.destination_token_account.amount;
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    let mut account_infos = ctx.remaining_accounts.to_vec();
+    account_infos.push(ctx.accounts.router_program.to_account_info());
+    invoke_signed(&swap_ix, &account_infos, &[vault_seeds])?;
+
+    // Enforce the minimum out on what actually arrived in the vault, which must still hold it
+    ctx.accounts.destination_token_account.reload()?;
+    let destination = &ctx.accounts.destination_token_account;
+    require!(
+        destination.owner == vault_key && destination.delegate.is_none(),
+        ErrorCode::InvalidTokenAccount
+    );
+    let amount_out = destination.amount.saturating_sub(amount_before);
+    require!(
+        amount_out >= minimum_amount_out,
+        ErrorCode::SlippageExceeded
+    );
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = Swapped {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        router: router_key,
+        destination_token_account: ctx.accounts.destination_token_account.key(),
+        mint: ctx.accounts.destination_token_account.mint,
+        amount_out,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Swapped through {} for {} tokens", router_key, amount_out);
+    Ok(())
+}
+
+pub(crate) fn create_limit_order(
+    ctx: Context<CreateLimitOrder>,
+    order_id: u64,
+    sell_mint: Pubkey,
+    buy_mint: Pubkey,
+    amount: u64,
+    min_price: u64,
+    expiry: i64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_opened();
+
+    require!(
+        amount > 0
+            && min_price > 0
+            && expiry > Clock::get()?.unix_timestamp
+            && sell_mint != buy_mint,
+        ErrorCode::InvalidLimitOrder
+    );
+    require_not_frozen(&ctx.accounts.asset_freeze, &Some(sell_mint))?;
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.sell_token_mint.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        amount,
+        ctx.accounts.sell_token_mint.decimals,
+    )?;
+
+    let order = &mut ctx.accounts.limit_order;
+    order.argent_account = argent_key;
+    order.order_id = order_id;
+    order.sell_mint = sell_mint;
+    order.buy_mint = buy_mint;
+    order.amount = amount;
+    order.min_price = min_price;
+    order.expiry = expiry;
+    order.sold = 0;
+    order.received = 0;
+    order.bump = ctx.bumps.limit_order;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = LimitOrderCreated {
+        argent_account: argent_key,
+        notification_tag: argent_account.notification_tag,
+        order_id,
+        sell_mint,
+        buy_mint,
+        amount,
+        min_price,
+        expiry,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Limit order {} placed for {} tokens", order_id, amount);
+    Ok(())
+}
+
+pub(crate) fn fill_limit_order<'info>(
+    ctx: Context<'_, '_, '_, 'info, FillLimitOrder<'info>>,
+    data: Vec<u8>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    use anchor_lang::solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program::invoke_signed,
+    };
+
+    let order = &ctx.accounts.limit_order;
+    require!(
+        Clock::get()?.unix_timestamp < order.expiry,
+        ErrorCode::LimitOrderExpired
+    );
+    require_not_frozen(&ctx.accounts.asset_freeze, &Some(order.sell_mint))?;
+    let router_key = ctx.accounts.router_program.key();
+    require!(
+        ctx.accounts.swap_router_allowlist.routers.contains(&router_key),
+        ErrorCode::SwapRouterNotAllowed
+    );
+
+    let order_key = order.key();
+    let accounts = ctx
+        .remaining_accounts
+        .iter()
+        .map(|info| AccountMeta {
+            pubkey: info.key(),
+            is_signer: info.is_signer || info.key() == order_key,
+            is_writable: info.is_writable,
+        })
+        .collect();
+    let fill_ix = Instruction {
+        program_id: router_key,
+        accounts,
+        data,
+    };
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let order_id_bytes = order.order_id.to_le_bytes();
+    let order_seeds: &[&[u8]] = &[
+        b"limit_order",
+        argent_key.as_ref(),
+        order_id_bytes.as_ref(),
+        &[order.bump],
+    ];
+    let escrow_before = ctx.accounts.escrow_token_account.amount;
+    let received_before = ctx.accounts.vault_buy_token_account.amount;
+
+    let mut account_infos = ctx.remaining_accounts.to_vec();
+    account_infos.push(ctx.accounts.router_program.to_account_info());
+    invoke_signed(&fill_ix, &account_infos, &[order_seeds])?;
+
+    // The order may only have sold from its escrow, which must still be its own
+    ctx.accounts.escrow_token_account.reload()?;
+    ctx.accounts.vault_buy_token_account.reload()?;
+    let escrow = &ctx.accounts.escrow_token_account;
+    require!(
+        escrow.owner == order_key && escrow.delegate.is_none(),
+        ErrorCode::InvalidTokenAccount
+    );
+    let sold = escrow_before.saturating_sub(escrow.amount);
+    let received = ctx
+        .accounts
+        .vault_buy_token_account
+        .amount
+        .saturating_sub(received_before);
+    require!(
+        core::meets_limit_price(sold, received, ctx.accounts.limit_order.min_price),
+        ErrorCode::LimitPriceNotMet
+    );
+
+    let remaining = escrow.amount;
+    let order = &mut ctx.accounts.limit_order;
+    order.sold += sold;
+    order.received += received;
+
+    emit_cpi!(LimitOrderFilled {
+        argent_account: argent_key,
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        order_id: order.order_id,
+        router: router_key,
+        sold,
+        received,
+        remaining,
+        state_version: wallet_state.state_version,
+    });
+
+    // A filled order hands its rent back to the vault
+    if remaining == 0 {
+        token_interface::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::CloseAccount {
+                account: ctx.accounts.escrow_token_account.to_account_info(),
+                destination: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.limit_order.to_account_info(),
+            },
+            &[order_seeds],
+        ))?;
+        ctx.accounts
+            .limit_order
+            .close(ctx.accounts.vault.to_account_info())?;
+        wallet_state.account_closed();
+    }
+
+    msg!("Limit order sold {} for {}", sold, received);
+    Ok(())
+}
+
+pub(crate) fn cancel_limit_order(ctx: Context<CancelLimitOrder>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.authority.key()],
+    )?;
+    wallet_state.account_closed();
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let order = &ctx.accounts.limit_order;
+    let order_id_bytes = order.order_id.to_le_bytes();
+    let order_seeds: &[&[u8]] = &[
+        b"limit_order",
+        argent_key.as_ref(),
+        order_id_bytes.as_ref(),
+        &[order.bump],
+    ];
+    let refunded = ctx.accounts.escrow_token_account.amount;
+
+    if refunded > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    mint: ctx.accounts.sell_token_mint.to_account_info(),
+                    to: ctx.accounts.vault_token_account.to_account_info(),
+                    authority: order.to_account_info(),
+                },
+                &[order_seeds],
+            ),
+            refunded,
+            ctx.accounts.sell_token_mint.decimals,
+        )?;
+    }
+    token_interface::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        token_interface::CloseAccount {
+            account: ctx.accounts.escrow_token_account.to_account_info(),
+            destination: ctx.accounts.vault.to_account_info(),
+            authority: order.to_account_info(),
+        },
+        &[order_seeds],
+    ))?;
+
+    let order_id = order.order_id;
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = LimitOrderCancelled {
+        argent_account: argent_key,
+        notification_tag: argent_account.notification_tag,
+        order_id,
+        refunded,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Limit order {} cancelled, {} returned to the vault", order_id, refunded);
+    Ok(())
+}
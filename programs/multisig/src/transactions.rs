@@ -0,0 +1,1193 @@
+// Transaction approval and execution
+//
+// The pending transaction a wallet executes with both keys, the guardian's proposals and
+// standalone approvals of it, immediate execution of allowlisted instructions, and a wallet
+// signing for another wallet it guards.
+
+use super::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Execute<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    /// CHECK: Must be the wallet guardian; signs live unless a guardian approval is given
+    #[account(mut, constraint = argent_account.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"pending_tx", argent_account.key().as_ref()], bump)]
+    pub pending_transaction: AccountLoader<'info, PendingTransaction>,
+    // Approval posted by the guardian, closed back to it once used
+    #[account(
+        mut,
+        close = guardian,
+        seeds = [b"guardian_approval", argent_account.key().as_ref(), guardian_approval.data_hash.as_ref()],
+        bump,
+    )]
+    pub guardian_approval: Option<Account<'info, GuardianApproval>>,
+    #[account(mut, seeds = [b"history", argent_account.key().as_ref()], bump)]
+    pub history: Option<AccountLoader<'info, History>>,
+    /// CHECK: The wallet's proposal nonce PDA, which has no data when it has none
+    #[account(seeds = [b"proposal_nonce", argent_account.key().as_ref()], bump)]
+    pub proposal_nonce: UncheckedAccount<'info>,
+    /// CHECK: Instructions sysvar, holding the nonce advance that starts the transaction
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeGuardianTransaction<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = GuardianProposal::SPACE,
+        seeds = [b"guardian_proposal", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub guardian_proposal: Account<'info, GuardianProposal>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ApproveGuardianProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [b"pending_tx", argent_account.key().as_ref()], bump)]
+    pub pending_transaction: AccountLoader<'info, PendingTransaction>,
+    // Lapses when the guardian who proposed it has been replaced
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"guardian_proposal", argent_account.key().as_ref()],
+        bump = guardian_proposal.bump,
+        has_one = payer,
+        constraint = guardian_proposal.guardian == argent_account.guardian @ ErrorCode::InvalidGuardian,
+    )]
+    pub guardian_proposal: Account<'info, GuardianProposal>,
+    /// CHECK: Receives the proposal rent; must match the payer recorded in the proposal
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"history", argent_account.key().as_ref()], bump)]
+    pub history: Option<AccountLoader<'info, History>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RejectGuardianProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        constraint = authority.key() == argent_account.owner
+            || authority.key() == argent_account.guardian @ ErrorCode::NotOwnerOrGuardian
+    )]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"guardian_proposal", argent_account.key().as_ref()],
+        bump = guardian_proposal.bump,
+        has_one = payer,
+    )]
+    pub guardian_proposal: Account<'info, GuardianProposal>,
+    /// CHECK: Receives the proposal rent; must match the payer recorded in the proposal
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteImmediate<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// The wallet vault PDA, signing the executed instructions
+    #[account(seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    /// CHECK: The wallet's writable allowlist PDA, read in the handler; absent when writes
+    /// are unrestricted
+    #[account(seeds = [b"writable_allowlist", argent_account.key().as_ref()], bump)]
+    pub writable_allowlist: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetWritableAllowlist<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = WritableAllowlist::SPACE,
+        seeds = [b"writable_allowlist", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub writable_allowlist: Account<'info, WritableAllowlist>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveWritableAllowlist<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"writable_allowlist", argent_account.key().as_ref()],
+        bump = writable_allowlist.bump,
+    )]
+    pub writable_allowlist: Account<'info, WritableAllowlist>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ActAsGuardian<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateVaultAccount<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// The wallet vault PDA, funding the new account
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    /// CHECK: Created by the System program; a signer unless derived from the vault with a seed
+    #[account(mut)]
+    pub new_account: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetProposalNonce<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ProposalNonce::SPACE,
+        seeds = [b"proposal_nonce", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub proposal_nonce: Account<'info, ProposalNonce>,
+    /// CHECK: Must be an initialized System nonce account; its data is parsed in the handler
+    #[account(owner = anchor_lang::solana_program::system_program::ID @ ErrorCode::InvalidNonceAccount)]
+    pub nonce_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveProposalNonce<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"proposal_nonce", argent_account.key().as_ref()],
+        bump = proposal_nonce.bump,
+    )]
+    pub proposal_nonce: Account<'info, ProposalNonce>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetComputeBudget<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(mut, seeds = [b"pending_tx", argent_account.key().as_ref()], bump)]
+    pub pending_transaction: AccountLoader<'info, PendingTransaction>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ForceAbandonTransaction<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(mut, seeds = [b"pending_tx", argent_account.key().as_ref()], bump)]
+    pub pending_transaction: AccountLoader<'info, PendingTransaction>,
+    #[account(mut, seeds = [b"history", argent_account.key().as_ref()], bump)]
+    pub history: Option<AccountLoader<'info, History>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(data_hash: [u8; 32])]
+pub struct PostGuardianApproval<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = GuardianApproval::SPACE,
+        seeds = [b"guardian_approval", argent_account.key().as_ref(), data_hash.as_ref()],
+        bump,
+    )]
+    pub guardian_approval: Account<'info, GuardianApproval>,
+    // Separate from the guardian, which may be a wallet account signing through act_as_guardian
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(data_hash: [u8; 32])]
+pub struct PostSignedGuardianApproval<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    /// CHECK: Must be the wallet guardian; its signature is verified by the ed25519 program
+    #[account(constraint = argent_account.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = GuardianApproval::SPACE,
+        seeds = [b"guardian_approval", argent_account.key().as_ref(), data_hash.as_ref()],
+        bump,
+    )]
+    pub guardian_approval: Account<'info, GuardianApproval>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Instructions sysvar, holding the guardian's signature verification
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(data_hash: [u8; 32])]
+pub struct RevokeGuardianApproval<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        mut,
+        close = guardian,
+        seeds = [b"guardian_approval", argent_account.key().as_ref(), data_hash.as_ref()],
+        bump,
+        has_one = guardian @ ErrorCode::InvalidGuardian,
+    )]
+    pub guardian_approval: Account<'info, GuardianApproval>,
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+}
+
+pub(crate) fn execute(ctx: Context<Execute>, data: Vec<u8>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    let mut signers = vec![ctx.accounts.owner.key()];
+    if ctx.accounts.guardian.is_signer {
+        signers.push(ctx.accounts.guardian.key());
+    }
+    wallet_state.record_activity(&ctx.accounts.argent_account, &signers)?;
+    if ctx.accounts.guardian_approval.is_some() {
+        wallet_state.account_closed();
+    }
+
+    require_proposal_nonce_advance(
+        &ctx.accounts.proposal_nonce,
+        &ctx.accounts.sysvar_instructions,
+    )?;
+
+    let argent_account = &ctx.accounts.argent_account;
+
+    // The owner is a Signer; only the guardian may approve in advance instead of signing
+    let guardian_signed = ctx.accounts.guardian.is_signer;
+
+    require!(
+        data.len() <= MAX_PENDING_TX_DATA_LEN,
+        ErrorCode::TransactionTooLarge
+    );
+
+    let data_hash = hash(&data).to_bytes();
+    match &ctx.accounts.guardian_approval {
+        Some(approval) => approval.require_valid(
+            &argent_account.guardian,
+            &data_hash,
+            Clock::get()?.unix_timestamp,
+            argent_account.approval_ttl,
+        )?,
+        None => require!(guardian_signed, ErrorCode::GuardianSignatureMissing),
+    }
+
+    wallet_state.next_state_version();
+
+    let event = TransactionApproved {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.owner.key(),
+        guardian: ctx.accounts.guardian.key(),
+        data_hash,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    if let Some(history) = &ctx.accounts.history {
+        let mut history = history.load_mut()?;
+        history.header.require_compatible()?;
+        history.record(HistoryEntry {
+            timestamp: Clock::get()?.unix_timestamp,
+            payload_hash: data_hash,
+            actors: [ctx.accounts.owner.key(), ctx.accounts.guardian.key()],
+            kind: HISTORY_KIND_EXECUTE,
+            ..Default::default()
+        });
+    }
+
+    // Store the transaction data for execution
+    let mut pending_transaction = ctx.accounts.pending_transaction.load_mut()?;
+    pending_transaction.header.require_compatible()?;
+    pending_transaction.store(&data)?;
+
+    msg!("Transaction approved and ready for execution!");
+    Ok(())
+}
+
+pub(crate) fn propose_guardian_transaction(
+    ctx: Context<ProposeGuardianTransaction>,
+    data: Vec<u8>,
+    reason: String,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.guardian.key()])?;
+    wallet_state.account_opened();
+
+    require!(
+        data.len() <= MAX_PENDING_TX_DATA_LEN,
+        ErrorCode::TransactionTooLarge
+    );
+    require!(
+        reason.len() <= MAX_PROPOSAL_REASON_LEN,
+        ErrorCode::ProposalReasonTooLong
+    );
+
+    let data_hash = hash(&data).to_bytes();
+    let proposal = &mut ctx.accounts.guardian_proposal;
+    proposal.argent_account = ctx.accounts.argent_account.key();
+    proposal.guardian = ctx.accounts.guardian.key();
+    proposal.data = data;
+    proposal.reason = reason.clone();
+    proposal.proposed_at = Clock::get()?.unix_timestamp;
+    proposal.payer = ctx.accounts.payer.key();
+    proposal.bump = ctx.bumps.guardian_proposal;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = GuardianTransactionProposed {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        guardian: ctx.accounts.guardian.key(),
+        data_hash,
+        reason,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Guardian proposed a transaction");
+    Ok(())
+}
+
+pub(crate) fn approve_guardian_proposal(
+    ctx: Context<ApproveGuardianProposal>,
+    data_hash: [u8; 32],
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+    wallet_state.account_closed();
+
+    let proposal = &ctx.accounts.guardian_proposal;
+    let argent_account = &mut ctx.accounts.argent_account;
+    require!(
+        hash(&proposal.data).to_bytes() == data_hash,
+        ErrorCode::GuardianProposalMismatch
+    );
+
+    wallet_state.next_state_version();
+
+    let event = GuardianProposalApproved {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.owner.key(),
+        guardian: proposal.guardian,
+        data_hash,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    if let Some(history) = &ctx.accounts.history {
+        let mut history = history.load_mut()?;
+        history.header.require_compatible()?;
+        history.record(HistoryEntry {
+            timestamp: Clock::get()?.unix_timestamp,
+            payload_hash: data_hash,
+            actors: [ctx.accounts.owner.key(), proposal.guardian],
+            kind: HISTORY_KIND_EXECUTE,
+            ..Default::default()
+        });
+    }
+
+    let mut pending_transaction = ctx.accounts.pending_transaction.load_mut()?;
+    pending_transaction.header.require_compatible()?;
+    pending_transaction.store(&proposal.data)?;
+
+    msg!("Guardian proposal approved and ready for execution!");
+    Ok(())
+}
+
+pub(crate) fn reject_guardian_proposal(ctx: Context<RejectGuardianProposal>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.authority.key()],
+    )?;
+    wallet_state.account_closed();
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = GuardianProposalRejected {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        authority: ctx.accounts.authority.key(),
+        data_hash: hash(&ctx.accounts.guardian_proposal.data).to_bytes(),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Guardian proposal rejected!");
+    Ok(())
+}
+
+pub(crate) fn execute_immediate<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteImmediate<'info>>,
+    instructions: Vec<VaultInstruction>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    use anchor_lang::solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program::invoke_signed,
+    };
+
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require_compute_units(instructions.len())?;
+    // Arbitrary instructions could move any vault asset
+    require!(
+        ctx.accounts.asset_freeze.data_is_empty(),
+        ErrorCode::AssetFrozen
+    );
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_key = ctx.accounts.vault.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+    let writable_allowlist = load_writable_allowlist(&ctx.accounts.writable_allowlist)?;
+    let remaining_account = |index: u8| {
+        ctx.remaining_accounts
+            .get(index as usize)
+            .ok_or(ErrorCode::InvalidAccountIndex)
+    };
+
+    for instruction in &instructions {
+        let program = remaining_account(instruction.program_index)?;
+        // Re-entering the wallet program would let the vault sign its own instructions
+        require_keys_neq!(program.key(), crate::ID, ErrorCode::SelfCpiNotAllowed);
+
+        let mut metas = Vec::with_capacity(instruction.account_indexes.len());
+        let mut account_infos = Vec::with_capacity(instruction.account_indexes.len() + 1);
+        for &index in &instruction.account_indexes {
+            let info = remaining_account(index)?;
+            if let Some(allowlist) = &writable_allowlist {
+                require!(
+                    !info.is_writable || allowlist.allows(info, &vault_key),
+                    ErrorCode::WritableAccountNotAllowed
+                );
+            }
+            metas.push(AccountMeta {
+                pubkey: info.key(),
+                is_signer: info.is_signer || info.key() == vault_key,
+                is_writable: info.is_writable,
+            });
+            account_infos.push(info.clone());
+        }
+        account_infos.push(program.clone());
+
+        invoke_signed(
+            &Instruction {
+                program_id: program.key(),
+                accounts: metas,
+                data: instruction.data.clone(),
+            },
+            &account_infos,
+            &[vault_seeds],
+        )?;
+    }
+
+    let argent_account = &ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let data: Vec<&[u8]> = instructions.iter().map(|ix| ix.data.as_slice()).collect();
+    let event = TransactionExecuted {
+        argent_account: argent_key,
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        data_hash: hashv(&data).to_bytes(),
+        instruction_count: instructions.len() as u32,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Executed {} instructions", instructions.len());
+    Ok(())
+}
+
+pub(crate) fn set_writable_allowlist(
+    ctx: Context<SetWritableAllowlist>,
+    addresses: Vec<Pubkey>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require!(
+        addresses.len() <= MAX_WRITABLE_ADDRESSES,
+        ErrorCode::TooManyWritableAddresses
+    );
+
+    let allowlist = &mut ctx.accounts.writable_allowlist;
+    if allowlist.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    allowlist.argent_account = ctx.accounts.argent_account.key();
+    allowlist.addresses = addresses;
+    allowlist.bump = ctx.bumps.writable_allowlist;
+
+    wallet_state.next_state_version();
+
+    msg!("Writable allowlist updated!");
+    Ok(())
+}
+
+pub(crate) fn remove_writable_allowlist(ctx: Context<RemoveWritableAllowlist>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_closed();
+
+    wallet_state.next_state_version();
+
+    msg!("Writable allowlist removed!");
+    Ok(())
+}
+
+pub(crate) fn act_as_guardian<'info>(
+    ctx: Context<'_, '_, '_, 'info, ActAsGuardian<'info>>,
+    data: Vec<u8>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    use anchor_lang::solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program::invoke_signed,
+    };
+
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require!(
+        is_guardian_role_instruction(&data),
+        ErrorCode::NotAGuardianInstruction
+    );
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let creator = ctx.accounts.argent_account.creator;
+    let index_bytes = ctx.accounts.argent_account.index.to_le_bytes();
+    let wallet_seeds: &[&[u8]] = &[
+        b"argent",
+        creator.as_ref(),
+        index_bytes.as_ref(),
+        &[ctx.accounts.argent_account.bump],
+    ];
+
+    let metas = ctx
+        .remaining_accounts
+        .iter()
+        .map(|info| AccountMeta {
+            pubkey: info.key(),
+            is_signer: info.is_signer || info.key() == argent_key,
+            is_writable: info.is_writable,
+        })
+        .collect();
+    let mut account_infos = ctx.remaining_accounts.to_vec();
+    account_infos.push(ctx.accounts.program.to_account_info());
+
+    // The inner instruction may write the wallet account too: hand it the recorded activity,
+    // then pick up its changes instead of overwriting them with the stale copy on exit
+    ctx.accounts.argent_account.exit(&crate::ID)?;
+    invoke_signed(
+        &Instruction {
+            program_id: crate::ID,
+            accounts: metas,
+            data: data.clone(),
+        },
+        &account_infos,
+        &[wallet_seeds],
+    )?;
+    ctx.accounts.argent_account.reload()?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = TransactionExecuted {
+        argent_account: argent_key,
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        data_hash: hash(&data).to_bytes(),
+        instruction_count: 1,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Instruction signed by the wallet as guardian");
+    Ok(())
+}
+
+pub(crate) fn set_proposal_nonce(ctx: Context<SetProposalNonce>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let nonce_account = &ctx.accounts.nonce_account;
+    let authority = core::nonce_authority(&nonce_account.try_borrow_data()?)
+        .map(Pubkey::new_from_array)
+        .ok_or(ErrorCode::InvalidNonceAccount)?;
+    require!(
+        authority == ctx.accounts.argent_account.owner
+            || authority == ctx.accounts.argent_account.guardian,
+        ErrorCode::InvalidNonceAccount
+    );
+
+    let proposal_nonce = &mut ctx.accounts.proposal_nonce;
+    if proposal_nonce.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    proposal_nonce.argent_account = ctx.accounts.argent_account.key();
+    proposal_nonce.nonce_account = nonce_account.key();
+    proposal_nonce.bump = ctx.bumps.proposal_nonce;
+
+    wallet_state.next_state_version();
+
+    msg!("Proposal nonce set to {}", nonce_account.key());
+    Ok(())
+}
+
+pub(crate) fn remove_proposal_nonce(ctx: Context<RemoveProposalNonce>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_closed();
+
+    wallet_state.next_state_version();
+
+    msg!("Proposal nonce removed!");
+    Ok(())
+}
+
+pub(crate) fn create_vault_account(
+    ctx: Context<CreateVaultAccount>,
+    lamports: u64,
+    space: u64,
+    program_owner: Pubkey,
+    seed: Option<String>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    use anchor_lang::solana_program::system_instruction::MAX_PERMITTED_DATA_LENGTH;
+
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require_not_frozen(&ctx.accounts.asset_freeze, &None)?;
+
+    require!(
+        space <= MAX_PERMITTED_DATA_LENGTH,
+        ErrorCode::InvalidAccountSpace
+    );
+    require!(
+        lamports >= Rent::get()?.minimum_balance(space as usize),
+        ErrorCode::AccountNotRentExempt
+    );
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_key = ctx.accounts.vault.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+
+    match &seed {
+        Some(seed) => {
+            let address = Pubkey::create_with_seed(&vault_key, seed, &program_owner)
+                .map_err(|_| ErrorCode::SeedAddressMismatch)?;
+            require_keys_eq!(
+                ctx.accounts.new_account.key(),
+                address,
+                ErrorCode::SeedAddressMismatch
+            );
+
+            system_program::create_account_with_seed(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::CreateAccountWithSeed {
+                        from: ctx.accounts.vault.to_account_info(),
+                        to: ctx.accounts.new_account.to_account_info(),
+                        base: ctx.accounts.vault.to_account_info(),
+                    },
+                    &[vault_seeds],
+                ),
+                seed,
+                lamports,
+                space,
+                &program_owner,
+            )?;
+        }
+        None => {
+            require!(
+                ctx.accounts.new_account.is_signer,
+                ErrorCode::NewAccountSignatureMissing
+            );
+
+            system_program::create_account(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::CreateAccount {
+                        from: ctx.accounts.vault.to_account_info(),
+                        to: ctx.accounts.new_account.to_account_info(),
+                    },
+                    &[vault_seeds],
+                ),
+                lamports,
+                space,
+                &program_owner,
+            )?;
+        }
+    }
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = VaultAccountCreated {
+        argent_account: argent_key,
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        account: ctx.accounts.new_account.key(),
+        program_owner,
+        lamports,
+        space,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Account {} created from the vault", ctx.accounts.new_account.key());
+    Ok(())
+}
+
+pub(crate) fn set_compute_budget(
+    ctx: Context<SetComputeBudget>,
+    compute_budget: Option<ComputeBudgetRequest>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let mut pending_transaction = ctx.accounts.pending_transaction.load_mut()?;
+    pending_transaction.header.require_compatible()?;
+    require!(
+        pending_transaction.is_pending(),
+        ErrorCode::NoPendingTransaction
+    );
+    if let Some(budget) = &compute_budget {
+        require!(
+            budget.compute_unit_limit > 0 && budget.compute_unit_limit <= MAX_COMPUTE_UNIT_LIMIT,
+            ErrorCode::InvalidComputeBudget
+        );
+    }
+
+    pending_transaction.set_compute_budget(compute_budget);
+
+    wallet_state.next_state_version();
+
+    msg!("Compute budget updated!");
+    Ok(())
+}
+
+pub(crate) fn force_abandon_transaction(
+    ctx: Context<ForceAbandonTransaction>,
+    reason: String,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+
+    require!(
+        reason.len() <= MAX_ABANDON_REASON_LEN,
+        ErrorCode::AbandonReasonTooLong
+    );
+    let mut pending_transaction = ctx.accounts.pending_transaction.load_mut()?;
+    pending_transaction.header.require_compatible()?;
+    require!(
+        pending_transaction.is_pending(),
+        ErrorCode::NoPendingTransaction
+    );
+    let data_hash = hash(pending_transaction.data()).to_bytes();
+    pending_transaction.clear();
+
+    wallet_state.next_state_version();
+
+    let event = TransactionAbandoned {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        data_hash,
+        reason,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    if let Some(history) = &ctx.accounts.history {
+        let mut history = history.load_mut()?;
+        history.header.require_compatible()?;
+        history.record(HistoryEntry {
+            timestamp: Clock::get()?.unix_timestamp,
+            payload_hash: data_hash,
+            actors: [ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+            kind: HISTORY_KIND_ABANDON,
+            ..Default::default()
+        });
+    }
+
+    msg!("Pending transaction abandoned!");
+    Ok(())
+}
+
+pub(crate) fn post_guardian_approval(
+    ctx: Context<PostGuardianApproval>,
+    data_hash: [u8; 32],
+    expires_at: i64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.guardian.key()])?;
+    wallet_state.account_opened();
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(expires_at > now, ErrorCode::InvalidApprovalExpiry);
+
+    let approval = &mut ctx.accounts.guardian_approval;
+    approval.argent_account = ctx.accounts.argent_account.key();
+    approval.guardian = ctx.accounts.guardian.key();
+    approval.data_hash = data_hash;
+    approval.expires_at = expires_at;
+    approval.posted_at = now;
+    approval.bump = ctx.bumps.guardian_approval;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = GuardianApprovalPosted {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        guardian: ctx.accounts.guardian.key(),
+        data_hash,
+        expires_at,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Guardian approval posted!");
+    Ok(())
+}
+
+pub(crate) fn post_signed_guardian_approval(
+    ctx: Context<PostSignedGuardianApproval>,
+    data_hash: [u8; 32],
+    expires_at: i64,
+    guardian_signature: [u8; 64],
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    let guardian_key = ctx.accounts.guardian.key();
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[guardian_key])?;
+    wallet_state.account_opened();
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(expires_at > now, ErrorCode::InvalidApprovalExpiry);
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    let signed = verify_ed25519_instruction(
+        &ctx.accounts.sysvar_instructions,
+        &guardian_key,
+        &guardian_signature,
+    )?;
+    // Either an off-chain message or an approval payload signed from a QR code, which
+    // must carry the same expiry
+    if signed.starts_with(APPROVAL_PAYLOAD_DOMAIN) {
+        let payload = ApprovalPayload::decode(&signed)?;
+        payload.require_authorizes(
+            &argent_account.key(),
+            wallet_state.state_version,
+            &data_hash,
+            0,
+            now,
+        )?;
+        require!(payload.expires_at == expires_at, ErrorCode::OffchainMessageMismatch);
+    } else {
+        OffchainMessage::decode(&signed)?.require_authorizes(
+            &argent_account.key(),
+            wallet_state.state_version,
+            OffchainAction::ApproveTransaction,
+            &hashv(&[&data_hash, &expires_at.to_le_bytes()]).to_bytes(),
+            Clock::get()?.slot,
+        )?;
+    }
+
+    let approval = &mut ctx.accounts.guardian_approval;
+    approval.argent_account = argent_account.key();
+    approval.guardian = guardian_key;
+    approval.data_hash = data_hash;
+    approval.expires_at = expires_at;
+    approval.posted_at = now;
+    approval.bump = ctx.bumps.guardian_approval;
+
+    wallet_state.next_state_version();
+
+    let event = GuardianApprovalPosted {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        guardian: guardian_key,
+        data_hash,
+        expires_at,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Signed guardian approval posted!");
+    Ok(())
+}
+
+pub(crate) fn revoke_guardian_approval(
+    ctx: Context<RevokeGuardianApproval>,
+    _data_hash: [u8; 32],
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.account_closed();
+    wallet_state.next_state_version();
+    msg!("Guardian approval revoked!");
+    Ok(())
+}
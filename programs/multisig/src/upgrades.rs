@@ -0,0 +1,623 @@
+// Program upgrades
+//
+// Timelocked upgrades of the programs whose upgrade authority the wallet holds, the list of
+// programs it manages, and handing their authority on.
+
+use super::*;
+
+#[derive(Accounts)]
+pub struct CreateManagedPrograms<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        init,
+        payer = payer,
+        space = ManagedPrograms::space(0),
+        seeds = [b"programs", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub managed_programs: Account<'info, ManagedPrograms>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RegisterProgram<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// CHECK: The program to manage
+    #[account(executable, owner = bpf_loader_upgradeable::ID @ ErrorCode::InvalidProgramAccount)]
+    pub target_program: AccountInfo<'info>,
+    #[account(
+        seeds = [target_program.key().as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(argent_account.key())
+            @ ErrorCode::InvalidUpgradeAuthority,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(
+        mut,
+        seeds = [b"programs", argent_account.key().as_ref()],
+        bump,
+        realloc = ManagedPrograms::space(managed_programs.programs.len() + 1),
+        realloc::payer = payer,
+        realloc::zero = false,
+    )]
+    pub managed_programs: Account<'info, ManagedPrograms>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UnregisterProgram<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(mut, seeds = [b"programs", argent_account.key().as_ref()], bump)]
+    pub managed_programs: Account<'info, ManagedPrograms>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeUpgrade<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// CHECK: The program to upgrade
+    #[account(executable, owner = bpf_loader_upgradeable::ID @ ErrorCode::InvalidProgramAccount)]
+    pub target_program: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UpgradeProposal::SPACE,
+        seeds = [b"upgrade", argent_account.key().as_ref(), target_program.key().as_ref()],
+        bump,
+    )]
+    pub upgrade_proposal: Account<'info, UpgradeProposal>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(mut, seeds = [b"fee_ledger", argent_account.key().as_ref()], bump)]
+    pub fee_ledger: Option<Account<'info, FeeLedger>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Upgrade<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// CHECK: This is the program to upgrade
+    #[account(mut, executable, owner = bpf_loader_upgradeable::ID @ ErrorCode::InvalidProgramAccount)]
+    pub target_program: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [target_program.key().as_ref()],
+        bump,
+        seeds::program = bpf_loader.key(),
+        constraint = program_data.upgrade_authority_address == Some(argent_account.key())
+            @ ErrorCode::InvalidUpgradeAuthority,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    /// CHECK: This is the buffer with the new program code
+    #[account(mut, owner = bpf_loader_upgradeable::ID @ ErrorCode::InvalidProgramAccount)]
+    pub buffer: AccountInfo<'info>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The upgradeable BPF loader
+    #[account(address = bpf_loader_upgradeable::ID @ ErrorCode::InvalidProgramAccount)]
+    pub bpf_loader: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub clock: Sysvar<'info, Clock>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"upgrade", argent_account.key().as_ref(), target_program.key().as_ref()],
+        bump,
+    )]
+    pub upgrade_proposal: Account<'info, UpgradeProposal>,
+    #[account(
+        mut,
+        seeds = [b"programs", argent_account.key().as_ref()],
+        bump,
+        constraint = managed_programs.programs.contains(&target_program.key())
+            @ ErrorCode::ProgramNotManaged,
+    )]
+    pub managed_programs: Account<'info, ManagedPrograms>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelUpgrade<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    /// CHECK: The program the proposal is for, only used to derive the proposal address
+    pub target_program: AccountInfo<'info>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"upgrade", argent_account.key().as_ref(), target_program.key().as_ref()],
+        bump,
+    )]
+    pub upgrade_proposal: Account<'info, UpgradeProposal>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetUpgradeAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// CHECK: The program whose authority changes
+    #[account(executable, owner = bpf_loader_upgradeable::ID @ ErrorCode::InvalidProgramAccount)]
+    pub target_program: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [target_program.key().as_ref()],
+        bump,
+        seeds::program = bpf_loader.key(),
+        constraint = program_data.upgrade_authority_address == Some(argent_account.key())
+            @ ErrorCode::InvalidUpgradeAuthority,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    /// CHECK: The new upgrade authority, omitted to make the program immutable
+    pub new_authority: Option<UncheckedAccount<'info>>,
+    /// CHECK: The upgradeable BPF loader
+    #[account(address = bpf_loader_upgradeable::ID @ ErrorCode::InvalidProgramAccount)]
+    pub bpf_loader: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"programs", argent_account.key().as_ref()],
+        bump,
+        constraint = managed_programs.programs.contains(&target_program.key())
+            @ ErrorCode::ProgramNotManaged,
+    )]
+    pub managed_programs: Account<'info, ManagedPrograms>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseBuffer<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// CHECK: Buffer to close; the loader checks the wallet is its authority
+    #[account(mut, owner = bpf_loader_upgradeable::ID @ ErrorCode::InvalidProgramAccount)]
+    pub buffer: AccountInfo<'info>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The upgradeable BPF loader
+    #[account(address = bpf_loader_upgradeable::ID @ ErrorCode::InvalidProgramAccount)]
+    pub bpf_loader: AccountInfo<'info>,
+}
+
+pub(crate) fn propose_upgrade(
+    ctx: Context<ProposeUpgrade>,
+    buffer: Pubkey,
+    code_hash: [u8; 32],
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let proposal = &mut ctx.accounts.upgrade_proposal;
+    // A first proposal for the program creates the account, at the payer's expense
+    if proposal.proposed_at == 0 {
+        wallet_state.account_opened();
+        let rent = Rent::get()?.minimum_balance(UpgradeProposal::SPACE);
+        if let Some(fee_ledger) = &mut ctx.accounts.fee_ledger {
+            fee_ledger.record(rent, 0, 0);
+        }
+        let event = RentPaid {
+            argent_account: ctx.accounts.argent_account.key(),
+            notification_tag: ctx.accounts.argent_account.notification_tag,
+            account: proposal.key(),
+            payer: ctx.accounts.payer.key(),
+            lamports: rent,
+            state_version: wallet_state.state_version,
+        };
+        wallet_state.record_audit(&event);
+        emit_cpi!(event);
+    }
+
+    proposal.argent_account = ctx.accounts.argent_account.key();
+    proposal.program = ctx.accounts.target_program.key();
+    proposal.buffer = buffer;
+    proposal.code_hash = code_hash;
+    proposal.proposed_at = Clock::get()?.unix_timestamp;
+
+    msg!("Upgrade proposed, executable after the security period");
+    Ok(())
+}
+
+pub(crate) fn upgrade(ctx: Context<Upgrade>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_closed();
+
+    // Only the proposed buffer, unchanged since the proposal, once the security period is over
+    let proposal = &ctx.accounts.upgrade_proposal;
+    require_keys_eq!(
+        ctx.accounts.buffer.key(),
+        proposal.buffer,
+        ErrorCode::UpgradeBufferMismatch
+    );
+    require!(
+        core::has_elapsed(
+            proposal.proposed_at,
+            ctx.accounts.argent_account.security_period,
+            Clock::get()?.unix_timestamp,
+        ),
+        ErrorCode::UpgradeNotReady
+    );
+    {
+        let data = ctx.accounts.buffer.try_borrow_data()?;
+        let code = data
+            .get(UpgradeableLoaderState::size_of_buffer_metadata()..)
+            .unwrap_or_default();
+        require!(
+            hash(code).to_bytes() == proposal.code_hash,
+            ErrorCode::UpgradeHashMismatch
+        );
+    }
+
+    use anchor_lang::solana_program::program::invoke_signed;
+
+    // The wallet account is the program's upgrade authority and signs for it; the buffer's
+    // lamports are refunded to the vault
+    let creator = ctx.accounts.argent_account.creator;
+    let index_bytes = ctx.accounts.argent_account.index.to_le_bytes();
+    let wallet_seeds: &[&[u8]] = &[
+        b"argent",
+        creator.as_ref(),
+        index_bytes.as_ref(),
+        &[ctx.accounts.argent_account.bump],
+    ];
+
+    let upgrade_ix = bpf_loader_upgradeable::upgrade(
+        ctx.accounts.target_program.key,
+        ctx.accounts.buffer.key,
+        &ctx.accounts.argent_account.key(),
+        &ctx.accounts.vault.key(),
+    );
+    invoke_signed(
+        &upgrade_ix,
+        &[
+            ctx.accounts.program_data.to_account_info(),
+            ctx.accounts.target_program.to_account_info(),
+            ctx.accounts.buffer.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.rent.to_account_info(),
+            ctx.accounts.clock.to_account_info(),
+            ctx.accounts.argent_account.to_account_info(),
+        ],
+        &[wallet_seeds],
+    )?;
+
+    let event = ProgramUpgraded {
+        argent_account: ctx.accounts.argent_account.key(),
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        program: ctx.accounts.target_program.key(),
+        buffer: ctx.accounts.buffer.key(),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Program implementation upgraded successfully!");
+    Ok(())
+}
+
+pub(crate) fn cancel_upgrade(ctx: Context<CancelUpgrade>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+    wallet_state.account_closed();
+
+    let proposal = &ctx.accounts.upgrade_proposal;
+    require!(
+        !core::has_elapsed(
+            proposal.proposed_at,
+            ctx.accounts.argent_account.security_period,
+            Clock::get()?.unix_timestamp,
+        ),
+        ErrorCode::UpgradeDelayElapsed
+    );
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = UpgradeCancelled {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.owner.key(),
+        program: proposal.program,
+        buffer: proposal.buffer,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Upgrade proposal cancelled!");
+    Ok(())
+}
+
+pub(crate) fn set_upgrade_authority(
+    ctx: Context<SetUpgradeAuthority>,
+    new_authority: Option<Pubkey>,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    use anchor_lang::solana_program::program::invoke_signed;
+
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    // The new authority must be passed as an account when one is set
+    let new_authority_info = match (new_authority, &ctx.accounts.new_authority) {
+        (Some(key), Some(account)) if account.key() == key => Some(account.to_account_info()),
+        (None, None) => None,
+        _ => return err!(ErrorCode::NewAuthorityMismatch),
+    };
+
+    let creator = ctx.accounts.argent_account.creator;
+    let index_bytes = ctx.accounts.argent_account.index.to_le_bytes();
+    let wallet_seeds: &[&[u8]] = &[
+        b"argent",
+        creator.as_ref(),
+        index_bytes.as_ref(),
+        &[ctx.accounts.argent_account.bump],
+    ];
+
+    let set_authority_ix = bpf_loader_upgradeable::set_upgrade_authority(
+        ctx.accounts.target_program.key,
+        &ctx.accounts.argent_account.key(),
+        new_authority.as_ref(),
+    );
+    let mut account_infos = vec![
+        ctx.accounts.program_data.to_account_info(),
+        ctx.accounts.argent_account.to_account_info(),
+    ];
+    account_infos.extend(new_authority_info);
+    invoke_signed(&set_authority_ix, &account_infos, &[wallet_seeds])?;
+
+    // The wallet no longer holds the authority
+    let program_key = ctx.accounts.target_program.key();
+    ctx.accounts.managed_programs.programs.retain(|program| *program != program_key);
+
+    let event = UpgradeAuthorityChanged {
+        argent_account: ctx.accounts.argent_account.key(),
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        program: ctx.accounts.target_program.key(),
+        new_authority,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Upgrade authority updated!");
+    Ok(())
+}
+
+pub(crate) fn close_buffer(ctx: Context<CloseBuffer>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    use anchor_lang::solana_program::program::invoke_signed;
+
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let creator = ctx.accounts.argent_account.creator;
+    let index_bytes = ctx.accounts.argent_account.index.to_le_bytes();
+    let wallet_seeds: &[&[u8]] = &[
+        b"argent",
+        creator.as_ref(),
+        index_bytes.as_ref(),
+        &[ctx.accounts.argent_account.bump],
+    ];
+
+    let close_ix = bpf_loader_upgradeable::close(
+        ctx.accounts.buffer.key,
+        &ctx.accounts.vault.key(),
+        &ctx.accounts.argent_account.key(),
+    );
+    invoke_signed(
+        &close_ix,
+        &[
+            ctx.accounts.buffer.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.argent_account.to_account_info(),
+        ],
+        &[wallet_seeds],
+    )?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = BufferClosed {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        buffer: ctx.accounts.buffer.key(),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Buffer {} closed", ctx.accounts.buffer.key());
+    Ok(())
+}
+
+pub(crate) fn create_managed_programs(ctx: Context<CreateManagedPrograms>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.account_opened();
+    let managed_programs = &mut ctx.accounts.managed_programs;
+    managed_programs.argent_account = ctx.accounts.argent_account.key();
+    managed_programs.programs = Vec::new();
+
+    msg!("Managed programs created!");
+    Ok(())
+}
+
+pub(crate) fn register_program(ctx: Context<RegisterProgram>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let program_key = ctx.accounts.target_program.key();
+    let managed_programs = &mut ctx.accounts.managed_programs;
+    require!(
+        !managed_programs.programs.contains(&program_key),
+        ErrorCode::ProgramAlreadyManaged
+    );
+    managed_programs.programs.push(program_key);
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = ProgramRegistered {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        program: program_key,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Program {} registered", program_key);
+    Ok(())
+}
+
+pub(crate) fn unregister_program(ctx: Context<UnregisterProgram>, program: Pubkey) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let managed_programs = &mut ctx.accounts.managed_programs;
+    require!(
+        managed_programs.programs.contains(&program),
+        ErrorCode::ProgramNotManaged
+    );
+    managed_programs.programs.retain(|managed| *managed != program);
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = ProgramUnregistered {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: ctx.accounts.approvers.owner.key(),
+        guardian: ctx.accounts.approvers.guardian.key(),
+        program,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Program {} unregistered", program);
+    Ok(())
+}
@@ -0,0 +1,810 @@
+// Vault outflows
+//
+// Sweeping the whole vault, the guardian's asset freeze, and sub-vaults with their own
+// spending policies.
+
+use super::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RequestSweep<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(mut, constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    /// CHECK: Wallet or account the vault will be swept to, only recorded
+    pub destination: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = SweepRequest::SPACE,
+        seeds = [b"sweep", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub sweep_request: Account<'info, SweepRequest>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelSweep<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        constraint = authority.key() == argent_account.owner || authority.key() == argent_account.guardian @ ErrorCode::NotOwnerOrGuardian,
+    )]
+    pub authority: Signer<'info>,
+    /// CHECK: The wallet owner, who paid for the request and receives the rent back
+    #[account(mut, address = argent_account.owner @ ErrorCode::InvalidOwner)]
+    pub owner: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"sweep", argent_account.key().as_ref()],
+        bump = sweep_request.bump,
+    )]
+    pub sweep_request: Account<'info, SweepRequest>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SweepAll<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(mut, constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    /// CHECK: The wallet guardian; signs unless a sweep request has passed its timelock
+    #[account(address = argent_account.guardian @ ErrorCode::InvalidGuardian)]
+    pub guardian: UncheckedAccount<'info>,
+    // Timelocked request, closed by the sweep; required when the guardian doesn't sign
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"sweep", argent_account.key().as_ref()],
+        bump = sweep_request.bump,
+    )]
+    pub sweep_request: Option<Account<'info, SweepRequest>>,
+    /// The wallet vault PDA being emptied
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    /// CHECK: Receives the SOL, and owns the destination token accounts
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FreezeAsset<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = AssetFreeze::SPACE,
+        seeds = [b"asset_freeze", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub asset_freeze: Account<'info, AssetFreeze>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UnfreezeAsset<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        mut,
+        seeds = [b"asset_freeze", argent_account.key().as_ref()],
+        bump = asset_freeze.bump,
+    )]
+    pub asset_freeze: Account<'info, AssetFreeze>,
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u8)]
+pub struct CreateSubVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = SubVaultConfig::SPACE,
+        seeds = [b"sub_vault_config", argent_account.key().as_ref(), &[index]],
+        bump,
+    )]
+    pub sub_vault_config: Account<'info, SubVaultConfig>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u8)]
+pub struct FundSubVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(seeds = [b"sub_vault_config", argent_account.key().as_ref(), &[index]], bump)]
+    pub sub_vault_config: Account<'info, SubVaultConfig>,
+    /// The wallet vault PDA funding the sub-vault
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    /// The sub-vault PDA receiving the funds
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref(), &[index]], bump)]
+    pub sub_vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u8)]
+pub struct WithdrawSubVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    /// CHECK: Must be the wallet guardian; its signature is only required by the sub-vault policy
+    #[account(constraint = argent_account.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: UncheckedAccount<'info>,
+    #[account(seeds = [b"sub_vault_config", argent_account.key().as_ref(), &[index]], bump)]
+    pub sub_vault_config: Account<'info, SubVaultConfig>,
+    /// The sub-vault PDA paying out
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref(), &[index]], bump)]
+    pub sub_vault: SystemAccount<'info>,
+    /// CHECK: The wallet's asset freeze PDA, read in the handler; absent when nothing is frozen
+    #[account(seeds = [b"asset_freeze", argent_account.key().as_ref()], bump)]
+    pub asset_freeze: UncheckedAccount<'info>,
+    /// CHECK: Any account can receive the withdrawal
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u8)]
+pub struct SweepSubVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        constraint = authority.key() == argent_account.owner
+            || authority.key() == argent_account.guardian
+            @ ErrorCode::NotOwnerOrGuardian
+    )]
+    pub authority: Signer<'info>,
+    #[account(seeds = [b"sub_vault_config", argent_account.key().as_ref(), &[index]], bump)]
+    pub sub_vault_config: Account<'info, SubVaultConfig>,
+    /// The wallet vault PDA receiving the balance
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// The sub-vault PDA being emptied
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref(), &[index]], bump)]
+    pub sub_vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u8)]
+pub struct CloseSubVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        mut,
+        close = vault,
+        seeds = [b"sub_vault_config", argent_account.key().as_ref(), &[index]],
+        bump,
+    )]
+    pub sub_vault_config: Account<'info, SubVaultConfig>,
+    /// The wallet vault PDA receiving the rent
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    /// The sub-vault PDA, which must be empty
+    #[account(seeds = [b"vault", argent_account.key().as_ref(), &[index]], bump)]
+    pub sub_vault: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(from_index: u8, to_index: u8)]
+pub struct InternalTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [b"sub_vault_config", argent_account.key().as_ref(), &[from_index]], bump)]
+    pub from_config: Account<'info, SubVaultConfig>,
+    #[account(seeds = [b"sub_vault_config", argent_account.key().as_ref(), &[to_index]], bump)]
+    pub to_config: Account<'info, SubVaultConfig>,
+    /// The sub-vault PDA paying out
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref(), &[from_index]], bump)]
+    pub from_sub_vault: SystemAccount<'info>,
+    /// The sub-vault PDA receiving the funds
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref(), &[to_index]], bump)]
+    pub to_sub_vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn request_sweep(ctx: Context<RequestSweep>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+    wallet_state.account_opened();
+
+    let request = &mut ctx.accounts.sweep_request;
+    request.argent_account = ctx.accounts.argent_account.key();
+    request.destination = ctx.accounts.destination.key();
+    request.requested_at = Clock::get()?.unix_timestamp;
+    request.bump = ctx.bumps.sweep_request;
+
+    wallet_state.next_state_version();
+
+    let event = SweepRequested {
+        argent_account: request.argent_account,
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        destination: request.destination,
+        executable_at: request
+            .requested_at
+            .saturating_add(ctx.accounts.argent_account.security_period),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Sweep to {} requested", request.destination);
+    Ok(())
+}
+
+pub(crate) fn cancel_sweep(ctx: Context<CancelSweep>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.authority.key()],
+    )?;
+    wallet_state.account_closed();
+    wallet_state.next_state_version();
+
+    let event = SweepCancelled {
+        argent_account: ctx.accounts.argent_account.key(),
+        notification_tag: ctx.accounts.argent_account.notification_tag,
+        cancelled_by: ctx.accounts.authority.key(),
+        destination: ctx.accounts.sweep_request.destination,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Sweep request cancelled!");
+    Ok(())
+}
+
+pub(crate) fn sweep_all<'info>(ctx: Context<'_, '_, 'info, 'info, SweepAll<'info>>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    let mut signers = vec![ctx.accounts.owner.key()];
+    if ctx.accounts.guardian.is_signer {
+        signers.push(ctx.accounts.guardian.key());
+    }
+    wallet_state.record_activity(&ctx.accounts.argent_account, &signers)?;
+    if ctx.accounts.sweep_request.is_some() {
+        wallet_state.account_closed();
+    }
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_key = ctx.accounts.vault.key();
+    let destination_key = ctx.accounts.destination.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+
+    if !ctx.accounts.guardian.is_signer {
+        let Some(request) = &ctx.accounts.sweep_request else {
+            return err!(ErrorCode::GuardianSignatureMissing);
+        };
+        require_keys_eq!(
+            request.destination,
+            destination_key,
+            ErrorCode::InvalidSweepDestination
+        );
+        require!(
+            core::has_elapsed(
+                request.requested_at,
+                ctx.accounts.argent_account.security_period,
+                Clock::get()?.unix_timestamp,
+            ),
+            ErrorCode::SecurityPeriodNotElapsed
+        );
+    }
+    require!(ctx.accounts.asset_freeze.data_is_empty(), ErrorCode::AssetFrozen);
+    require!(
+        ctx.remaining_accounts.chunks_exact(3).remainder().is_empty(),
+        ErrorCode::MissingPaymentAccounts
+    );
+    require_compute_units(ctx.remaining_accounts.len() / 3)?;
+
+    let token_program = &ctx.accounts.token_program;
+    for accounts in ctx.remaining_accounts.chunks_exact(3) {
+        let vault_token_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[0])?;
+        let mint = InterfaceAccount::<Mint>::try_from(&accounts[1])?;
+        let destination_token_account =
+            InterfaceAccount::<TokenAccount>::try_from(&accounts[2])?;
+        require_keys_eq!(vault_token_account.owner, vault_key, ErrorCode::InvalidTokenAccount);
+        require_keys_eq!(
+            destination_token_account.owner,
+            destination_key,
+            ErrorCode::InvalidTokenAccount
+        );
+        require!(
+            vault_token_account.mint == mint.key()
+                && destination_token_account.mint == mint.key(),
+            ErrorCode::InvalidTokenAccount
+        );
+        if vault_token_account.amount == 0 {
+            continue;
+        }
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: accounts[0].clone(),
+                    mint: accounts[1].clone(),
+                    to: accounts[2].clone(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            vault_token_account.amount,
+            mint.decimals,
+        )?;
+    }
+
+    let lamports = ctx.accounts.vault.lamports();
+    if lamports > 0 {
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            lamports,
+        )?;
+    }
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = VaultSwept {
+        argent_account: argent_key,
+        notification_tag: argent_account.notification_tag,
+        destination: destination_key,
+        lamports,
+        token_accounts: (ctx.remaining_accounts.len() / 3) as u32,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Vault swept to {}", destination_key);
+    Ok(())
+}
+
+pub(crate) fn freeze_asset(ctx: Context<FreezeAsset>, mint: Option<Pubkey>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.guardian.key()])?;
+
+    let asset_freeze = &mut ctx.accounts.asset_freeze;
+    if asset_freeze.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    asset_freeze.argent_account = ctx.accounts.argent_account.key();
+    asset_freeze.bump = ctx.bumps.asset_freeze;
+    match mint {
+        None => asset_freeze.sol_frozen = true,
+        Some(mint) if !asset_freeze.mints.contains(&mint) => {
+            require!(
+                asset_freeze.mints.len() < MAX_FROZEN_MINTS,
+                ErrorCode::TooManyFrozenMints
+            );
+            asset_freeze.mints.push(mint);
+        }
+        Some(_) => {}
+    }
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = AssetFrozen {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        mint,
+        frozen: true,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Asset frozen!");
+    Ok(())
+}
+
+pub(crate) fn unfreeze_asset(ctx: Context<UnfreezeAsset>, mint: Option<Pubkey>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let asset_freeze = &mut ctx.accounts.asset_freeze;
+    match mint {
+        None => asset_freeze.sol_frozen = false,
+        Some(mint) => asset_freeze.mints.retain(|frozen| *frozen != mint),
+    }
+    if !asset_freeze.sol_frozen && asset_freeze.mints.is_empty() {
+        asset_freeze.close(ctx.accounts.vault.to_account_info())?;
+        wallet_state.account_closed();
+    }
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    wallet_state.next_state_version();
+
+    let event = AssetFrozen {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        mint,
+        frozen: false,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Asset unfrozen!");
+    Ok(())
+}
+
+pub(crate) fn create_sub_vault(
+    ctx: Context<CreateSubVault>,
+    index: u8,
+    name: String,
+    policy: SubVaultPolicy,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_opened();
+
+    require!(
+        name.len() <= MAX_SUB_VAULT_NAME_LEN,
+        ErrorCode::SubVaultNameTooLong
+    );
+    if let SubVaultPolicy::TimeLocked { unlock_at } = policy {
+        require!(
+            unlock_at > Clock::get()?.unix_timestamp,
+            ErrorCode::InvalidUnlockTime
+        );
+    }
+
+    let config = &mut ctx.accounts.sub_vault_config;
+    config.argent_account = ctx.accounts.argent_account.key();
+    config.index = index;
+    config.name = name;
+    config.policy = policy;
+
+    msg!("Sub-vault {} created!", index);
+    Ok(())
+}
+
+pub(crate) fn fund_sub_vault(ctx: Context<FundSubVault>, _index: u8, amount: u64) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    require_not_frozen(&ctx.accounts.asset_freeze, &None)?;
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.sub_vault.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        amount,
+    )?;
+
+    wallet_state.next_state_version();
+
+    msg!("Sub-vault funded!");
+    Ok(())
+}
+
+pub(crate) fn withdraw_sub_vault(ctx: Context<WithdrawSubVault>, index: u8, amount: u64) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    let mut signers = vec![ctx.accounts.owner.key()];
+    if ctx.accounts.guardian.is_signer {
+        signers.push(ctx.accounts.guardian.key());
+    }
+    wallet_state.record_activity(&ctx.accounts.argent_account, &signers)?;
+    require_not_frozen(&ctx.accounts.asset_freeze, &None)?;
+
+    let guardian_signed = ctx.accounts.guardian.is_signer;
+
+    match ctx.accounts.sub_vault_config.policy {
+        SubVaultPolicy::DualSigned => {
+            require!(guardian_signed, ErrorCode::GuardianSignatureMissing);
+        }
+        SubVaultPolicy::OwnerUnderLimit { limit } => {
+            require!(
+                guardian_signed || amount <= limit,
+                ErrorCode::SubVaultLimitExceeded
+            );
+        }
+        SubVaultPolicy::TimeLocked { unlock_at } => {
+            require!(guardian_signed, ErrorCode::GuardianSignatureMissing);
+            require!(
+                Clock::get()?.unix_timestamp >= unlock_at,
+                ErrorCode::SubVaultLocked
+            );
+        }
+    }
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let sub_vault_seeds: &[&[u8]] = &[
+        b"vault",
+        argent_key.as_ref(),
+        &[index],
+        &[ctx.bumps.sub_vault],
+    ];
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.sub_vault.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+            },
+            &[sub_vault_seeds],
+        ),
+        amount,
+    )?;
+
+    wallet_state.next_state_version();
+
+    msg!("Sub-vault withdrawal completed!");
+    Ok(())
+}
+
+pub(crate) fn sweep_sub_vault(ctx: Context<SweepSubVault>, index: u8) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.authority.key()],
+    )?;
+
+    if let SubVaultPolicy::TimeLocked { unlock_at } = ctx.accounts.sub_vault_config.policy {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= unlock_at
+                || wallet_state.recently_escaped(ctx.accounts.argent_account.security_period, now),
+            ErrorCode::SubVaultLocked
+        );
+    }
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let sub_vault_seeds: &[&[u8]] = &[
+        b"vault",
+        argent_key.as_ref(),
+        &[index],
+        &[ctx.bumps.sub_vault],
+    ];
+
+    let lamports = ctx.accounts.sub_vault.lamports();
+    if lamports > 0 {
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sub_vault.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                },
+                &[sub_vault_seeds],
+            ),
+            lamports,
+        )?;
+    }
+
+    wallet_state.next_state_version();
+
+    msg!("Sub-vault swept!");
+    Ok(())
+}
+
+pub(crate) fn close_sub_vault(ctx: Context<CloseSubVault>, _index: u8) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+    wallet_state.account_closed();
+
+    require!(
+        ctx.accounts.sub_vault.lamports() == 0,
+        ErrorCode::SubVaultNotEmpty
+    );
+
+    wallet_state.next_state_version();
+
+    msg!("Sub-vault closed!");
+    Ok(())
+}
+
+pub(crate) fn internal_transfer(
+    ctx: Context<InternalTransfer>,
+    from_index: u8,
+    to_index: u8,
+    amount: u64,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+
+    require!(from_index != to_index, ErrorCode::InvalidInternalTransfer);
+    let from_policy = &ctx.accounts.from_config.policy;
+    let to_policy = &ctx.accounts.to_config.policy;
+    if let SubVaultPolicy::TimeLocked { unlock_at } = from_policy {
+        require!(
+            Clock::get()?.unix_timestamp >= *unlock_at,
+            ErrorCode::SubVaultLocked
+        );
+    }
+    require!(
+        to_policy.owner_limit() <= from_policy.owner_limit(),
+        ErrorCode::InvalidInternalTransfer
+    );
+
+    let argent_key = ctx.accounts.argent_account.key();
+    let from_seeds: &[&[u8]] = &[
+        b"vault",
+        argent_key.as_ref(),
+        &[from_index],
+        &[ctx.bumps.from_sub_vault],
+    ];
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.from_sub_vault.to_account_info(),
+                to: ctx.accounts.to_sub_vault.to_account_info(),
+            },
+            &[from_seeds],
+        ),
+        amount,
+    )?;
+
+    wallet_state.next_state_version();
+
+    msg!("Moved {} lamports from sub-vault {} to {}", amount, from_index, to_index);
+    Ok(())
+}
@@ -0,0 +1,1314 @@
+// Wallet lifecycle
+//
+// Creating wallets, directly, counterfactually or derived from a controller wallet, and
+// everything about a wallet as a whole rather than one of its features: the owner's lock,
+// metadata and viewers, activity reads, layout migrations and finally closing it.
+
+use super::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct Create<'info> {
+    #[account(
+        init,
+        seeds = [b"argent", payer.key().as_ref(), index.to_le_bytes().as_ref()],
+        bump,
+        payer = payer,
+        space = WalletConfig::SPACE
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(
+        init,
+        seeds = [b"pending_tx", argent_account.key().as_ref()],
+        bump,
+        payer = payer,
+        space = PendingTransaction::SPACE
+    )]
+    pub pending_transaction: AccountLoader<'info, PendingTransaction>,
+    #[account(
+        init,
+        seeds = [b"wallet_state", argent_account.key().as_ref()],
+        bump,
+        payer = payer,
+        space = WalletState::SPACE
+    )]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    // When given, the guardian must be a vetted guardian service
+    #[account(
+        seeds = [b"guardian_service", guardian_service.guardian.as_ref()],
+        bump = guardian_service.bump,
+    )]
+    pub guardian_service: Option<Account<'info, GuardianService>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct CreateAndExecute<'info> {
+    #[account(
+        init,
+        seeds = [b"argent", payer.key().as_ref(), index.to_le_bytes().as_ref()],
+        bump,
+        payer = payer,
+        space = WalletConfig::SPACE
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(
+        init,
+        seeds = [b"pending_tx", argent_account.key().as_ref()],
+        bump,
+        payer = payer,
+        space = PendingTransaction::SPACE
+    )]
+    pub pending_transaction: AccountLoader<'info, PendingTransaction>,
+    #[account(
+        init,
+        seeds = [b"wallet_state", argent_account.key().as_ref()],
+        bump,
+        payer = payer,
+        space = WalletState::SPACE
+    )]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    // When given, the guardian must be a vetted guardian service
+    #[account(
+        seeds = [b"guardian_service", guardian_service.guardian.as_ref()],
+        bump = guardian_service.bump,
+    )]
+    pub guardian_service: Option<Account<'info, GuardianService>>,
+    pub owner: Signer<'info>,
+    pub guardian: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateControllerIndex<'info> {
+    #[account(
+        seeds = [b"argent", controller.creator.as_ref(), controller.index.to_le_bytes().as_ref()],
+        bump = controller.bump,
+        constraint = controller.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub controller: Account<'info, WalletConfig>,
+    #[account(
+        init,
+        payer = payer,
+        space = ControllerIndex::space(0),
+        seeds = [b"controller", controller.key().as_ref()],
+        bump,
+    )]
+    pub controller_index: Account<'info, ControllerIndex>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct CreateDerivedWallet<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", controller.creator.as_ref(), controller.index.to_le_bytes().as_ref()],
+        bump = controller.bump,
+        constraint = controller.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !controller.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub controller: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", controller.key().as_ref()], bump)]
+    pub controller_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = controller.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+    #[account(constraint = controller.guardian == guardian.key() @ ErrorCode::InvalidGuardian)]
+    pub guardian: Signer<'info>,
+    #[account(
+        init,
+        seeds = [b"argent", controller.key().as_ref(), index.to_le_bytes().as_ref()],
+        bump,
+        payer = payer,
+        space = WalletConfig::SPACE
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(
+        init,
+        seeds = [b"pending_tx", argent_account.key().as_ref()],
+        bump,
+        payer = payer,
+        space = PendingTransaction::SPACE
+    )]
+    pub pending_transaction: AccountLoader<'info, PendingTransaction>,
+    #[account(
+        init,
+        seeds = [b"wallet_state", argent_account.key().as_ref()],
+        bump,
+        payer = payer,
+        space = WalletState::SPACE
+    )]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        mut,
+        seeds = [b"controller", controller.key().as_ref()],
+        bump,
+        realloc = ControllerIndex::space(controller_index.wallets.len() + 1),
+        realloc::payer = payer,
+        realloc::zero = false,
+    )]
+    pub controller_index: Account<'info, ControllerIndex>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct LockWallet<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = argent_account.owner == owner.key() @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UnlockWallet<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAccount<'info> {
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"pending_tx", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub pending_transaction: AccountLoader<'info, PendingTransaction>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"wallet_state", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    /// The wallet vault PDA being emptied
+    #[account(mut, seeds = [b"vault", argent_account.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"delegations", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub delegation_registry: Option<AccountLoader<'info, DelegationRegistry>>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"swap_routers", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub swap_router_allowlist: Option<Account<'info, SwapRouterAllowlist>>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"quarantine", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub quarantine: Option<AccountLoader<'info, Quarantine>>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"history", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub history: Option<AccountLoader<'info, History>>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"inheritance", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub inheritance_plan: Option<Account<'info, InheritancePlan>>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"fee_ledger", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub fee_ledger: Option<Account<'info, FeeLedger>>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"programs", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub managed_programs: Option<Account<'info, ManagedPrograms>>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"viewers", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub viewer_list: Option<Account<'info, ViewerList>>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"label_policy", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub label_policy: Option<Account<'info, LabelPolicy>>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"relayers", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub relayer_registry: Option<Account<'info, RelayerRegistry>>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"paymaster", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub paymaster_config: Option<Account<'info, PaymasterConfig>>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"fee_rebate_policy", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub fee_rebate_policy: Option<Account<'info, FeeRebatePolicy>>,
+    /// CHECK: Any account can receive the remaining lamports
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CountOpenAccounts<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: A wallet account in any layout version, decoded by the handler
+    #[account(
+        mut,
+        owner = crate::ID,
+    )]
+    pub argent_account: UncheckedAccount<'info>,
+    // Created for wallets from before it existed, taking over their pending transaction
+    #[account(
+        init_if_needed,
+        seeds = [b"pending_tx", argent_account.key().as_ref()],
+        bump,
+        payer = payer,
+        space = PendingTransaction::SPACE
+    )]
+    pub pending_transaction: AccountLoader<'info, PendingTransaction>,
+    // Created for wallets from before it existed, taking over the hot state they kept inline
+    #[account(
+        init_if_needed,
+        seeds = [b"wallet_state", argent_account.key().as_ref()],
+        bump,
+        payer = payer,
+        space = WalletState::SPACE
+    )]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateDerivedAccount<'info> {
+    /// CHECK: Any account of this program other than a wallet, in the layout from before the
+    /// account header; checked by the handler
+    #[account(
+        mut,
+        owner = crate::ID,
+    )]
+    pub account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetNotificationTag<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawExcessLamports<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    /// CHECK: Any account can receive the excess lamports
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateHistory<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        init,
+        payer = payer,
+        space = History::SPACE,
+        seeds = [b"history", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub history: AccountLoader<'info, History>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Heartbeat<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(
+        constraint = authority.key() == argent_account.owner
+            || authority.key() == argent_account.guardian @ ErrorCode::NotOwnerOrGuardian
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct GetLastActivity<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+}
+
+#[derive(Accounts)]
+pub struct SetViewers<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ViewerList::SPACE,
+        seeds = [b"viewers", argent_account.key().as_ref()],
+        bump,
+    )]
+    pub viewer_list: Account<'info, ViewerList>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetWalletDetails<'info> {
+    #[account(
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    pub viewer: Signer<'info>,
+    #[account(
+        seeds = [b"viewers", argent_account.key().as_ref()],
+        bump = viewer_list.bump,
+    )]
+    pub viewer_list: Option<Account<'info, ViewerList>>,
+}
+
+#[derive(Accounts)]
+pub struct SetApprovalTtl<'info> {
+    #[account(
+        mut,
+        seeds = [b"argent", argent_account.creator.as_ref(), argent_account.index.to_le_bytes().as_ref()],
+        bump = argent_account.bump,
+        constraint = argent_account.is_compatible() @ ErrorCode::IncompatibleAccountLayout,
+        constraint = !argent_account.is_locked(Clock::get()?.unix_timestamp) @ ErrorCode::WalletLockActive,
+    )]
+    pub argent_account: Account<'info, WalletConfig>,
+    #[account(mut, seeds = [b"wallet_state", argent_account.key().as_ref()], bump)]
+    pub wallet_state: AccountLoader<'info, WalletState>,
+    #[account(constraint = approvers.belong_to(&argent_account)?)]
+    pub approvers: Approvers<'info>,
+}
+
+pub(crate) fn create(
+    ctx: Context<Create>,
+    index: u64,
+    owner: Pubkey,
+    guardian: Pubkey,
+    security_period: Option<i64>,
+) -> Result<()> {
+    let argent_account = &mut ctx.accounts.argent_account;
+    argent_account.owner = owner;
+    argent_account.guardian = guardian;
+    argent_account.guardian_backup = None;
+    argent_account.require_distinct_roles()?;
+    if let Some(guardian_service) = &ctx.accounts.guardian_service {
+        guardian_service.require_vetted(&guardian)?;
+    }
+
+    // Set security period (default 7 days = 604800 seconds)
+    argent_account.security_period = security_period.unwrap_or(604800);
+
+    // Initialize pending transaction and hot state
+    ctx.accounts
+        .pending_transaction
+        .load_init()?
+        .initialize(argent_account.key());
+    let mut wallet_state = ctx.accounts.wallet_state.load_init()?;
+    wallet_state.initialize(argent_account.key());
+    argent_account.message_requires_guardian = false;
+
+    argent_account.version = CURRENT_ACCOUNT_VERSION;
+    argent_account.creator = ctx.accounts.payer.key();
+    argent_account.index = index;
+    argent_account.bump = ctx.bumps.argent_account;
+
+    // Inactivity timers, such as backup promotion, run from the wallet's creation
+    let now = Clock::get()?.unix_timestamp;
+    wallet_state.last_owner_action_at = now;
+    wallet_state.last_guardian_action_at = now;
+
+    let event = WalletCreated {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        creator: argent_account.creator,
+        index,
+        owner,
+        guardian,
+        security_period: argent_account.security_period,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn create_and_execute(
+    ctx: Context<CreateAndExecute>,
+    index: u64,
+    security_period: Option<i64>,
+    data: Vec<u8>,
+) -> Result<()> {
+    require!(
+        data.len() <= MAX_PENDING_TX_DATA_LEN,
+        ErrorCode::TransactionTooLarge
+    );
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    argent_account.owner = ctx.accounts.owner.key();
+    argent_account.guardian = ctx.accounts.guardian.key();
+    argent_account.guardian_backup = None;
+    argent_account.require_distinct_roles()?;
+    if let Some(guardian_service) = &ctx.accounts.guardian_service {
+        guardian_service.require_vetted(&ctx.accounts.guardian.key())?;
+    }
+    argent_account.security_period = security_period.unwrap_or(604800);
+    argent_account.version = CURRENT_ACCOUNT_VERSION;
+    argent_account.creator = ctx.accounts.payer.key();
+    argent_account.index = index;
+    argent_account.bump = ctx.bumps.argent_account;
+    let mut wallet_state = ctx.accounts.wallet_state.load_init()?;
+    wallet_state.initialize(argent_account.key());
+    wallet_state.record_activity(
+        argent_account,
+        &[ctx.accounts.owner.key(), ctx.accounts.guardian.key()],
+    )?;
+
+    let event = WalletCreated {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        creator: argent_account.creator,
+        index,
+        owner: argent_account.owner,
+        guardian: argent_account.guardian,
+        security_period: argent_account.security_period,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+    let event = TransactionApproved {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        owner: argent_account.owner,
+        guardian: argent_account.guardian,
+        data_hash: hash(&data).to_bytes(),
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    // Store the transaction data for execution
+    let mut pending_transaction = ctx.accounts.pending_transaction.load_init()?;
+    pending_transaction.initialize(argent_account.key());
+    pending_transaction.store(&data)?;
+
+    msg!("Account created and transaction approved!");
+    Ok(())
+}
+
+pub(crate) fn create_controller_index(ctx: Context<CreateControllerIndex>) -> Result<()> {
+    let controller_index = &mut ctx.accounts.controller_index;
+    controller_index.controller = ctx.accounts.controller.key();
+    controller_index.wallets = Vec::new();
+
+    msg!("Controller index created!");
+    Ok(())
+}
+
+pub(crate) fn create_derived_wallet(ctx: Context<CreateDerivedWallet>, index: u64) -> Result<()> {
+    let mut controller_state = ctx.accounts.controller_state.load_mut()?;
+    controller_state.header.require_compatible()?;
+    controller_state.record_activity(
+        &ctx.accounts.controller,
+        &[ctx.accounts.owner.key(), ctx.accounts.guardian.key()],
+    )?;
+
+    let controller = &mut ctx.accounts.controller;
+    let argent_account = &mut ctx.accounts.argent_account;
+    argent_account.owner = controller.owner;
+    argent_account.guardian = controller.guardian;
+    argent_account.guardian_backup = controller.guardian_backup;
+    argent_account.security_period = controller.security_period;
+    ctx.accounts
+        .pending_transaction
+        .load_init()?
+        .initialize(argent_account.key());
+    let mut wallet_state = ctx.accounts.wallet_state.load_init()?;
+    wallet_state.initialize(argent_account.key());
+    argent_account.message_requires_guardian = false;
+
+    argent_account.version = CURRENT_ACCOUNT_VERSION;
+    argent_account.creator = controller.key();
+    argent_account.index = index;
+    argent_account.bump = ctx.bumps.argent_account;
+
+    // Inactivity timers, such as backup promotion, run from the wallet's creation
+    let now = Clock::get()?.unix_timestamp;
+    wallet_state.last_owner_action_at = now;
+    wallet_state.last_guardian_action_at = now;
+
+    ctx.accounts.controller_index.wallets.push(argent_account.key());
+    controller_state.next_state_version();
+
+    let event = WalletCreated {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        creator: argent_account.creator,
+        index,
+        owner: argent_account.owner,
+        guardian: argent_account.guardian,
+        security_period: argent_account.security_period,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    let event = DerivedWalletCreated {
+        controller: controller.key(),
+        argent_account: argent_account.key(),
+        index,
+        owner: ctx.accounts.owner.key(),
+        guardian: ctx.accounts.guardian.key(),
+        state_version: controller_state.state_version,
+    };
+    controller_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Derived wallet created!");
+    Ok(())
+}
+
+pub(crate) fn lock_wallet(ctx: Context<LockWallet>, duration: i64) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(&ctx.accounts.argent_account, &[ctx.accounts.owner.key()])?;
+
+    require!(
+        duration > 0 && duration <= MAX_WALLET_LOCK_DURATION,
+        ErrorCode::InvalidLockDuration
+    );
+
+    let argent_account = &mut ctx.accounts.argent_account;
+    let now = Clock::get()?.unix_timestamp;
+    argent_account.locked_until = argent_account.locked_until.max(now + duration);
+
+    wallet_state.next_state_version();
+
+    let event = WalletLocked {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        locked_until: argent_account.locked_until,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Wallet locked until {}", argent_account.locked_until);
+    Ok(())
+}
+
+pub(crate) fn unlock_wallet(ctx: Context<UnlockWallet>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+
+    argent_account.locked_until = 0;
+
+    wallet_state.next_state_version();
+
+    let event = WalletUnlocked {
+        argent_account: argent_account.key(),
+        notification_tag: argent_account.notification_tag,
+        state_version: wallet_state.state_version,
+    };
+    wallet_state.record_audit(&event);
+    emit_cpi!(event);
+
+    msg!("Wallet unlocked!");
+    Ok(())
+}
+
+pub(crate) fn close_account<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CloseAccount<'info>>,
+) -> Result<()> {
+    let wallet_state = ctx.accounts.wallet_state.load()?;
+    wallet_state.header.require_compatible()?;
+    let argent_account = &ctx.accounts.argent_account;
+
+    // Nothing may be left in flight
+    let pending_transaction = ctx.accounts.pending_transaction.load()?;
+    pending_transaction.header.require_compatible()?;
+    require!(
+        !pending_transaction.is_pending(),
+        ErrorCode::PendingTransactionExists
+    );
+    drop(pending_transaction);
+    require!(
+        wallet_state.escape_type() == EscapeType::None,
+        ErrorCode::EscapeInProgress
+    );
+
+    // Every other account derived from the wallet must be closed with it, so none is
+    // stranded or carries over to a wallet re-created at the same address
+    let accounts = &ctx.accounts;
+    let closed_here = [
+        accounts.delegation_registry.is_some(),
+        accounts.swap_router_allowlist.is_some(),
+        accounts.quarantine.is_some(),
+        accounts.history.is_some(),
+        accounts.inheritance_plan.is_some(),
+        accounts.fee_ledger.is_some(),
+        accounts.managed_programs.is_some(),
+        accounts.viewer_list.is_some(),
+        accounts.label_policy.is_some(),
+        accounts.relayer_registry.is_some(),
+        accounts.paymaster_config.is_some(),
+        accounts.fee_rebate_policy.is_some(),
+    ]
+    .into_iter()
+    .filter(|closed| *closed)
+    .count();
+    require!(
+        wallet_state.open_accounts != UNCOUNTED_OPEN_ACCOUNTS,
+        ErrorCode::OpenAccountsUncounted
+    );
+    require!(
+        wallet_state.open_accounts as usize <= closed_here,
+        ErrorCode::WalletAccountsOpen
+    );
+
+    let triples = ctx.remaining_accounts.chunks_exact(3);
+    require!(
+        triples.remainder().is_empty(),
+        ErrorCode::InvalidRemainingAccounts
+    );
+
+    let argent_key = argent_account.key();
+    let vault_key = ctx.accounts.vault.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", argent_key.as_ref(), &[ctx.bumps.vault]];
+
+    // Sweep and close every vault token account
+    for triple in triples {
+        let (account_info, mint_info, destination_info) = (&triple[0], &triple[1], &triple[2]);
+        let token_account = InterfaceAccount::<TokenAccount>::try_from(account_info)?;
+        let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+        require_keys_eq!(token_account.owner, vault_key, ErrorCode::InvalidTokenAccount);
+        require_keys_eq!(token_account.mint, mint.key(), ErrorCode::InvalidTokenAccount);
+        require_keys_eq!(
+            *account_info.owner,
+            ctx.accounts.token_program.key(),
+            ErrorCode::InvalidTokenAccount
+        );
+
+        if token_account.amount > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token_interface::TransferChecked {
+                        from: account_info.clone(),
+                        mint: mint_info.clone(),
+                        to: destination_info.clone(),
+                        authority: ctx.accounts.vault.to_account_info(),
+                    },
+                    &[vault_seeds],
+                ),
+                token_account.amount,
+                mint.decimals,
+            )?;
+        }
+        token_interface::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::CloseAccount {
+                account: account_info.clone(),
+                destination: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            &[vault_seeds],
+        ))?;
+    }
+
+    // Empty the vault, which closes it
+    let vault_lamports = ctx.accounts.vault.lamports();
+    if vault_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            vault_lamports,
+        )?;
+    }
+
+    msg!("Account closed!");
+    Ok(())
+}
+
+pub(crate) fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+    let account_info = ctx.accounts.argent_account.to_account_info();
+    let argent_key = account_info.key();
+
+    // Decode a copy padded with zeroes to the largest older layout, which older layouts
+    // read their missing fields from. Accounts from before the account header start their
+    // body right after the discriminator; of those, accounts from before the fixed header
+    // have the guardian backup's Option tag (0 or 1) where the fixed header now keeps the
+    // version. Only those carry their hot state inline
+    let (mut migrated, inline_state) = {
+        let mut data = account_info.try_borrow_data()?.to_vec();
+        require!(
+            data.starts_with(WalletConfig::DISCRIMINATOR),
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        data.resize(data.len().max(PrefixlessArgentAccount::SPACE), 0);
+        if AccountHeader::is_present(&data) {
+            (WalletConfig::try_deserialize(&mut &data[..])?, None)
+        } else if data[WALLET_VERSION_OFFSET - AccountHeader::LEN] >= FIXED_HEADER_ACCOUNT_VERSION {
+            let prefixless = PrefixlessArgentAccount::deserialize(&mut &data[8..])?;
+            let inline_state = InlineWalletState::from(&prefixless);
+            (prefixless.into(), Some(inline_state))
+        } else {
+            let legacy = LegacyArgentAccount::deserialize(&mut &data[8..])?;
+            let inline_state = InlineWalletState::from(&legacy);
+            (legacy.into(), Some(inline_state))
+        }
+    };
+    let argent_account = &mut migrated;
+
+    require!(
+        argent_account.version < CURRENT_ACCOUNT_VERSION,
+        ErrorCode::AlreadyMigrated
+    );
+
+    // Version 0 -> 1: introduce the version field itself
+    // Version 1 -> 2: wallets are derived from [b"argent", creator, index]; older wallets
+    // keep their owner/guardian derived address and record no creator
+    if argent_account.version < 2 {
+        argent_account.creator = Pubkey::default();
+        argent_account.index = 0;
+    }
+
+    // Version 2 -> 3: space is derived from the layout and the account grows to fit
+    // the largest pending transaction; the account is resized below
+    // Version 3 -> 4: name and metadata_uri are read as empty from the zeroed tail
+    if argent_account.version < 4 {
+        argent_account.name = String::new();
+        argent_account.metadata_uri = String::new();
+    }
+
+    // Version 4 -> 5: record the canonical bump; wallets from before version 2 have no
+    // creator/index address and cannot pass the address checks
+    if argent_account.version < 5 && argent_account.creator != Pubkey::default() {
+        let (address, bump) = Pubkey::find_program_address(
+            &[
+                b"argent",
+                argent_account.creator.as_ref(),
+                argent_account.index.to_le_bytes().as_ref(),
+            ],
+            ctx.program_id,
+        );
+        require_keys_eq!(address, argent_key, ErrorCode::InvalidAccountAddress);
+        argent_account.bump = bump;
+    }
+
+    // Version 5 -> 6: state_version starts counting from the zeroed tail
+    // Version 6 -> 7: activity timestamps start at 0 until the next signed instruction
+    // Version 7 -> 8: compute_budget is read as None from the zeroed tail
+    // Version 8 -> 9: message approvals need only the owner until the policy is changed
+    // Version 9 -> 10: escape_new_key is read as None from the zeroed tail
+    // Version 10 -> 11: the audit hash chain starts from the zeroed tail
+    // Version 11 -> 12: notification_tag is read as all zeroes (unset) from the zeroed tail
+    // Version 12 -> 13: escape_stats start counting from the zeroed tail
+    // Version 13 -> 14: approval_ttl is read as 0 (no limit) from the zeroed tail
+    // Version 14 -> 15: features is read as 0 (no layout features) from the zeroed tail
+    // Version 15 -> 16: locked_until is read as 0 (unlocked) from the zeroed tail
+    // Version 16 -> 17: owner, guardian, version and escape_type move to a fixed header;
+    // the legacy layout was decoded above and the account is rewritten below
+    // Version 17 -> 18: the accounts the wallet already derived aren't known here, so
+    // open_accounts stays uncounted until count_open_accounts establishes it
+    // Version 18 -> 19: the layout features move into the account header in front of the
+    // body; the prefixless layout was decoded above and the account is rewritten below.
+    // The pending transaction and the hot state (state version, escape progress, activity,
+    // audit head, open accounts and compute budget) move out to their own accounts, leaving
+    // the wallet account its config
+    let mut pending_transaction = match ctx.accounts.pending_transaction.load_mut() {
+        Ok(pending_transaction) => pending_transaction,
+        Err(_) => {
+            let mut pending_transaction = ctx.accounts.pending_transaction.load_init()?;
+            pending_transaction.initialize(argent_key);
+            pending_transaction
+        }
+    };
+    pending_transaction.header.require_compatible()?;
+    let mut wallet_state = match ctx.accounts.wallet_state.load_mut() {
+        Ok(wallet_state) => wallet_state,
+        Err(_) => {
+            let mut wallet_state = ctx.accounts.wallet_state.load_init()?;
+            wallet_state.initialize(argent_key);
+            wallet_state
+        }
+    };
+    wallet_state.header.require_compatible()?;
+    if let Some(inline_state) = inline_state {
+        if let Some(pending_tx) = inline_state.pending_tx {
+            pending_transaction.store(&pending_tx.data)?;
+            pending_transaction.owner_approved = pending_tx.owner_approved as u8;
+            pending_transaction.guardian_approved = pending_tx.guardian_approved as u8;
+            pending_transaction.set_compute_budget(inline_state.compute_budget);
+        }
+        wallet_state.state_version = inline_state.state_version;
+        wallet_state.last_owner_action_at = inline_state.last_owner_action_at;
+        wallet_state.last_guardian_action_at = inline_state.last_guardian_action_at;
+        wallet_state.audit_head = inline_state.audit_head;
+        wallet_state.open_accounts = if argent_account.version < 18 {
+            UNCOUNTED_OPEN_ACCOUNTS
+        } else {
+            inline_state.open_accounts
+        };
+        wallet_state.start_escape(
+            inline_state.escape_type,
+            inline_state.escape_initiated_at,
+            inline_state.escape_new_key,
+        );
+        wallet_state.escape_stats = inline_state.escape_stats;
+    }
+
+    argent_account.version = CURRENT_ACCOUNT_VERSION;
+    wallet_state.next_state_version();
+
+    // Resize the account to the current layout, with the payer covering any extra rent
+    if account_info.data_len() != WalletConfig::SPACE {
+        let required = Rent::get()?.minimum_balance(WalletConfig::SPACE);
+        let shortfall = required.saturating_sub(account_info.lamports());
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: account_info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        account_info.realloc(WalletConfig::SPACE, false)?;
+    }
+    migrated.try_serialize(&mut &mut account_info.try_borrow_mut_data()?[..])?;
+
+    msg!("Account migrated to version {}", CURRENT_ACCOUNT_VERSION);
+    Ok(())
+}
+
+pub(crate) fn migrate_derived_account(ctx: Context<MigrateDerivedAccount>) -> Result<()> {
+    let account_info = ctx.accounts.account.to_account_info();
+    let len = account_info.data_len();
+    {
+        let data = account_info.try_borrow_data()?;
+        require!(
+            len > 8 && !data.starts_with(WalletConfig::DISCRIMINATOR),
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        require!(!AccountHeader::is_present(&data), ErrorCode::AlreadyMigrated);
+    }
+
+    let migrated_len = len + AccountHeader::LEN;
+    let required = Rent::get()?.minimum_balance(migrated_len);
+    let shortfall = required.saturating_sub(account_info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: account_info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account_info.realloc(migrated_len, false)?;
+
+    // Shift the body behind the header, keeping the discriminator in front
+    let mut data = account_info.try_borrow_mut_data()?;
+    data.copy_within(8..len, 8 + AccountHeader::LEN);
+    AccountHeader::CURRENT.serialize(&mut &mut data[8..8 + AccountHeader::LEN])?;
+
+    msg!("Account {} migrated to the account header", account_info.key());
+    Ok(())
+}
+
+pub(crate) fn count_open_accounts(ctx: Context<CountOpenAccounts>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let argent_key = ctx.accounts.argent_account.key();
+    require!(
+        wallet_state.open_accounts == UNCOUNTED_OPEN_ACCOUNTS,
+        ErrorCode::OpenAccountsAlreadyCounted
+    );
+
+    let mut counted: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+    for account in ctx.remaining_accounts {
+        let data = account.try_borrow_data()?;
+        require!(
+            *account.owner == crate::ID
+                && account.key() != argent_key
+                && !data.starts_with(PendingTransaction::DISCRIMINATOR)
+                && !data.starts_with(WalletState::DISCRIMINATOR)
+                && !counted.contains(account.key)
+                && data.get(DERIVED_ACCOUNT_WALLET_OFFSET..DERIVED_ACCOUNT_WALLET_OFFSET + 32)
+                    == Some(argent_key.as_ref()),
+            ErrorCode::InvalidRemainingAccounts
+        );
+        counted.push(account.key());
+    }
+
+    wallet_state.open_accounts = counted.len() as u32;
+    wallet_state.next_state_version();
+
+    msg!("{} open accounts counted", counted.len());
+    Ok(())
+}
+
+pub(crate) fn set_metadata(
+    ctx: Context<SetMetadata>,
+    name: String,
+    metadata_uri: String,
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+
+    require!(
+        name.len() <= MAX_WALLET_NAME_LEN && metadata_uri.len() <= MAX_METADATA_URI_LEN,
+        ErrorCode::MetadataTooLong
+    );
+
+    argent_account.name = name;
+    argent_account.metadata_uri = metadata_uri;
+
+    wallet_state.next_state_version();
+
+    msg!("Wallet metadata updated!");
+    Ok(())
+}
+
+pub(crate) fn set_notification_tag(
+    ctx: Context<SetNotificationTag>,
+    notification_tag: [u8; 32],
+) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+
+    argent_account.notification_tag = notification_tag;
+
+    wallet_state.next_state_version();
+
+    msg!("Notification tag updated!");
+    Ok(())
+}
+
+pub(crate) fn withdraw_excess_lamports(ctx: Context<WithdrawExcessLamports>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let account_info = ctx.accounts.argent_account.to_account_info();
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(account_info.data_len());
+    let excess = account_info.lamports().saturating_sub(rent_exempt_minimum);
+    require!(excess > 0, ErrorCode::NoExcessLamports);
+
+    // The wallet account is owned by this program, so its lamports can be moved directly
+    account_info.sub_lamports(excess)?;
+    ctx.accounts.destination.add_lamports(excess)?;
+
+    wallet_state.next_state_version();
+
+    msg!("Withdrew {} excess lamports", excess);
+    Ok(())
+}
+
+pub(crate) fn create_history(ctx: Context<CreateHistory>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.account_opened();
+    let mut history = ctx.accounts.history.load_init()?;
+    history.header = AccountHeader::CURRENT;
+    history.argent_account = ctx.accounts.argent_account.key();
+
+    wallet_state.next_state_version();
+
+    msg!("History created!");
+    Ok(())
+}
+
+pub(crate) fn heartbeat(ctx: Context<Heartbeat>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.authority.key()],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn get_last_activity(ctx: Context<GetLastActivity>) -> Result<LastActivity> {
+    let wallet_state = ctx.accounts.wallet_state.load()?;
+    wallet_state.header.require_compatible()?;
+    let argent_account = &ctx.accounts.argent_account;
+    let activity = LastActivity {
+        argent_account: argent_account.key(),
+        last_owner_action_at: wallet_state.last_owner_action_at,
+        last_guardian_action_at: wallet_state.last_guardian_action_at,
+        state_version: wallet_state.state_version,
+    };
+
+    emit_cpi!(activity.clone());
+    Ok(activity)
+}
+
+pub(crate) fn set_viewers(ctx: Context<SetViewers>, viewers: Vec<Pubkey>) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    require!(viewers.len() <= MAX_VIEWERS, ErrorCode::TooManyViewers);
+
+    let viewer_list = &mut ctx.accounts.viewer_list;
+    if viewer_list.argent_account == Pubkey::default() {
+        wallet_state.account_opened();
+    }
+    viewer_list.argent_account = ctx.accounts.argent_account.key();
+    viewer_list.viewers = viewers;
+    viewer_list.bump = ctx.bumps.viewer_list;
+
+    wallet_state.next_state_version();
+
+    msg!("Viewers updated!");
+    Ok(())
+}
+
+pub(crate) fn get_wallet_details(ctx: Context<GetWalletDetails>) -> Result<WalletDetails> {
+    let wallet_state = ctx.accounts.wallet_state.load()?;
+    wallet_state.header.require_compatible()?;
+    let argent_account = &ctx.accounts.argent_account;
+    let viewer = ctx.accounts.viewer.key();
+    let allowed = argent_account.is_signer_role(&viewer)
+        || ctx
+            .accounts
+            .viewer_list
+            .as_ref()
+            .is_some_and(|list| list.viewers.contains(&viewer));
+    require!(allowed, ErrorCode::ViewerNotAllowed);
+
+    Ok(WalletDetails {
+        argent_account: argent_account.key(),
+        name: argent_account.name.clone(),
+        metadata_uri: argent_account.metadata_uri.clone(),
+        notification_tag: argent_account.notification_tag,
+        state_version: wallet_state.state_version,
+    })
+}
+
+pub(crate) fn set_approval_ttl(ctx: Context<SetApprovalTtl>, approval_ttl: i64) -> Result<()> {
+    let mut wallet_state = ctx.accounts.wallet_state.load_mut()?;
+    wallet_state.header.require_compatible()?;
+    wallet_state.record_activity(
+        &ctx.accounts.argent_account,
+        &[ctx.accounts.approvers.owner.key(), ctx.accounts.approvers.guardian.key()],
+    )?;
+
+    let argent_account = &mut ctx.accounts.argent_account;
+
+    require!(approval_ttl >= 0, ErrorCode::InvalidApprovalExpiry);
+    argent_account.approval_ttl = approval_ttl;
+
+    wallet_state.next_state_version();
+
+    msg!("Approval TTL set to {}s", approval_ttl);
+    Ok(())
+}